@@ -176,6 +176,13 @@ impl App {
     ) {
         self.state.status.loading = false;
 
+        // A vCard attachment being imported into contacts
+        if self.state.reader.pending_contact_import == Some(attachment_index) {
+            self.state.reader.pending_contact_import = None;
+            self.import_vcard(&data).await;
+            return;
+        }
+
         // Check if this is for a pending save/open operation
         if let Some((pending_index, save_path)) = self.state.reader.pending_attachment_save.take()
             && pending_index == attachment_index
@@ -254,13 +261,14 @@ impl App {
     ) {
         self.state.status.loading = false;
         self.state.reader.pending_attachment_save = None;
+        self.state.reader.pending_contact_import = None;
         self.state
             .set_error(format!("Failed to fetch attachment: {}", error));
     }
 }
 
 /// Generate a unique save path by appending numbers to avoid overwriting
-fn unique_save_path(base_dir: &std::path::Path, filename: &str) -> PathBuf {
+pub(super) fn unique_save_path(base_dir: &std::path::Path, filename: &str) -> PathBuf {
     let path = base_dir.join(filename);
     if !path.exists() {
         return path;
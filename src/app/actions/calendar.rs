@@ -0,0 +1,114 @@
+//! Calendar invitations (answering, conflicts with other cached invites)
+
+use chrono::Utc;
+
+use crate::mail::calendar::{self, PartStat, Person};
+use crate::mail::types::ComposeEmail;
+
+use super::super::App;
+
+impl App {
+    /// Answer the invitation in the open message with an iMIP REPLY to the organizer
+    pub(super) async fn respond_to_invite(&mut self, status: PartStat) {
+        let Some((uid, email)) = self.get_current_email_for_compose() else {
+            self.state.set_error("No email selected");
+            return;
+        };
+        let Some(invite) = self.state.reader.invite.clone() else {
+            self.state.set_error("Not a calendar invitation");
+            return;
+        };
+        if !invite.is_request() {
+            self.state.set_error(if invite.is_cancelled() {
+                "This event was cancelled"
+            } else {
+                "Only invitations (METHOD:REQUEST) can be answered"
+            });
+            return;
+        }
+        let Some(organizer) = invite.event.organizer.clone() else {
+            self.state
+                .set_error("The invitation has no organizer to reply to");
+            return;
+        };
+
+        let fallback_identity = self.identity_for_reply(&email).await;
+        let Some(handle) = self.accounts.get(self.state.connection.account_index) else {
+            return;
+        };
+        let config = &handle.config;
+
+        // Answer as the address that was invited, from the matching identity,
+        // so the organizer's calendar can match the reply to its attendee
+        let (attendee, identity_index) = match invite.event.attendee(config.own_addresses()) {
+            Some(attendee) => (
+                attendee.person.clone(),
+                config.identity_for_recipients([attendee.person.email.as_str()]),
+            ),
+            None => {
+                let identity = config.identity(fallback_identity);
+                let person = Person {
+                    email: identity.email,
+                    name: identity.display_name,
+                };
+                (person, fallback_identity)
+            }
+        };
+
+        let title = invite.event.title();
+        let reply = ComposeEmail {
+            to: organizer.email.clone(),
+            subject: format!("{}: {}", status.verb(), title),
+            body: format!(
+                "{} has {} the invitation: {}\n",
+                attendee.display(),
+                status.verb().to_lowercase(),
+                title
+            ),
+            reply_to_uid: Some(uid),
+            identity_index,
+            calendar: Some(calendar::reply(&invite, &attendee, status, Utc::now())),
+            ..ComposeEmail::reply_to(&email, "")
+        };
+
+        match self.config.compose.undo_send_delay() {
+            0 => self.do_send(reply).await,
+            delay => self.queue_send(reply, delay),
+        }
+    }
+
+    /// Look up cached invitations that overlap the one being shown.
+    /// Runs once per opened message; returns true if the card needs redrawing.
+    pub(crate) async fn check_invite_conflicts(&mut self) -> bool {
+        let reader = &self.state.reader;
+        let Some(ref invite) = reader.invite else {
+            return false;
+        };
+        if reader.invite_conflicts.is_some() {
+            return false;
+        }
+        if !invite.is_request() {
+            self.state.reader.invite_conflicts = Some(Vec::new());
+            return false;
+        }
+
+        let event = invite.event.clone();
+        let found = match self.cache.get_account_calendars(self.account_id()).await {
+            Ok(texts) => {
+                let own = self
+                    .accounts
+                    .get(self.state.connection.account_index)
+                    .map(|h| h.config.own_addresses())
+                    .unwrap_or_default();
+                let others = texts.iter().filter_map(|t| calendar::parse(t)).collect();
+                calendar::conflicts(&event, others, &own)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load cached invitations: {}", e);
+                Vec::new()
+            }
+        };
+        self.state.reader.invite_conflicts = Some(found);
+        true
+    }
+}
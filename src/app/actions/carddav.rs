@@ -0,0 +1,111 @@
+//! Background CardDAV address book sync
+
+use std::time::Instant;
+
+use crate::app::state::View;
+use crate::contacts::carddav::{self, SyncSummary};
+
+use super::super::{App, CardDavSyncResult};
+
+impl App {
+    /// Apply finished syncs and start the next one when due.
+    /// Returns true if the UI needs a re-render.
+    pub(crate) async fn process_carddav_sync(&mut self) -> bool {
+        if self.config.carddav.is_empty() {
+            return false;
+        }
+
+        let mut processed = false;
+        while let Ok(CardDavSyncResult { manual, result }) = self.carddav_rx.try_recv() {
+            self.carddav_syncing = false;
+            match result {
+                Ok(summary) => {
+                    if !summary.is_empty() {
+                        self.refresh_contacts_view().await;
+                    }
+                    if manual || !summary.is_empty() {
+                        self.state
+                            .set_status(format!("Contacts synced: {}", summary));
+                        processed = true;
+                    }
+                }
+                Err(e) if manual => {
+                    self.state.set_error(format!("Contacts sync failed: {}", e));
+                    processed = true;
+                }
+                Err(e) => tracing::warn!("CardDAV sync failed: {}", e),
+            }
+        }
+
+        let interval = self
+            .config
+            .carddav
+            .iter()
+            .map(|book| book.sync_interval_secs)
+            .min()
+            .unwrap_or_default();
+        let due = self
+            .last_carddav_sync
+            .is_none_or(|t| t.elapsed().as_secs() >= interval);
+        if due && !self.carddav_syncing {
+            self.start_carddav_sync(false);
+        }
+        processed
+    }
+
+    /// Sync all address books now (`:sync-contacts`)
+    pub(crate) fn sync_contacts(&mut self) {
+        if self.config.carddav.is_empty() {
+            self.state
+                .set_error("No [[carddav]] address book configured");
+        } else if self.carddav_syncing {
+            self.state.set_status("Contacts sync already running");
+        } else {
+            self.start_carddav_sync(true);
+            self.state.set_status("Syncing contacts...");
+        }
+    }
+
+    /// Push local contact changes soon instead of at the next interval
+    pub(crate) fn request_carddav_sync(&mut self) {
+        self.last_carddav_sync = None;
+    }
+
+    fn start_carddav_sync(&mut self, manual: bool) {
+        self.carddav_syncing = true;
+        self.last_carddav_sync = Some(Instant::now());
+
+        let books = self.config.carddav.clone();
+        let db = self.contacts.clone();
+        let tx = self.carddav_tx.clone();
+        tokio::spawn(async move {
+            let mut total = SyncSummary::default();
+            let mut result = Ok(());
+            for book in &books {
+                match carddav::sync(book, &db).await {
+                    Ok(summary) => total += summary,
+                    Err(e) => {
+                        result = Err(format!("{}: {:#}", book.url, e));
+                        break;
+                    }
+                }
+            }
+            let result = result.map(|()| total);
+            let _ = tx.send(CardDavSyncResult { manual, result }).await;
+        });
+    }
+
+    /// Reload the contacts list after a sync changed it
+    async fn refresh_contacts_view(&mut self) {
+        if !matches!(self.state.view, View::Contacts) || self.state.contacts.editing.is_some() {
+            return;
+        }
+        if let Ok(list) = self.contacts.get_all().await {
+            self.state.contacts.list = list;
+            let len = self.state.contacts.list.len();
+            if self.state.contacts.selected >= len {
+                self.state.contacts.selected = len.saturating_sub(1);
+            }
+        }
+    }
+}
@@ -11,7 +11,7 @@ use crate::ui::theme;
 use super::super::App;
 
 impl App {
-    pub(super) fn execute_command(&mut self) {
+    pub(super) async fn execute_command(&mut self) {
         let input = match &self.state.modal {
             ModalState::Command { input, .. } => input.trim().to_string(),
             _ => return,
@@ -57,6 +57,40 @@ impl App {
             Some(ParsedCommand::Theme(name)) => {
                 self.handle_theme_command(&name);
             }
+            Some(ParsedCommand::Sort(name)) => {
+                self.handle_sort_command(&name).await;
+            }
+            Some(ParsedCommand::Template(name)) => {
+                self.handle_template_command(&name).await;
+            }
+            Some(ParsedCommand::Scheduled) => {
+                self.exit_command_mode();
+                self.open_scheduled().await;
+            }
+            Some(ParsedCommand::Unsubscribe) => {
+                self.exit_command_mode();
+                self.unsubscribe().await;
+            }
+            Some(ParsedCommand::ListArchive) => {
+                self.exit_command_mode();
+                self.open_list_archive();
+            }
+            Some(ParsedCommand::Invite(status)) => {
+                self.exit_command_mode();
+                self.respond_to_invite(status).await;
+            }
+            Some(ParsedCommand::ImportContacts(path)) => {
+                self.exit_command_mode();
+                self.import_contacts_file(&path).await;
+            }
+            Some(ParsedCommand::ExportContacts(path)) => {
+                self.exit_command_mode();
+                self.export_contacts(&path).await;
+            }
+            Some(ParsedCommand::SyncContacts) => {
+                self.exit_command_mode();
+                self.sync_contacts();
+            }
             None => {
                 if let ModalState::Command {
                     input: cmd_input,
@@ -134,6 +168,9 @@ impl App {
                         }
                     }
                 }
+                PendingCommand::Unsubscribe { list, uri } => {
+                    self.one_click_unsubscribe(&list, &uri);
+                }
             }
             self.exit_command_mode();
         }
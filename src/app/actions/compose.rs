@@ -1,17 +1,22 @@
 //! Email composition actions (reply, forward, send)
 
-use crate::app::state::{ComposerField, View};
-use crate::config::AuthMethod;
+use anyhow::{Result, anyhow, bail};
+
+use crate::app::state::{ComposerField, FromChoice, FromPicker, RawPurpose, View};
+use crate::config::{AccountConfig, AuthMethod, PgpConfig, SendTransport};
 use crate::credentials::CredentialStore;
-use crate::mail::types::{ComposeEmail, EmailFlags, EmailHeader};
-use crate::mail::{ImapCommand, SmtpClient};
+use crate::input::Action;
+use crate::mail::pgp::{self, PgpBackend};
+use crate::mail::smime;
+use crate::mail::types::{ComposeEmail, EmailFlags, EmailHeader, Protection, Scheme};
+use crate::mail::{ImapCommand, SmtpClient, address, parser, signature};
 
 use super::super::App;
 
 impl App {
     /// Get the current email for compose operations (reply, forward).
     /// Returns (uid, email) or None if not available.
-    fn get_current_email_for_compose(&self) -> Option<(u32, EmailHeader)> {
+    pub(super) fn get_current_email_for_compose(&self) -> Option<(u32, EmailHeader)> {
         match &self.state.view {
             View::Inbox => self
                 .state
@@ -39,6 +44,40 @@ impl App {
         String::new()
     }
 
+    /// Pick the identity of the current account that the original was sent to
+    ///
+    /// Checks Delivered-To / X-Original-To from the cached raw message first,
+    /// then the original To and Cc.
+    pub(super) async fn identity_for_reply(&self, original: &EmailHeader) -> usize {
+        let Some(handle) = self.accounts.get(self.state.connection.account_index) else {
+            return 0;
+        };
+        if handle.config.identities.is_empty() {
+            return 0;
+        }
+
+        let delivered_to = self
+            .cache
+            .get_raw_message(&self.cache_key_for_uid(original.uid), original.uid)
+            .await
+            .ok()
+            .flatten()
+            .map(|raw| parser::parse_delivered_to(&raw))
+            .unwrap_or_default();
+
+        let recipients: Vec<_> = original
+            .to_addr
+            .iter()
+            .chain(original.cc_addr.iter())
+            .flat_map(|field| address::valid_addresses(field))
+            .collect();
+        let recipients = delivered_to
+            .iter()
+            .map(String::as_str)
+            .chain(recipients.iter().map(|a| a.email.as_str()));
+        handle.config.identity_for_recipients(recipients)
+    }
+
     pub(super) async fn start_reply(&mut self) {
         let (uid, email) = match self.get_current_email_for_compose() {
             Some(result) => result,
@@ -51,10 +90,8 @@ impl App {
         let body_text = self.get_email_body_text(uid).await;
         let mut reply = ComposeEmail::reply_to(&email, &body_text);
         reply.reply_to_uid = Some(uid); // Track original email for ANSWERED flag
-        self.state.view = View::Composer {
-            email: reply,
-            field: ComposerField::Body,
-        };
+        reply.identity_index = self.identity_for_reply(&email).await;
+        self.open_composer(reply, ComposerField::Body);
     }
 
     pub(super) async fn start_reply_all(&mut self) {
@@ -68,22 +105,45 @@ impl App {
 
         let body_text = self.get_email_body_text(uid).await;
 
-        // Get our email address for filtering
-        let my_email = self
+        // Get our own addresses (including aliases) for filtering
+        let my_addresses = self
             .accounts
             .get(self.state.connection.account_index)
-            .map(|h| h.config.email.as_str())
-            .unwrap_or("");
+            .map(|h| h.config.own_addresses())
+            .unwrap_or_default();
 
-        let mut reply = ComposeEmail::reply_all(&email, &body_text, my_email);
+        let mut reply = ComposeEmail::reply_all(&email, &body_text, &my_addresses);
         reply.reply_to_uid = Some(uid); // Track original email for ANSWERED flag
-        self.state.view = View::Composer {
-            email: reply,
-            field: ComposerField::Body,
+        reply.identity_index = self.identity_for_reply(&email).await;
+        self.open_composer(reply, ComposerField::Body);
+    }
+
+    /// Reply to the List-Post address of the current email's mailing list
+    pub(super) async fn start_reply_to_list(&mut self) {
+        let (uid, email) = match self.get_current_email_for_compose() {
+            Some(result) => result,
+            None => {
+                self.state.set_error("No email selected for reply");
+                return;
+            }
         };
+        let Some(post) = email.list.as_ref().and_then(|l| l.post.clone()) else {
+            self.state
+                .set_error("Not a mailing-list message, or the list does not accept posts");
+            return;
+        };
+
+        let body_text = self.get_email_body_text(uid).await;
+        let mut reply = ComposeEmail::reply_to(&email, &body_text);
+        reply.to = post;
+        reply.reply_to_uid = Some(uid); // Track original email for ANSWERED flag
+        reply.identity_index = self.identity_for_reply(&email).await;
+        self.open_composer(reply, ComposerField::Body);
     }
 
-    pub(super) async fn start_forward(&mut self) {
+    /// Forward the current email, either inline with its attachments
+    /// re-attached or wrapped whole as a message/rfc822 attachment
+    pub(super) async fn start_forward(&mut self, as_attachment: bool) {
         let (uid, email) = match self.get_current_email_for_compose() {
             Some(result) => result,
             None => {
@@ -92,19 +152,214 @@ impl App {
             }
         };
 
+        // Attachments come from the raw message, which may need fetching first
+        if as_attachment || email.has_attachments {
+            self.request_raw(uid, RawPurpose::Forward { as_attachment })
+                .await;
+            return;
+        }
+
         let body_text = self.get_email_body_text(uid).await;
         let forward = ComposeEmail::forward(&email, &body_text);
-        self.state.view = View::Composer {
-            email: forward,
-            field: ComposerField::To, // Start at To since it's empty
+        self.open_composer(forward, ComposerField::To); // Start at To since it's empty
+    }
+
+    /// Open the forward composer once the raw message is available
+    pub(super) async fn finish_forward(&mut self, uid: u32, raw: Vec<u8>, as_attachment: bool) {
+        // The user may have moved on while the message was being fetched
+        if !matches!(self.state.view, View::Inbox | View::Reader { .. }) {
+            return;
+        }
+        let Some(email) = self.state.emails.iter().find(|e| e.uid == uid).cloned() else {
+            self.state
+                .set_error("Email to forward is no longer available");
+            return;
+        };
+
+        let forward = if as_attachment {
+            ComposeEmail::forward_as_attachment(&email, raw)
+        } else {
+            let body_text = self.get_email_body_text(uid).await;
+            let mut forward = ComposeEmail::forward(&email, &body_text);
+            forward.attachments = parser::extract_attachments_for_forward(&raw);
+            forward
         };
+        let count = forward.attachments.len();
+        self.open_composer(forward, ComposerField::To); // Start at To since it's empty
+        if count > 0 {
+            self.state.set_status(format!(
+                "Forwarding with {} attachment{}",
+                count,
+                if count == 1 { "" } else { "s" }
+            ));
+        }
     }
 
     pub(super) fn start_compose(&mut self) {
-        self.state.view = View::Composer {
-            email: ComposeEmail::new(),
-            field: ComposerField::To,
+        self.open_composer(ComposeEmail::new(), ComposerField::To);
+    }
+
+    /// Switch to the composer, applying the sending account's compose defaults
+    pub(super) fn open_composer(&mut self, mut email: ComposeEmail, field: ComposerField) {
+        let account_index = email
+            .from_account_index
+            .unwrap_or(self.accounts.active_index());
+        if let Some(handle) = self.accounts.get(account_index) {
+            let identity = handle.config.identity(email.identity_index);
+            email.markdown = handle.config.markdown;
+            let smime_default = handle
+                .config
+                .smime
+                .as_ref()
+                .is_some_and(|s| s.sign_by_default)
+                && smime::backend().is_some_and(|b| b.has_identity(&identity.email));
+            if smime_default {
+                email.protection = Protection::SMIME_SIGN;
+            } else if self.config.pgp.sign_by_default && pgp::backend().is_some() {
+                email.protection = Protection::PGP_SIGN;
+            }
+            if let Some(sig) = identity.signature_text() {
+                email.body =
+                    signature::insert(&email.body, &sig, handle.config.signature_placement);
+                email.signature = Some(sig);
+            }
+        }
+        self.show_composer(email, field);
+    }
+
+    /// Switch to the composer with a message as-is (e.g. one restored by undo)
+    pub(super) fn show_composer(&mut self, email: ComposeEmail, field: ComposerField) {
+        let account_index = email
+            .from_account_index
+            .unwrap_or(self.accounts.active_index());
+        self.state.composer.from_label = self
+            .accounts
+            .get(account_index)
+            .map(|h| h.config.identity(email.identity_index).label())
+            .unwrap_or_default();
+        self.state.composer.internal_domains = self
+            .accounts
+            .get(account_index)
+            .map(|h| h.config.internal_domains.clone())
+            .unwrap_or_default();
+        self.state.composer.external_confirmed.clear();
+        self.state.composer.preview = false;
+        self.state.composer.from_picker = None;
+        self.state.composer.schedule_input = None;
+        self.state.composer.template_picker = None;
+        self.state.composer.from_selectable = self.accounts.count() > 1
+            || self
+                .accounts
+                .get(account_index)
+                .is_some_and(|h| h.config.identity_count() > 1);
+        self.state.view = View::Composer { email, field };
+    }
+
+    /// Change the sending account and identity, swapping in its signature
+    fn set_sender(&mut self, account_index: usize, identity_index: usize) {
+        let Some(handle) = self.accounts.get(account_index) else {
+            return;
         };
+        let identity = handle.config.identity(identity_index);
+        let placement = handle.config.signature_placement;
+
+        if let View::Composer { ref mut email, .. } = self.state.view {
+            email.from_account_index = Some(account_index);
+            email.identity_index = identity_index;
+
+            let new_sig = identity.signature_text();
+            match signature::replace(
+                &email.body,
+                email.signature.as_deref(),
+                new_sig.as_deref(),
+                placement,
+            ) {
+                Some(body) => {
+                    email.body = body;
+                    email.signature = new_sig;
+                }
+                None => self
+                    .state
+                    .set_status("Signature was edited; not replacing it"),
+            }
+            self.state.composer.from_label = identity.label();
+            self.state.composer.internal_domains = handle.config.internal_domains.clone();
+        }
+    }
+
+    /// Toggle Markdown mode for the message being composed
+    pub(super) fn toggle_markdown(&mut self) {
+        if let View::Composer { ref mut email, .. } = self.state.view {
+            email.markdown = !email.markdown;
+            let enabled = email.markdown;
+            if !enabled {
+                self.state.composer.preview = false;
+            }
+            self.state.set_status(if enabled {
+                "Markdown enabled (sent as text + HTML)"
+            } else {
+                "Markdown disabled (sent as plain text)"
+            });
+        }
+    }
+
+    /// Step through the available protection: off, the OpenPGP settings, and
+    /// S/MIME signing when the sender has an identity
+    pub(super) fn cycle_protection(&mut self) {
+        let View::Composer { ref email, .. } = self.state.view else {
+            return;
+        };
+        let sender = self.sender_address(email);
+        let pgp_backend = pgp::backend();
+        let smime_ok = smime::backend().is_some_and(|b| b.has_identity(&sender));
+        if pgp_backend.is_none() && !smime_ok {
+            self.state.set_error(
+                "No OpenPGP backend or S/MIME identity for this sender (see [pgp] and [accounts.smime])",
+            );
+            return;
+        }
+        let protection = email.protection.cycle(pgp_backend.is_some(), smime_ok);
+        let missing = match pgp_backend {
+            Some(backend) if protection.encrypt && protection.scheme == Scheme::OpenPgp => {
+                recipients_without_key(backend, email)
+            }
+            _ => Vec::new(),
+        };
+        if let View::Composer { ref mut email, .. } = self.state.view {
+            email.protection = protection;
+        }
+
+        match protection.label() {
+            None => self.state.set_status("Sending unsigned and unencrypted"),
+            Some(label) if missing.is_empty() => self.state.set_status(label),
+            Some(label) => {
+                self.state
+                    .set_error(format!("{}: no key for {}", label, missing.join(", ")))
+            }
+        }
+    }
+
+    /// Address the message in the composer is sent from
+    fn sender_address(&self, email: &ComposeEmail) -> String {
+        let account_index = email
+            .from_account_index
+            .unwrap_or(self.accounts.active_index());
+        self.accounts
+            .get(account_index)
+            .map(|h| h.config.identity(email.identity_index).email)
+            .unwrap_or_default()
+    }
+
+    /// Toggle the rendered Markdown preview in the composer body
+    pub(super) fn toggle_markdown_preview(&mut self) {
+        if let View::Composer { ref email, .. } = self.state.view {
+            if !email.markdown {
+                self.state
+                    .set_status("Enable Markdown (Ctrl+E) to preview the HTML part");
+                return;
+            }
+            self.state.composer.preview = !self.state.composer.preview;
+        }
     }
 
     pub(super) fn next_composer_field(&mut self) {
@@ -120,144 +375,122 @@ impl App {
     }
 
     pub(super) async fn send_email(&mut self) {
-        if let View::Composer { ref email, .. } = self.state.view {
-            if email.to.is_empty() {
-                self.state.set_error("Recipient is required");
-                return;
-            }
-            if email.subject.is_empty() {
-                self.state.set_error("Subject is required");
-                return;
-            }
+        let View::Composer { ref email, .. } = self.state.view else {
+            return;
+        };
+        if let Err(e) = validate_for_send(email) {
+            self.state.set_error(e);
+            return;
+        }
+        // Catch missing keys now rather than after the undo-send delay
+        let sender = self.sender_address(email);
+        if let Err(e) = resolve_protection(email, &sender, &self.config.pgp) {
+            self.state.set_error(e.to_string());
+            return;
+        }
+        let email = email.clone();
+        if !self.confirm_external_recipients(Action::Send) {
+            return;
+        }
 
-            let email = email.clone();
-            self.do_send(email).await;
+        match self.config.compose.undo_send_delay() {
+            0 => self.do_send(email).await,
+            delay => self.queue_send(email, delay),
         }
     }
 
+    /// Warn once before sending from a work account to outside domains
+    ///
+    /// Returns true if the message can go out: no external recipients, or the
+    /// same external domains were already warned about.
+    pub(super) fn confirm_external_recipients(&mut self, action: Action) -> bool {
+        let View::Composer { ref email, .. } = self.state.view else {
+            return false;
+        };
+        let external = external_domains(email, &self.state.composer.internal_domains);
+        if external.is_empty() || external == self.state.composer.external_confirmed {
+            return true;
+        }
+
+        let again = match self.bindings.key_for(action) {
+            Some(key) => format!("press {} again to continue", key),
+            None => "repeat to continue".to_string(),
+        };
+        self.state.set_error(format!(
+            "External recipients at {}; {}",
+            external.join(", "),
+            again
+        ));
+        self.state.composer.external_confirmed = external;
+        false
+    }
+
     pub(crate) async fn do_send(&mut self, email: ComposeEmail) {
         self.state.status.loading = true;
         self.dirty = true;
         self.state.set_status("Sending...");
 
+        match self.deliver(&email).await {
+            Ok(account_name) => {
+                self.state
+                    .set_status(format!("Email sent from {}", account_name));
+                self.state.view = View::Inbox;
+            }
+            Err(e) => self.state.set_error(e.to_string()),
+        }
+
+        self.state.status.loading = false;
+    }
+
+    /// Send a message over SMTP and record the side effects (contacts,
+    /// ANSWERED flag). Leaves the view alone so it can also be used for
+    /// delayed sends. Returns the sending account's name.
+    pub(crate) async fn deliver(&mut self, email: &ComposeEmail) -> Result<String> {
         // Determine which account to send from
         let send_account_index = email
             .from_account_index
             .unwrap_or(self.accounts.active_index());
         let account = match self.accounts.get(send_account_index) {
             Some(h) => &h.config,
-            None => {
-                self.state.set_error("Invalid sending account");
-                self.state.status.loading = false;
-                return;
-            }
+            None => bail!("Invalid sending account"),
         };
 
-        // Create SMTP client for the sending account
-        // Note: We create a fresh connection each time to support cross-account sending
-        let credentials = CredentialStore::new(&account.email);
-
-        // Get credentials based on auth method
-        let password = match &account.auth {
-            AuthMethod::Password => match credentials.get_smtp_password() {
-                Ok(p) => p,
-                Err(e) => {
-                    self.state
-                        .set_error(format!("Failed to get SMTP password: {}", e));
-                    self.state.status.loading = false;
-                    return;
-                }
-            },
-            AuthMethod::OAuth2 { client_id, .. } => {
-                // For OAuth2, get the stored refresh token and exchange for a fresh access token
-                let refresh_token = match credentials.get_oauth2_refresh_token() {
-                    Ok(token) => token,
-                    Err(e) => {
-                        self.state.set_error(format!(
-                            "OAuth2 refresh token not found: {}. Please re-authenticate.",
-                            e
-                        ));
-                        self.state.status.loading = false;
-                        return;
-                    }
-                };
+        let email = transmit(account, email, &self.config.pgp).await?;
+        self.record_sent(&email).await;
 
-                match crate::oauth2::get_access_token(client_id, &refresh_token).await {
-                    Ok(access_token) => access_token,
-                    Err(e) => {
-                        self.state.set_error(format!(
-                            "Failed to refresh OAuth2 access token: {}. Please re-authenticate.",
-                            e
-                        ));
-                        self.state.status.loading = false;
-                        return;
-                    }
-                }
-            }
-        };
+        Ok(self
+            .state
+            .connection
+            .account_names
+            .get(send_account_index)
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string()))
+    }
 
-        let smtp = match SmtpClient::new_with_auth(
-            &account.smtp,
-            account.username_or_email(),
-            &password,
-            &account.email,
-            account.display_name.as_deref(),
-            &account.auth,
-        )
-        .await
-        {
-            Ok(client) => client,
-            Err(e) => {
-                self.state
-                    .set_error(format!("Failed to connect to SMTP: {}", e));
-                self.state.status.loading = false;
-                return;
+    /// Side effects of a sent message: the recipient becomes a contact and
+    /// the message it replies to is flagged ANSWERED
+    pub(crate) async fn record_sent(&mut self, email: &ComposeEmail) {
+        // Add recipient to contacts
+        self.contacts.add_or_update(&email.to, None).await.ok();
+
+        // Set ANSWERED flag on original email if this was a reply
+        if let Some(reply_to_uid) = email.reply_to_uid {
+            // Update local state immediately (optimistic update)
+            if let Some(original) = self.state.emails.iter_mut().find(|e| e.uid == reply_to_uid) {
+                original.flags.insert(EmailFlags::ANSWERED);
             }
-        };
 
-        match smtp.send(&email).await {
-            Ok(_) => {
-                // Add recipient to contacts
-                self.contacts.add_or_update(&email.to, None).await.ok();
-
-                // Set ANSWERED flag on original email if this was a reply
-                if let Some(reply_to_uid) = email.reply_to_uid {
-                    // Update local state immediately (optimistic update)
-                    if let Some(original) =
-                        self.state.emails.iter_mut().find(|e| e.uid == reply_to_uid)
-                    {
-                        original.flags.insert(EmailFlags::ANSWERED);
-                    }
-
-                    // Send IMAP command to server (use email's actual folder)
-                    let folder = self.folder_for_uid(reply_to_uid);
-                    self.accounts
-                        .send_command(ImapCommand::SetFlag {
-                            uid: reply_to_uid,
-                            flag: EmailFlags::ANSWERED,
-                            folder,
-                        })
-                        .await
-                        .ok();
-                }
-
-                let account_name = self
-                    .state
-                    .connection
-                    .account_names
-                    .get(send_account_index)
-                    .cloned()
-                    .unwrap_or_else(|| "unknown".to_string());
-                self.state
-                    .set_status(format!("Email sent from {}", account_name));
-                self.state.view = View::Inbox;
-            }
-            Err(e) => {
-                self.state.set_error(format!("Failed to send: {}", e));
-            }
+            // Send IMAP command to server (use email's actual folder)
+            let folder = self.folder_for_uid(reply_to_uid);
+            self.accounts
+                .send_command(ImapCommand::SetFlag {
+                    uid: reply_to_uid,
+                    flag: EmailFlags::ANSWERED,
+                    folder,
+                })
+                .await
+                .ok();
         }
-
-        self.state.status.loading = false;
     }
 
     pub(super) fn cancel_compose(&mut self) {
@@ -268,7 +501,7 @@ impl App {
 
     /// Cycle through accounts for sending in composer
     pub(super) fn cycle_send_account(&mut self) {
-        if let View::Composer { ref mut email, .. } = self.state.view {
+        if let View::Composer { ref email, .. } = self.state.view {
             let account_count = self.accounts.count();
             if account_count <= 1 {
                 return; // Only one account, nothing to cycle
@@ -278,10 +511,84 @@ impl App {
                 .from_account_index
                 .unwrap_or(self.state.connection.account_index);
             let next_index = (current_index + 1) % account_count;
-            email.from_account_index = Some(next_index);
+            self.set_sender(next_index, 0);
+        }
+    }
+
+    /// Open the From selector listing every account and identity
+    pub(super) fn open_from_picker(&mut self) {
+        let View::Composer { ref email, .. } = self.state.view else {
+            return;
+        };
+        let current_account = email
+            .from_account_index
+            .unwrap_or(self.state.connection.account_index);
+        let current_identity = email.identity_index;
+
+        let multiple_accounts = self.accounts.count() > 1;
+        let mut choices = Vec::new();
+        for account_index in 0..self.accounts.count() {
+            let Some(handle) = self.accounts.get(account_index) else {
+                continue;
+            };
+            for identity_index in 0..handle.config.identity_count() {
+                let identity = handle.config.identity(identity_index);
+                let label = if multiple_accounts {
+                    format!("{}: {}", handle.config.account_name(), identity.label())
+                } else {
+                    identity.label()
+                };
+                choices.push(FromChoice {
+                    account_index,
+                    identity_index,
+                    label,
+                });
+            }
+        }
+
+        if choices.len() <= 1 {
+            self.state.set_status("No other identities to send from");
+            return;
+        }
+
+        let selected = choices
+            .iter()
+            .position(|c| {
+                c.account_index == current_account && c.identity_index == current_identity
+            })
+            .unwrap_or(0);
+        self.state.composer.from_picker = Some(FromPicker { choices, selected });
+    }
+
+    /// Move From selector up
+    pub(super) fn sender_picker_up(&mut self) {
+        if let Some(ref mut picker) = self.state.composer.from_picker {
+            picker.selected = picker.selected.saturating_sub(1);
+        }
+    }
+
+    /// Move From selector down
+    pub(super) fn sender_picker_down(&mut self) {
+        if let Some(ref mut picker) = self.state.composer.from_picker {
+            let max = picker.choices.len().saturating_sub(1);
+            picker.selected = (picker.selected + 1).min(max);
         }
     }
 
+    /// Send from the selected account and identity
+    pub(super) fn sender_picker_select(&mut self) {
+        if let Some(picker) = self.state.composer.from_picker.take()
+            && let Some(choice) = picker.choices.get(picker.selected)
+        {
+            self.set_sender(choice.account_index, choice.identity_index);
+        }
+    }
+
+    /// Close the From selector without changing the sender
+    pub(super) fn sender_picker_close(&mut self) {
+        self.state.composer.from_picker = None;
+    }
+
     /// Update autocomplete suggestions based on To or Cc field content
     pub(crate) async fn update_autocomplete(&mut self) {
         if let View::Composer { ref email, field } = self.state.view {
@@ -302,12 +609,8 @@ impl App {
                 return;
             }
 
-            // Get the text after the last comma (for multi-recipient support)
-            let search_text = field_value
-                .rfind(',')
-                .map(|idx| field_value[idx + 1..].trim())
-                .unwrap_or(field_value)
-                .trim();
+            // Search on the mailbox being typed (earlier ones may contain quoted commas)
+            let search_text = field_value[address::current_entry_start(field_value)..].trim();
 
             if search_text.is_empty() {
                 self.state.autocomplete.visible = false;
@@ -368,15 +671,17 @@ impl App {
                 }
             };
 
-            // For multi-recipient: find the last comma and replace text after it
-            if let Some(comma_idx) = field_value.rfind(',') {
-                // Keep everything up to and including the comma, add selected email
-                let prefix = field_value[..=comma_idx].to_string();
-                *field_value = format!("{} {}, ", prefix, contact.email);
-            } else {
-                // Single recipient - just replace with selected email
-                *field_value = format!("{}, ", contact.email);
+            // Replace the mailbox being typed with the contact's name and address
+            let start = address::current_entry_start(field_value);
+            let mailbox = address::Address {
+                name: contact.name.clone(),
+                email: contact.email.clone(),
+            };
+            field_value.truncate(start);
+            if start > 0 && !field_value.ends_with(' ') {
+                field_value.push(' ');
             }
+            field_value.push_str(&format!("{}, ", mailbox));
         }
         self.state.autocomplete.visible = false;
         self.state.autocomplete.suggestions.clear();
@@ -387,3 +692,148 @@ impl App {
         self.state.autocomplete.visible = false;
     }
 }
+
+/// Sign or encrypt as asked and hand the message to `account`'s SMTP server
+/// (or send command). Needs nothing from the app, so scheduled sends can run
+/// it in a background task. Returns the message as sent.
+pub(crate) async fn transmit(
+    account: &AccountConfig,
+    email: &ComposeEmail,
+    pgp: &PgpConfig,
+) -> Result<ComposeEmail> {
+    // A fresh connection each time, so any account can send
+    let identity = account.identity(email.identity_index);
+    let email = ComposeEmail {
+        protection: resolve_protection(email, &identity.email, pgp)?,
+        ..email.clone()
+    };
+    let smtp = match account.smtp.transport {
+        // A local command does its own authentication; no credentials are stored
+        SendTransport::Command => SmtpClient::with_command(
+            &account.smtp,
+            &identity.email,
+            identity.display_name.as_deref(),
+        )?,
+        SendTransport::Smtp => {
+            let password = smtp_password(account).await?;
+            SmtpClient::new_with_auth(
+                &account.smtp,
+                account.username_or_email(),
+                &password,
+                &identity.email,
+                identity.display_name.as_deref(),
+                &account.auth,
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to connect to SMTP: {}", e))?
+        }
+    };
+
+    smtp.send(&email)
+        .await
+        .map_err(|e| anyhow!("Failed to send: {}", e))?;
+    Ok(email)
+}
+
+/// Get the SMTP password or a fresh OAuth2 access token for an account
+async fn smtp_password(account: &AccountConfig) -> Result<String> {
+    let credentials = CredentialStore::new(&account.email);
+
+    // Get credentials based on auth method
+    let password = match &account.auth {
+        AuthMethod::Password => credentials
+            .get_smtp_password()
+            .map_err(|e| anyhow!("Failed to get SMTP password: {}", e))?,
+        AuthMethod::OAuth2 { client_id, .. } => {
+            // For OAuth2, get the stored refresh token and exchange for a fresh access token
+            let refresh_token = credentials.get_oauth2_refresh_token().map_err(|e| {
+                anyhow!(
+                    "OAuth2 refresh token not found: {}. Please re-authenticate.",
+                    e
+                )
+            })?;
+
+            crate::oauth2::get_access_token(client_id, &refresh_token)
+                .await
+                .map_err(|e| {
+                    anyhow!(
+                        "Failed to refresh OAuth2 access token: {}. Please re-authenticate.",
+                        e
+                    )
+                })?
+        }
+    };
+    Ok(password)
+}
+
+/// Check that a message has the fields needed to send it
+pub(super) fn validate_for_send(email: &ComposeEmail) -> std::result::Result<(), String> {
+    if address::parse_entries(&email.to).is_empty() {
+        return Err("Recipient is required".to_string());
+    }
+    address::parse_list(&email.to).map_err(|e| format!("To: {}", e))?;
+    address::parse_list(&email.cc).map_err(|e| format!("Cc: {}", e))?;
+    if email.subject.is_empty() {
+        return Err("Subject is required".to_string());
+    }
+    Ok(())
+}
+
+/// Recipients with no usable OpenPGP public key
+fn recipients_without_key(backend: &dyn PgpBackend, email: &ComposeEmail) -> Vec<String> {
+    address::valid_addresses(&email.to)
+        .into_iter()
+        .chain(address::valid_addresses(&email.cc))
+        .map(|a| a.email)
+        .filter(|email| !backend.has_public_key(email))
+        .collect()
+}
+
+/// Protection a message actually goes out with
+///
+/// S/MIME needs an identity for `sender`. OpenPGP encryption requested in the
+/// composer needs a key for every recipient; with `auto_encrypt` a message is
+/// encrypted whenever all recipients have one.
+fn resolve_protection(
+    email: &ComposeEmail,
+    sender: &str,
+    config: &PgpConfig,
+) -> Result<Protection> {
+    let mut protection = email.protection;
+    if protection.scheme == Scheme::Smime && !protection.is_off() {
+        if !smime::backend().is_some_and(|b| b.has_identity(sender)) {
+            bail!("No S/MIME identity configured for {}", sender);
+        }
+        return Ok(protection);
+    }
+    let Some(backend) = pgp::backend() else {
+        if !protection.is_off() {
+            bail!("OpenPGP support is turned off");
+        }
+        return Ok(protection);
+    };
+    if protection.encrypt || config.auto_encrypt {
+        let missing = recipients_without_key(backend, email);
+        if protection.encrypt && !missing.is_empty() {
+            bail!("No OpenPGP key for {}", missing.join(", "));
+        }
+        protection.encrypt = missing.is_empty();
+    }
+    Ok(protection)
+}
+
+/// Recipient domains outside `internal_domains` (empty if no domains are configured)
+pub(crate) fn external_domains(email: &ComposeEmail, internal_domains: &[String]) -> Vec<String> {
+    if internal_domains.is_empty() {
+        return Vec::new();
+    }
+    let mut domains: Vec<String> = address::valid_addresses(&email.to)
+        .into_iter()
+        .chain(address::valid_addresses(&email.cc))
+        .filter(|a| !a.is_in_domains(internal_domains))
+        .map(|a| a.domain().to_lowercase())
+        .collect();
+    domains.sort();
+    domains.dedup();
+    domains
+}
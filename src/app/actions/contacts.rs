@@ -1,9 +1,15 @@
-//! Contact management actions
+//! Contact management actions (editing, vCard import and export)
+
+use std::path::PathBuf;
 
 use crate::app::state::{ComposerField, ContactEditState, View};
+use crate::config::expand_home;
+use crate::contacts::{Contact, vcard};
+use crate::mail::ImapCommand;
 use crate::mail::types::ComposeEmail;
 
 use super::super::App;
+use super::attachment::unique_save_path;
 
 impl App {
     /// Open the contacts view
@@ -14,6 +20,7 @@ impl App {
                 self.state.contacts.selected = 0;
                 self.state.contacts.scroll_offset = 0;
                 self.state.contacts.editing = None;
+                self.state.contacts.marked.clear();
                 self.state.view = View::Contacts;
             }
             Err(e) => {
@@ -56,33 +63,78 @@ impl App {
                 self.state.contacts.selected = self.state.contacts.list.len().saturating_sub(1);
             }
             self.state.set_status("Contact deleted");
+            self.request_carddav_sync();
         }
     }
 
-    /// Start editing the selected contact's name
+    /// Start editing the selected contact
     pub(crate) fn contacts_start_edit(&mut self) {
         if let Some(contact) = self.state.contacts.list.get(self.state.contacts.selected) {
             self.state.contacts.editing = Some(ContactEditState {
                 contact_id: contact.id,
                 name: contact.name.clone().unwrap_or_default(),
+                other_emails: contact.other_emails.join(", "),
+                phone: contact.phone.clone().unwrap_or_default(),
+                organization: contact.organization.clone().unwrap_or_default(),
+                ..Default::default()
             });
         }
     }
 
-    /// Save the contact name edit
+    /// Save the contact edit
     pub(crate) async fn contacts_save_edit(&mut self) {
-        if let Some(edit) = self.state.contacts.editing.take() {
-            if let Err(e) = self.contacts.update_name(edit.contact_id, &edit.name).await {
+        let Some(ref edit) = self.state.contacts.editing else {
+            return;
+        };
+        let Some(contact) = self
+            .state
+            .contacts
+            .list
+            .iter()
+            .find(|c| c.id == edit.contact_id)
+        else {
+            self.state.contacts.editing = None;
+            return;
+        };
+
+        let mut other_emails: Vec<String> = Vec::new();
+        for address in edit
+            .other_emails
+            .split([',', ' '])
+            .filter(|a| !a.is_empty())
+        {
+            if !address.contains('@') {
                 self.state
-                    .set_error(format!("Failed to update contact: {}", e));
+                    .set_error(format!("Invalid email address: {}", address));
                 return;
             }
-            // Refresh the list
-            if let Ok(contacts) = self.contacts.get_all().await {
-                self.state.contacts.list = contacts;
+            if !contact.email.eq_ignore_ascii_case(address)
+                && !other_emails.iter().any(|e| e.eq_ignore_ascii_case(address))
+            {
+                other_emails.push(address.to_string());
             }
-            self.state.set_status("Contact updated");
         }
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let updated = Contact {
+            name: non_empty(&edit.name),
+            other_emails,
+            phone: non_empty(&edit.phone),
+            organization: non_empty(&edit.organization),
+            ..contact.clone()
+        };
+
+        self.state.contacts.editing = None;
+        if let Err(e) = self.contacts.update_details(&updated).await {
+            self.state
+                .set_error(format!("Failed to update contact: {}", e));
+            return;
+        }
+        // Refresh the list
+        if let Ok(contacts) = self.contacts.get_all().await {
+            self.state.contacts.list = contacts;
+        }
+        self.state.set_status("Contact updated");
+        self.request_carddav_sync();
     }
 
     /// Cancel the contact edit
@@ -95,24 +147,203 @@ impl App {
         if let Some(contact) = self.state.contacts.list.get(self.state.contacts.selected) {
             let mut email = ComposeEmail::new();
             email.to = contact.email.clone();
-            self.state.view = View::Composer {
-                email,
-                field: ComposerField::Subject, // Skip To since it's filled
-            };
+            self.open_composer(email, ComposerField::Subject); // Skip To since it's filled
         }
     }
 
     /// Handle character input in contacts edit mode
     pub(crate) fn contacts_edit_char(&mut self, c: char) {
         if let Some(ref mut edit) = self.state.contacts.editing {
-            edit.name.push(c);
+            edit.active_mut().push(c);
         }
     }
 
     /// Handle backspace in contacts edit mode
     pub(crate) fn contacts_edit_backspace(&mut self) {
         if let Some(ref mut edit) = self.state.contacts.editing {
-            edit.name.pop();
+            edit.active_mut().pop();
+        }
+    }
+
+    /// Move to the next field of the edit popup
+    pub(crate) fn contacts_next_field(&mut self) {
+        if let Some(ref mut edit) = self.state.contacts.editing {
+            edit.field = edit.field.next();
+        }
+    }
+
+    /// Move to the previous field of the edit popup
+    pub(crate) fn contacts_prev_field(&mut self) {
+        if let Some(ref mut edit) = self.state.contacts.editing {
+            edit.field = edit.field.prev();
         }
     }
+
+    /// Mark or unmark the selected contact for export and move down
+    pub(crate) fn contacts_toggle_mark(&mut self) {
+        let Some(contact) = self.state.contacts.list.get(self.state.contacts.selected) else {
+            return;
+        };
+        let id = contact.id;
+        if !self.state.contacts.marked.remove(&id) {
+            self.state.contacts.marked.insert(id);
+        }
+        self.contacts_move_down();
+    }
+
+    /// Export the marked contacts (or the selected one) to the downloads folder
+    pub(crate) async fn contacts_export_selection(&mut self) {
+        let contacts: Vec<Contact> = if self.state.contacts.marked.is_empty() {
+            self.state
+                .contacts
+                .list
+                .get(self.state.contacts.selected)
+                .cloned()
+                .into_iter()
+                .collect()
+        } else {
+            self.state
+                .contacts
+                .list
+                .iter()
+                .filter(|c| self.state.contacts.marked.contains(&c.id))
+                .cloned()
+                .collect()
+        };
+        if contacts.is_empty() {
+            return;
+        }
+        let filename = match contacts.as_slice() {
+            [contact] => format!("{}.vcf", contact.display_name().replace(['/', '\\'], "_")),
+            _ => "contacts.vcf".to_string(),
+        };
+        let path = unique_save_path(&downloads_dir(), &filename);
+        if self.write_vcard(&contacts, path).await {
+            self.state.contacts.marked.clear();
+        }
+    }
+
+    /// Export the whole address book to `path` (default: the downloads folder)
+    pub(crate) async fn export_contacts(&mut self, path: &str) {
+        let contacts = match self.contacts.get_all().await {
+            Ok(contacts) => contacts,
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to load contacts: {}", e));
+                return;
+            }
+        };
+        let path = if path.is_empty() {
+            unique_save_path(&downloads_dir(), "contacts.vcf")
+        } else {
+            expand_home(path)
+        };
+        self.write_vcard(&contacts, path).await;
+    }
+
+    async fn write_vcard(&mut self, contacts: &[Contact], path: PathBuf) -> bool {
+        match tokio::fs::write(&path, vcard::export(contacts)).await {
+            Ok(()) => {
+                let noun = if contacts.len() == 1 {
+                    "contact"
+                } else {
+                    "contacts"
+                };
+                self.state.set_status(format!(
+                    "Exported {} {} to {}",
+                    contacts.len(),
+                    noun,
+                    path.display()
+                ));
+                true
+            }
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to write {}: {}", path.display(), e));
+                false
+            }
+        }
+    }
+
+    /// Import a .vcf file into the address book
+    pub(crate) async fn import_contacts_file(&mut self, path: &str) {
+        if path.is_empty() {
+            self.state.set_error("Usage: :import-contacts <file.vcf>");
+            return;
+        }
+        let path = expand_home(path);
+        match tokio::fs::read(&path).await {
+            Ok(data) => self.import_vcard(&data).await,
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Fetch the selected attachment and import it as a vCard
+    pub(crate) async fn import_vcard_attachment(&mut self) {
+        let View::Reader { uid } = self.state.view else {
+            return;
+        };
+        if !self.state.reader.show_attachments {
+            return;
+        }
+        let selected = self.state.reader.attachment_selected;
+        let Some(attachment) = self.state.reader.attachments.get(selected) else {
+            return;
+        };
+        let is_vcard = attachment.mime_type.eq_ignore_ascii_case("text/vcard")
+            || attachment.mime_type.eq_ignore_ascii_case("text/x-vcard")
+            || attachment.filename.to_lowercase().ends_with(".vcf");
+        if !is_vcard {
+            self.state
+                .set_error(format!("{} is not a vCard", attachment.filename));
+            return;
+        }
+
+        self.state.reader.pending_contact_import = Some(selected);
+        self.state.status.loading = true;
+        self.dirty = true;
+        let folder = self.folder_for_uid(uid);
+        self.accounts
+            .send_command(ImapCommand::FetchAttachment {
+                uid,
+                folder,
+                attachment_index: selected,
+            })
+            .await
+            .ok();
+    }
+
+    /// Add the cards in `data` to the address book and report what changed
+    pub(crate) async fn import_vcard(&mut self, data: &[u8]) {
+        let cards = vcard::parse(&String::from_utf8_lossy(data));
+        if cards.is_empty() {
+            self.state
+                .set_error("No contacts with an email address found");
+            return;
+        }
+        match self.contacts.import(&cards).await {
+            Ok(summary) => {
+                self.state.set_status(format!(
+                    "Imported contacts: {} added, {} updated",
+                    summary.added, summary.updated
+                ));
+                if matches!(self.state.view, View::Contacts)
+                    && let Ok(contacts) = self.contacts.get_all().await
+                {
+                    self.state.contacts.list = contacts;
+                }
+            }
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to import contacts: {}", e)),
+        }
+    }
+}
+
+fn downloads_dir() -> PathBuf {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
 }
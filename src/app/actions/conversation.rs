@@ -0,0 +1,284 @@
+//! Conversation reader (every message of a thread stacked in the reader)
+
+use std::collections::BTreeMap;
+
+use crate::app::state::{Conversation, ConversationMessage, View};
+use crate::mail::ImapCommand;
+use crate::mail::types::{EmailBody, EmailFlags, EmailHeader};
+
+use super::super::App;
+
+impl App {
+    /// Stack the thread of the open (or selected) message, or go back to the
+    /// single message
+    pub(super) async fn toggle_conversation(&mut self) {
+        if self.state.reader.conversation.take().is_some() {
+            return;
+        }
+        let focus = match self.state.view {
+            View::Reader { uid } => self.state.emails.iter().find(|e| e.uid == uid),
+            View::Inbox => self.state.current_email_from_thread(),
+            _ => None,
+        };
+        let Some(focus) = focus.cloned() else {
+            return;
+        };
+        let Some(thread) = self
+            .state
+            .thread
+            .threads
+            .iter()
+            .find(|t| {
+                t.email_indices.iter().any(|&i| {
+                    self.state
+                        .emails
+                        .get(i)
+                        .is_some_and(|e| e.uid == focus.uid && e.folder == focus.folder)
+                })
+            })
+            .cloned()
+        else {
+            return;
+        };
+        if thread.len() < 2 {
+            self.state.set_status("This message has no replies");
+            return;
+        }
+
+        let emails = thread
+            .email_indices
+            .iter()
+            .filter_map(|&i| self.state.emails.get(i));
+        let mut messages = conversation_messages(emails, focus.uid, &self.state.folder.current);
+
+        // Bodies from the cache; the rest in one request per folder
+        for message in &mut messages {
+            let cache_key = self.email_cache_key(&message.email);
+            if let Ok(Some(body)) = self
+                .cache
+                .get_email_body(&cache_key, message.email.uid)
+                .await
+            {
+                message.set_body(body);
+            }
+        }
+        for command in fetch_missing_bodies(&messages) {
+            if let Err(e) = self.accounts.send_command(command).await {
+                tracing::debug!("Failed to send FetchBodies command: {}", e);
+            }
+        }
+
+        // Mark what is shown unfolded as read
+        for message in messages.iter().filter(|m| m.expanded && !m.email.is_seen()) {
+            if let Err(e) = self
+                .accounts
+                .send_command(ImapCommand::SetFlag {
+                    uid: message.email.uid,
+                    flag: EmailFlags::SEEN,
+                    folder: message.folder.clone(),
+                })
+                .await
+            {
+                tracing::debug!("Failed to send SetFlag command: {}", e);
+            }
+        }
+
+        let start = start_message(&messages, focus.uid);
+        if matches!(self.state.view, View::Inbox) {
+            self.state.reader.show_quotes = !self.config.ui.collapse_quotes;
+        }
+        let mut conversation = Conversation {
+            messages,
+            selected: 0,
+            scroll: 0,
+        };
+        conversation.select(start, self.state.reader.show_quotes);
+        self.state.reader.conversation = Some(conversation);
+        self.state.sync_conversation_focus(true);
+    }
+
+    /// Scroll the conversation by `delta` rows
+    pub(super) fn conversation_scroll(&mut self, delta: i32) {
+        let show_quotes = self.state.reader.show_quotes;
+        if let Some(conversation) = self.state.reader.conversation.as_mut() {
+            conversation.scroll_by(delta, show_quotes);
+        }
+        self.state.sync_conversation_focus(false);
+    }
+
+    /// Jump to the header of the next (or previous) message
+    pub(super) fn conversation_step(&mut self, forward: bool) {
+        let show_quotes = self.state.reader.show_quotes;
+        let Some(conversation) = self.state.reader.conversation.as_mut() else {
+            return;
+        };
+        let target = if forward {
+            (conversation.selected + 1).min(conversation.messages.len().saturating_sub(1))
+        } else {
+            conversation.selected.saturating_sub(1)
+        };
+        conversation.select(target, show_quotes);
+        self.state.sync_conversation_focus(false);
+    }
+
+    /// Expand or collapse the selected message
+    pub(super) fn conversation_toggle_message(&mut self) {
+        let show_quotes = self.state.reader.show_quotes;
+        if let Some(conversation) = self.state.reader.conversation.as_mut() {
+            conversation.toggle_selected(show_quotes);
+        }
+    }
+
+    /// Put a fetched body into the conversation (returns true if it was
+    /// waiting for it)
+    pub(crate) fn conversation_body_fetched(
+        &mut self,
+        uid: u32,
+        folder: &str,
+        body: &EmailBody,
+    ) -> bool {
+        self.state
+            .reader
+            .conversation
+            .as_mut()
+            .is_some_and(|c| c.body_fetched(uid, folder, body))
+    }
+}
+
+/// The thread's messages oldest first: unread ones (and the one opened)
+/// expanded, read ones collapsed
+fn conversation_messages<'a>(
+    emails: impl Iterator<Item = &'a EmailHeader>,
+    focus: u32,
+    current_folder: &str,
+) -> Vec<ConversationMessage> {
+    let mut messages: Vec<ConversationMessage> = emails
+        .map(|email| {
+            let folder = email
+                .folder
+                .clone()
+                .unwrap_or_else(|| current_folder.to_string());
+            let expanded = !email.is_seen() || email.uid == focus;
+            ConversationMessage::new(email.clone(), folder, expanded)
+        })
+        .collect();
+    messages.sort_by_key(|m| m.email.date);
+    messages
+}
+
+/// Message to start at: the first unread one, else the one opened
+fn start_message(messages: &[ConversationMessage], focus: u32) -> usize {
+    messages
+        .iter()
+        .position(|m| !m.email.is_seen())
+        .or_else(|| messages.iter().position(|m| m.email.uid == focus))
+        .unwrap_or(0)
+}
+
+/// One request per folder for the bodies not found in the cache
+fn fetch_missing_bodies(messages: &[ConversationMessage]) -> Vec<ImapCommand> {
+    let mut missing: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for message in messages.iter().filter(|m| m.body.is_none()) {
+        missing
+            .entry(&message.folder)
+            .or_default()
+            .push(message.email.uid);
+    }
+    missing
+        .into_iter()
+        .map(|(folder, uids)| ImapCommand::FetchBodies {
+            uids,
+            folder: folder.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(uid: u32, date: i64, seen: bool, folder: Option<&str>) -> EmailHeader {
+        EmailHeader {
+            uid,
+            message_id: None,
+            subject: "Plans".to_string(),
+            from_addr: "alice@example.com".to_string(),
+            from_name: None,
+            to_addr: None,
+            cc_addr: None,
+            date,
+            flags: if seen {
+                EmailFlags::SEEN
+            } else {
+                EmailFlags::empty()
+            },
+            has_attachments: false,
+            preview: None,
+            body_cached: false,
+            in_reply_to: None,
+            references: Vec::new(),
+            folder: folder.map(str::to_string),
+            list: None,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn test_conversation_messages() {
+        let emails = [
+            header(3, 30, false, None),
+            header(1, 10, true, None),
+            header(2, 20, true, Some("Sent")),
+            header(4, 40, true, None),
+        ];
+        let messages = conversation_messages(emails.iter(), 4, "INBOX");
+
+        let summary: Vec<(u32, &str, bool)> = messages
+            .iter()
+            .map(|m| (m.email.uid, m.folder.as_str(), m.expanded))
+            .collect();
+        // Oldest first; read ones collapsed except the one opened
+        assert_eq!(
+            summary,
+            vec![
+                (1, "INBOX", false),
+                (2, "Sent", false),
+                (3, "INBOX", true),
+                (4, "INBOX", true),
+            ]
+        );
+        assert_eq!(start_message(&messages, 4), 2);
+
+        let emails = [header(1, 10, true, None), header(2, 20, true, None)];
+        let messages = conversation_messages(emails.iter(), 2, "INBOX");
+        assert_eq!(start_message(&messages, 2), 1);
+    }
+
+    #[test]
+    fn test_fetch_missing_bodies_per_folder() {
+        let emails = [
+            header(1, 10, false, Some("INBOX")),
+            header(2, 20, false, Some("Sent")),
+            header(3, 30, false, Some("INBOX")),
+            header(4, 40, false, Some("Archive")),
+        ];
+        let mut messages = conversation_messages(emails.iter(), 1, "INBOX");
+        // Found in the cache
+        messages[3].set_body(EmailBody::default());
+
+        let fetches: Vec<(String, Vec<u32>)> = fetch_missing_bodies(&messages)
+            .into_iter()
+            .map(|command| match command {
+                ImapCommand::FetchBodies { uids, folder } => (folder, uids),
+                other => panic!("unexpected command: {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            fetches,
+            vec![
+                ("INBOX".to_string(), vec![1, 3]),
+                ("Sent".to_string(), vec![2]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Decrypting encrypted messages once they are opened
+//!
+//! Fetching keeps encrypted mail as ciphertext, so prefetch never runs the
+//! crypto tools (or a passphrase prompt) for messages nobody opened.
+
+use crate::app::DecryptedBody;
+use crate::app::state::View;
+use crate::mail::parser::decrypt_body;
+
+use super::super::App;
+
+impl App {
+    /// Start decrypting the open message and the expanded messages of the
+    /// conversation, if they are still locked
+    pub(crate) fn start_decryption(&mut self) {
+        let mut locked = Vec::new();
+        if let View::Reader { uid } = self.state.view
+            && let Some(raw) = self
+                .state
+                .reader
+                .body
+                .as_ref()
+                .and_then(|b| b.ciphertext.clone())
+        {
+            locked.push((self.cache_key_for_uid(uid), uid, raw));
+        }
+        if let Some(conversation) = self.state.reader.conversation.as_ref() {
+            for message in conversation.messages.iter().filter(|m| m.expanded) {
+                if let Some(raw) = message.body.as_ref().and_then(|b| b.ciphertext.clone()) {
+                    locked.push((self.email_cache_key(&message.email), message.email.uid, raw));
+                }
+            }
+        }
+
+        for (cache_key, uid, raw) in locked {
+            if !self.decrypting.insert((cache_key.clone(), uid)) {
+                continue;
+            }
+            self.state.set_status("Decrypting...");
+            let tx = self.decrypt_tx.clone();
+            tokio::spawn(async move {
+                let Ok(body) = tokio::task::spawn_blocking(move || decrypt_body(&raw)).await else {
+                    return;
+                };
+                let _ = tx
+                    .send(DecryptedBody {
+                        cache_key,
+                        uid,
+                        body,
+                    })
+                    .await;
+            });
+        }
+    }
+
+    /// Show messages decrypted in the background
+    pub(crate) async fn process_decrypted_bodies(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(DecryptedBody {
+            cache_key,
+            uid,
+            body,
+        }) = self.decrypt_rx.try_recv()
+        {
+            self.decrypting.remove(&(cache_key.clone(), uid));
+            // Kept in memory only (decrypted text never goes to disk)
+            if let Err(e) = self.cache.insert_email_body(&cache_key, uid, &body).await {
+                tracing::warn!("Failed to cache decrypted body for uid {}: {}", uid, e);
+            }
+            if self.state.status.message == "Decrypting..." {
+                self.state.set_status("");
+            }
+
+            let open = matches!(self.state.view, View::Reader { uid: current } if current == uid)
+                && self.cache_key_for_uid(uid) == cache_key
+                && self
+                    .state
+                    .reader
+                    .body
+                    .as_ref()
+                    .is_some_and(|b| b.is_locked());
+            if open {
+                self.state.reader.set_body(Some(body.clone()));
+            }
+            let keys: Vec<String> = self
+                .state
+                .reader
+                .conversation
+                .as_ref()
+                .map(|c| {
+                    c.messages
+                        .iter()
+                        .map(|m| self.email_cache_key(&m.email))
+                        .collect()
+                })
+                .unwrap_or_default();
+            if let Some(conversation) = self.state.reader.conversation.as_mut() {
+                for (message, key) in conversation.messages.iter_mut().zip(keys) {
+                    if message.email.uid == uid
+                        && key == cache_key
+                        && message.body.as_ref().is_some_and(|b| b.is_locked())
+                    {
+                        message.set_body(body.clone());
+                    }
+                }
+            }
+            changed = true;
+        }
+        changed
+    }
+}
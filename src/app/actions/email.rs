@@ -2,10 +2,10 @@
 
 use std::time::Instant;
 
-use crate::app::state::{ModalState, View};
+use crate::app::state::{BodyFind, ModalState, View};
 use crate::app::undo::{PendingDeletion, UndoEntry, UndoableAction};
+use crate::mail::ImapCommand;
 use crate::mail::types::EmailFlags;
-use crate::mail::{ImapCommand, group_into_threads};
 
 use super::super::App;
 
@@ -29,6 +29,12 @@ impl App {
             return;
         }
 
+        // Handle send-later queue - edit selected message
+        if matches!(self.state.view, View::Scheduled) {
+            self.scheduled_edit().await;
+            return;
+        }
+
         let email = match &self.state.view {
             View::Inbox => self.state.current_email_from_thread().cloned(),
             _ => None,
@@ -60,6 +66,10 @@ impl App {
             self.state.view = View::Reader { uid };
             self.state.reader.reset_scroll();
             self.state.reader.set_body(None);
+            self.state.reader.conversation = None;
+            // Opened from search results: highlight what matched
+            self.state.reader.find = BodyFind::seeded(&self.state.search.query);
+            self.state.reader.show_quotes = !self.config.ui.collapse_quotes;
 
             // Check local cache first (instant) - use email's folder cache key
             if let Ok(Some(body)) = self.cache.get_email_body(&cache_key, uid).await {
@@ -114,6 +124,10 @@ impl App {
             View::Reader { uid } => {
                 let uid = *uid;
                 self.state.view = View::Inbox;
+                self.state.reader.source = None;
+                self.state.reader.show_images = false;
+                self.state.reader.find = BodyFind::default();
+                self.state.reader.conversation = None;
                 // Try to keep body from cache for smooth transition back to inbox preview
                 // Use email's folder to get the correct cache key
                 let cache_key = self
@@ -142,6 +156,9 @@ impl App {
                     self.state.view = View::Inbox;
                 }
             }
+            View::Scheduled => {
+                self.state.view = View::Inbox;
+            }
             View::AddAccount { .. } => {
                 // Cancel wizard and go back to inbox
                 self.state.view = View::Inbox;
@@ -231,6 +248,12 @@ impl App {
             return;
         }
 
+        // Handle send-later queue - cancel selected message
+        if matches!(self.state.view, View::Scheduled) {
+            self.scheduled_cancel().await;
+            return;
+        }
+
         let uid = match &self.state.view {
             View::Inbox => self.state.current_email_from_thread().map(|e| e.uid),
             View::Reader { uid } => Some(*uid),
@@ -268,7 +291,7 @@ impl App {
 
                 // Optimistic UI update: remove from local state immediately
                 self.state.emails.retain(|e| e.uid != uid);
-                self.state.thread.threads = group_into_threads(&self.state.emails);
+                self.state.rebuild_threads();
                 // Invalidate search cache since threads changed
                 self.state.invalidate_search_cache();
 
@@ -488,4 +511,56 @@ impl App {
             }
         }
     }
+
+    /// Switch the reader and preview between the plain and HTML parts
+    pub(super) fn toggle_html_part(&mut self) {
+        let Some(ref body) = self.state.reader.body else {
+            return;
+        };
+        let (has_text, has_html) = (body.text.is_some(), body.html.is_some());
+        if !has_html {
+            self.state.set_status("No HTML part");
+            return;
+        }
+        if !has_text {
+            self.state.set_status("No plain-text part");
+            return;
+        }
+        let show_html = !self.state.reader.shows_html();
+        self.state.reader.set_show_html(show_html);
+        self.state.set_status(if show_html {
+            "Showing HTML part"
+        } else {
+            "Showing plain-text part"
+        });
+    }
+
+    /// Fold or unfold quoted text and the signature in the reader
+    pub(super) fn toggle_quotes(&mut self) {
+        if !matches!(self.state.view, View::Reader { .. }) {
+            return;
+        }
+        if self.state.reader.conversation_shown() {
+            // Keep the selected message in view as the rows change
+            let reader = &mut self.state.reader;
+            reader.show_quotes = !reader.show_quotes;
+            if let Some(conversation) = reader.conversation.as_mut() {
+                conversation.select(conversation.selected, reader.show_quotes);
+            }
+        } else if let View::Reader { uid } = self.state.view
+            && self.state.reader.body.is_some()
+        {
+            let text = crate::ui::pane_text(&self.state, uid);
+            self.state
+                .reader
+                .toggle_quotes(|reader| crate::ui::reader_layout(reader, text.as_deref()));
+        } else {
+            return;
+        }
+        self.state.set_status(if self.state.reader.show_quotes {
+            "Showing quoted text"
+        } else {
+            "Folding quoted text"
+        });
+    }
 }
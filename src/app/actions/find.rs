@@ -0,0 +1,64 @@
+//! Find in the open message (or in its raw source when that is shown)
+
+use crate::app::state::View;
+
+use super::super::App;
+
+impl App {
+    /// Start typing a search term
+    pub(super) fn find_start(&mut self) {
+        if self.state.reader.source.is_some() {
+            self.source_find_start();
+        } else if self.state.reader.conversation_shown() {
+            self.state
+                .set_status("Find works on single messages (t leaves the conversation)");
+        } else if matches!(self.state.view, View::Reader { .. }) && !self.state.reader.show_images {
+            self.state.reader.find.input = Some(String::new());
+        }
+    }
+
+    /// Search for the typed term from the current line
+    pub(super) fn find_submit(&mut self) {
+        if self.state.reader.source.is_some() {
+            self.source_find_submit();
+            return;
+        }
+        let Some(query) = self.state.reader.find.input.take() else {
+            return;
+        };
+        self.state.reader.find.query = query;
+        self.state.reader.find.seeded = false;
+        if !self.state.reader.find.query.is_empty() {
+            let layout = self.body_layout();
+            let found = self.state.reader.find_in_body(true, true, &layout);
+            self.report_body_find(found);
+        }
+    }
+
+    /// Stop typing, or stop highlighting the search term
+    pub(super) fn find_cancel(&mut self) {
+        if self.state.reader.source.is_some() {
+            self.source_find_cancel();
+        } else if self.state.reader.find.input.take().is_none() {
+            self.state.reader.find.query.clear();
+        }
+    }
+
+    /// Jump to the next (or previous) line containing the search term
+    pub(super) fn find_next(&mut self, forward: bool) {
+        if self.state.reader.source.is_some() {
+            self.source_find(forward);
+        } else if !self.state.reader.find.query.is_empty() {
+            let layout = self.body_layout();
+            let found = self.state.reader.find_in_body(forward, false, &layout);
+            self.report_body_find(found);
+        }
+    }
+
+    fn report_body_find(&mut self, found: bool) {
+        if !found {
+            let query = self.state.reader.find.query.clone();
+            self.state.set_error(format!("Not found: {}", query));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Image viewer in the reader (inline images, image attachments, remote images)
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::LoadedImages;
+use crate::app::state::{RawPurpose, View};
+use crate::graphics::{self, InlineImage};
+use crate::mail::html;
+use crate::mail::parser::extract_images;
+
+use super::super::App;
+
+/// Remote images fetched per message at most
+const MAX_REMOTE_IMAGES: usize = 10;
+
+/// Largest remote image downloaded
+const MAX_REMOTE_BYTES: usize = 10 * 1024 * 1024;
+
+impl App {
+    /// Show or hide the message's images in place of the body
+    pub(super) async fn toggle_images(&mut self) {
+        let View::Reader { uid } = self.state.view else {
+            return;
+        };
+        if self.state.reader.show_images {
+            self.state.reader.show_images = false;
+            return;
+        }
+        if graphics::protocol().is_none() {
+            self.state
+                .set_status("Images are turned off (ui.images = \"off\")");
+            return;
+        }
+        if self.state.reader.images_uid == Some(uid) {
+            self.show_loaded_images();
+            return;
+        }
+        self.request_raw(uid, RawPurpose::Images).await;
+    }
+
+    /// Extract the images from the raw message (and fetch remote ones if
+    /// allowed) off the event loop
+    pub(super) fn load_images(&mut self, uid: u32, raw: Vec<u8>) {
+        let html = self.state.reader.body.as_ref().and_then(|b| b.html.clone());
+        let remote_allowed = self.config.ui.remote_images;
+        let tx = self.images_tx.clone();
+        self.state.set_status("Loading images...");
+        tokio::spawn(async move {
+            let sources = html
+                .as_deref()
+                .and_then(|h| html::render(h, 80))
+                .map(|r| r.images)
+                .unwrap_or_default();
+            let parts = tokio::task::spawn_blocking({
+                let sources = sources.clone();
+                move || {
+                    extract_images(&raw, &sources)
+                        .into_iter()
+                        .map(|(name, data)| InlineImage::new(name, data))
+                        .collect::<Vec<_>>()
+                }
+            })
+            .await
+            .unwrap_or_default();
+            let mut images = parts;
+            if remote_allowed {
+                let fetched = fetch_remote(&sources).await;
+                // Decoding is CPU work too
+                let remote = tokio::task::spawn_blocking(move || {
+                    fetched
+                        .into_iter()
+                        .map(|(name, data)| InlineImage::new(name, data))
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .unwrap_or_default();
+                images.extend(remote);
+            }
+            images.retain(InlineImage::is_image);
+            let _ = tx.send(LoadedImages { uid, images }).await;
+        });
+    }
+
+    /// Take images loaded in the background
+    pub(crate) fn process_loaded_images(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(LoadedImages { uid, images }) = self.images_rx.try_recv() {
+            if !matches!(self.state.view, View::Reader { uid: current } if current == uid) {
+                continue;
+            }
+            self.state.reader.images = images.into_iter().map(Arc::new).collect();
+            self.state.reader.images_uid = Some(uid);
+            self.show_loaded_images();
+            changed = true;
+        }
+        changed
+    }
+
+    fn show_loaded_images(&mut self) {
+        let count = self.state.reader.images.len();
+        if count == 0 {
+            let hint = if self.config.ui.remote_images {
+                ""
+            } else {
+                " (remote images are off)"
+            };
+            self.state
+                .set_status(format!("No images in this message{}", hint));
+            return;
+        }
+        self.state.reader.show_images = true;
+        self.state.reader.image_selected = self.state.reader.image_selected.min(count - 1);
+        self.state.set_status("");
+    }
+
+    /// Open the shown image with the system viewer
+    pub(super) async fn open_image(&mut self) {
+        let Some(image) = self
+            .state
+            .reader
+            .images
+            .get(self.state.reader.image_selected)
+            .cloned()
+        else {
+            return;
+        };
+        let temp_dir = std::env::temp_dir().join("bltz_attachments");
+        let path = temp_dir.join(&image.name);
+        let result = tokio::task::spawn_blocking(move || -> Result<(), String> {
+            std::fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to save: {}", e))?;
+            std::fs::write(&path, &image.data).map_err(|e| format!("Failed to save: {}", e))?;
+            open::that(&path).map_err(|e| format!("Failed to open: {}", e))
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {
+                let name = &self.state.reader.images[self.state.reader.image_selected].name;
+                self.state.set_status(format!("Opened {}", name));
+            }
+            Ok(Err(error)) => self.state.set_error(error),
+            Err(error) => self
+                .state
+                .set_error(format!("Image open task failed: {}", error)),
+        }
+    }
+}
+
+/// Download the http(s) images the HTML part refers to, as (name, data)
+async fn fetch_remote(sources: &[String]) -> Vec<(String, Vec<u8>)> {
+    let Ok(client) = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+    else {
+        return Vec::new();
+    };
+    let mut images = Vec::new();
+    let remote = sources
+        .iter()
+        .filter(|src| src.starts_with("https://") || src.starts_with("http://"))
+        .take(MAX_REMOTE_IMAGES);
+    for src in remote {
+        let response = match client.get(src).send().await {
+            Ok(r) if r.status().is_success() => r,
+            Ok(r) => {
+                tracing::debug!("Remote image {}: HTTP {}", src, r.status());
+                continue;
+            }
+            Err(e) => {
+                tracing::debug!("Remote image {}: {}", src, e);
+                continue;
+            }
+        };
+        if response
+            .content_length()
+            .is_some_and(|len| len as usize > MAX_REMOTE_BYTES)
+        {
+            continue;
+        }
+        let Ok(data) = response.bytes().await else {
+            continue;
+        };
+        if data.len() > MAX_REMOTE_BYTES {
+            continue;
+        }
+        let name = reqwest::Url::parse(src)
+            .ok()
+            .and_then(|url| {
+                url.path_segments()
+                    .and_then(|mut s| s.next_back().map(str::to_string))
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "image".to_string());
+        images.push((name, data.to_vec()));
+    }
+    images
+}
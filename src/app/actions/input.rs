@@ -9,12 +9,50 @@ use super::super::App;
 
 impl App {
     pub(crate) async fn handle_char(&mut self, c: char) {
+        // Handle send-later prompt input
+        if matches!(self.state.view, View::Composer { .. })
+            && let Some(ref mut input) = self.state.composer.schedule_input
+        {
+            input.push(c);
+            return;
+        }
+
         // Handle contacts edit input
         if matches!(self.state.view, View::Contacts) && self.state.contacts.editing.is_some() {
             self.contacts_edit_char(c);
             return;
         }
 
+        // Handle link label input
+        if self.state.reader.link_hint.is_some() {
+            self.link_hint_char(c);
+            return;
+        }
+
+        // Handle pipe command input
+        if let Some(ref mut prompt) = self.state.pipe.prompt {
+            prompt.input.push(c);
+            return;
+        }
+
+        // Handle source view search input
+        if self
+            .state
+            .reader
+            .source
+            .as_ref()
+            .is_some_and(|s| s.find_input.is_some())
+        {
+            self.source_find_char(c);
+            return;
+        }
+
+        // Handle find-in-message input
+        if let Some(ref mut input) = self.state.reader.find.input {
+            input.push(c);
+            return;
+        }
+
         // Handle command input
         if let ModalState::Command {
             input,
@@ -87,12 +125,50 @@ impl App {
     }
 
     pub(crate) async fn handle_backspace(&mut self) {
+        // Handle send-later prompt backspace
+        if matches!(self.state.view, View::Composer { .. })
+            && let Some(ref mut input) = self.state.composer.schedule_input
+        {
+            input.pop();
+            return;
+        }
+
         // Handle contacts edit backspace
         if matches!(self.state.view, View::Contacts) && self.state.contacts.editing.is_some() {
             self.contacts_edit_backspace();
             return;
         }
 
+        // Handle link label backspace
+        if self.state.reader.link_hint.is_some() {
+            self.link_hint_backspace();
+            return;
+        }
+
+        // Handle pipe command backspace
+        if let Some(ref mut prompt) = self.state.pipe.prompt {
+            prompt.input.pop();
+            return;
+        }
+
+        // Handle source view search backspace
+        if self
+            .state
+            .reader
+            .source
+            .as_ref()
+            .is_some_and(|s| s.find_input.is_some())
+        {
+            self.source_find_backspace();
+            return;
+        }
+
+        // Handle find-in-message backspace
+        if let Some(ref mut input) = self.state.reader.find.input {
+            input.pop();
+            return;
+        }
+
         // Handle command backspace
         if let ModalState::Command {
             input, completion, ..
@@ -0,0 +1,90 @@
+//! Link hints in the reader (pick a footnoted link by number, open or copy it)
+
+use crate::app::state::{LinkHint, View};
+use crate::clipboard;
+
+use super::super::App;
+
+impl App {
+    /// Start picking a link, or follow the label typed so far
+    pub(super) fn link_hint_start(&mut self, copy: bool) {
+        if !matches!(self.state.view, View::Reader { .. }) {
+            return;
+        }
+        if let Some(hint) = self.state.reader.link_hint.take() {
+            match hint.label.parse::<usize>() {
+                Ok(n) if (1..=self.state.reader.links.len()).contains(&n) => {
+                    self.follow_link(n - 1, hint.copy)
+                }
+                // Nothing typed yet
+                _ => self.state.reader.link_hint = Some(hint),
+            }
+            return;
+        }
+        match self.state.reader.links.len() {
+            0 => self.state.set_status("No links in this message"),
+            // Nothing to choose between
+            1 => self.follow_link(0, copy),
+            n => {
+                self.state.reader.link_hint = Some(LinkHint {
+                    label: String::new(),
+                    copy,
+                });
+                let verb = if copy { "copy" } else { "open" };
+                self.state
+                    .set_status(format!("Link to {} (1-{}):", verb, n));
+            }
+        }
+    }
+
+    /// Type a digit of the link label, following it once no longer label can match
+    pub(super) fn link_hint_char(&mut self, c: char) {
+        let count = self.state.reader.links.len();
+        let Some(ref mut hint) = self.state.reader.link_hint else {
+            return;
+        };
+        if hint.label.is_empty() && c == '0' {
+            return;
+        }
+        hint.label.push(c);
+        let Ok(n) = hint.label.parse::<usize>() else {
+            return;
+        };
+        if n > count {
+            hint.label.pop();
+            return;
+        }
+        if n * 10 > count {
+            let copy = hint.copy;
+            self.state.reader.link_hint = None;
+            self.follow_link(n - 1, copy);
+        }
+    }
+
+    pub(super) fn link_hint_backspace(&mut self) {
+        if let Some(ref mut hint) = self.state.reader.link_hint {
+            hint.label.pop();
+        }
+    }
+
+    pub(super) fn link_hint_cancel(&mut self) {
+        self.state.reader.link_hint = None;
+    }
+
+    fn follow_link(&mut self, index: usize, copy: bool) {
+        let Some(link) = self.state.reader.links.get(index).cloned() else {
+            return;
+        };
+        if copy {
+            self.pending_escape = clipboard::copy(&link.url);
+            self.state.set_status(format!("Copied {}", link.url));
+        } else {
+            match open::that(&link.url) {
+                Ok(()) => self.state.set_status(format!("Opened {}", link.url)),
+                Err(e) => self
+                    .state
+                    .set_error(format!("Failed to open {}: {}", link.url, e)),
+            }
+        }
+    }
+}
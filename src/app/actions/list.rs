@@ -0,0 +1,137 @@
+//! Mailing-list actions (unsubscribe, list archive, group-by-list view)
+
+use crate::app::UnsubscribeResult;
+use crate::app::state::{ComposerField, ModalState, View, ViewMode};
+use crate::command::{CommandResult, PendingCommand};
+use crate::mail::list::{self, Unsubscribe};
+use crate::mail::types::{EmailHeader, ListInfo};
+
+use super::super::App;
+
+impl App {
+    /// The selected email and its list headers, or an error if it has none
+    fn current_list_email(&mut self) -> Option<(EmailHeader, ListInfo)> {
+        let Some((_, email)) = self.get_current_email_for_compose() else {
+            self.state.set_error("No email selected");
+            return None;
+        };
+        match email.list.clone() {
+            Some(list) => Some((email, list)),
+            None => {
+                self.state.set_error("Not a mailing-list message");
+                None
+            }
+        }
+    }
+
+    /// Unsubscribe from the selected email's list
+    ///
+    /// One-click asks for confirmation first since it takes effect at once;
+    /// a mailto: unsubscribe opens the composer so the message can be reviewed.
+    pub(super) async fn unsubscribe(&mut self) {
+        let Some((email, list)) = self.current_list_email() else {
+            return;
+        };
+        let name = list
+            .label()
+            .or(list.id.as_deref())
+            .unwrap_or("this list")
+            .to_string();
+
+        match list::unsubscribe_method(&list) {
+            Some(Unsubscribe::OneClick(uri)) => {
+                self.state.modal = ModalState::Command {
+                    input: String::new(),
+                    result: Some(CommandResult::Success(format!(
+                        "Unsubscribe from {}? (y/N)",
+                        name
+                    ))),
+                    pending: Some(PendingCommand::Unsubscribe { list: name, uri }),
+                    completion: None,
+                };
+            }
+            Some(Unsubscribe::Mail(message)) => {
+                let mut message = *message;
+                // Unsubscribe from the address that is subscribed
+                message.identity_index = self.identity_for_reply(&email).await;
+                self.open_composer(message, ComposerField::Body);
+                self.state
+                    .set_status(format!("Send this message to unsubscribe from {}", name));
+            }
+            Some(Unsubscribe::Web(uri)) => self.open_in_browser(&uri),
+            None => self
+                .state
+                .set_error(format!("{} has no unsubscribe link", name)),
+        }
+    }
+
+    /// Send a confirmed one-click unsubscribe request in the background
+    pub(super) fn one_click_unsubscribe(&mut self, name: &str, uri: &str) {
+        self.state.status.loading = true;
+        self.state
+            .set_status(format!("Unsubscribing from {}...", name));
+        let tx = self.unsubscribe_tx.clone();
+        let list = name.to_string();
+        let uri = uri.to_string();
+        tokio::spawn(async move {
+            let result = list::one_click(&uri).await.map_err(|e| format!("{:#}", e));
+            let _ = tx.send(UnsubscribeResult { list, result }).await;
+        });
+    }
+
+    /// Report one-click unsubscribes that finished
+    pub(crate) fn process_unsubscribe_results(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(UnsubscribeResult { list, result }) = self.unsubscribe_rx.try_recv() {
+            self.state.status.loading = false;
+            match result {
+                Ok(()) => self.state.set_status(format!("Unsubscribed from {}", list)),
+                Err(e) => self.state.set_error(e),
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Open the List-Archive of the selected email
+    pub(super) fn open_list_archive(&mut self) {
+        let Some((_, list)) = self.current_list_email() else {
+            return;
+        };
+        match list.archive {
+            Some(uri) => self.open_in_browser(&uri),
+            None => self.state.set_error("This list has no archive link"),
+        }
+    }
+
+    /// Toggle the view that shows only list mail, grouped by list
+    pub(super) fn toggle_lists_view(&mut self) {
+        if !matches!(self.state.view, View::Inbox) || self.state.modal.is_active() {
+            return;
+        }
+        self.state.toggle_view_mode(ViewMode::Lists);
+        if self.state.is_lists_view() {
+            self.state.set_status("Showing mailing lists");
+        } else {
+            self.state.set_status("Showing all emails");
+        }
+    }
+
+    /// Open a URI from the list headers; only web links, since the headers
+    /// come from the sender
+    fn open_in_browser(&mut self, uri: &str) {
+        if !list::is_web(uri) {
+            self.state.set_status(format!(
+                "Not opening {}: only http(s) links are opened",
+                uri
+            ));
+            return;
+        }
+        match open::that(uri) {
+            Ok(()) => self.state.set_status(format!("Opened {}", uri)),
+            Err(e) => self
+                .state
+                .set_error(format!("Failed to open {}: {}", uri, e)),
+        }
+    }
+}
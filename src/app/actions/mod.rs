@@ -9,29 +9,58 @@
 //! - `command`: Command mode operations
 //! - `wizard`: Add account wizard
 //! - `contacts`: Contact management
+//! - `carddav`: Background CardDAV address book sync
 //! - `ai`: AI-powered features (summarization, polish)
 //! - `attachment`: Attachment viewing and saving
+//! - `images`: Image viewer in the reader
+//! - `links`: Link hints in the reader (open or copy by number)
+//! - `list`: Mailing-list actions (unsubscribe, archive, grouping)
+//! - `calendar`: Calendar invitations (answering, conflict check)
+//! - `source`: Raw message source view (full headers, search, save)
+//! - `find`: Find in the open message
+//! - `conversation`: Conversation reader (a whole thread stacked)
+//! - `pipe`: Piping messages and threads to shell commands
+//! - `raw`: Raw message retrieval (cache or IMAP)
+//! - `send`: Undo-send window and send-later queue
+//! - `template`: Message templates
 
 mod ai;
 mod attachment;
+mod calendar;
+mod carddav;
 mod command;
 mod compose;
 mod contacts;
+mod conversation;
+mod decrypt;
 mod email;
+mod find;
+mod images;
 mod input;
+mod links;
+mod list;
 mod navigation;
+mod pipe;
 mod prefetch;
+mod raw;
+mod send;
+mod sort;
+mod source;
+mod template;
 mod undo;
 mod wizard;
 
 use anyhow::Result;
 
-use crate::app::state::{ModalState, View};
+use crate::app::state::{ModalState, View, ViewMode};
 use crate::input::Action;
 use crate::mail::ImapCommand;
+use crate::mail::calendar::PartStat;
 
 use super::App;
 
+pub(crate) use pipe::{InteractivePipe, PipeJob};
+
 impl App {
     pub(crate) async fn handle_action(&mut self, action: Action) -> Result<()> {
         match action {
@@ -95,7 +124,7 @@ impl App {
             // Starred view toggle
             Action::ViewStarred => {
                 if matches!(self.state.view, View::Inbox) && !self.state.modal.is_active() {
-                    self.state.toggle_view_mode();
+                    self.state.toggle_view_mode(ViewMode::Starred);
                     if self.state.is_starred_view() {
                         self.state.set_status("Showing starred emails");
                     } else {
@@ -108,6 +137,8 @@ impl App {
             Action::Search => {
                 if matches!(self.state.view, View::Inbox) && !self.state.modal.is_active() {
                     self.state.modal = ModalState::Search;
+                } else if matches!(self.state.view, View::Reader { .. }) {
+                    self.find_start();
                 } else if !matches!(self.state.view, View::Inbox) {
                     self.state
                         .set_error("Search is only available in inbox view");
@@ -133,7 +164,7 @@ impl App {
                 }
             }
             Action::ExecuteCommand => {
-                self.execute_command();
+                self.execute_command().await;
             }
             Action::ConfirmCommand => {
                 self.confirm_pending_command().await;
@@ -145,16 +176,49 @@ impl App {
                 self.handle_tab_completion();
             }
 
+            // Mailing lists
+            Action::Unsubscribe => self.unsubscribe().await,
+            Action::ReplyToList => self.start_reply_to_list().await,
+            Action::ViewLists => self.toggle_lists_view(),
+
+            // Calendar invitations
+            Action::AcceptInvite => self.respond_to_invite(PartStat::Accepted).await,
+            Action::TentativeInvite => self.respond_to_invite(PartStat::Tentative).await,
+            Action::DeclineInvite => self.respond_to_invite(PartStat::Declined).await,
+
             // Composer
             Action::Reply => self.start_reply().await,
             Action::ReplyAll => self.start_reply_all().await,
-            Action::Forward => self.start_forward().await,
+            Action::Forward => self.start_forward(false).await,
+            Action::ForwardAsAttachment => self.start_forward(true).await,
             Action::Compose => self.start_compose(),
+            Action::NextField if self.state.contacts.editing.is_some() => {
+                self.contacts_next_field()
+            }
+            Action::PrevField if self.state.contacts.editing.is_some() => {
+                self.contacts_prev_field()
+            }
             Action::NextField => self.next_composer_field(),
             Action::PrevField => self.prev_composer_field(),
             Action::Send => self.send_email().await,
+            Action::SendLater => self.open_schedule_prompt(),
+            Action::ConfirmSchedule => self.confirm_schedule().await,
+            Action::CancelSchedule => self.close_schedule_prompt(),
             Action::Cancel => self.cancel_compose(),
             Action::CycleSendAccount => self.cycle_send_account(),
+            Action::ToggleMarkdown => self.toggle_markdown(),
+            Action::ToggleMarkdownPreview => self.toggle_markdown_preview(),
+            Action::CycleProtection => self.cycle_protection(),
+            Action::SelectFrom => self.open_from_picker(),
+            Action::FromPickerUp => self.sender_picker_up(),
+            Action::FromPickerDown => self.sender_picker_down(),
+            Action::FromPickerSelect => self.sender_picker_select(),
+            Action::FromPickerClose => self.sender_picker_close(),
+            Action::SelectTemplate => self.open_template_picker(),
+            Action::TemplatePickerUp => self.template_picker_up(),
+            Action::TemplatePickerDown => self.template_picker_down(),
+            Action::TemplatePickerSelect => self.template_picker_select(),
+            Action::TemplatePickerClose => self.template_picker_close(),
 
             // Add account wizard
             Action::WizardNext => self.wizard_next().await,
@@ -175,6 +239,16 @@ impl App {
                     self.contacts_start_edit();
                 }
             }
+            Action::MarkContact => {
+                if matches!(self.state.view, View::Contacts) {
+                    self.contacts_toggle_mark();
+                }
+            }
+            Action::ExportContacts => {
+                if matches!(self.state.view, View::Contacts) {
+                    self.contacts_export_selection().await;
+                }
+            }
 
             // Autocomplete (handled in input handler, but included for completeness)
             Action::AutocompleteUp => self.autocomplete_up(),
@@ -191,8 +265,19 @@ impl App {
 
             // Preview
             Action::ToggleHeaderExpand => {
-                self.state.reader.headers_expanded = !self.state.reader.headers_expanded;
+                if self.state.reader.source.is_some() {
+                    self.source_toggle_headers();
+                } else {
+                    self.state.reader.headers_expanded = !self.state.reader.headers_expanded;
+                }
             }
+            Action::ToggleHtml => self.toggle_html_part(),
+            Action::ToggleQuotes => self.toggle_quotes(),
+            Action::CycleSort => self.cycle_sort().await,
+            Action::ToggleThreadStack => self.toggle_conversation().await,
+            Action::ConversationNext => self.conversation_step(true),
+            Action::ConversationPrev => self.conversation_step(false),
+            Action::ToggleMessage => self.conversation_toggle_message(),
 
             // Help
             Action::Help => {
@@ -214,6 +299,40 @@ impl App {
             Action::OpenAttachment => {
                 self.open_attachment().await;
             }
+            Action::ImportVcard => {
+                self.import_vcard_attachment().await;
+            }
+            Action::ToggleImages => self.toggle_images().await,
+            Action::OpenImage => self.open_image().await,
+
+            // Raw source
+            Action::ToggleSource => self.toggle_source().await,
+            Action::SaveEml => self.save_eml().await,
+
+            // Find in message
+            Action::Find => self.find_start(),
+            Action::FindNext => self.find_next(true),
+            Action::FindPrev => self.find_next(false),
+            Action::SubmitPrompt => {
+                if self.state.pipe.prompt.is_some() {
+                    self.pipe_prompt_submit().await;
+                } else {
+                    self.find_submit();
+                }
+            }
+            Action::CancelPrompt => {
+                if self.state.pipe.prompt.take().is_none() {
+                    self.find_cancel();
+                }
+            }
+
+            // Pipe
+            Action::PipeMessage => self.pipe_prompt_start(),
+            Action::RunPipe(index) => self.run_pipe_command(index).await,
+            Action::ClosePipeOutput => self.state.pipe.output = None,
+            Action::FollowLink => self.link_hint_start(false),
+            Action::CopyLink => self.link_hint_start(true),
+            Action::CancelLinkHint => self.link_hint_cancel(),
         }
         Ok(())
     }
@@ -1,11 +1,16 @@
 //! Navigation actions (movement, scrolling)
 
-use crate::app::state::View;
+use crate::app::state::{BodyLayout, View};
 
 use super::super::App;
 
 impl App {
     pub(crate) fn move_up(&mut self) {
+        if let Some(output) = self.state.pipe.output.as_mut() {
+            output.scroll_by(-1);
+            return;
+        }
+
         // Handle folder sidebar navigation
         if self.state.folder.sidebar_visible && self.state.folder.sidebar_focused {
             if self.state.folder.sidebar_selected > 0 {
@@ -14,12 +19,22 @@ impl App {
             return;
         }
 
+        if self.state.reader.conversation_shown() && matches!(self.state.view, View::Reader { .. })
+        {
+            self.conversation_scroll(-1);
+            return;
+        }
+
         match &mut self.state.view {
             View::Inbox => {
                 self.state.move_up();
             }
             View::Reader { .. } => {
-                if self.state.reader.show_attachments {
+                if let Some(source) = self.state.reader.source.as_mut() {
+                    source.scroll_by(-1);
+                } else if self.state.reader.show_images {
+                    self.state.reader.image_up();
+                } else if self.state.reader.show_attachments {
                     self.state.reader.attachment_up();
                 } else {
                     self.state.reader.scroll_up();
@@ -28,6 +43,9 @@ impl App {
             View::Contacts => {
                 self.contacts_move_up();
             }
+            View::Scheduled => {
+                self.scheduled_move_up();
+            }
             View::AddAccount { step, data } => {
                 use crate::app::state::{AddAccountAuth, AddAccountStep};
                 if matches!(step, AddAccountStep::ChooseAuthMethod) {
@@ -43,6 +61,11 @@ impl App {
     }
 
     pub(crate) fn move_down(&mut self) {
+        if let Some(output) = self.state.pipe.output.as_mut() {
+            output.scroll_by(1);
+            return;
+        }
+
         // Handle folder sidebar navigation
         if self.state.folder.sidebar_visible && self.state.folder.sidebar_focused {
             if self.state.folder.sidebar_selected < self.state.folder.list.len().saturating_sub(1) {
@@ -51,20 +74,34 @@ impl App {
             return;
         }
 
+        if self.state.reader.conversation_shown() && matches!(self.state.view, View::Reader { .. })
+        {
+            self.conversation_scroll(1);
+            return;
+        }
+
         match &mut self.state.view {
             View::Inbox => {
                 self.state.move_down();
             }
             View::Reader { .. } => {
-                if self.state.reader.show_attachments {
+                if let Some(source) = self.state.reader.source.as_mut() {
+                    source.scroll_by(1);
+                } else if self.state.reader.show_images {
+                    self.state.reader.image_down();
+                } else if self.state.reader.show_attachments {
                     self.state.reader.attachment_down();
                 } else {
-                    self.state.reader.scroll_down();
+                    let layout = self.body_layout();
+                    self.state.reader.scroll_down(&layout);
                 }
             }
             View::Contacts => {
                 self.contacts_move_down();
             }
+            View::Scheduled => {
+                self.scheduled_move_down();
+            }
             View::AddAccount { step, data } => {
                 use crate::app::state::{AddAccountAuth, AddAccountStep};
                 if matches!(step, AddAccountStep::ChooseAuthMethod) {
@@ -110,7 +147,7 @@ impl App {
                 self.state.view = View::Inbox;
                 self.state.reader.set_body(None);
             }
-            View::Contacts => {
+            View::Contacts | View::Scheduled => {
                 // Go back to inbox
                 self.state.view = View::Inbox;
             }
@@ -133,6 +170,15 @@ impl App {
     }
 
     pub(super) fn move_page(&mut self, delta: i32) {
+        if let Some(output) = self.state.pipe.output.as_mut() {
+            output.scroll_by(delta);
+            return;
+        }
+        if self.state.reader.conversation_shown() && matches!(self.state.view, View::Reader { .. })
+        {
+            self.conversation_scroll(delta);
+            return;
+        }
         match &self.state.view {
             View::Inbox => {
                 for _ in 0..delta.abs() {
@@ -143,13 +189,22 @@ impl App {
                     }
                 }
             }
-            View::Reader { .. } => {
-                self.state.reader.scroll_by(delta);
-            }
+            View::Reader { .. } => match self.state.reader.source.as_mut() {
+                Some(source) => source.scroll_by(delta),
+                None => {
+                    let layout = self.body_layout();
+                    self.state.reader.scroll_by(delta, &layout);
+                }
+            },
             _ => {}
         }
     }
 
+    /// The reader body as wrapped to the terminal width
+    pub(super) fn body_layout(&self) -> BodyLayout {
+        crate::ui::body_layout(&self.state)
+    }
+
     pub(super) fn toggle_thread(&mut self) {
         if matches!(self.state.view, View::Inbox) {
             self.state.toggle_thread_expansion();
@@ -157,17 +212,36 @@ impl App {
     }
 
     pub(super) fn move_to_top(&mut self) {
+        if self.state.reader.conversation_shown() && matches!(self.state.view, View::Reader { .. })
+        {
+            self.conversation_scroll(i32::MIN);
+            return;
+        }
         match &self.state.view {
             View::Inbox => {
                 self.state.thread.selected = 0;
                 self.state.thread.selected_in_thread = 0;
             }
-            View::Reader { .. } => self.state.reader.reset_scroll(),
+            View::Reader { .. } => match self.state.reader.source.as_mut() {
+                Some(source) => source.scroll = 0,
+                None => self.state.reader.reset_scroll(),
+            },
             _ => {}
         }
     }
 
     pub(super) fn move_to_bottom(&mut self) {
+        if self.state.reader.conversation_shown() && matches!(self.state.view, View::Reader { .. })
+        {
+            self.conversation_scroll(i32::MAX);
+            return;
+        }
+        if matches!(self.state.view, View::Reader { .. })
+            && let Some(source) = self.state.reader.source.as_mut()
+        {
+            source.scroll_to_end();
+            return;
+        }
         if let View::Inbox = &self.state.view {
             // Use visible_thread_count to respect search/filter, not total thread count
             let visible_count = self.state.visible_thread_count();
@@ -0,0 +1,322 @@
+//! Piping the message, its text or its thread to shell commands
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use crate::app::PipeResult;
+use crate::app::render_thread::RenderThread;
+use crate::app::state::{PipeOutputView, PipePrompt, View};
+use crate::config::PipeInput;
+use crate::mail::ImapCommand;
+use crate::mail::types::EmailHeader;
+use crate::pipe::{self, MboxMessage};
+
+use super::super::App;
+
+/// A pipe command waiting for raw messages from IMAP
+pub(crate) struct PipeJob {
+    command: String,
+    input: PipeInput,
+    interactive: bool,
+    /// Messages in thread order, with their raw source once known
+    messages: Vec<(EmailHeader, Option<Vec<u8>>)>,
+}
+
+impl PipeJob {
+    fn is_complete(&self) -> bool {
+        self.messages.iter().all(|(_, raw)| raw.is_some())
+    }
+
+    /// What the command gets on stdin
+    fn stdin(&self) -> Vec<u8> {
+        match self.input {
+            PipeInput::Thread => pipe::mbox(
+                &self
+                    .messages
+                    .iter()
+                    .map(|(email, raw)| MboxMessage {
+                        sender: &email.from_addr,
+                        date: email.date,
+                        raw: raw.as_deref().unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => self
+                .messages
+                .first()
+                .and_then(|(_, raw)| raw.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A command to run with the terminal handed over to it
+pub(crate) struct InteractivePipe {
+    command: String,
+    input: Vec<u8>,
+}
+
+impl App {
+    /// Open the pipe prompt, or switch what is piped if it is open
+    pub(super) fn pipe_prompt_start(&mut self) {
+        if let Some(ref mut prompt) = self.state.pipe.prompt {
+            prompt.target = match prompt.target {
+                PipeInput::Raw => PipeInput::Text,
+                PipeInput::Text => PipeInput::Thread,
+                PipeInput::Thread => PipeInput::Raw,
+            };
+            return;
+        }
+        if self.pipe_targets(false).is_empty() {
+            return;
+        }
+        self.state.pipe.prompt = Some(PipePrompt::default());
+    }
+
+    /// Run the typed command (a leading `!` hands it the terminal)
+    pub(super) async fn pipe_prompt_submit(&mut self) {
+        let Some(prompt) = self.state.pipe.prompt.take() else {
+            return;
+        };
+        let command = prompt.input.trim();
+        let (command, interactive) = match command.strip_prefix('!') {
+            Some(rest) => (rest.trim(), true),
+            None => (command, false),
+        };
+        if command.is_empty() {
+            return;
+        }
+        self.start_pipe(command.to_string(), prompt.target, interactive)
+            .await;
+    }
+
+    /// Run a pipe command from the config
+    pub(super) async fn run_pipe_command(&mut self, index: usize) {
+        let Some(config) = self.config.pipe.get(index).cloned() else {
+            return;
+        };
+        self.start_pipe(config.command, config.input, config.interactive)
+            .await;
+    }
+
+    /// Messages a pipe command acts on: the open (or selected) message, or
+    /// its whole thread
+    fn pipe_targets(&self, thread: bool) -> Vec<EmailHeader> {
+        let selected = match self.state.view {
+            View::Reader { uid } => self.state.emails.iter().find(|e| e.uid == uid),
+            View::Inbox => self.state.current_email_from_thread(),
+            _ => None,
+        };
+        let Some(selected) = selected.cloned() else {
+            return Vec::new();
+        };
+        if !thread {
+            return vec![selected];
+        }
+        self.state
+            .current_thread()
+            .map(|t| {
+                t.email_indices
+                    .iter()
+                    .filter_map(|&i| self.state.emails.get(i).cloned())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|emails| emails.iter().any(|e| e.uid == selected.uid))
+            .unwrap_or_else(|| vec![selected])
+    }
+
+    async fn start_pipe(&mut self, command: String, input: PipeInput, interactive: bool) {
+        if input == PipeInput::Text {
+            let Some(text) = self.state.reader.body.as_ref().map(|b| b.display_text()) else {
+                self.state.set_error("The message body is not loaded yet");
+                return;
+            };
+            self.execute_pipe(command, text.into_bytes(), interactive);
+            return;
+        }
+
+        let targets = self.pipe_targets(input == PipeInput::Thread);
+        if targets.is_empty() {
+            return;
+        }
+        let mut job = PipeJob {
+            command,
+            input,
+            interactive,
+            messages: Vec::with_capacity(targets.len()),
+        };
+        for email in targets {
+            let cache_key = self.email_cache_key(&email);
+            let raw = self
+                .cache
+                .get_raw_message(&cache_key, email.uid)
+                .await
+                .ok()
+                .flatten();
+            job.messages.push((email, raw));
+        }
+        if job.is_complete() {
+            let stdin = job.stdin();
+            self.execute_pipe(job.command, stdin, job.interactive);
+            return;
+        }
+
+        // Fetch what isn't cached; the job finishes when the last one arrives
+        self.state.set_status("Fetching messages...");
+        self.state.status.loading = true;
+        let missing: Vec<(u32, String)> = job
+            .messages
+            .iter()
+            .filter(|(_, raw)| raw.is_none())
+            .map(|(email, _)| (email.uid, self.folder_for_uid(email.uid)))
+            .collect();
+        self.pending_pipe = Some(job);
+        for (uid, folder) in missing {
+            if let Err(e) = self
+                .accounts
+                .send_command(ImapCommand::FetchRaw { uid, folder })
+                .await
+            {
+                self.pending_pipe = None;
+                self.state.status.loading = false;
+                self.state
+                    .set_error(format!("Failed to fetch message: {}", e));
+                return;
+            }
+        }
+    }
+
+    /// Hand a fetched raw message to the waiting pipe job (true if it was
+    /// waiting for it)
+    pub(super) fn pipe_raw_fetched(&mut self, uid: u32, raw: &[u8]) -> bool {
+        let Some(job) = self.pending_pipe.as_mut() else {
+            return false;
+        };
+        let Some(slot) = job
+            .messages
+            .iter_mut()
+            .find(|(email, raw)| email.uid == uid && raw.is_none())
+        else {
+            return false;
+        };
+        slot.1 = Some(raw.to_vec());
+        if job.is_complete()
+            && let Some(job) = self.pending_pipe.take()
+        {
+            self.state.status.loading = false;
+            let stdin = job.stdin();
+            self.execute_pipe(job.command, stdin, job.interactive);
+        }
+        true
+    }
+
+    /// Give up on the waiting pipe job if it needed this message
+    pub(super) fn pipe_raw_fetch_failed(&mut self, uid: u32, error: &str) -> bool {
+        if self
+            .pending_pipe
+            .take_if(|job| job.messages.iter().any(|(email, _)| email.uid == uid))
+            .is_none()
+        {
+            return false;
+        }
+        self.state.status.loading = false;
+        self.state
+            .set_error(format!("Failed to fetch message: {}", error));
+        true
+    }
+
+    fn execute_pipe(&mut self, command: String, input: Vec<u8>, interactive: bool) {
+        if interactive {
+            // Run by the event loop, which can hand over the terminal
+            self.pending_interactive = Some(InteractivePipe { command, input });
+            return;
+        }
+
+        let tx = self.pipe_tx.clone();
+        self.state.set_status(format!("Running {}...", command));
+        tokio::spawn(async move {
+            let result = pipe::run(&command, input)
+                .await
+                .map_err(|e| format!("{:#}", e));
+            let _ = tx.send(PipeResult { command, result }).await;
+        });
+    }
+
+    /// Show the output of finished pipe commands
+    pub(crate) fn process_pipe_results(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(PipeResult { command, result }) = self.pipe_rx.try_recv() {
+            match result {
+                Ok(output) if output.success && output.output.trim().is_empty() => {
+                    self.state.set_status(format!("{} finished", command));
+                }
+                Ok(output) => {
+                    let status = output
+                        .code
+                        .map_or("killed by a signal".to_string(), |c| format!("exit {}", c));
+                    self.state.set_status("");
+                    self.state.pipe.output = Some(PipeOutputView {
+                        command,
+                        status,
+                        success: output.success,
+                        lines: Arc::new(output.output.lines().map(str::to_string).collect()),
+                        scroll: 0,
+                    });
+                }
+                Err(e) => self.state.set_error(format!("{}: {}", command, e)),
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Run a waiting interactive command with the TUI suspended
+    pub(crate) async fn run_interactive_pipe(&mut self, render_thread: &RenderThread) {
+        let Some(InteractivePipe { command, input }) = self.pending_interactive.take() else {
+            return;
+        };
+        render_thread.suspend();
+        let result = tokio::task::spawn_blocking({
+            let command = command.clone();
+            move || run_in_terminal(&command, input)
+        })
+        .await;
+        render_thread.resume();
+
+        match result {
+            Ok(Ok(status)) if status.success() => {
+                self.state.set_status(format!("{} finished", command));
+            }
+            Ok(Ok(status)) => self
+                .state
+                .set_error(format!("{} failed: {}", command, status)),
+            Ok(Err(e)) => self.state.set_error(format!("{}: {}", command, e)),
+            Err(e) => self.state.set_error(format!("Pipe task failed: {}", e)),
+        }
+    }
+}
+
+/// Run `command` on the real terminal with `input` on stdin, then wait for
+/// Enter so its output can be read
+fn run_in_terminal(command: &str, input: Vec<u8>) -> std::io::Result<std::process::ExitStatus> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take();
+    let writer = std::thread::spawn(move || {
+        if let Some(ref mut stdin) = stdin {
+            let _ = stdin.write_all(&input);
+        }
+    });
+    let status = child.wait()?;
+    let _ = writer.join();
+
+    print!("\n[{}] Press Enter to return to bltz", status);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(status)
+}
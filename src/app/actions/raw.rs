@@ -0,0 +1,66 @@
+//! Raw message retrieval for actions that need the full message source
+
+use crate::app::state::{PendingRaw, RawPurpose};
+use crate::mail::ImapCommand;
+
+use super::super::App;
+
+impl App {
+    /// Get the raw message for `uid` and act on it according to `purpose`.
+    /// Uses the cached copy if there is one, otherwise fetches it over IMAP
+    /// and finishes when `RawFetched` arrives.
+    pub(super) async fn request_raw(&mut self, uid: u32, purpose: RawPurpose) {
+        let cache_key = self.cache_key_for_uid(uid);
+        if let Ok(Some(raw)) = self.cache.get_raw_message(&cache_key, uid).await {
+            self.complete_raw(uid, raw, purpose).await;
+            return;
+        }
+
+        self.state.pending_raw = Some(PendingRaw { uid, purpose });
+        self.state.set_status("Fetching message...");
+        self.state.status.loading = true;
+        self.dirty = true;
+
+        let folder = self.folder_for_uid(uid);
+        if let Err(e) = self
+            .accounts
+            .send_command(ImapCommand::FetchRaw { uid, folder })
+            .await
+        {
+            self.state.pending_raw = None;
+            self.state.status.loading = false;
+            self.state
+                .set_error(format!("Failed to fetch message: {}", e));
+        }
+    }
+
+    /// Handle a fetched raw message (ignored if nothing is waiting for it)
+    pub(crate) async fn handle_raw_fetched(&mut self, uid: u32, raw: Vec<u8>) {
+        self.pipe_raw_fetched(uid, &raw);
+        let Some(pending) = self.state.pending_raw.take_if(|p| p.uid == uid) else {
+            return;
+        };
+        self.state.status.loading = false;
+        self.complete_raw(uid, raw, pending.purpose).await;
+    }
+
+    pub(crate) fn handle_raw_fetch_failed(&mut self, uid: u32, error: String) {
+        self.pipe_raw_fetch_failed(uid, &error);
+        if self.state.pending_raw.take_if(|p| p.uid == uid).is_none() {
+            return;
+        }
+        self.state.status.loading = false;
+        self.state
+            .set_error(format!("Failed to fetch message: {}", error));
+    }
+
+    async fn complete_raw(&mut self, uid: u32, raw: Vec<u8>, purpose: RawPurpose) {
+        match purpose {
+            RawPurpose::Forward { as_attachment } => {
+                self.finish_forward(uid, raw, as_attachment).await
+            }
+            RawPurpose::Images => self.load_images(uid, raw),
+            RawPurpose::Source => self.show_source(uid, raw),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Delayed sending: the undo-send window and the send-later queue
+
+use std::time::Instant;
+
+use chrono::{Local, Utc};
+
+use crate::app::schedule::{SEND_TIME_HINT, format_send_time, parse_send_time};
+use crate::app::state::{ComposerField, View};
+use crate::app::undo::{PendingSend, UndoEntry, UndoableAction};
+use crate::constants::SCHEDULED_CHECK_INTERVAL_SECS;
+use crate::input::Action;
+use crate::mail::types::ComposeEmail;
+
+use super::super::{App, ScheduledSendResult};
+use super::compose::{transmit, validate_for_send};
+
+impl App {
+    /// Hold a message back for the undo-send window instead of sending it now
+    pub(super) fn queue_send(&mut self, mut email: ComposeEmail, delay: u64) {
+        // Pin the sending account in case the user switches before it goes out
+        email
+            .from_account_index
+            .get_or_insert(self.accounts.active_index());
+
+        let id = self.next_send_id;
+        self.next_send_id += 1;
+
+        self.pending_sends.push(PendingSend {
+            id,
+            email,
+            initiated_at: Instant::now(),
+        });
+        self.undo_stack.push(UndoEntry {
+            action: UndoableAction::Send { id },
+            account_id: self.account_id().to_string(),
+            folder: self.state.folder.current.clone(),
+        });
+
+        self.state.view = View::Inbox;
+        self.state
+            .set_status(match self.bindings.key_for(Action::Undo) {
+                Some(key) => format!("Sending in {}s, press {} to undo", delay, key),
+                None => format!("Sending in {}s", delay),
+            });
+    }
+
+    /// Cancel a held-back send and reopen the message in the composer
+    pub(super) fn undo_send(&mut self, id: u64) {
+        let Some(pos) = self.pending_sends.iter().position(|ps| ps.id == id) else {
+            self.state.set_status("Message was already sent");
+            return;
+        };
+        let pending = self.pending_sends.remove(pos);
+        self.show_composer(pending.email, ComposerField::Body);
+        self.state.set_status("Undo: send cancelled");
+    }
+
+    /// Send a message whose undo window has closed
+    ///
+    /// The composer is gone by now, so a failed message is kept in the
+    /// send-later queue rather than lost.
+    pub(crate) async fn send_held(&mut self, email: ComposeEmail) {
+        self.state.status.loading = true;
+        self.dirty = true;
+
+        match self.deliver(&email).await {
+            Ok(account_name) => {
+                self.state
+                    .set_status(format!("Email sent from {}", account_name));
+            }
+            Err(e) => {
+                let account_id = self.send_account_id(&email);
+                let now = Utc::now().timestamp();
+                let error = e.to_string();
+                match self
+                    .cache
+                    .insert_scheduled(&account_id, now, &email, Some(&error))
+                    .await
+                {
+                    Ok(_) => self
+                        .state
+                        .set_error(format!("{} (message kept in :scheduled)", error)),
+                    Err(save_err) => self.state.set_error(format!(
+                        "{} (and the message could not be saved: {})",
+                        error, save_err
+                    )),
+                }
+            }
+        }
+
+        self.state.status.loading = false;
+    }
+
+    /// ID of the account a message will be sent from
+    fn send_account_id(&self, email: &ComposeEmail) -> String {
+        let index = email
+            .from_account_index
+            .unwrap_or(self.accounts.active_index());
+        self.accounts
+            .get(index)
+            .map(|h| h.account_id.clone())
+            .unwrap_or_else(|| self.account_id().to_string())
+    }
+
+    //
+    // Send later
+    //
+
+    /// Open the "send later" time prompt in the composer
+    pub(super) fn open_schedule_prompt(&mut self) {
+        let View::Composer { ref email, .. } = self.state.view else {
+            return;
+        };
+        if let Err(e) = validate_for_send(email) {
+            self.state.set_error(e);
+            return;
+        }
+        if !self.confirm_external_recipients(Action::SendLater) {
+            return;
+        }
+        self.state.composer.schedule_input = Some(String::new());
+    }
+
+    pub(super) fn close_schedule_prompt(&mut self) {
+        self.state.composer.schedule_input = None;
+    }
+
+    /// Queue the composed message for the time entered in the prompt
+    pub(super) async fn confirm_schedule(&mut self) {
+        let View::Composer { ref email, .. } = self.state.view else {
+            return;
+        };
+        let Some(input) = self.state.composer.schedule_input.as_deref() else {
+            return;
+        };
+
+        let Some(send_at) = parse_send_time(input, &Local::now()) else {
+            self.state.set_error(format!(
+                "Can't schedule for \"{}\" (try {})",
+                input.trim(),
+                SEND_TIME_HINT
+            ));
+            return;
+        };
+
+        let email = email.clone();
+        let account_id = self.send_account_id(&email);
+        let send_at = send_at.timestamp();
+        match self
+            .cache
+            .insert_scheduled(&account_id, send_at, &email, None)
+            .await
+        {
+            Ok(_) => {
+                self.state.composer.schedule_input = None;
+                self.state.view = View::Inbox;
+                self.state
+                    .set_status(format!("Scheduled for {}", format_send_time(send_at)));
+            }
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to schedule email: {}", e));
+            }
+        }
+    }
+
+    /// Report scheduled sends finished in the background, and start
+    /// sending the queued messages that are due. Only checks the queue every
+    /// `SCHEDULED_CHECK_INTERVAL_SECS`; returns true if anything was sent or
+    /// failed.
+    pub(crate) async fn process_scheduled_sends(&mut self) -> bool {
+        let mut processed = false;
+        while let Ok(ScheduledSendResult {
+            id,
+            subject,
+            result,
+        }) = self.scheduled_rx.try_recv()
+        {
+            self.scheduled_sending.remove(&id);
+            match result {
+                Ok(email) => {
+                    self.cache.delete_scheduled(id).await.ok();
+                    self.record_sent(&email).await;
+                    self.state
+                        .set_status(format!("Sent scheduled email \"{}\"", subject));
+                }
+                Err(e) => self.scheduled_send_failed(id, &e).await,
+            }
+            processed = true;
+        }
+
+        if self
+            .last_scheduled_check
+            .is_none_or(|t| t.elapsed().as_secs() >= SCHEDULED_CHECK_INTERVAL_SECS)
+        {
+            self.last_scheduled_check = Some(Instant::now());
+            processed |= self.start_scheduled_sends().await;
+        }
+
+        if processed && matches!(self.state.view, View::Scheduled) {
+            self.reload_scheduled().await;
+        }
+        processed
+    }
+
+    /// Send the due messages (not already going out) in a background task,
+    /// one after another. Returns true if any could not be started.
+    async fn start_scheduled_sends(&mut self) -> bool {
+        let due = match self.cache.get_due_scheduled(Utc::now().timestamp()).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::warn!("Failed to read send-later queue: {}", e);
+                return false;
+            }
+        };
+
+        let mut failed = false;
+        let mut jobs = Vec::new();
+        for message in due {
+            if self.scheduled_sending.contains(&message.id) {
+                continue;
+            }
+            let account = self
+                .accounts
+                .index_of(&message.account_id)
+                .and_then(|index| Some((index, self.accounts.get(index)?.config.clone())));
+            let Some((index, account)) = account else {
+                let error = format!("Account {} is no longer configured", message.account_id);
+                self.scheduled_send_failed(message.id, &error).await;
+                failed = true;
+                continue;
+            };
+            let mut email = message.email;
+            email.from_account_index = Some(index);
+            jobs.push((message.id, account, email));
+            self.scheduled_sending.insert(message.id);
+        }
+        if jobs.is_empty() {
+            return failed;
+        }
+
+        let tx = self.scheduled_tx.clone();
+        let pgp = self.config.pgp.clone();
+        tokio::spawn(async move {
+            for (id, account, email) in jobs {
+                let result = transmit(&account, &email, &pgp)
+                    .await
+                    .map_err(|e| e.to_string());
+                let subject = email.subject;
+                if tx
+                    .send(ScheduledSendResult {
+                        id,
+                        subject,
+                        result,
+                    })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        failed
+    }
+
+    /// Keep a queued message that could not be sent, with the reason
+    async fn scheduled_send_failed(&mut self, id: i64, error: &str) {
+        self.cache.set_scheduled_error(id, error).await.ok();
+        self.state
+            .set_error(format!("Scheduled send failed: {}", error));
+    }
+
+    //
+    // Scheduled view
+    //
+
+    /// Open the list of scheduled messages
+    pub(crate) async fn open_scheduled(&mut self) {
+        self.state.scheduled.selected = 0;
+        self.state.view = View::Scheduled;
+        self.reload_scheduled().await;
+    }
+
+    async fn reload_scheduled(&mut self) {
+        match self.cache.get_scheduled().await {
+            Ok(messages) => {
+                let max = messages.len().saturating_sub(1);
+                self.state.scheduled.messages = messages;
+                self.state.scheduled.selected = self.state.scheduled.selected.min(max);
+            }
+            Err(e) => {
+                self.state
+                    .set_error(format!("Failed to load scheduled emails: {}", e));
+            }
+        }
+    }
+
+    /// Move selection up in scheduled view
+    pub(crate) fn scheduled_move_up(&mut self) {
+        self.state.scheduled.selected = self.state.scheduled.selected.saturating_sub(1);
+    }
+
+    /// Move selection down in scheduled view
+    pub(crate) fn scheduled_move_down(&mut self) {
+        let max = self.state.scheduled.messages.len().saturating_sub(1);
+        self.state.scheduled.selected = (self.state.scheduled.selected + 1).min(max);
+    }
+
+    /// Cancel the selected scheduled message
+    pub(crate) async fn scheduled_cancel(&mut self) {
+        let Some(message) = self
+            .state
+            .scheduled
+            .messages
+            .get(self.state.scheduled.selected)
+        else {
+            return;
+        };
+        if self.scheduled_sending.contains(&message.id) {
+            self.state.set_error("This email is being sent");
+            return;
+        }
+        if let Err(e) = self.cache.delete_scheduled(message.id).await {
+            self.state
+                .set_error(format!("Failed to cancel scheduled email: {}", e));
+            return;
+        }
+        self.reload_scheduled().await;
+        self.state.set_status("Scheduled email cancelled");
+    }
+
+    /// Take the selected message out of the queue and open it in the composer
+    pub(crate) async fn scheduled_edit(&mut self) {
+        let Some(message) = self
+            .state
+            .scheduled
+            .messages
+            .get(self.state.scheduled.selected)
+            .cloned()
+        else {
+            return;
+        };
+        if self.scheduled_sending.contains(&message.id) {
+            self.state.set_error("This email is being sent");
+            return;
+        }
+        let Some(account_index) = self.accounts.index_of(&message.account_id) else {
+            self.state.set_error(format!(
+                "Account {} is no longer configured",
+                message.account_id
+            ));
+            return;
+        };
+        if let Err(e) = self.cache.delete_scheduled(message.id).await {
+            self.state
+                .set_error(format!("Failed to edit scheduled email: {}", e));
+            return;
+        }
+
+        let mut email = message.email;
+        email.from_account_index = Some(account_index);
+        self.show_composer(email, ComposerField::Body);
+        self.state
+            .set_status("Removed from schedule; send or schedule it again");
+    }
+}
@@ -0,0 +1,56 @@
+//! List sort orders: the cycle key and the `:sort` command
+
+use crate::command::CommandResult;
+use crate::mail::types::SortOrder;
+
+use super::super::App;
+
+impl App {
+    /// Switch the list to the next sort order
+    pub(super) async fn cycle_sort(&mut self) {
+        self.set_sort_order(self.state.pagination.order.next())
+            .await;
+    }
+
+    /// `:sort <order>`, or the orders with the current one marked
+    pub(super) async fn handle_sort_command(&mut self, name: &str) {
+        if name.is_empty() {
+            let current = self.state.pagination.order;
+            let list = SortOrder::ALL
+                .iter()
+                .map(|order| {
+                    let marker = if *order == current { " *" } else { "" };
+                    format!("  {:<8} {}{}", order.name(), order.label(), marker)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            self.set_command_result(CommandResult::Success(format!(
+                "Sort orders (* = current):\n{}",
+                list
+            )));
+            return;
+        }
+        let Some(order) = SortOrder::parse(name) else {
+            self.set_command_result(CommandResult::Error(format!(
+                "Unknown sort order: {}. Type :sort to list them.",
+                name
+            )));
+            return;
+        };
+        self.exit_command_mode();
+        self.set_sort_order(order).await;
+    }
+
+    /// Remember `order` for the current folder and reload the list in it
+    async fn set_sort_order(&mut self, order: SortOrder) {
+        if let Err(e) = self.cache.set_sort_order(&self.cache_key(), order).await {
+            self.state
+                .set_error(format!("Failed to save sort order: {}", e));
+            return;
+        }
+        self.state.thread.selected = 0;
+        self.state.thread.selected_in_thread = 0;
+        self.reload_from_cache().await;
+        self.state.set_status(format!("Sort: {}", order.label()));
+    }
+}
@@ -0,0 +1,183 @@
+//! Raw message source view (full headers, search, save as .eml)
+
+use std::path::PathBuf;
+
+use crate::app::state::{RawPurpose, SourceView, View};
+
+use super::super::App;
+use super::attachment::unique_save_path;
+
+impl App {
+    /// Show or hide the raw source of the open message
+    pub(super) async fn toggle_source(&mut self) {
+        let View::Reader { uid } = self.state.view else {
+            return;
+        };
+        if self.state.reader.source.take().is_some() {
+            return;
+        }
+        self.request_raw(uid, RawPurpose::Source).await;
+    }
+
+    /// Open the source view once the raw message is available
+    pub(super) fn show_source(&mut self, uid: u32, raw: Vec<u8>) {
+        if !matches!(self.state.view, View::Reader { uid: current } if current == uid) {
+            return;
+        }
+        self.state.reader.show_images = false;
+        self.state.reader.show_attachments = false;
+        self.state.reader.source = Some(SourceView::new(uid, raw));
+    }
+
+    /// Switch between the raw source and the header table
+    pub(super) fn source_toggle_headers(&mut self) {
+        if let Some(source) = self.state.reader.source.as_mut() {
+            source.show_headers = !source.show_headers;
+            source.scroll = 0;
+        }
+    }
+
+    pub(super) fn source_find_start(&mut self) {
+        if let Some(source) = self.state.reader.source.as_mut() {
+            source.find_input = Some(String::new());
+        }
+    }
+
+    pub(super) fn source_find_char(&mut self, c: char) {
+        if let Some(input) = self
+            .state
+            .reader
+            .source
+            .as_mut()
+            .and_then(|s| s.find_input.as_mut())
+        {
+            input.push(c);
+        }
+    }
+
+    pub(super) fn source_find_backspace(&mut self) {
+        if let Some(input) = self
+            .state
+            .reader
+            .source
+            .as_mut()
+            .and_then(|s| s.find_input.as_mut())
+        {
+            input.pop();
+        }
+    }
+
+    pub(super) fn source_find_cancel(&mut self) {
+        if let Some(source) = self.state.reader.source.as_mut() {
+            source.find_input = None;
+        }
+    }
+
+    /// Search for the typed term
+    pub(super) fn source_find_submit(&mut self) {
+        let Some(source) = self.state.reader.source.as_mut() else {
+            return;
+        };
+        let Some(query) = source.find_input.take() else {
+            return;
+        };
+        source.query = query;
+        if !source.query.is_empty() {
+            let found = source.find_first();
+            self.report_find(found);
+        }
+    }
+
+    /// Jump to the next (or previous) row containing the search term
+    pub(super) fn source_find(&mut self, forward: bool) {
+        let Some(source) = self.state.reader.source.as_mut() else {
+            return;
+        };
+        if !source.query.is_empty() {
+            let found = source.find_next(forward);
+            self.report_find(found);
+        }
+    }
+
+    fn report_find(&mut self, found: bool) {
+        let Some(source) = self.state.reader.source.as_ref() else {
+            return;
+        };
+        if found {
+            let count = source.match_count();
+            self.state.set_status(format!(
+                "{} matching line{}",
+                count,
+                if count == 1 { "" } else { "s" }
+            ));
+        } else {
+            let query = source.query.clone();
+            self.state.set_error(format!("Not found: {}", query));
+        }
+    }
+
+    /// Save the raw message to the downloads folder
+    pub(super) async fn save_eml(&mut self) {
+        let Some(source) = self.state.reader.source.as_ref() else {
+            return;
+        };
+        let raw = source.raw.clone();
+        let subject = source
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Subject"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        let filename = eml_filename(subject, source.uid);
+        let downloads_dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let path = unique_save_path(&downloads_dir, &filename);
+
+        let result = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || std::fs::write(&path, raw.as_slice())
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => self
+                .state
+                .set_status(format!("Saved to {}", path.display())),
+            Ok(Err(e)) => self.state.set_error(format!("Failed to save: {}", e)),
+            Err(e) => self.state.set_error(format!("Save task failed: {}", e)),
+        }
+    }
+}
+
+/// File name for a saved message: its subject, made safe for file systems
+fn eml_filename(subject: &str, uid: u32) -> String {
+    let name: String = subject
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(80)
+        .collect();
+    let name = name.trim().trim_start_matches('.');
+    if name.is_empty() {
+        format!("message-{}.eml", uid)
+    } else {
+        format!("{}.eml", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eml_filename() {
+        assert_eq!(eml_filename("Re: a/b?", 7), "Re_ a_b_.eml");
+        assert_eq!(eml_filename("  ", 7), "message-7.eml");
+        assert_eq!(eml_filename("../etc", 7), "_etc.eml");
+    }
+}
@@ -0,0 +1,160 @@
+//! Message templates: the composer picker and the `:template` command
+
+use crate::app::state::{ModalState, TemplatePicker, View};
+use crate::command::CommandResult;
+use crate::config::Config;
+use crate::mail::template::{self, Template, TemplateContext};
+
+use super::super::App;
+
+impl App {
+    /// Read the templates directory
+    fn load_templates(&mut self) -> Option<Vec<Template>> {
+        let dir = match Config::templates_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.state.set_error(e.to_string());
+                return None;
+            }
+        };
+        match template::load_all(&dir) {
+            Ok(templates) if templates.is_empty() => {
+                self.state
+                    .set_error(format!("No templates in {}", dir.display()));
+                None
+            }
+            Ok(templates) => Some(templates),
+            Err(e) => {
+                self.state.set_error(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// Fill in a template and apply it to the message in the composer
+    fn apply_template(&mut self, template: &Template) {
+        let View::Composer { ref mut email, .. } = self.state.view else {
+            return;
+        };
+
+        let original = email
+            .reply_to_uid
+            .and_then(|uid| self.state.emails.iter().find(|e| e.uid == uid));
+        let account_index = email
+            .from_account_index
+            .unwrap_or(self.accounts.active_index());
+        let Some(identity) = self
+            .accounts
+            .get(account_index)
+            .map(|h| h.config.identity(email.identity_index))
+        else {
+            return;
+        };
+        let ctx = TemplateContext::new(original, identity.display_name.as_deref(), &identity.email);
+
+        template.apply(email, &ctx);
+        self.state
+            .set_status(format!("Inserted template \"{}\"", template.name));
+    }
+
+    /// Open the template picker in the composer
+    pub(super) fn open_template_picker(&mut self) {
+        if !matches!(self.state.view, View::Composer { .. }) {
+            return;
+        }
+        if let Some(templates) = self.load_templates() {
+            self.state.composer.template_picker = Some(TemplatePicker {
+                templates,
+                selected: 0,
+            });
+        }
+    }
+
+    /// Move template picker up
+    pub(super) fn template_picker_up(&mut self) {
+        if let Some(ref mut picker) = self.state.composer.template_picker {
+            picker.selected = picker.selected.saturating_sub(1);
+        }
+    }
+
+    /// Move template picker down
+    pub(super) fn template_picker_down(&mut self) {
+        if let Some(ref mut picker) = self.state.composer.template_picker {
+            let max = picker.templates.len().saturating_sub(1);
+            picker.selected = (picker.selected + 1).min(max);
+        }
+    }
+
+    /// Insert the selected template
+    pub(super) fn template_picker_select(&mut self) {
+        if let Some(picker) = self.state.composer.template_picker.take()
+            && let Some(template) = picker.templates.get(picker.selected)
+        {
+            self.apply_template(template);
+        }
+    }
+
+    /// Close the template picker without inserting anything
+    pub(super) fn template_picker_close(&mut self) {
+        self.state.composer.template_picker = None;
+    }
+
+    /// `:template [name]` - list templates, or reply to the selected email
+    /// (or start a new one) using the named template
+    pub(super) async fn handle_template_command(&mut self, name: &str) {
+        let dir = match Config::templates_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                self.set_command_result(CommandResult::Error(e.to_string()));
+                return;
+            }
+        };
+        let templates = match template::load_all(&dir) {
+            Ok(templates) => templates,
+            Err(e) => {
+                self.set_command_result(CommandResult::Error(e.to_string()));
+                return;
+            }
+        };
+
+        if name.is_empty() {
+            let result = if templates.is_empty() {
+                CommandResult::Error(format!("No templates in {}", dir.display()))
+            } else {
+                let list = templates
+                    .iter()
+                    .map(|t| format!("  {}", t.name))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                CommandResult::Success(format!("Available templates:\n{}", list))
+            };
+            self.set_command_result(result);
+            return;
+        }
+
+        let Some(template) = templates.into_iter().find(|t| t.name == name) else {
+            self.set_command_result(CommandResult::Error(format!(
+                "Unknown template: {}. Type :template to list them.",
+                name
+            )));
+            return;
+        };
+
+        self.exit_command_mode();
+        if !matches!(self.state.view, View::Composer { .. }) {
+            if self.get_current_email_for_compose().is_some() {
+                self.start_reply().await;
+            } else {
+                self.start_compose();
+            }
+        }
+        self.apply_template(&template);
+    }
+
+    pub(super) fn set_command_result(&mut self, command_result: CommandResult) {
+        if let ModalState::Command { input, result, .. } = &mut self.state.modal {
+            *result = Some(command_result);
+            input.clear();
+        }
+    }
+}
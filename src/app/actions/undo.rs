@@ -1,8 +1,8 @@
 //! Undo action implementation
 
 use crate::app::undo::UndoableAction;
+use crate::mail::ImapCommand;
 use crate::mail::types::EmailFlags;
-use crate::mail::{ImapCommand, group_into_threads};
 
 use super::super::App;
 
@@ -18,8 +18,12 @@ impl App {
             }
         };
 
-        // Verify we're on the same account/folder (undo is context-sensitive)
-        if entry.account_id != self.account_id() || entry.folder != self.state.folder.current {
+        // Verify we're on the same account/folder (undo is context-sensitive,
+        // except for undo-send which isn't tied to the folder being viewed)
+        let is_send = matches!(entry.action, UndoableAction::Send { .. });
+        if !is_send
+            && (entry.account_id != self.account_id() || entry.folder != self.state.folder.current)
+        {
             self.state
                 .set_error("Cannot undo action from different account/folder");
             return;
@@ -39,6 +43,7 @@ impl App {
             } => {
                 self.undo_delete(*email, thread_index).await;
             }
+            UndoableAction::Send { id } => self.undo_send(id),
         }
     }
 
@@ -135,8 +140,9 @@ impl App {
 
         // Restore email to state
         self.state.emails.push(email);
-        self.state.emails.sort_by(|a, b| b.date.cmp(&a.date));
-        self.state.thread.threads = group_into_threads(&self.state.emails);
+        let order = self.state.pagination.order;
+        self.state.emails.sort_by(|a, b| order.compare(a, b));
+        self.state.rebuild_threads();
         // Invalidate search cache since threads changed
         self.state.invalidate_search_cache();
 
@@ -3,7 +3,7 @@
 use anyhow::Context;
 
 use crate::app::state::{AddAccountAuth, AddAccountStep, View};
-use crate::config::{AccountConfig, AuthMethod, ImapConfig, SmtpConfig};
+use crate::config::{AccountConfig, AuthMethod, ImapConfig, SignaturePlacement, SmtpConfig};
 use crate::credentials::CredentialStore;
 use crate::oauth2::{GmailOAuth2, get_client_id, get_client_secret};
 
@@ -234,9 +234,19 @@ impl App {
                     server: data.smtp_server.clone(),
                     port: 587,
                     tls: true,
+                    transport: Default::default(),
+                    command: None,
                 },
                 notifications: None,
                 auth,
+                markdown: false,
+                signature: None,
+                signature_file: None,
+                signature_placement: SignaturePlacement::default(),
+                identities: Vec::new(),
+                internal_domains: Vec::new(),
+                authserv_ids: Vec::new(),
+                smime: None,
             };
 
             // Store credentials
@@ -2,15 +2,17 @@
 
 use anyhow::Result;
 use crossterm::event;
+use std::collections::HashSet;
 use std::time::{Duration, Instant};
 
 use crate::constants::{DELETION_DELAY_SECS, SEARCH_DEBOUNCE_MS, THEME_CHECK_INTERVAL_SECS};
 use crate::input::{InputResult, handle_input};
 use crate::mail::{
-    ImapCommand, ImapEvent, folder_cache_key, group_into_threads, merge_into_threads,
+    ImapCommand, ImapEvent, folder_cache_key, group_into_threads, merge_into_threads, sort_threads,
 };
 
 use super::render_thread::RenderThread;
+use super::state::ReaderState;
 use super::{App, EMAIL_PAGE_SIZE};
 
 impl App {
@@ -49,6 +51,58 @@ impl App {
                 self.dirty = true;
             }
 
+            // Send held-back messages whose undo window has closed
+            if self.process_pending_sends().await {
+                self.dirty = true;
+            }
+
+            // Send scheduled messages that are due
+            if self.process_scheduled_sends().await {
+                self.dirty = true;
+            }
+
+            // Sync CardDAV address books in the background
+            if self.process_carddav_sync().await {
+                self.dirty = true;
+            }
+
+            // Show images loaded in the background
+            if self.process_loaded_images() {
+                self.dirty = true;
+            }
+
+            // Decrypt encrypted messages once opened
+            self.start_decryption();
+            if self.process_decrypted_bodies().await {
+                self.dirty = true;
+            }
+
+            // Report piped commands that finished
+            if self.process_pipe_results() {
+                self.dirty = true;
+            }
+
+            // Report one-click unsubscribes that finished
+            if self.process_unsubscribe_results() {
+                self.dirty = true;
+            }
+
+            // Hand the terminal to an interactive pipe command
+            if self.pending_interactive.is_some() {
+                self.run_interactive_pipe(render_thread).await;
+                self.dirty = true;
+            }
+
+            // The render thread owns the terminal, so it writes escapes too
+            if let Some(sequence) = self.pending_escape.take() {
+                render_thread.write(sequence);
+            }
+
+            // Look for calendar conflicts once an invitation is shown
+            if self.check_invite_conflicts().await {
+                self.dirty = true;
+            }
+
             // Render only when dirty (non-blocking - sends to render thread)
             if self.dirty {
                 render_thread.render(self.state.clone());
@@ -85,6 +139,13 @@ impl App {
                 let evt = event::read()?;
                 // Any input event (including resize) requires re-render
                 self.dirty = true;
+                if let event::Event::Resize(width, _) = evt {
+                    // The body rewraps, so keep the scroll position within it
+                    self.state.reader.width = width;
+                    let layout = crate::ui::body_layout(&self.state);
+                    let max = ReaderState::max_scroll(&layout);
+                    self.state.reader.scroll = self.state.reader.scroll.min(max);
+                }
                 match handle_input(evt, &self.state, &self.bindings) {
                     InputResult::Quit => break,
                     InputResult::Action(action) => {
@@ -266,6 +327,16 @@ impl App {
                         self.handle_attachment_fetch_failed(uid, attachment_index, error);
                     }
                 }
+                ImapEvent::RawFetched { uid, raw } => {
+                    if is_active {
+                        self.handle_raw_fetched(uid, raw).await;
+                    }
+                }
+                ImapEvent::RawFetchFailed { uid, error } => {
+                    if is_active {
+                        self.handle_raw_fetch_failed(uid, error);
+                    }
+                }
                 ImapEvent::Error(e) => {
                     if is_active {
                         self.handle_imap_error(e);
@@ -425,10 +496,18 @@ impl App {
     /// Reload state from cache (resets to first page using keyset pagination)
     pub(crate) async fn reload_from_cache(&mut self) {
         let cache_key = self.cache_key();
+        let order = self
+            .cache
+            .get_sort_order(&cache_key)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        self.state.pagination.order = order;
         // First page: no cursor (None)
-        if let Ok(mut emails) = self
+        if let Ok((mut emails, cursor)) = self
             .cache
-            .get_emails_before_cursor(&cache_key, None, EMAIL_PAGE_SIZE)
+            .get_emails_page(&cache_key, order, None, EMAIL_PAGE_SIZE)
             .await
         {
             let inbox_count = emails.len();
@@ -450,9 +529,9 @@ impl App {
                     "reload_from_cache: conversation mode enabled, looking for sent emails in '{}'",
                     sent_cache_key
                 );
-                if let Ok(sent_emails) = self
+                if let Ok((sent_emails, _)) = self
                     .cache
-                    .get_emails_before_cursor(&sent_cache_key, None, EMAIL_PAGE_SIZE)
+                    .get_emails_page(&sent_cache_key, order, None, EMAIL_PAGE_SIZE)
                     .await
                 {
                     tracing::info!(
@@ -466,8 +545,8 @@ impl App {
                     );
                     // Merge sent emails (they have folder field set so can be distinguished)
                     emails.extend(sent_emails);
-                    // Sort by date descending for consistent ordering
-                    emails.sort_by(|a, b| b.date.cmp(&a.date));
+                    // Interleave them in the folder's sort order
+                    emails.sort_by(|a, b| order.compare(a, b));
                 }
             } else {
                 tracing::debug!(
@@ -481,11 +560,11 @@ impl App {
 
             self.state.pagination.emails_loaded = emails.len();
             self.state.pagination.all_loaded = emails.len() < EMAIL_PAGE_SIZE;
-            // Update pagination cursor to oldest email's (date, uid) for deterministic ordering
-            self.state.pagination.cursor = emails.last().map(|e| (e.date, e.uid));
+            // Next page starts after the folder's last email (not a merged sent one)
+            self.state.pagination.cursor = cursor;
             // Assign emails first, then build threads from reference (avoids clone)
             self.state.emails = emails;
-            self.state.thread.threads = group_into_threads(&self.state.emails);
+            self.state.rebuild_threads();
             // Invalidate search cache since threads changed
             self.state.invalidate_search_cache();
         }
@@ -611,9 +690,14 @@ impl App {
 
         let cache_key = self.cache_key();
         // Use cursor-based pagination: get emails older than the last loaded email
-        if let Ok(more_emails) = self
+        if let Ok((more_emails, cursor)) = self
             .cache
-            .get_emails_before_cursor(&cache_key, self.state.pagination.cursor, EMAIL_PAGE_SIZE)
+            .get_emails_page(
+                &cache_key,
+                self.state.pagination.order,
+                self.state.pagination.cursor.as_ref(),
+                EMAIL_PAGE_SIZE,
+            )
             .await
         {
             if more_emails.is_empty() {
@@ -624,8 +708,21 @@ impl App {
             let loaded_count = more_emails.len();
             self.state.pagination.emails_loaded += loaded_count;
             self.state.pagination.all_loaded = loaded_count < EMAIL_PAGE_SIZE;
-            // Update cursor to new oldest email's (date, uid) for deterministic ordering
-            self.state.pagination.cursor = more_emails.last().map(|e| (e.date, e.uid));
+            // Next page starts after the last email in sort order
+            self.state.pagination.cursor = cursor;
+
+            // An email whose flags changed since its page loaded can sort
+            // past the cursor (e.g. read in unread-first order); keep one copy
+            let loaded: HashSet<(u32, Option<&str>)> = self
+                .state
+                .emails
+                .iter()
+                .map(|e| (e.uid, e.folder.as_deref()))
+                .collect();
+            let more_emails: Vec<_> = more_emails
+                .into_iter()
+                .filter(|e| !loaded.contains(&(e.uid, e.folder.as_deref())))
+                .collect();
 
             // Try incremental merge first (much faster for pagination)
             let start_idx = self.state.emails.len();
@@ -640,6 +737,7 @@ impl App {
                 // Incremental merge not possible - do full rebuild
                 self.state.thread.threads = group_into_threads(&self.state.emails);
             }
+            sort_threads(&mut self.state.thread.threads, self.state.pagination.order);
 
             // Invalidate search cache since threads changed
             self.state.invalidate_search_cache();
@@ -722,6 +820,37 @@ impl App {
         had_deletions
     }
 
+    /// Send held-back messages whose undo-send window has closed.
+    /// Returns true if any messages were sent.
+    async fn process_pending_sends(&mut self) -> bool {
+        use crate::app::undo::UndoableAction;
+
+        let delay = self.config.compose.undo_send_delay();
+        let now = Instant::now();
+
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_sends)
+            .into_iter()
+            .partition(|ps| now.duration_since(ps.initiated_at).as_secs() >= delay);
+        self.pending_sends = waiting;
+
+        if due.is_empty() {
+            return false;
+        }
+
+        // Sent messages can no longer be undone
+        let pending_sends = &self.pending_sends;
+        self.undo_stack.retain(|entry| match &entry.action {
+            UndoableAction::Send { id } => pending_sends.iter().any(|ps| ps.id == *id),
+            _ => true,
+        });
+
+        for ps in due {
+            self.send_held(ps.email).await;
+        }
+
+        true
+    }
+
     /// Check if system theme (dark/light mode) has changed and update theme if needed.
     /// Returns true if the theme was changed.
     fn check_system_theme_change(&mut self) -> bool {
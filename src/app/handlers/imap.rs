@@ -140,6 +140,10 @@ impl App {
         // Remove from in-flight tracking
         self.prefetch.in_flight.remove(&uid);
 
+        if self.conversation_body_fetched(uid, folder, &body) {
+            self.dirty = true;
+        }
+
         // Helper to check if an email's folder matches the fetched body's folder.
         // If email has no folder set, assume it's from the current folder.
         let folder_matches = |email: &crate::mail::types::EmailHeader| {
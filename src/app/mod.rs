@@ -4,6 +4,7 @@ mod actions;
 mod event_loop;
 mod handlers;
 pub mod render_thread;
+pub mod schedule;
 pub mod state;
 pub mod undo;
 
@@ -13,7 +14,8 @@ use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 
-use crate::mail::types::EmailBody;
+use crate::mail::types::{ComposeEmail, EmailBody};
+use actions::{InteractivePipe, PipeJob};
 use render_thread::RenderThread;
 
 use crate::account::AccountManager;
@@ -22,14 +24,19 @@ use crate::cache::Cache;
 use crate::config::Config;
 use crate::constants::{EMAIL_PAGE_SIZE, PREFETCH_DEBOUNCE_MS};
 use crate::contacts::ContactsDb;
+use crate::contacts::carddav::SyncSummary;
 use crate::credentials::CredentialStore;
+use crate::graphics::InlineImage;
 use crate::input::KeyBindings;
-use crate::mail::{folder_cache_key, group_into_threads};
+use crate::mail::auth::AuthPolicy;
+use crate::mail::{folder_cache_key, group_into_threads, sort_threads};
+use crate::pipe::PipeOutput;
 use state::{
-    AppState, ConnectionState, FolderState, PaginationState, PolishState, StatusState, ThreadState,
+    AppState, ConnectionState, FolderState, PaginationState, PolishState, ReaderState, StatusState,
+    ThreadState,
 };
 
-use self::undo::{PendingDeletion, UndoEntry};
+use self::undo::{PendingDeletion, PendingSend, UndoEntry};
 
 /// Tracks startup sequence for conversation mode
 ///
@@ -109,6 +116,47 @@ pub struct BodyFetchResult {
     pub result: Result<EmailBody, String>,
 }
 
+/// Result of a background CardDAV sync of all configured address books
+pub struct CardDavSyncResult {
+    /// Started with :sync-contacts (reported even when nothing changed)
+    pub manual: bool,
+    pub result: Result<SyncSummary, String>,
+}
+
+/// Output of a shell command a message was piped to
+pub struct PipeResult {
+    pub command: String,
+    pub result: Result<PipeOutput, String>,
+}
+
+/// Images of a message, extracted (and optionally fetched) in the background
+pub struct LoadedImages {
+    pub uid: u32,
+    pub images: Vec<InlineImage>,
+}
+
+/// Outcome of a one-click unsubscribe request sent in the background
+pub struct UnsubscribeResult {
+    pub list: String,
+    pub result: Result<(), String>,
+}
+
+/// Outcome of a scheduled message sent in the background
+pub struct ScheduledSendResult {
+    /// Row in the send-later queue
+    pub id: i64,
+    pub subject: String,
+    /// The message as sent
+    pub result: Result<ComposeEmail, String>,
+}
+
+/// A message decrypted in the background
+pub struct DecryptedBody {
+    pub cache_key: String,
+    pub uid: u32,
+    pub body: EmailBody,
+}
+
 pub struct App {
     pub(crate) config: Config,
     pub(crate) cache: Arc<Cache>,
@@ -124,10 +172,54 @@ pub struct App {
     pub(crate) body_fetch_rx: mpsc::Receiver<BodyFetchResult>,
     /// Sender for body fetch results (cloned to spawned tasks)
     pub(crate) body_fetch_tx: mpsc::Sender<BodyFetchResult>,
+    /// Channel for receiving CardDAV sync results from background tasks
+    pub(crate) carddav_rx: mpsc::Receiver<CardDavSyncResult>,
+    /// Sender for CardDAV sync results (cloned to spawned tasks)
+    pub(crate) carddav_tx: mpsc::Sender<CardDavSyncResult>,
+    /// When the last CardDAV sync started (None = sync on the next loop)
+    pub(crate) last_carddav_sync: Option<Instant>,
+    /// A CardDAV sync task is running
+    pub(crate) carddav_syncing: bool,
+    /// Channel for receiving message images from background tasks
+    pub(crate) images_rx: mpsc::Receiver<LoadedImages>,
+    /// Sender for message images (cloned to spawned tasks)
+    pub(crate) images_tx: mpsc::Sender<LoadedImages>,
+    /// Channel for receiving the output of piped commands
+    pub(crate) pipe_rx: mpsc::Receiver<PipeResult>,
+    /// Sender for piped command output (cloned to spawned tasks)
+    pub(crate) pipe_tx: mpsc::Sender<PipeResult>,
+    /// Channel for receiving messages decrypted in the background
+    pub(crate) decrypt_rx: mpsc::Receiver<DecryptedBody>,
+    /// Sender for decrypted messages (cloned to spawned tasks)
+    pub(crate) decrypt_tx: mpsc::Sender<DecryptedBody>,
+    /// Channel for receiving one-click unsubscribe outcomes
+    pub(crate) unsubscribe_rx: mpsc::Receiver<UnsubscribeResult>,
+    /// Sender for unsubscribe outcomes (cloned to spawned tasks)
+    pub(crate) unsubscribe_tx: mpsc::Sender<UnsubscribeResult>,
+    /// Messages being decrypted, by (cache key, uid)
+    pub(crate) decrypting: HashSet<(String, u32)>,
+    /// Pipe command waiting for raw messages to be fetched
+    pub(crate) pending_pipe: Option<PipeJob>,
+    /// Interactive pipe command for the event loop to run with the TUI suspended
+    pub(crate) pending_interactive: Option<InteractivePipe>,
+    /// Escape sequence for the render thread to write (OSC 52 copy)
+    pub(crate) pending_escape: Option<String>,
     /// Stack of undoable actions (most recent first)
     pub(crate) undo_stack: Vec<UndoEntry>,
     /// Pending deletions waiting to be executed (delayed by 10 seconds)
     pub(crate) pending_deletions: Vec<PendingDeletion>,
+    /// Sent messages held back for the undo-send window
+    pub(crate) pending_sends: Vec<PendingSend>,
+    /// ID for the next pending send (matches undo entries to pending sends)
+    pub(crate) next_send_id: u64,
+    /// Last time the send-later queue was checked for due messages
+    pub(crate) last_scheduled_check: Option<Instant>,
+    /// Channel for receiving scheduled sends finished in the background
+    pub(crate) scheduled_rx: mpsc::Receiver<ScheduledSendResult>,
+    /// Sender for scheduled send outcomes (cloned to spawned tasks)
+    pub(crate) scheduled_tx: mpsc::Sender<ScheduledSendResult>,
+    /// Queued messages being sent, by ID
+    pub(crate) scheduled_sending: HashSet<i64>,
     /// Tracks when the last search input was received (for debouncing body FTS)
     pub(crate) last_search_input: Option<Instant>,
     /// AI actor handle for summarization and polish (None if AI features disabled)
@@ -157,16 +249,21 @@ impl App {
         let account_name = accounts.active().display_name().to_string();
 
         // Create keybindings
-        let bindings = KeyBindings::new(&config.ui.keybinding_mode);
+        let mut bindings = KeyBindings::new(&config.ui.keybinding_mode);
+        bindings.bind_pipe_commands(&config.pipe);
 
         // Default folder is INBOX
         let default_folder = "INBOX".to_string();
         let cache_key = folder_cache_key(&account_id, &default_folder);
 
         // Load initial state from cache (for default folder)
-        let emails = cache.get_emails(&cache_key, EMAIL_PAGE_SIZE, 0).await?;
+        let order = cache.get_sort_order(&cache_key).await?.unwrap_or_default();
+        let (emails, cursor) = cache
+            .get_emails_page(&cache_key, order, None, EMAIL_PAGE_SIZE)
+            .await?;
         let emails_loaded = emails.len();
-        let threads = group_into_threads(&emails);
+        let mut threads = group_into_threads(&emails);
+        sort_threads(&mut threads, order);
         let total_count = cache.get_email_count(&cache_key).await?;
         let unread_count = cache.get_unread_count(&cache_key).await?;
 
@@ -200,7 +297,8 @@ impl App {
             pagination: PaginationState {
                 emails_loaded,
                 all_loaded: emails_loaded < EMAIL_PAGE_SIZE,
-                ..Default::default()
+                cursor,
+                order,
             },
             folder: FolderState {
                 current: default_folder,
@@ -210,6 +308,11 @@ impl App {
                 enabled: config.ai.is_enabled() && config.ai.enable_polish,
                 ..Default::default()
             },
+            reader: ReaderState {
+                auth_policy: AuthPolicy::for_account(&accounts.active().config),
+                width: crossterm::terminal::size().map_or(80, |(width, _)| width),
+                ..Default::default()
+            },
             ..Default::default()
         };
 
@@ -232,6 +335,12 @@ impl App {
 
         // Create channel for body fetch results from background tasks
         let (body_fetch_tx, body_fetch_rx) = mpsc::channel(64);
+        let (carddav_tx, carddav_rx) = mpsc::channel(4);
+        let (images_tx, images_rx) = mpsc::channel(4);
+        let (pipe_tx, pipe_rx) = mpsc::channel(4);
+        let (decrypt_tx, decrypt_rx) = mpsc::channel(4);
+        let (unsubscribe_tx, unsubscribe_rx) = mpsc::channel(4);
+        let (scheduled_tx, scheduled_rx) = mpsc::channel(4);
 
         let mut app = Self {
             config,
@@ -244,8 +353,30 @@ impl App {
             prefetch: PrefetchState::default(),
             body_fetch_rx,
             body_fetch_tx,
+            carddav_rx,
+            carddav_tx,
+            last_carddav_sync: None,
+            carddav_syncing: false,
+            images_rx,
+            images_tx,
+            pipe_rx,
+            pipe_tx,
+            decrypt_rx,
+            decrypt_tx,
+            unsubscribe_rx,
+            unsubscribe_tx,
+            decrypting: HashSet::new(),
+            pending_pipe: None,
+            pending_interactive: None,
+            pending_escape: None,
             undo_stack: Vec::new(),
             pending_deletions: Vec::new(),
+            pending_sends: Vec::new(),
+            next_send_id: 0,
+            last_scheduled_check: None,
+            scheduled_rx,
+            scheduled_tx,
+            scheduled_sending: HashSet::new(),
             last_search_input: None,
             ai_actor,
             dirty: true, // Start dirty for initial render
@@ -274,8 +405,9 @@ impl App {
         // Run event loop
         let result = self.event_loop(&render_thread).await;
 
-        // Flush any pending deletions before shutdown
+        // Flush any pending deletions and held-back sends before shutdown
         self.flush_pending_deletions().await;
+        self.flush_pending_sends().await;
 
         // Shutdown render thread (handles terminal cleanup)
         render_thread.shutdown();
@@ -310,6 +442,14 @@ impl App {
         self.undo_stack.clear();
     }
 
+    /// Send all held-back messages immediately (for app shutdown)
+    async fn flush_pending_sends(&mut self) {
+        let pending: Vec<_> = self.pending_sends.drain(..).collect();
+        for ps in pending {
+            self.send_held(ps.email).await;
+        }
+    }
+
     /// Execute hybrid search: instant header search + async body FTS
     /// Called after debounce timeout to run the full body search
     pub(crate) async fn execute_search(&mut self) {
@@ -368,6 +508,7 @@ impl App {
         self.state.connection.account_name = account.display_name().to_string();
         self.state.connection.account_index = self.accounts.active_index();
         self.state.connection.connected = account.connected;
+        self.state.reader.auth_policy = AuthPolicy::for_account(&account.config);
 
         // Sync folder list from account handle to state
         if !account.folder_list.is_empty() {
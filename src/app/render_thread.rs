@@ -4,22 +4,29 @@
 //! sent from the main event loop. This keeps the event loop responsive
 //! to IMAP events and user input.
 
-use std::io;
+use std::io::{self, Write};
 use std::sync::mpsc::{self, SyncSender, TrySendError};
 use std::thread::{self, JoinHandle};
 
 use crossterm::{
-    execute,
+    cursor, execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 
 use super::state::AppState;
+use crate::graphics::{self, Placement};
 
 /// Command sent to the render thread.
 pub enum RenderCommand {
     /// Render this state snapshot
     Render(Box<AppState>),
+    /// Hand the terminal back (for an interactive command), then acknowledge
+    Suspend(SyncSender<()>),
+    /// Take the terminal again after `Suspend`, then acknowledge
+    Resume(SyncSender<()>),
+    /// Write an escape sequence (OSC 52 copy) between frames
+    Write(String),
     /// Shutdown the render thread
     Shutdown,
 }
@@ -32,6 +39,35 @@ pub struct RenderThread {
     handle: Option<JoinHandle<()>>,
 }
 
+/// Replace the image drawn after the last frame with `placement`
+fn draw_image(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    state: &AppState,
+    erase: bool,
+    placement: &Option<Placement>,
+) -> io::Result<()> {
+    let Some(protocol) = graphics::protocol() else {
+        return Ok(());
+    };
+    if erase {
+        match graphics::erase_sequence(protocol) {
+            Some(sequence) => terminal.backend_mut().write_all(sequence.as_bytes())?,
+            // Images painted into the cells go away with a full repaint
+            None => {
+                terminal.clear()?;
+                terminal.draw(|f| crate::ui::render(f, state))?;
+            }
+        }
+    }
+    if let Some(sequence) = placement
+        .as_ref()
+        .and_then(|p| graphics::draw_sequence(protocol, p))
+    {
+        terminal.backend_mut().write_all(sequence.as_bytes())?;
+    }
+    terminal.backend_mut().flush()
+}
+
 impl RenderThread {
     /// Spawn a new render thread.
     ///
@@ -65,6 +101,9 @@ impl RenderThread {
                 }
             };
 
+            // Image drawn with a graphics protocol after the last frame
+            let mut drawn: Option<Placement> = None;
+
             // Render loop
             while let Ok(cmd) = cmd_rx.recv() {
                 match cmd {
@@ -72,6 +111,37 @@ impl RenderThread {
                         if let Err(e) = terminal.draw(|f| crate::ui::render(f, &state)) {
                             tracing::error!("Render error: {}", e);
                         }
+                        let placement = state.reader.image_placement.borrow().clone();
+                        if placement != drawn {
+                            if let Err(e) =
+                                draw_image(&mut terminal, &state, drawn.is_some(), &placement)
+                            {
+                                tracing::error!("Image render error: {}", e);
+                            }
+                            drawn = placement;
+                        }
+                    }
+                    RenderCommand::Suspend(ack) => {
+                        disable_raw_mode().ok();
+                        execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show).ok();
+                        let _ = ack.send(());
+                    }
+                    RenderCommand::Resume(ack) => {
+                        enable_raw_mode().ok();
+                        execute!(terminal.backend_mut(), EnterAlternateScreen).ok();
+                        // The next frame repaints everything, images included
+                        terminal.clear().ok();
+                        drawn = None;
+                        let _ = ack.send(());
+                    }
+                    RenderCommand::Write(sequence) => {
+                        let backend = terminal.backend_mut();
+                        if let Err(e) = backend
+                            .write_all(sequence.as_bytes())
+                            .and_then(|()| backend.flush())
+                        {
+                            tracing::error!("Terminal write error: {}", e);
+                        }
                     }
                     RenderCommand::Shutdown => break,
                 }
@@ -106,6 +176,30 @@ impl RenderThread {
         }
     }
 
+    /// Restore the normal terminal so a command can use it, waiting until
+    /// the render thread has done so.
+    pub fn suspend(&self) {
+        self.wait_for(RenderCommand::Suspend);
+    }
+
+    /// Take the terminal back after `suspend`.
+    pub fn resume(&self) {
+        self.wait_for(RenderCommand::Resume);
+    }
+
+    /// Write an escape sequence to the terminal. Unlike frames it must not
+    /// be dropped, so this waits for room in the channel.
+    pub fn write(&self, sequence: String) {
+        let _ = self.cmd_tx.send(RenderCommand::Write(sequence));
+    }
+
+    fn wait_for(&self, command: fn(SyncSender<()>) -> RenderCommand) {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        if self.cmd_tx.send(command(ack_tx)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+    }
+
     /// Shutdown the render thread and wait for it to finish.
     pub fn shutdown(mut self) {
         // Send shutdown command (blocking to ensure it's received)
@@ -0,0 +1,142 @@
+//! Parsing of "send later" times entered in the composer
+//!
+//! Accepted forms:
+//! - `+30m`, `+2h`, `+1d` - relative to now
+//! - `14:30` - today, or tomorrow if that time has passed
+//! - `tomorrow`, `tomorrow 9:15` - tomorrow (08:00 if no time given)
+//! - `2025-03-01`, `2025-03-01 17:00` - a specific date (08:00 if no time given)
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+
+/// Example shown in the prompt
+pub const SEND_TIME_HINT: &str = "+30m, 14:30, tomorrow 9:00, 2025-03-01 17:00";
+
+/// Time used when only a day is given
+fn default_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(8, 0, 0).expect("valid time")
+}
+
+/// Parse a send time relative to `now`
+///
+/// Returns None if the input isn't understood or isn't in the future.
+pub fn parse_send_time<Tz: TimeZone>(input: &str, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let input = input.trim().to_lowercase();
+
+    let time = if let Some(offset) = input.strip_prefix('+') {
+        now.clone() + parse_offset(offset.trim())?
+    } else {
+        let mut parts = input.split_whitespace();
+        let first = parts.next()?;
+        let second = parts.next();
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let today = now.date_naive();
+        let (date, clock) = match (first, second) {
+            ("today", clock) => (today, clock),
+            ("tomorrow", clock) => (today.succ_opt()?, clock),
+            (clock, None) if parse_clock(clock).is_some() => {
+                // A bare time means the next time the clock shows it
+                let clock = parse_clock(clock)?;
+                let candidate = at(now, today, clock)?;
+                if candidate > *now {
+                    return Some(candidate);
+                }
+                return at(now, today.succ_opt()?, clock);
+            }
+            (date, clock) => (NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?, clock),
+        };
+        let clock = match clock {
+            Some(clock) => parse_clock(clock)?,
+            None => default_time(),
+        };
+        at(now, date, clock)?
+    };
+
+    (time > *now).then_some(time)
+}
+
+/// Format a send time (Unix timestamp) for display
+pub fn format_send_time(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_else(Utc::now)
+        .with_timezone(&Local)
+        .format("%a %b %-d %H:%M")
+        .to_string()
+}
+
+/// Parse "30m", "2h" or "1d"
+fn parse_offset(offset: &str) -> Option<TimeDelta> {
+    let unit = offset.chars().last()?;
+    let amount: i64 = offset[..offset.len() - unit.len_utf8()]
+        .trim()
+        .parse()
+        .ok()?;
+    match unit {
+        'm' => TimeDelta::try_minutes(amount),
+        'h' => TimeDelta::try_hours(amount),
+        'd' => TimeDelta::try_days(amount),
+        _ => None,
+    }
+}
+
+fn parse_clock(clock: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(clock, "%H:%M").ok()
+}
+
+/// Combine a date and time in `now`'s timezone
+fn at<Tz: TimeZone>(now: &DateTime<Tz>, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Tz>> {
+    now.timezone()
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    fn parse(input: &str) -> Option<String> {
+        parse_send_time(input, &now()).map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn test_relative_offsets() {
+        assert_eq!(parse("+30m").as_deref(), Some("2025-03-10 12:30"));
+        assert_eq!(parse("+ 2h").as_deref(), Some("2025-03-10 14:00"));
+        assert_eq!(parse("+1d").as_deref(), Some("2025-03-11 12:00"));
+        assert_eq!(parse("+0m"), None);
+        assert_eq!(parse("+5x"), None);
+    }
+
+    #[test]
+    fn test_clock_time_rolls_over() {
+        assert_eq!(parse("14:30").as_deref(), Some("2025-03-10 14:30"));
+        assert_eq!(parse("9:15").as_deref(), Some("2025-03-11 09:15"));
+    }
+
+    #[test]
+    fn test_named_days() {
+        assert_eq!(parse("tomorrow").as_deref(), Some("2025-03-11 08:00"));
+        assert_eq!(parse("Tomorrow 17:45").as_deref(), Some("2025-03-11 17:45"));
+        assert_eq!(parse("today 18:00").as_deref(), Some("2025-03-10 18:00"));
+        assert_eq!(parse("today 08:00"), None);
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(parse("2025-04-01").as_deref(), Some("2025-04-01 08:00"));
+        assert_eq!(
+            parse("2025-04-01 17:00").as_deref(),
+            Some("2025-04-01 17:00")
+        );
+        assert_eq!(parse("2024-01-01 17:00"), None);
+        assert_eq!(parse("2025-04-01 17:00 extra"), None);
+        assert_eq!(parse("next week"), None);
+        assert_eq!(parse(""), None);
+    }
+}
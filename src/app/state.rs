@@ -4,15 +4,27 @@
 //! UI layer imports from app layer, not vice versa.
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use aho_corasick::AhoCorasick;
 
+use crate::cache::SortCursor;
 use crate::command::{CommandHelp, CommandResult, PendingCommand};
+use crate::config::PipeInput;
 use crate::constants::ERROR_TTL_SECS;
 use crate::contacts::Contact;
+use crate::graphics::{InlineImage, Placement};
 use crate::input::KeybindingEntry;
-use crate::mail::types::{Attachment, ComposeEmail, EmailBody, EmailHeader};
-use crate::mail::{EmailThread, ThreadId};
+use crate::mail::auth::AuthPolicy;
+use crate::mail::calendar::{self, Event, Invite};
+use crate::mail::html::{self, Run, StyledLine};
+use crate::mail::links::{self, Link};
+use crate::mail::quotes::{self, Row};
+use crate::mail::template::Template;
+use crate::mail::types::{
+    Attachment, ComposeEmail, EmailBody, EmailHeader, ScheduledMessage, SortOrder,
+};
+use crate::mail::{EmailThread, ThreadId, group_into_threads, sort_threads};
 
 /// Info about another account for the status bar indicators
 #[derive(Debug, Clone, Default)]
@@ -48,6 +60,8 @@ pub enum View {
     },
     /// Contacts view
     Contacts,
+    /// Send-later queue
+    Scheduled,
 }
 
 /// Steps in the add account wizard
@@ -208,12 +222,13 @@ impl ModalState {
     }
 }
 
-/// View mode filter - show all emails or only starred
+/// View mode filter - show all emails, only starred, or mailing lists grouped by list
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ViewMode {
     #[default]
     All,
     Starred,
+    Lists,
 }
 
 /// Type of match for search results - used to show [body] indicator
@@ -229,11 +244,58 @@ pub enum MatchType {
     Both,
 }
 
-/// State for editing a contact's name
+/// State for editing a contact
 #[derive(Debug, Clone, Default)]
 pub struct ContactEditState {
     pub contact_id: i64,
+    pub field: ContactField,
     pub name: String,
+    /// Addresses besides the primary one, comma separated
+    pub other_emails: String,
+    pub phone: String,
+    pub organization: String,
+}
+
+impl ContactEditState {
+    /// Text of the field being edited
+    pub fn active_mut(&mut self) -> &mut String {
+        match self.field {
+            ContactField::Name => &mut self.name,
+            ContactField::OtherEmails => &mut self.other_emails,
+            ContactField::Phone => &mut self.phone,
+            ContactField::Organization => &mut self.organization,
+        }
+    }
+}
+
+/// Editable fields of a contact, in popup order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContactField {
+    #[default]
+    Name,
+    OtherEmails,
+    Phone,
+    Organization,
+}
+
+impl ContactField {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Name => Self::OtherEmails,
+            Self::OtherEmails => Self::Phone,
+            Self::Phone => Self::Organization,
+            Self::Organization => Self::Name,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            Self::Name => Self::Organization,
+            Self::OtherEmails => Self::Name,
+            Self::Phone => Self::OtherEmails,
+            Self::Organization => Self::Phone,
+        }
+    }
 }
 
 /// State for AI grammar/polish preview
@@ -295,13 +357,15 @@ impl StatusState {
 }
 
 /// Pagination state for keyset pagination
-/// Uses composite cursor (date, uid) for deterministic ordering when dates are identical
+/// Uses a composite cursor (sort key, date, uid) for deterministic ordering when keys are identical
 #[derive(Debug, Clone, Default)]
 pub struct PaginationState {
     pub emails_loaded: usize,
     pub all_loaded: bool,
-    /// Composite cursor: (date, uid) for deterministic pagination
-    pub cursor: Option<(i64, u32)>,
+    /// Composite cursor: where the last loaded page ended
+    pub cursor: Option<SortCursor>,
+    /// Order of the list (remembered per folder)
+    pub order: SortOrder,
 }
 
 /// Contacts view state
@@ -311,6 +375,15 @@ pub struct ContactsViewState {
     pub selected: usize,
     pub scroll_offset: usize,
     pub editing: Option<ContactEditState>,
+    /// Contacts marked for export (by id)
+    pub marked: HashSet<i64>,
+}
+
+/// Send-later queue view state
+#[derive(Debug, Clone, Default)]
+pub struct ScheduledViewState {
+    pub messages: Vec<ScheduledMessage>,
+    pub selected: usize,
 }
 
 /// Composer autocomplete state
@@ -375,6 +448,100 @@ pub struct PolishState {
     pub enabled: bool,
 }
 
+/// Composer UI state that lives outside the message being composed
+#[derive(Debug, Clone, Default)]
+pub struct ComposerState {
+    /// Show the rendered Markdown preview instead of the editable body
+    pub preview: bool,
+    /// "Name <address>" of the identity the message is sent from
+    pub from_label: String,
+    /// Whether there is more than one account or identity to send from
+    pub from_selectable: bool,
+    /// From selector popup (None = closed)
+    pub from_picker: Option<FromPicker>,
+    /// "Send later" time prompt (None = closed)
+    pub schedule_input: Option<String>,
+    /// Template picker popup (None = closed)
+    pub template_picker: Option<TemplatePicker>,
+    /// Internal domains of the sending account (recipients elsewhere are external)
+    pub internal_domains: Vec<String>,
+    /// External domains already warned about; sending again goes ahead
+    pub external_confirmed: Vec<String>,
+}
+
+/// Template picker listing the files in the templates directory
+#[derive(Debug, Clone, Default)]
+pub struct TemplatePicker {
+    pub templates: Vec<Template>,
+    pub selected: usize,
+}
+
+/// From selector listing every account and identity
+#[derive(Debug, Clone, Default)]
+pub struct FromPicker {
+    pub choices: Vec<FromChoice>,
+    pub selected: usize,
+}
+
+/// A sender choice in the From selector
+#[derive(Debug, Clone)]
+pub struct FromChoice {
+    pub account_index: usize,
+    pub identity_index: usize,
+    pub label: String,
+}
+
+/// What to do with a raw message once it has been fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawPurpose {
+    /// Forward with the original attachments, or wrapped as message/rfc822
+    Forward { as_attachment: bool },
+    /// Show the message's images
+    Images,
+    /// Show the raw source and full headers
+    Source,
+}
+
+/// A raw message request waiting on IMAP
+#[derive(Debug, Clone)]
+pub struct PendingRaw {
+    pub uid: u32,
+    pub purpose: RawPurpose,
+}
+
+/// Piping messages to shell commands
+#[derive(Debug, Clone, Default)]
+pub struct PipeState {
+    /// Command being typed after `|`
+    pub prompt: Option<PipePrompt>,
+    /// Output of the last captured command, shown in a popup
+    pub output: Option<PipeOutputView>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PipePrompt {
+    pub input: String,
+    /// What the command gets on stdin (Tab cycles)
+    pub target: PipeInput,
+}
+
+#[derive(Debug, Clone)]
+pub struct PipeOutputView {
+    pub command: String,
+    /// Exit status, e.g. "exit 0"
+    pub status: String,
+    pub success: bool,
+    pub lines: Arc<Vec<String>>,
+    pub scroll: usize,
+}
+
+impl PipeOutputView {
+    pub fn scroll_by(&mut self, delta: i32) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll = (self.scroll as i64 + i64::from(delta)).clamp(0, max as i64) as usize;
+    }
+}
+
 /// Thread navigation state
 #[derive(Debug, Clone, Default)]
 pub struct ThreadState {
@@ -402,10 +569,16 @@ pub struct SearchState {
 #[derive(Debug, Clone, Default)]
 pub struct ReaderState {
     pub body: Option<EmailBody>,
-    /// Cached sanitized body text (invalidated when body changes).
+    /// Cached rendered body, keyed by pane width and whether it is the HTML part
+    /// (invalidated when body changes).
     /// Uses RefCell for interior mutability so rendering can populate cache.
-    pub cached_sanitized: std::cell::RefCell<Option<String>>,
+    pub cached_sanitized: std::cell::RefCell<Option<(u16, bool, Arc<RenderedBody>)>>,
+    /// Show the HTML part of multipart/alternative messages instead of the plain one
+    pub show_html: bool,
+    /// Wrapped row at the top of the body pane
     pub scroll: usize,
+    /// Columns the body pane is wrapped to (the terminal width)
+    pub width: u16,
     pub show_summary: bool,
     pub cached_summary: Option<(u32, String)>,
     pub cached_thread_summary: Option<(ThreadId, String)>,
@@ -420,46 +593,574 @@ pub struct ReaderState {
     pub show_attachments: bool,
     /// Pending attachment save (index, save path)
     pub pending_attachment_save: Option<(usize, std::path::PathBuf)>,
+    /// Attachment index being fetched to import as vCard
+    pub pending_contact_import: Option<usize>,
+    /// Which authentication results the active account believes
+    pub auth_policy: AuthPolicy,
+    /// Calendar invitation carried by the body
+    pub invite: Option<Invite>,
+    /// Other cached invitations overlapping `invite` (None = not checked yet)
+    pub invite_conflicts: Option<Vec<Event>>,
+    /// Links in the body, listed as numbered footnotes under it
+    pub links: Vec<Link>,
+    /// Link-hint prompt (None = not choosing a link)
+    pub link_hint: Option<LinkHint>,
+    /// Images of the message: inline (`cid:`) parts, image attachments and,
+    /// if allowed, remote images
+    pub images: Vec<Arc<InlineImage>>,
+    /// Message `images` were loaded for
+    pub images_uid: Option<u32>,
+    pub image_selected: usize,
+    /// Whether the image viewer replaces the body
+    pub show_images: bool,
+    /// Image the UI placed for drawing with a graphics protocol after the frame
+    pub image_placement: std::cell::RefCell<Option<Placement>>,
+    /// Raw source shown in place of the body
+    pub source: Option<SourceView>,
+    /// Search within the body
+    pub find: BodyFind,
+    /// Show quoted text and the signature instead of folding them
+    pub show_quotes: bool,
+    /// Whole thread stacked in place of the single body
+    pub conversation: Option<Conversation>,
+}
+
+/// Every message of a thread stacked in the reader, oldest first
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub messages: Vec<ConversationMessage>,
+    /// Message at the top of the view (the one replies and summaries use)
+    pub selected: usize,
+    /// First row shown
+    pub scroll: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversationMessage {
+    pub email: EmailHeader,
+    /// Folder the message is fetched from
+    pub folder: String,
+    /// None until fetched
+    pub body: Option<Arc<EmailBody>>,
+    /// Body text by line
+    pub lines: Arc<Vec<String>>,
+    /// Body shown (collapsed messages are a header line)
+    pub expanded: bool,
+}
+
+impl ConversationMessage {
+    pub fn new(email: EmailHeader, folder: String, expanded: bool) -> Self {
+        Self {
+            email,
+            folder,
+            body: None,
+            lines: Arc::default(),
+            expanded,
+        }
+    }
+
+    pub fn set_body(&mut self, body: EmailBody) {
+        self.lines = Arc::new(body.display_text().lines().map(str::to_string).collect());
+        self.body = Some(Arc::new(body));
+    }
+}
+
+/// A row of the conversation view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversationRow {
+    /// Header line of a message
+    Header(usize),
+    /// A body row of a message (quotes of earlier messages folded)
+    Body(usize, Row),
+    /// Body of an expanded message still being fetched
+    Loading(usize),
+    /// Blank line after an expanded message
+    Gap(usize),
+}
+
+impl ConversationRow {
+    pub fn message(&self) -> usize {
+        match *self {
+            ConversationRow::Header(n)
+            | ConversationRow::Body(n, _)
+            | ConversationRow::Loading(n)
+            | ConversationRow::Gap(n) => n,
+        }
+    }
+}
+
+impl Conversation {
+    /// Rows of the stacked thread; quoted blocks and signatures are folded
+    /// unless `show_quotes`
+    pub fn rows(&self, show_quotes: bool) -> Vec<ConversationRow> {
+        let mut rows = Vec::new();
+        for (n, message) in self.messages.iter().enumerate() {
+            rows.push(ConversationRow::Header(n));
+            if !message.expanded {
+                continue;
+            }
+            if message.body.is_none() {
+                rows.push(ConversationRow::Loading(n));
+            } else {
+                let folded = if show_quotes {
+                    Vec::new()
+                } else {
+                    quotes::detect(&message.lines)
+                };
+                rows.extend(
+                    quotes::rows(message.lines.len(), &folded)
+                        .into_iter()
+                        .map(|row| ConversationRow::Body(n, row)),
+                );
+            }
+            rows.push(ConversationRow::Gap(n));
+        }
+        rows
+    }
+
+    /// Scroll by `delta` rows; the message at the top becomes the selected one
+    pub fn scroll_by(&mut self, delta: i32, show_quotes: bool) {
+        let rows = self.rows(show_quotes);
+        let max = rows.len().saturating_sub(1);
+        self.scroll = (self.scroll as i64 + i64::from(delta)).clamp(0, max as i64) as usize;
+        if let Some(row) = rows.get(self.scroll) {
+            self.selected = row.message();
+        }
+    }
+
+    /// Scroll to the header of message `n` and select it
+    pub fn select(&mut self, n: usize, show_quotes: bool) {
+        if let Some(row) = self
+            .rows(show_quotes)
+            .iter()
+            .position(|row| *row == ConversationRow::Header(n))
+        {
+            self.scroll = row;
+            self.selected = n;
+        }
+    }
+
+    /// Expand or collapse the selected message
+    pub fn toggle_selected(&mut self, show_quotes: bool) {
+        if let Some(message) = self.messages.get_mut(self.selected) {
+            message.expanded = !message.expanded;
+        }
+        self.select(self.selected, show_quotes);
+    }
+
+    /// Put a fetched body in place (returns true if a message was waiting
+    /// for it)
+    pub fn body_fetched(&mut self, uid: u32, folder: &str, body: &EmailBody) -> bool {
+        let Some(message) = self
+            .messages
+            .iter_mut()
+            .find(|m| m.email.uid == uid && m.folder == folder)
+        else {
+            return false;
+        };
+        message.set_body(body.clone());
+        true
+    }
+}
+
+/// Search term highlighted in the body
+#[derive(Debug, Clone, Default)]
+pub struct BodyFind {
+    pub query: String,
+    /// Search term being typed (None = not typing)
+    pub input: Option<String>,
+    /// `query` came from the inbox search, not a find in this message
+    pub seeded: bool,
+}
+
+impl BodyFind {
+    /// Highlight `query` from the start (e.g. the inbox search term)
+    pub fn seeded(query: &str) -> Self {
+        Self {
+            query: query.trim().to_string(),
+            input: None,
+            seeded: true,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.input.is_some() || !self.query.is_empty()
+    }
+}
+
+/// Body laid out for display, with control characters removed
+#[derive(Debug)]
+pub enum RenderedBody {
+    Text(String),
+    /// HTML part laid out at the pane width
+    Html(Vec<StyledLine>),
+}
+
+impl RenderedBody {
+    /// Text of each line, without styling
+    pub fn line_texts(&self) -> Vec<String> {
+        match self {
+            RenderedBody::Text(text) => text.lines().map(str::to_string).collect(),
+            RenderedBody::Html(lines) => lines
+                .iter()
+                .map(|line| line.iter().map(|run| run.text.as_str()).collect())
+                .collect(),
+        }
+    }
+}
+
+/// Label typed so far while choosing a link to follow
+#[derive(Debug, Clone, Default)]
+pub struct LinkHint {
+    pub label: String,
+    /// Copy the URL to the clipboard instead of opening it
+    pub copy: bool,
+}
+
+/// Raw message source and full header table
+#[derive(Debug, Clone)]
+pub struct SourceView {
+    pub uid: u32,
+    /// The message exactly as stored, for saving and piping
+    pub raw: Arc<Vec<u8>>,
+    /// Source lines for display (invalid UTF-8 replaced)
+    pub lines: Arc<Vec<String>>,
+    /// Every header field in order, with decoded values
+    pub headers: Arc<Vec<(String, String)>>,
+    /// Show the header table instead of the source
+    pub show_headers: bool,
+    /// First line (or header row) shown
+    pub scroll: usize,
+    /// Search term highlighted in the source
+    pub query: String,
+    /// Search term being typed (None = not searching)
+    pub find_input: Option<String>,
+}
+
+impl SourceView {
+    pub fn new(uid: u32, raw: Vec<u8>) -> Self {
+        let lines = String::from_utf8_lossy(&raw)
+            .lines()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        let headers = crate::mail::parser::parse_headers(&raw);
+        Self {
+            uid,
+            raw: Arc::new(raw),
+            lines: Arc::new(lines),
+            headers: Arc::new(headers),
+            show_headers: false,
+            scroll: 0,
+            query: String::new(),
+            find_input: None,
+        }
+    }
+
+    /// Rows in the current mode (source lines or header fields)
+    pub fn row_count(&self) -> usize {
+        if self.show_headers {
+            self.headers.len()
+        } else {
+            self.lines.len()
+        }
+    }
+
+    fn row_matches(&self, row: usize, needle: &str) -> bool {
+        let contains = |text: &str| text.to_lowercase().contains(needle);
+        if self.show_headers {
+            self.headers
+                .get(row)
+                .is_some_and(|(name, value)| contains(name) || contains(value))
+        } else {
+            self.lines.get(row).is_some_and(|line| contains(line))
+        }
+    }
+
+    /// Rows containing the search term
+    pub fn match_count(&self) -> usize {
+        let needle = self.query.to_lowercase();
+        if needle.is_empty() {
+            return 0;
+        }
+        (0..self.row_count())
+            .filter(|row| self.row_matches(*row, &needle))
+            .count()
+    }
+
+    /// Scroll to the next (or previous) row containing the search term,
+    /// wrapping around; false if nothing matches
+    pub fn find_next(&mut self, forward: bool) -> bool {
+        self.find(forward, 1)
+    }
+
+    /// Scroll to the first row from the current one containing the search term
+    pub fn find_first(&mut self) -> bool {
+        self.find(true, 0)
+    }
+
+    fn find(&mut self, forward: bool, first_step: usize) -> bool {
+        let needle = self.query.to_lowercase();
+        if needle.is_empty() {
+            return false;
+        }
+        let found = find_row(self.row_count(), self.scroll, forward, first_step, |row| {
+            self.row_matches(row, &needle)
+        });
+        if let Some(row) = found {
+            self.scroll = row;
+        }
+        found.is_some()
+    }
+
+    pub fn scroll_by(&mut self, delta: i32) {
+        let max = self.row_count().saturating_sub(1);
+        self.scroll = (self.scroll as i64 + i64::from(delta)).clamp(0, max as i64) as usize;
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = self.row_count().saturating_sub(1);
+    }
+}
+
+/// Where each line of the body pane starts once wrapped to the pane width
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BodyLayout {
+    /// First wrapped row of each line: body rows, then the link footnotes
+    pub starts: Vec<usize>,
+    /// Wrapped rows in all
+    pub rows: usize,
+}
+
+impl BodyLayout {
+    /// Line shown at wrapped row `row`
+    pub fn line_at(&self, row: usize) -> usize {
+        self.starts
+            .partition_point(|&start| start <= row)
+            .saturating_sub(1)
+    }
+
+    /// First wrapped row of line `line`
+    pub fn start_of(&self, line: usize) -> usize {
+        self.starts.get(line).copied().unwrap_or(self.rows)
+    }
+}
+
+/// Rows with a line containing `query` (case-insensitive), in order
+pub fn matching_rows(lines: &[String], rows: &[Row], query: &str) -> Vec<usize> {
+    let needle = query.to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let matches = |line: usize| lines[line].to_lowercase().contains(&needle);
+    rows.iter()
+        .enumerate()
+        .filter(|(_, row)| match **row {
+            Row::Line(line) => matches(line),
+            Row::Folded(block) => (block.start..block.start + block.len).any(matches),
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// First of `count` rows, stepping from `start` (skipping `first_step` rows)
+/// and wrapping around, for which `matches` holds
+fn find_row(
+    count: usize,
+    start: usize,
+    forward: bool,
+    first_step: usize,
+    matches: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    (first_step..first_step + count)
+        .map(|step| {
+            if forward {
+                (start + step) % count
+            } else {
+                (start + count * 2 - step) % count
+            }
+        })
+        .find(|row| matches(*row))
 }
 
 impl ReaderState {
     /// Set body and invalidate sanitized cache
     pub fn set_body(&mut self, body: Option<EmailBody>) {
+        self.invite = body
+            .as_ref()
+            .and_then(|b| b.calendar.as_deref())
+            .and_then(calendar::parse);
+        self.invite_conflicts = None;
+        self.show_images = false;
+        self.source = None;
         self.body = body;
+        self.refresh_links();
         *self.cached_sanitized.borrow_mut() = None;
     }
 
-    /// Get sanitized body text, computing and caching if needed.
-    /// Uses interior mutability to cache on first access.
-    pub fn sanitized_body(&self, sanitize_fn: fn(&str) -> String) -> String {
+    /// Whether the reader shows the conversation stack (not the source,
+    /// images, attachments or a summary in its place)
+    pub fn conversation_shown(&self) -> bool {
+        self.conversation.is_some()
+            && self.source.is_none()
+            && !self.show_images
+            && !self.show_attachments
+            && !self.show_summary
+            && !self.summary_loading
+    }
+
+    /// Whether the body is displayed from its HTML part
+    pub fn shows_html(&self) -> bool {
+        self.body
+            .as_ref()
+            .is_some_and(|b| b.shows_html(self.show_html))
+    }
+
+    /// Switch between the plain and HTML parts
+    pub fn set_show_html(&mut self, show_html: bool) {
+        self.show_html = show_html;
+        self.refresh_links();
+        self.reset_scroll();
+    }
+
+    /// Number links in the order the displayed part shows them
+    fn refresh_links(&mut self) {
+        let html_shown = self.shows_html();
+        self.links = self
+            .body
+            .as_ref()
+            .map(|b| links::collect(b, html_shown))
+            .unwrap_or_default();
+        self.link_hint = None;
+    }
+
+    /// Get the body laid out for a pane `width` columns wide, computing and
+    /// caching if needed. Uses interior mutability to cache on first access.
+    pub fn rendered_body(&self, width: u16, sanitize_fn: fn(&str) -> String) -> Arc<RenderedBody> {
+        let html = self.shows_html();
+        // Plain text is wrapped by the paragraph, so only HTML depends on width
+        let key_width = if html { width } else { 0 };
         {
             let cache = self.cached_sanitized.borrow();
-            if let Some(ref s) = *cache {
-                return s.clone();
+            if let Some((w, h, ref rendered)) = *cache
+                && w == key_width
+                && h == html
+            {
+                return rendered.clone();
             }
         }
         // Cache miss - compute and store
-        let sanitized = if let Some(ref body) = self.body {
-            sanitize_fn(&body.display_text())
-        } else {
-            String::new()
+        let rendered = match self.body {
+            Some(ref body) => {
+                let html_lines = body
+                    .html
+                    .as_deref()
+                    .filter(|_| html)
+                    .and_then(|h| html::render(h, width as usize));
+                match html_lines {
+                    Some(rendered) => RenderedBody::Html(
+                        rendered
+                            .lines
+                            .into_iter()
+                            .map(|line| {
+                                line.into_iter()
+                                    .map(|run| Run {
+                                        text: sanitize_fn(&run.text),
+                                        style: run.style,
+                                    })
+                                    .collect()
+                            })
+                            .collect(),
+                    ),
+                    None => RenderedBody::Text(sanitize_fn(&body.display_text())),
+                }
+            }
+            None => RenderedBody::Text(String::new()),
         };
-        *self.cached_sanitized.borrow_mut() = Some(sanitized.clone());
-        sanitized
+        let rendered = Arc::new(rendered);
+        *self.cached_sanitized.borrow_mut() = Some((key_width, html, rendered.clone()));
+        rendered
+    }
+
+    /// Body lines as searched: the laid-out body once rendered, else the text
+    fn body_lines(&self) -> Vec<String> {
+        match *self.cached_sanitized.borrow() {
+            Some((_, _, ref rendered)) => rendered.line_texts(),
+            None => self
+                .body
+                .as_ref()
+                .map(|b| b.display_text().lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
     }
 
-    /// Get maximum scroll value based on current content
-    pub fn max_scroll(&self) -> usize {
-        if let Some(ref body) = self.body {
-            body.display_text().lines().count().saturating_sub(1)
+    /// Body lines and the rows showing them, with quoted blocks and the
+    /// signature folded unless `show_quotes`
+    pub fn body_rows(&self) -> (Vec<String>, Vec<Row>) {
+        let lines = self.body_lines();
+        let folded = if self.show_quotes {
+            Vec::new()
         } else {
-            0
+            quotes::detect(&lines)
+        };
+        let rows = quotes::rows(lines.len(), &folded);
+        (lines, rows)
+    }
+
+    /// Fold or unfold quoted blocks, keeping the top line in view;
+    /// `layout` lays the body out as it is shown
+    pub fn toggle_quotes(&mut self, layout: impl Fn(&Self) -> BodyLayout) {
+        let (_, rows) = self.body_rows();
+        let top = rows
+            .get(layout(self).line_at(self.scroll))
+            .map(Row::first_line);
+        self.show_quotes = !self.show_quotes;
+        let layout = layout(self);
+        if let Some(top) = top {
+            let (_, rows) = self.body_rows();
+            let row = rows.iter().position(|r| r.covers(top)).unwrap_or(0);
+            self.scroll = layout.start_of(row);
         }
+        self.scroll = self.scroll.min(Self::max_scroll(&layout));
+    }
+
+    /// Whether the search term highlights anything in the body
+    pub fn find_has_matches(&self) -> bool {
+        if self.find.query.is_empty() {
+            return false;
+        }
+        let (lines, rows) = self.body_rows();
+        !matching_rows(&lines, &rows, &self.find.query).is_empty()
+    }
+
+    /// Scroll to the next (or previous) row containing the search term,
+    /// wrapping around; `first` starts at the current row. False if nothing
+    /// matches.
+    pub fn find_in_body(&mut self, forward: bool, first: bool, layout: &BodyLayout) -> bool {
+        let (lines, rows) = self.body_rows();
+        let matches = matching_rows(&lines, &rows, &self.find.query);
+        let first_step = if first { 0 } else { 1 };
+        // The scroll position may be down in the link footnotes
+        let start = layout.line_at(self.scroll).min(rows.len());
+        let found = find_row(rows.len(), start, forward, first_step, |row| {
+            matches.binary_search(&row).is_ok()
+        });
+        if let Some(row) = found {
+            self.scroll = layout.start_of(row);
+        }
+        found.is_some()
+    }
+
+    /// Get maximum scroll value based on the wrapped body
+    pub fn max_scroll(layout: &BodyLayout) -> usize {
+        layout.rows.saturating_sub(1)
     }
 
     /// Scroll down by one line (bounded)
-    pub fn scroll_down(&mut self) {
-        let max = self.max_scroll();
+    pub fn scroll_down(&mut self, layout: &BodyLayout) {
+        let max = Self::max_scroll(layout);
         if self.scroll < max {
             self.scroll += 1;
         }
@@ -471,8 +1172,8 @@ impl ReaderState {
     }
 
     /// Scroll by delta (bounded)
-    pub fn scroll_by(&mut self, delta: i32) {
-        let max = self.max_scroll();
+    pub fn scroll_by(&mut self, delta: i32, layout: &BodyLayout) {
+        let max = Self::max_scroll(layout);
         let new_scroll = (self.scroll as i32 + delta).clamp(0, max as i32);
         self.scroll = new_scroll as usize;
     }
@@ -495,6 +1196,18 @@ impl ReaderState {
             self.attachment_selected -= 1;
         }
     }
+
+    /// Show the next image
+    pub fn image_down(&mut self) {
+        if self.image_selected + 1 < self.images.len() {
+            self.image_selected += 1;
+        }
+    }
+
+    /// Show the previous image
+    pub fn image_up(&mut self) {
+        self.image_selected = self.image_selected.saturating_sub(1);
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -537,17 +1250,29 @@ pub struct AppState {
     // View mode (all emails or starred only)
     pub view_mode: ViewMode,
 
-    // Pagination state (keyset pagination using date cursor)
+    // Pagination state (keyset pagination in the folder's sort order)
     pub pagination: PaginationState,
 
     // Contacts view state
     pub contacts: ContactsViewState,
 
+    // Send-later queue view state
+    pub scheduled: ScheduledViewState,
+
     // Composer autocomplete state
     pub autocomplete: AutocompleteState,
 
     // AI polish state
     pub polish: PolishState,
+
+    // Composer UI state (markdown preview, From selector)
+    pub composer: ComposerState,
+
+    // Raw message request waiting on IMAP
+    pub pending_raw: Option<PendingRaw>,
+
+    // Pipe prompt and command output popup
+    pub pipe: PipeState,
 }
 
 impl AppState {
@@ -580,6 +1305,31 @@ impl AppState {
     }
 
     /// Toggle thread expansion
+    /// Make the selected conversation message the reader's message, so
+    /// replies, summaries and the other reader actions act on it
+    pub fn sync_conversation_focus(&mut self, force: bool) {
+        let Some(message) = self
+            .reader
+            .conversation
+            .as_ref()
+            .and_then(|c| c.messages.get(c.selected))
+        else {
+            return;
+        };
+        let uid = message.email.uid;
+        if !force && matches!(self.view, View::Reader { uid: current } if current == uid) {
+            return;
+        }
+        let body = message.body.as_deref().cloned();
+        self.view = View::Reader { uid };
+        self.reader.set_body(body);
+        self.reader.reset_scroll();
+        self.reader.find = Default::default();
+        self.reader.show_summary = false;
+        self.reader.show_attachments = false;
+        self.reader.attachments.clear();
+    }
+
     pub fn toggle_thread_expansion(&mut self) {
         if let Some(thread) = self.current_thread() {
             let id = thread.id.clone();
@@ -807,19 +1557,49 @@ impl AppState {
                 .into_iter()
                 .filter(|thread| thread.emails(&self.emails).any(|e| e.is_flagged()))
                 .collect(),
+            ViewMode::Lists => {
+                let mut lists: Vec<&EmailThread> = filtered
+                    .into_iter()
+                    .filter(|thread| self.thread_list_label(thread).is_some())
+                    .collect();
+                // Stable sort keeps newest-first order within each list
+                lists.sort_by_cached_key(|thread| self.thread_list_key(thread));
+                lists
+            }
         }
     }
 
+    /// Label of the mailing list a thread belongs to (newest message with one)
+    pub fn thread_list_label<'a>(&'a self, thread: &EmailThread) -> Option<&'a str> {
+        thread
+            .emails(&self.emails)
+            .find_map(|e| e.list.as_ref().and_then(|l| l.label()))
+    }
+
+    /// Sort key for grouping threads by list
+    fn thread_list_key(&self, thread: &EmailThread) -> String {
+        self.thread_list_label(thread)
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+
+    /// Rebuild the threads from `emails`, in the list's sort order
+    pub fn rebuild_threads(&mut self) {
+        self.thread.threads = group_into_threads(&self.emails);
+        sort_threads(&mut self.thread.threads, self.pagination.order);
+    }
+
     /// Invalidate the search cache (call when threads change)
     pub fn invalidate_search_cache(&mut self) {
         self.search.cached_visible_indices = None;
     }
 
-    /// Toggle between all emails and starred-only view
-    pub fn toggle_view_mode(&mut self) {
-        self.view_mode = match self.view_mode {
-            ViewMode::All => ViewMode::Starred,
-            ViewMode::Starred => ViewMode::All,
+    /// Switch to the given filtered view, or back to all emails if already in it
+    pub fn toggle_view_mode(&mut self, mode: ViewMode) {
+        self.view_mode = if self.view_mode == mode {
+            ViewMode::All
+        } else {
+            mode
         };
         // Update search cache with new view mode, preserving existing body matches
         let existing_body_matches = self.search.body_match_uids.clone();
@@ -834,6 +1614,11 @@ impl AppState {
         matches!(self.view_mode, ViewMode::Starred)
     }
 
+    /// Check if currently in the group-by-list view
+    pub fn is_lists_view(&self) -> bool {
+        matches!(self.view_mode, ViewMode::Lists)
+    }
+
     /// Clear search and reset selection
     pub fn clear_search(&mut self) {
         self.search.query.clear();
@@ -911,7 +1696,7 @@ impl AppState {
         }
 
         // Merge: thread visible if ANY email matches headers OR body
-        let indices: Vec<usize> = self
+        let mut indices: Vec<usize> = self
             .thread
             .threads
             .iter()
@@ -926,12 +1711,16 @@ impl AppState {
                 let matches_view = match self.view_mode {
                     ViewMode::All => true,
                     ViewMode::Starred => thread.emails(&self.emails).any(|e| e.is_flagged()),
+                    ViewMode::Lists => self.thread_list_label(thread).is_some(),
                 };
 
                 matches_search && matches_view
             })
             .map(|(i, _)| i)
             .collect();
+        if self.view_mode == ViewMode::Lists {
+            indices.sort_by_cached_key(|&i| self.thread_list_key(&self.thread.threads[i]));
+        }
 
         self.search.cached_visible_indices = Some(indices);
         self.search.cached_query = self.search.query.clone();
@@ -1035,3 +1824,293 @@ impl AppState {
         uids
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reply whose attribution and quote (lines 3-6) fold into row 3
+    const REPLY: &str = "Agenda looks fine.\n\
+                         Nothing to add.\n\
+                         \n\
+                         On Mon, Alice wrote:\n\
+                         > Here is the AGENDA\n\
+                         > for Monday\n\
+                         > Alice\n\
+                         Thanks, agenda noted";
+
+    fn reader(text: &str) -> ReaderState {
+        let mut reader = ReaderState::default();
+        reader.set_body(Some(EmailBody {
+            text: Some(text.to_string()),
+            ..Default::default()
+        }));
+        reader
+    }
+
+    /// One wrapped row per body row
+    fn unwrapped(reader: &ReaderState) -> BodyLayout {
+        let (_, rows) = reader.body_rows();
+        BodyLayout {
+            starts: (0..rows.len()).collect(),
+            rows: rows.len(),
+        }
+    }
+
+    fn header(uid: u32) -> EmailHeader {
+        EmailHeader {
+            uid,
+            message_id: None,
+            subject: "Plans".to_string(),
+            from_addr: "alice@example.com".to_string(),
+            from_name: None,
+            to_addr: None,
+            cc_addr: None,
+            date: i64::from(uid),
+            flags: crate::mail::types::EmailFlags::empty(),
+            has_attachments: false,
+            preview: None,
+            body_cached: false,
+            in_reply_to: None,
+            references: Vec::new(),
+            folder: None,
+            list: None,
+            size: 0,
+        }
+    }
+
+    fn message(uid: u32, text: Option<&str>, expanded: bool) -> ConversationMessage {
+        let mut message = ConversationMessage::new(header(uid), "INBOX".to_string(), expanded);
+        if let Some(text) = text {
+            message.set_body(EmailBody {
+                text: Some(text.to_string()),
+                ..Default::default()
+            });
+        }
+        message
+    }
+
+    /// A collapsed message, one still loading and a two-line one
+    fn conversation() -> Conversation {
+        Conversation {
+            messages: vec![
+                message(1, Some("Seen before"), false),
+                message(2, None, true),
+                message(3, Some("See you\nBob"), true),
+            ],
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    #[test]
+    fn test_conversation_rows() {
+        use ConversationRow::*;
+
+        assert_eq!(
+            conversation().rows(false),
+            vec![
+                Header(0),
+                Header(1),
+                Loading(1),
+                Gap(1),
+                Header(2),
+                Body(2, Row::Line(0)),
+                Body(2, Row::Line(1)),
+                Gap(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conversation_scroll_selects_top_message() {
+        let mut conversation = conversation();
+
+        conversation.scroll_by(2, false);
+        assert_eq!((conversation.scroll, conversation.selected), (2, 1));
+        conversation.scroll_by(3, false);
+        assert_eq!((conversation.scroll, conversation.selected), (5, 2));
+        // Clamped at both ends
+        conversation.scroll_by(100, false);
+        assert_eq!((conversation.scroll, conversation.selected), (7, 2));
+        conversation.scroll_by(-100, false);
+        assert_eq!((conversation.scroll, conversation.selected), (0, 0));
+    }
+
+    #[test]
+    fn test_conversation_toggle_keeps_selected_in_view() {
+        let mut conversation = Conversation {
+            messages: vec![message(1, Some(REPLY), true), message(2, Some("Ok"), true)],
+            selected: 0,
+            scroll: 0,
+        };
+
+        // Header, five rows with the quote folded, gap
+        conversation.select(1, false);
+        assert_eq!(conversation.scroll, 7);
+        // Showing the quote pushes the message down; selecting follows it
+        conversation.select(conversation.selected, true);
+        assert_eq!(conversation.scroll, 10);
+
+        conversation.select(0, false);
+        conversation.toggle_selected(false);
+        assert!(!conversation.messages[0].expanded);
+        assert_eq!((conversation.scroll, conversation.selected), (0, 0));
+        conversation.select(1, false);
+        assert_eq!(conversation.scroll, 1);
+        conversation.toggle_selected(false);
+        assert!(!conversation.messages[1].expanded);
+        assert_eq!((conversation.scroll, conversation.selected), (1, 1));
+    }
+
+    #[test]
+    fn test_conversation_body_fetched() {
+        let mut conversation = conversation();
+        let body = EmailBody {
+            text: Some("Running late".to_string()),
+            ..Default::default()
+        };
+
+        assert!(!conversation.body_fetched(2, "Sent", &body));
+        assert!(!conversation.body_fetched(9, "INBOX", &body));
+        assert!(conversation.body_fetched(2, "INBOX", &body));
+        assert_eq!(
+            conversation.rows(false)[2],
+            ConversationRow::Body(1, Row::Line(0))
+        );
+    }
+
+    #[test]
+    fn test_sync_conversation_focus() {
+        let mut state = AppState {
+            view: View::Reader { uid: 1 },
+            ..Default::default()
+        };
+        let mut conversation = conversation();
+        conversation.select(2, false);
+        state.reader.conversation = Some(conversation);
+
+        state.sync_conversation_focus(false);
+        assert!(matches!(state.view, View::Reader { uid: 3 }));
+        assert_eq!(
+            state.reader.body.as_ref().unwrap().text.as_deref(),
+            Some("See you\nBob")
+        );
+
+        // Already the reader's message: left alone unless forced
+        state.reader.scroll = 1;
+        state.sync_conversation_focus(false);
+        assert_eq!(state.reader.scroll, 1);
+        state.sync_conversation_focus(true);
+        assert_eq!(state.reader.scroll, 0);
+    }
+
+    #[test]
+    fn test_matching_rows_ignore_case() {
+        let lines: Vec<String> = vec!["Hello there".into(), "nothing".into(), "HELLO".into()];
+        let rows = [Row::Line(0), Row::Line(1), Row::Line(2)];
+
+        assert_eq!(matching_rows(&lines, &rows, "hello"), vec![0, 2]);
+        assert_eq!(matching_rows(&lines, &rows, "HeLLo"), vec![0, 2]);
+        assert!(matching_rows(&lines, &rows, "").is_empty());
+    }
+
+    #[test]
+    fn test_matching_rows_in_folded_quotes() {
+        let mut reader = reader(REPLY);
+        let (lines, rows) = reader.body_rows();
+        assert_eq!(rows.len(), 5);
+        assert!(matches!(rows[3], Row::Folded(_)));
+        assert_eq!(matching_rows(&lines, &rows, "agenda"), vec![0, 3, 4]);
+        assert_eq!(matching_rows(&lines, &rows, "monday"), vec![3]);
+
+        // Unfolded, the match is on the quoted line's own row
+        reader.show_quotes = true;
+        let (lines, rows) = reader.body_rows();
+        assert_eq!(matching_rows(&lines, &rows, "monday"), vec![5]);
+    }
+
+    #[test]
+    fn test_find_row() {
+        let matches = [1, 4];
+        let is_match = |row| matches.contains(&row);
+
+        // The first match counts the current row, the next one skips it
+        assert_eq!(find_row(6, 1, true, 0, is_match), Some(1));
+        assert_eq!(find_row(6, 1, true, 1, is_match), Some(4));
+        assert_eq!(find_row(6, 1, false, 1, is_match), Some(4));
+        // Both directions wrap around
+        assert_eq!(find_row(6, 4, true, 1, is_match), Some(1));
+        assert_eq!(find_row(6, 5, true, 1, is_match), Some(1));
+        assert_eq!(find_row(6, 0, false, 1, is_match), Some(4));
+        // A lone match finds itself again
+        assert_eq!(find_row(6, 1, true, 1, |row| row == 1), Some(1));
+        assert_eq!(find_row(6, 0, true, 0, |_| false), None);
+        assert_eq!(find_row(0, 0, true, 0, |_| true), None);
+    }
+
+    #[test]
+    fn test_find_in_body() {
+        let mut reader = reader(REPLY);
+        reader.find.query = "AGENDA".to_string();
+        // The first row wraps onto two
+        let layout = BodyLayout {
+            starts: vec![0, 2, 3, 4, 5],
+            rows: 6,
+        };
+
+        assert!(reader.find_in_body(true, true, &layout));
+        assert_eq!(reader.scroll, 0);
+        // Into the folded quote, then past it
+        assert!(reader.find_in_body(true, false, &layout));
+        assert_eq!(reader.scroll, 4);
+        assert!(reader.find_in_body(true, false, &layout));
+        assert_eq!(reader.scroll, 5);
+        assert!(reader.find_in_body(true, false, &layout));
+        assert_eq!(reader.scroll, 0);
+        assert!(reader.find_in_body(false, false, &layout));
+        assert_eq!(reader.scroll, 5);
+
+        // Partway down a wrapped row, that row is the current one
+        reader.scroll = 1;
+        assert!(reader.find_in_body(true, false, &layout));
+        assert_eq!(reader.scroll, 4);
+
+        reader.find.query = "receipt".to_string();
+        assert!(!reader.find_in_body(true, true, &layout));
+        assert_eq!(reader.scroll, 4);
+    }
+
+    #[test]
+    fn test_find_has_matches() {
+        // Regression: n/N and Esc only act on the term while it matches
+        let mut reader = reader(REPLY);
+        reader.find = BodyFind::seeded("  Monday ");
+        assert_eq!(reader.find.query, "Monday");
+        assert!(reader.find.seeded);
+        assert!(reader.find_has_matches());
+
+        reader.find.query = "receipt".to_string();
+        assert!(!reader.find_has_matches());
+        reader.find.query.clear();
+        assert!(!reader.find_has_matches());
+    }
+
+    #[test]
+    fn test_toggle_quotes_keeps_top_line() {
+        let mut reader = reader(REPLY);
+        reader.scroll = 4; // "Thanks, agenda noted"
+
+        reader.toggle_quotes(unwrapped);
+        assert!(reader.show_quotes);
+        assert_eq!(reader.scroll, 7);
+        reader.toggle_quotes(unwrapped);
+        assert_eq!(reader.scroll, 4);
+
+        // A quoted line on top lands on its placeholder
+        reader.toggle_quotes(unwrapped);
+        reader.scroll = 5;
+        reader.toggle_quotes(unwrapped);
+        assert_eq!(reader.scroll, 3);
+    }
+}
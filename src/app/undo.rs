@@ -4,10 +4,11 @@
 //! - Toggle read/unread
 //! - Toggle star
 //! - Delete (with delayed execution)
+//! - Send (with delayed execution)
 
 use std::time::Instant;
 
-use crate::mail::types::EmailHeader;
+use crate::mail::types::{ComposeEmail, EmailHeader};
 
 /// Represents an action that can be undone
 #[derive(Debug, Clone)]
@@ -24,6 +25,8 @@ pub enum UndoableAction {
         /// Index in threads where email was (for restoring selection)
         thread_index: usize,
     },
+    /// Send was performed - the message is held in `pending_sends` until the window closes
+    Send { id: u64 },
 }
 
 /// Entry in the undo stack
@@ -44,3 +47,11 @@ pub struct PendingDeletion {
     pub account_id: String,
     pub folder: String,
 }
+
+/// A sent message that is held back until the undo-send window closes
+#[derive(Debug, Clone)]
+pub struct PendingSend {
+    pub id: u64,
+    pub email: ComposeEmail,
+    pub initiated_at: Instant,
+}
@@ -4,7 +4,7 @@ use anyhow::Result;
 use sqlx::{Row, SqlitePool};
 use std::collections::HashSet;
 
-use crate::mail::types::EmailBody;
+use crate::mail::types::{EmailBody, SecurityInfo};
 
 /// Cache key for body cache: (account_id, uid)
 pub type BodyCacheKey = (String, u32);
@@ -28,7 +28,7 @@ pub async fn get_email_body(
 
     // L2: Query SQLite database
     let row = sqlx::query(
-        "SELECT text_body, html_body FROM email_bodies WHERE account_id = ? AND uid = ?",
+        "SELECT text_body, html_body, security, auth_results, calendar, raw_message FROM email_bodies WHERE account_id = ? AND uid = ?",
     )
     .bind(account_id)
     .bind(uid as i64)
@@ -36,9 +36,29 @@ pub async fn get_email_body(
     .await?;
 
     if let Some(ref r) = row {
+        let security = r
+            .get::<Option<String>, _>("security")
+            .map(|s| SecurityInfo::from_cache_string(&s));
+        // Decrypted text is never stored: encrypted mail comes back locked,
+        // to be decrypted from the raw message when opened
+        let ciphertext = if security.as_ref().is_some_and(|s| s.encrypted) {
+            match r.get::<Option<Vec<u8>>, _>("raw_message") {
+                Some(raw) => Some(raw.into()),
+                None => return Ok(None),
+            }
+        } else {
+            None
+        };
         let body = EmailBody {
             text: r.get("text_body"),
             html: r.get("html_body"),
+            security,
+            auth_results: r
+                .get::<Option<String>, _>("auth_results")
+                .map(|s| s.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            calendar: r.get("calendar"),
+            ciphertext,
         };
         // Populate L1 cache for future reads
         body_cache.insert(key, body.clone()).await;
@@ -104,14 +124,28 @@ pub async fn insert_email_body(
     uid: u32,
     body: &EmailBody,
 ) -> Result<()> {
+    if let Some(ref raw) = body.ciphertext {
+        // Not decrypted yet: keep the raw message to decrypt later
+        return insert_email_body_with_raw(pool, body_cache, account_id, uid, body, raw).await;
+    }
+    let key = (account_id.to_string(), uid);
+    if is_encrypted(body) {
+        // Keep decrypted mail in memory only
+        body_cache.insert(key, body.clone()).await;
+        return Ok(());
+    }
+
     // Write to SQLite L2
     sqlx::query(
-        "INSERT OR REPLACE INTO email_bodies (account_id, uid, text_body, html_body) VALUES (?, ?, ?, ?)",
+        "INSERT OR REPLACE INTO email_bodies (account_id, uid, text_body, html_body, security, auth_results, calendar) VALUES (?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(account_id)
     .bind(uid as i64)
     .bind(&body.text)
     .bind(&body.html)
+    .bind(body.security.as_ref().map(SecurityInfo::to_cache_string))
+    .bind(auth_results_column(body))
+    .bind(&body.calendar)
     .execute(pool)
     .await?;
 
@@ -122,12 +156,20 @@ pub async fn insert_email_body(
         .await?;
 
     // Also populate moka L1 hot cache
-    let key = (account_id.to_string(), uid);
     body_cache.insert(key, body.clone()).await;
 
     Ok(())
 }
 
+fn is_encrypted(body: &EmailBody) -> bool {
+    body.security.as_ref().is_some_and(|s| s.encrypted)
+}
+
+/// Authentication headers are single unfolded lines, so store one per line
+fn auth_results_column(body: &EmailBody) -> Option<String> {
+    (!body.auth_results.is_empty()).then(|| body.auth_results.join("\n"))
+}
+
 /// Insert an email body with raw message into both L1 and L2 caches.
 /// The raw message is stored for later attachment extraction.
 pub async fn insert_email_body_with_raw(
@@ -138,15 +180,27 @@ pub async fn insert_email_body_with_raw(
     body: &EmailBody,
     raw_message: &[u8],
 ) -> Result<()> {
-    // Write to SQLite L2 with raw message
+    // Write to SQLite L2 with raw message (only the ciphertext for encrypted mail)
+    let (text, html, calendar) = if is_encrypted(body) {
+        (None, None, None)
+    } else {
+        (
+            body.text.as_ref(),
+            body.html.as_ref(),
+            body.calendar.as_ref(),
+        )
+    };
     sqlx::query(
-        "INSERT OR REPLACE INTO email_bodies (account_id, uid, text_body, html_body, raw_message) VALUES (?, ?, ?, ?, ?)",
+        "INSERT OR REPLACE INTO email_bodies (account_id, uid, text_body, html_body, raw_message, security, auth_results, calendar) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(account_id)
     .bind(uid as i64)
-    .bind(&body.text)
-    .bind(&body.html)
+    .bind(text)
+    .bind(html)
     .bind(raw_message)
+    .bind(body.security.as_ref().map(SecurityInfo::to_cache_string))
+    .bind(auth_results_column(body))
+    .bind(calendar)
     .execute(pool)
     .await?;
 
@@ -163,6 +217,18 @@ pub async fn insert_email_body_with_raw(
     Ok(())
 }
 
+/// Calendar parts of every cached body in an account's folders
+/// (bodies are keyed by `account/folder`)
+pub async fn get_account_calendars(pool: &SqlitePool, account: &str) -> Result<Vec<String>> {
+    let calendars = sqlx::query_scalar(
+        "SELECT calendar FROM email_bodies WHERE calendar IS NOT NULL AND instr(account_id, ?) = 1 ORDER BY account_id, uid",
+    )
+    .bind(format!("{}/", account))
+    .fetch_all(pool)
+    .await?;
+    Ok(calendars)
+}
+
 /// Invalidate all body cache entries for a given account.
 pub fn invalidate_body_cache_for_account(body_cache: &BodyCache, account_id: &str) {
     // moka doesn't have a prefix-based invalidation, so we need to iterate
@@ -4,7 +4,10 @@ use anyhow::Result;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqlitePool};
 
-use crate::mail::types::{EmailFlags, EmailHeader};
+use crate::mail::types::{EmailFlags, EmailHeader, ListInfo, SortOrder};
+
+/// Columns read into an EmailHeader
+const EMAIL_COLUMNS: &str = "uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder, list_id, list_name, list_unsubscribe, list_one_click, list_archive, list_post, size";
 
 /// Convert a SQLite row to an EmailHeader.
 fn row_to_email_header(row: SqliteRow) -> EmailHeader {
@@ -28,9 +31,46 @@ fn row_to_email_header(row: SqliteRow) -> EmailHeader {
         in_reply_to: row.get("in_reply_to"),
         references,
         folder: row.get("folder"),
+        list: row_to_list(&row),
+        size: row.get::<i64, _>("size") as u32,
     }
 }
 
+/// Rebuild mailing-list info from the list_* columns (None if the message had none).
+fn row_to_list(row: &SqliteRow) -> Option<ListInfo> {
+    let unsubscribe: Option<String> = row.get("list_unsubscribe");
+    let list = ListInfo {
+        id: row.get("list_id"),
+        name: row.get("list_name"),
+        unsubscribe: unsubscribe
+            .map(|s| s.lines().map(|s| s.to_string()).collect())
+            .unwrap_or_default(),
+        one_click: row
+            .get::<Option<bool>, _>("list_one_click")
+            .unwrap_or(false),
+        archive: row.get("list_archive"),
+        post: row.get("list_post"),
+    };
+    (list != ListInfo::default()).then_some(list)
+}
+
+/// Bind the list_* columns of an INSERT (URIs cannot contain newlines).
+fn bind_list<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    list: Option<&'q ListInfo>,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(list.and_then(|l| l.id.as_deref()))
+        .bind(list.and_then(|l| l.name.as_deref()))
+        .bind(
+            list.filter(|l| !l.unsubscribe.is_empty())
+                .map(|l| l.unsubscribe.join("\n")),
+        )
+        .bind(list.is_some_and(|l| l.one_click))
+        .bind(list.and_then(|l| l.archive.as_deref()))
+        .bind(list.and_then(|l| l.post.as_deref()))
+}
+
 /// Insert a single email header (for tests).
 #[cfg(test)]
 pub async fn insert_email(pool: &SqlitePool, account_id: &str, header: &EmailHeader) -> Result<()> {
@@ -44,11 +84,11 @@ pub async fn insert_email(pool: &SqlitePool, account_id: &str, header: &EmailHea
         .folder
         .clone()
         .or_else(|| account_id.split_once('/').map(|(_, f)| f.to_string()));
-    sqlx::query(
+    let query = sqlx::query(
         r#"
         INSERT OR REPLACE INTO emails
-        (uid, account_id, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        (uid, account_id, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder, list_id, list_name, list_unsubscribe, list_one_click, list_archive, list_post, size)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(header.uid as i64)
@@ -66,9 +106,11 @@ pub async fn insert_email(pool: &SqlitePool, account_id: &str, header: &EmailHea
     .bind(header.body_cached)
     .bind(&header.in_reply_to)
     .bind(references_str)
-    .bind(&folder)
-    .execute(pool)
-    .await?;
+    .bind(&folder);
+    bind_list(query, header.list.as_ref())
+        .bind(i64::from(header.size))
+        .execute(pool)
+        .await?;
     Ok(())
 }
 
@@ -101,11 +143,11 @@ pub async fn insert_emails(
             Some(header.references.join(" "))
         };
         let folder = header.folder.as_ref().or(default_folder.as_ref());
-        sqlx::query(
+        let query = sqlx::query(
             r#"
             INSERT OR REPLACE INTO emails
-            (uid, account_id, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            (uid, account_id, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder, list_id, list_name, list_unsubscribe, list_one_click, list_archive, list_post, size)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(header.uid as i64)
@@ -123,16 +165,19 @@ pub async fn insert_emails(
         .bind(header.body_cached)
         .bind(&header.in_reply_to)
         .bind(references_str)
-        .bind(folder)
-        .execute(&mut *tx)
-        .await?;
+        .bind(folder);
+        bind_list(query, header.list.as_ref())
+            .bind(i64::from(header.size))
+            .execute(&mut *tx)
+            .await?;
     }
 
     tx.commit().await?;
     Ok(())
 }
 
-/// Get emails with OFFSET pagination (legacy, kept for compatibility).
+/// Get emails with OFFSET pagination (for tests).
+#[cfg(test)]
 pub async fn get_emails(
     pool: &SqlitePool,
     account_id: &str,
@@ -141,7 +186,7 @@ pub async fn get_emails(
 ) -> Result<Vec<EmailHeader>> {
     let rows = sqlx::query(
         r#"
-        SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder
+        SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder, list_id, list_name, list_unsubscribe, list_one_click, list_archive, list_post, size
         FROM emails
         WHERE account_id = ?
         ORDER BY date DESC
@@ -157,54 +202,144 @@ pub async fn get_emails(
     Ok(rows.into_iter().map(row_to_email_header).collect())
 }
 
-/// Keyset pagination: get emails before a given (date, uid) cursor (O(1) vs O(offset)).
-/// Uses composite cursor to handle identical timestamps deterministically.
-pub async fn get_emails_before_cursor(
+/// Where a page of the email list ends: the last row's sort key, date and
+/// uid (the uid keeps rows with equal keys in a deterministic order).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortCursor {
+    key: SortKey,
+    date: i64,
+    uid: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SortKey {
+    /// Date orders have no key of their own
+    None,
+    Int(i64),
+    Text(String),
+}
+
+impl SortCursor {
+    /// Cursor just past `row`, read from a page query. The key is the value
+    /// SQL sorted by, so later flag changes in memory can't move the cursor.
+    fn from_row(order: SortOrder, row: &SqliteRow) -> Self {
+        let key = match order {
+            SortOrder::DateDesc | SortOrder::DateAsc => SortKey::None,
+            SortOrder::Sender | SortOrder::Subject => SortKey::Text(row.get("sort_key")),
+            SortOrder::Size | SortOrder::UnreadFirst | SortOrder::FlaggedFirst => {
+                SortKey::Int(row.get("sort_key"))
+            }
+        };
+        Self {
+            key,
+            date: row.get("date"),
+            uid: row.get::<i64, _>("uid") as u32,
+        }
+    }
+}
+
+/// SQL expression an order sorts by before the date, and whether it sorts
+/// descending. Text keys compare with NOCASE so the cursor's raw value
+/// orders the same way.
+fn sort_key(order: SortOrder) -> Option<(String, bool)> {
+    match order {
+        SortOrder::DateDesc | SortOrder::DateAsc => None,
+        SortOrder::Sender => Some((
+            "COALESCE(NULLIF(from_name, ''), from_addr) COLLATE NOCASE".to_string(),
+            false,
+        )),
+        SortOrder::Subject => Some(("subject COLLATE NOCASE".to_string(), false)),
+        SortOrder::Size => Some(("size".to_string(), true)),
+        SortOrder::UnreadFirst => {
+            Some((format!("((flags & {}) = 0)", EmailFlags::SEEN.bits()), true))
+        }
+        SortOrder::FlaggedFirst => Some((
+            format!("((flags & {}) != 0)", EmailFlags::FLAGGED.bits()),
+            true,
+        )),
+    }
+}
+
+/// SELECT for one page in `order`: the sort key, then the date (newest
+/// first, except for `DateAsc`), then the uid
+fn page_query(order: SortOrder, after_cursor: bool) -> String {
+    let (dir, op) = if order == SortOrder::DateAsc {
+        ("ASC", ">")
+    } else {
+        ("DESC", "<")
+    };
+    let mut order_by = format!("date {dir}, uid {dir}");
+    let mut after = format!("(date {op} ? OR (date = ? AND uid {op} ?))");
+    if let Some((key, descending)) = sort_key(order) {
+        let (dir, op) = if descending {
+            ("DESC", "<")
+        } else {
+            ("ASC", ">")
+        };
+        order_by = format!("{key} {dir}, {order_by}");
+        after = format!("({key} {op} ? OR ({key} = ? AND {after}))");
+    }
+    let filter = if after_cursor {
+        format!(" AND {after}")
+    } else {
+        String::new()
+    };
+    let key_column = match sort_key(order) {
+        Some((key, _)) => format!(", {key} AS sort_key"),
+        None => String::new(),
+    };
+    format!(
+        "SELECT {EMAIL_COLUMNS}{key_column} FROM emails WHERE account_id = ?{filter} ORDER BY {order_by} LIMIT ?"
+    )
+}
+
+/// Keyset pagination: get the emails after a cursor in the given order
+/// (O(1) vs O(offset)). No cursor = the first page. Also returns the
+/// cursor for the next page (None if the page is empty).
+pub async fn get_emails_page(
     pool: &SqlitePool,
     account_id: &str,
-    cursor: Option<(i64, u32)>,
+    order: SortOrder,
+    cursor: Option<&SortCursor>,
     limit: usize,
-) -> Result<Vec<EmailHeader>> {
-    let rows = match cursor {
-        Some((date, uid)) => {
-            // Composite cursor: get emails that are either:
-            // 1. Older than the cursor date, OR
-            // 2. Same date but with a smaller UID
-            sqlx::query(
-                r#"
-                SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder
-                FROM emails
-                WHERE account_id = ? AND (date < ? OR (date = ? AND uid < ?))
-                ORDER BY date DESC, uid DESC
-                LIMIT ?
-                "#,
-            )
-            .bind(account_id)
-            .bind(date)
-            .bind(date)
-            .bind(uid as i64)
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-        None => {
-            sqlx::query(
-                r#"
-                SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder
-                FROM emails
-                WHERE account_id = ?
-                ORDER BY date DESC, uid DESC
-                LIMIT ?
-                "#,
-            )
+) -> Result<(Vec<EmailHeader>, Option<SortCursor>)> {
+    let sql = page_query(order, cursor.is_some());
+    let mut query = sqlx::query(&sql).bind(account_id);
+    if let Some(cursor) = cursor {
+        query = match cursor.key {
+            SortKey::None => query,
+            SortKey::Int(key) => query.bind(key).bind(key),
+            SortKey::Text(ref key) => query.bind(key).bind(key),
+        };
+        query = query
+            .bind(cursor.date)
+            .bind(cursor.date)
+            .bind(cursor.uid as i64);
+    }
+    let rows = query.bind(limit as i64).fetch_all(pool).await?;
+
+    let next = rows.last().map(|row| SortCursor::from_row(order, row));
+    Ok((rows.into_iter().map(row_to_email_header).collect(), next))
+}
+
+/// Sort order remembered for a folder (None = never changed).
+pub async fn get_sort_order(pool: &SqlitePool, account_id: &str) -> Result<Option<SortOrder>> {
+    let name: Option<String> =
+        sqlx::query_scalar("SELECT sort_order FROM folder_sort WHERE account_id = ?")
             .bind(account_id)
-            .bind(limit as i64)
-            .fetch_all(pool)
-            .await?
-        }
-    };
+            .fetch_optional(pool)
+            .await?;
+    Ok(name.as_deref().and_then(SortOrder::parse))
+}
 
-    Ok(rows.into_iter().map(row_to_email_header).collect())
+/// Remember a folder's sort order.
+pub async fn set_sort_order(pool: &SqlitePool, account_id: &str, order: SortOrder) -> Result<()> {
+    sqlx::query("INSERT OR REPLACE INTO folder_sort (account_id, sort_order) VALUES (?, ?)")
+        .bind(account_id)
+        .bind(order.name())
+        .execute(pool)
+        .await?;
+    Ok(())
 }
 
 /// Get a single email by UID.
@@ -216,7 +351,7 @@ pub async fn get_email(
 ) -> Result<Option<EmailHeader>> {
     let row = sqlx::query(
         r#"
-        SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder
+        SELECT uid, message_id, subject, from_addr, from_name, to_addr, cc_addr, date, flags, has_attachments, preview, body_cached, in_reply_to, references_list, folder, list_id, list_name, list_unsubscribe, list_one_click, list_archive, list_post, size
         FROM emails
         WHERE account_id = ? AND uid = ?
         "#,
@@ -325,6 +460,31 @@ pub async fn remove_flag(
     }
 }
 
+/// Get UIDs whose size is unknown (cached before sizes were stored).
+pub async fn get_uids_without_size(pool: &SqlitePool, account_id: &str) -> Result<Vec<u32>> {
+    let uids: Vec<i64> =
+        sqlx::query_scalar("SELECT uid FROM emails WHERE account_id = ? AND size = 0")
+            .bind(account_id)
+            .fetch_all(pool)
+            .await?;
+    Ok(uids.into_iter().map(|uid| uid as u32).collect())
+}
+
+/// Store RFC822.SIZE values fetched for already-cached emails.
+pub async fn update_sizes(pool: &SqlitePool, account_id: &str, sizes: &[(u32, u32)]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    for &(uid, size) in sizes {
+        sqlx::query("UPDATE emails SET size = ? WHERE account_id = ? AND uid = ?")
+            .bind(i64::from(size))
+            .bind(account_id)
+            .bind(i64::from(uid))
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
 /// Get all UIDs and their flags from cache (for flag sync).
 pub async fn get_all_uid_flags(
     pool: &SqlitePool,
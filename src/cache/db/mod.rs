@@ -7,10 +7,12 @@
 //! - `body.rs` - Email body caching with L1 (moka) and L2 (SQLite)
 //! - `attachment.rs` - Attachment metadata caching
 //! - `search.rs` - Full-text search using FTS5
+//! - `scheduled.rs` - Send-later queue
 
 mod attachment;
 mod body;
 mod email;
+mod scheduled;
 mod schema;
 mod search;
 
@@ -23,7 +25,11 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use super::sync::SyncState;
-use crate::mail::types::{Attachment, EmailBody, EmailFlags, EmailHeader};
+use crate::mail::types::{
+    Attachment, ComposeEmail, EmailBody, EmailFlags, EmailHeader, ScheduledMessage, SortOrder,
+};
+
+pub use email::SortCursor;
 
 /// Connection pool size - allows concurrent reads and writes.
 /// Sized for multi-account usage with concurrent operations:
@@ -190,6 +196,7 @@ impl Cache {
         email::insert_emails(&self.pool, account_id, headers).await
     }
 
+    #[cfg(test)]
     pub async fn get_emails(
         &self,
         account_id: &str,
@@ -199,13 +206,22 @@ impl Cache {
         email::get_emails(&self.pool, account_id, limit, offset).await
     }
 
-    pub async fn get_emails_before_cursor(
+    pub async fn get_emails_page(
         &self,
         account_id: &str,
-        cursor: Option<(i64, u32)>,
+        order: SortOrder,
+        cursor: Option<&SortCursor>,
         limit: usize,
-    ) -> Result<Vec<EmailHeader>> {
-        email::get_emails_before_cursor(&self.pool, account_id, cursor, limit).await
+    ) -> Result<(Vec<EmailHeader>, Option<SortCursor>)> {
+        email::get_emails_page(&self.pool, account_id, order, cursor, limit).await
+    }
+
+    pub async fn get_sort_order(&self, account_id: &str) -> Result<Option<SortOrder>> {
+        email::get_sort_order(&self.pool, account_id).await
+    }
+
+    pub async fn set_sort_order(&self, account_id: &str, order: SortOrder) -> Result<()> {
+        email::set_sort_order(&self.pool, account_id, order).await
     }
 
     #[allow(dead_code)]
@@ -241,6 +257,14 @@ impl Cache {
         email::get_all_uid_flags(&self.pool, account_id).await
     }
 
+    pub async fn get_uids_without_size(&self, account_id: &str) -> Result<Vec<u32>> {
+        email::get_uids_without_size(&self.pool, account_id).await
+    }
+
+    pub async fn update_sizes(&self, account_id: &str, sizes: &[(u32, u32)]) -> Result<()> {
+        email::update_sizes(&self.pool, account_id, sizes).await
+    }
+
     pub async fn delete_email(&self, account_id: &str, uid: u32) -> Result<()> {
         email::delete_email(&self.pool, account_id, uid).await
     }
@@ -283,6 +307,11 @@ impl Cache {
         body::insert_email_body(&self.pool, &self.body_cache, account_id, uid, body).await
     }
 
+    /// Cached calendar invitations across all folders of `account`
+    pub async fn get_account_calendars(&self, account: &str) -> Result<Vec<String>> {
+        body::get_account_calendars(&self.pool, account).await
+    }
+
     //
     // Search Operations (delegated to search module)
     //
@@ -337,11 +366,42 @@ impl Cache {
         )
         .await
     }
+
+    //
+    // Send-later Queue Operations (delegated to scheduled module)
+    //
+
+    pub async fn insert_scheduled(
+        &self,
+        account_id: &str,
+        send_at: i64,
+        email: &ComposeEmail,
+        last_error: Option<&str>,
+    ) -> Result<i64> {
+        scheduled::insert_scheduled(&self.pool, account_id, send_at, email, last_error).await
+    }
+
+    pub async fn get_scheduled(&self) -> Result<Vec<ScheduledMessage>> {
+        scheduled::get_scheduled(&self.pool).await
+    }
+
+    pub async fn get_due_scheduled(&self, now: i64) -> Result<Vec<ScheduledMessage>> {
+        scheduled::get_due_scheduled(&self.pool, now).await
+    }
+
+    pub async fn delete_scheduled(&self, id: i64) -> Result<()> {
+        scheduled::delete_scheduled(&self.pool, id).await
+    }
+
+    pub async fn set_scheduled_error(&self, id: i64, error: &str) -> Result<()> {
+        scheduled::set_scheduled_error(&self.pool, id, error).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mail::types::ListInfo;
 
     const TEST_ACCOUNT: &str = "test@example.com";
 
@@ -365,6 +425,8 @@ mod tests {
             in_reply_to: None,
             references: Vec::new(),
             folder: None,
+            list: None,
+            size: 0,
         };
 
         cache.insert_email(TEST_ACCOUNT, &header).await.unwrap();
@@ -382,11 +444,116 @@ mod tests {
         assert!(updated.flags.contains(EmailFlags::SEEN));
         assert!(updated.flags.contains(EmailFlags::FLAGGED));
 
+        // Test size backfill
+        assert_eq!(
+            cache.get_uids_without_size(TEST_ACCOUNT).await.unwrap(),
+            vec![1]
+        );
+        cache
+            .update_sizes(TEST_ACCOUNT, &[(1, 2048)])
+            .await
+            .unwrap();
+        assert!(
+            cache
+                .get_uids_without_size(TEST_ACCOUNT)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let updated = cache.get_email(TEST_ACCOUNT, 1).await.unwrap().unwrap();
+        assert_eq!(updated.size, 2048);
+
         // Test delete
         cache.delete_email(TEST_ACCOUNT, 1).await.unwrap();
         assert!(cache.get_email(TEST_ACCOUNT, 1).await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn test_list_headers_round_trip() {
+        let cache = Cache::open_in_memory().await.unwrap();
+        let list = ListInfo {
+            id: Some("news.example.com".to_string()),
+            name: Some("Example News".to_string()),
+            unsubscribe: vec![
+                "mailto:leave@example.com".to_string(),
+                "https://example.com/unsub".to_string(),
+            ],
+            one_click: true,
+            archive: Some("https://example.com/archive".to_string()),
+            post: None,
+        };
+        let mut header = EmailHeader {
+            uid: 1,
+            message_id: None,
+            subject: "Newsletter".to_string(),
+            from_addr: "news@example.com".to_string(),
+            from_name: None,
+            to_addr: None,
+            cc_addr: None,
+            date: 1000,
+            flags: EmailFlags::empty(),
+            has_attachments: false,
+            preview: None,
+            body_cached: false,
+            in_reply_to: None,
+            references: Vec::new(),
+            folder: None,
+            list: Some(list.clone()),
+            size: 0,
+        };
+        cache.insert_email(TEST_ACCOUNT, &header).await.unwrap();
+        header.uid = 2;
+        header.list = None;
+        cache.insert_email(TEST_ACCOUNT, &header).await.unwrap();
+
+        let first = cache.get_email(TEST_ACCOUNT, 1).await.unwrap().unwrap();
+        assert_eq!(first.list, Some(list));
+        let second = cache.get_email(TEST_ACCOUNT, 2).await.unwrap().unwrap();
+        assert_eq!(second.list, None);
+    }
+
+    #[tokio::test]
+    async fn test_account_calendars() {
+        let cache = Cache::open_in_memory().await.unwrap();
+        let invite = |uid: &str| EmailBody {
+            text: Some("Join us".to_string()),
+            calendar: Some(format!(
+                "BEGIN:VCALENDAR\r\nUID:{}\r\nEND:VCALENDAR\r\n",
+                uid
+            )),
+            ..Default::default()
+        };
+        let plain = EmailBody {
+            text: Some("No invite".to_string()),
+            ..Default::default()
+        };
+        cache
+            .insert_email_body("me/INBOX", 1, &invite("inbox"))
+            .await
+            .unwrap();
+        cache
+            .insert_email_body_with_raw("me/Archive", 2, &invite("archive"), b"raw")
+            .await
+            .unwrap();
+        cache
+            .insert_email_body("me/INBOX", 3, &plain)
+            .await
+            .unwrap();
+        cache
+            .insert_email_body("meg/INBOX", 4, &invite("other account"))
+            .await
+            .unwrap();
+
+        let found = cache.get_account_calendars("me").await.unwrap();
+        assert_eq!(
+            found,
+            vec![
+                invite("archive").calendar.unwrap(),
+                invite("inbox").calendar.unwrap()
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_multi_account_isolation() {
         let cache = Cache::open_in_memory().await.unwrap();
@@ -407,6 +574,8 @@ mod tests {
             in_reply_to: None,
             references: Vec::new(),
             folder: None,
+            list: None,
+            size: 0,
         };
 
         let header2 = EmailHeader {
@@ -425,6 +594,8 @@ mod tests {
             in_reply_to: None,
             references: Vec::new(),
             folder: None,
+            list: None,
+            size: 0,
         };
 
         cache
@@ -507,4 +678,196 @@ mod tests {
         assert_eq!(retrieved1.uid_next, Some(50));
         assert_eq!(retrieved2.uid_next, Some(100));
     }
+
+    #[tokio::test]
+    async fn test_sorted_pages() {
+        let cache = Cache::open_in_memory().await.unwrap();
+        // (uid, sender, subject, date, size, flags): ties on every key
+        let rows = [
+            (1, "bob", "Lunch", 100, 500, EmailFlags::SEEN),
+            (2, "alice", "beta", 300, 900, EmailFlags::empty()),
+            (3, "Bob", "alpha", 200, 500, EmailFlags::FLAGGED),
+            (4, "carol", "Lunch", 300, 100, EmailFlags::SEEN),
+            (5, "alice", "Alpha", 400, 900, EmailFlags::SEEN),
+        ];
+        for (uid, from, subject, date, size, flags) in rows {
+            let header = EmailHeader {
+                uid,
+                message_id: None,
+                subject: subject.to_string(),
+                from_addr: format!("{}@example.com", from.to_lowercase()),
+                from_name: Some(from.to_string()),
+                to_addr: None,
+                cc_addr: None,
+                date,
+                flags,
+                has_attachments: false,
+                preview: None,
+                body_cached: false,
+                in_reply_to: None,
+                references: Vec::new(),
+                folder: None,
+                list: None,
+                size,
+            };
+            cache.insert_email(TEST_ACCOUNT, &header).await.unwrap();
+        }
+        let all = cache.get_emails(TEST_ACCOUNT, 100, 0).await.unwrap();
+
+        // Two at a time, the pages join up to the whole list in order
+        for order in SortOrder::ALL {
+            let mut paged = Vec::new();
+            let mut cursor = None;
+            loop {
+                let (page, next) = cache
+                    .get_emails_page(TEST_ACCOUNT, order, cursor.as_ref(), 2)
+                    .await
+                    .unwrap();
+                if page.is_empty() {
+                    break;
+                }
+                cursor = next;
+                paged.extend(page.iter().map(|e| e.uid));
+            }
+            let mut expected = all.clone();
+            expected.sort_by(|a, b| order.compare(a, b));
+            let expected: Vec<u32> = expected.iter().map(|e| e.uid).collect();
+            assert_eq!(paged, expected, "{:?}", order);
+        }
+
+        let (sender, _) = cache
+            .get_emails_page(TEST_ACCOUNT, SortOrder::Sender, None, 10)
+            .await
+            .unwrap();
+        let uids: Vec<u32> = sender.iter().map(|e| e.uid).collect();
+        assert_eq!(uids, vec![5, 2, 3, 1, 4]);
+
+        // Remembered per folder
+        assert_eq!(cache.get_sort_order(TEST_ACCOUNT).await.unwrap(), None);
+        cache
+            .set_sort_order(TEST_ACCOUNT, SortOrder::Size)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.get_sort_order(TEST_ACCOUNT).await.unwrap(),
+            Some(SortOrder::Size)
+        );
+        assert_eq!(cache.get_sort_order("other").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_pages_across_equal_sort_keys() {
+        let cache = Cache::open_in_memory().await.unwrap();
+        // Two runs of emails identical in every sort key, so page
+        // boundaries fall inside a run
+        for uid in 1..=9u32 {
+            let (date, size) = if uid <= 5 { (100, 500) } else { (200, 900) };
+            let header = EmailHeader {
+                uid,
+                message_id: None,
+                subject: "Same".to_string(),
+                from_addr: "bob@example.com".to_string(),
+                from_name: Some("Bob".to_string()),
+                to_addr: None,
+                cc_addr: None,
+                date,
+                flags: EmailFlags::SEEN,
+                has_attachments: false,
+                preview: None,
+                body_cached: false,
+                in_reply_to: None,
+                references: Vec::new(),
+                folder: None,
+                list: None,
+                size,
+            };
+            cache.insert_email(TEST_ACCOUNT, &header).await.unwrap();
+        }
+
+        for order in SortOrder::ALL {
+            for page_size in [1, 2, 4] {
+                let mut paged = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let (page, next) = cache
+                        .get_emails_page(TEST_ACCOUNT, order, cursor.as_ref(), page_size)
+                        .await
+                        .unwrap();
+                    if page.is_empty() {
+                        break;
+                    }
+                    cursor = next;
+                    paged.extend(page.iter().map(|e| e.uid));
+                }
+                let mut unique = paged.clone();
+                unique.sort_unstable();
+                unique.dedup();
+                assert_eq!(unique.len(), paged.len(), "duplicates: {:?}", order);
+                assert_eq!(unique, (1..=9).collect::<Vec<_>>(), "gaps: {:?}", order);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_queue() {
+        use crate::mail::types::{ComposeAttachment, Protection, Scheme};
+
+        let cache = Cache::open_in_memory().await.unwrap();
+
+        let mut email = ComposeEmail::new();
+        email.to = "bob@example.com".to_string();
+        email.subject = "Later".to_string();
+        email.identity_index = 1;
+        email.reply_to_uid = Some(42);
+        email.protection = Protection {
+            sign: true,
+            encrypt: true,
+            scheme: Scheme::Smime,
+        };
+        email.attachments.push(ComposeAttachment {
+            filename: "notes.txt".to_string(),
+            mime_type: "text/plain".to_string(),
+            data: b"hello".to_vec(),
+        });
+
+        let later = cache
+            .insert_scheduled(TEST_ACCOUNT, 2000, &email, None)
+            .await
+            .unwrap();
+        let sooner = cache
+            .insert_scheduled(TEST_ACCOUNT, 1000, &ComposeEmail::new(), None)
+            .await
+            .unwrap();
+
+        let all = cache.get_scheduled().await.unwrap();
+        assert_eq!(
+            all.iter().map(|m| m.id).collect::<Vec<_>>(),
+            vec![sooner, later]
+        );
+        assert_eq!(all[1].email.subject, "Later");
+        assert_eq!(all[1].email.identity_index, 1);
+        assert_eq!(all[1].email.reply_to_uid, Some(42));
+        assert_eq!(all[0].email.reply_to_uid, None);
+        assert_eq!(all[1].email.protection, email.protection);
+        assert_eq!(all[0].email.protection, Protection::default());
+        assert_eq!(all[1].email.attachments[0].data, b"hello");
+
+        // Only due messages that haven't failed are picked up
+        let due = cache.get_due_scheduled(1500).await.unwrap();
+        assert_eq!(due.len(), 1);
+        cache
+            .set_scheduled_error(sooner, "connection refused")
+            .await
+            .unwrap();
+        assert!(cache.get_due_scheduled(1500).await.unwrap().is_empty());
+
+        // Deleting removes attachments as well
+        cache.delete_scheduled(later).await.unwrap();
+        assert_eq!(cache.get_scheduled().await.unwrap().len(), 1);
+        let attachments: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scheduled_attachments")
+            .fetch_one(cache.pool())
+            .await
+            .unwrap();
+        assert_eq!(attachments, 0);
+    }
 }
@@ -0,0 +1,148 @@
+//! Send-later queue operations.
+
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+
+use crate::mail::types::{ComposeAttachment, ComposeEmail, Protection, ScheduledMessage, Scheme};
+
+/// Queue a message to be sent at `send_at` (Unix timestamp).
+/// Returns the ID of the queued message.
+pub async fn insert_scheduled(
+    pool: &SqlitePool,
+    account_id: &str,
+    send_at: i64,
+    email: &ComposeEmail,
+    last_error: Option<&str>,
+) -> Result<i64> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query(
+        "INSERT INTO scheduled_messages (account_id, identity_index, send_at, to_addr, cc_addr, subject, body, in_reply_to, references_list, markdown, sign, encrypt, scheme, calendar, reply_to_uid, last_error) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(account_id)
+    .bind(email.identity_index as i64)
+    .bind(send_at)
+    .bind(&email.to)
+    .bind(&email.cc)
+    .bind(&email.subject)
+    .bind(&email.body)
+    .bind(&email.in_reply_to)
+    .bind(&email.references)
+    .bind(email.markdown)
+    .bind(email.protection.sign)
+    .bind(email.protection.encrypt)
+    .bind(email.protection.scheme.key())
+    .bind(&email.calendar)
+    .bind(email.reply_to_uid.map(i64::from))
+    .bind(last_error)
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+
+    for attachment in &email.attachments {
+        sqlx::query(
+            "INSERT INTO scheduled_attachments (message_id, filename, mime_type, data) VALUES (?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(&attachment.filename)
+        .bind(&attachment.mime_type)
+        .bind(&attachment.data)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// Get all queued messages, soonest first.
+pub async fn get_scheduled(pool: &SqlitePool) -> Result<Vec<ScheduledMessage>> {
+    let rows = sqlx::query("SELECT * FROM scheduled_messages ORDER BY send_at, id")
+        .fetch_all(pool)
+        .await?;
+    load_messages(pool, rows).await
+}
+
+/// Get queued messages that are due at `now` and haven't failed.
+pub async fn get_due_scheduled(pool: &SqlitePool, now: i64) -> Result<Vec<ScheduledMessage>> {
+    let rows = sqlx::query(
+        "SELECT * FROM scheduled_messages WHERE send_at <= ? AND last_error IS NULL ORDER BY send_at, id",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+    load_messages(pool, rows).await
+}
+
+/// Remove a message from the queue (attachments are removed by cascade).
+pub async fn delete_scheduled(pool: &SqlitePool, id: i64) -> Result<()> {
+    sqlx::query("DELETE FROM scheduled_messages WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Record a failed send attempt so the message isn't retried automatically.
+pub async fn set_scheduled_error(pool: &SqlitePool, id: i64, error: &str) -> Result<()> {
+    sqlx::query("UPDATE scheduled_messages SET last_error = ? WHERE id = ?")
+        .bind(error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn load_messages(
+    pool: &SqlitePool,
+    rows: Vec<sqlx::sqlite::SqliteRow>,
+) -> Result<Vec<ScheduledMessage>> {
+    let mut messages = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: i64 = row.get("id");
+        let attachments = sqlx::query(
+            "SELECT filename, mime_type, data FROM scheduled_attachments WHERE message_id = ? ORDER BY id",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|a| ComposeAttachment {
+            filename: a.get("filename"),
+            mime_type: a.get("mime_type"),
+            data: a.get("data"),
+        })
+        .collect();
+
+        let email = ComposeEmail {
+            to: row.get("to_addr"),
+            cc: row.get("cc_addr"),
+            subject: row.get("subject"),
+            body: row.get("body"),
+            in_reply_to: row.get("in_reply_to"),
+            references: row.get("references_list"),
+            identity_index: row.get::<i64, _>("identity_index") as usize,
+            markdown: row.get("markdown"),
+            attachments,
+            protection: Protection {
+                sign: row.get("sign"),
+                encrypt: row.get("encrypt"),
+                scheme: Scheme::from_key(row.get("scheme")),
+            },
+            calendar: row.get("calendar"),
+            reply_to_uid: row
+                .get::<Option<i64>, _>("reply_to_uid")
+                .map(|uid| uid as u32),
+            ..ComposeEmail::new()
+        };
+
+        messages.push(ScheduledMessage {
+            id,
+            account_id: row.get("account_id"),
+            send_at: row.get("send_at"),
+            email,
+            last_error: row.get("last_error"),
+        });
+    }
+    Ok(messages)
+}
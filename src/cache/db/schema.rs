@@ -91,6 +91,48 @@ pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
 
         CREATE INDEX IF NOT EXISTS idx_contacts_email ON contacts(email);
         CREATE INDEX IF NOT EXISTS idx_contacts_name ON contacts(name);
+
+        -- Inbox sort order per folder (account_id is the folder cache key)
+        CREATE TABLE IF NOT EXISTS folder_sort (
+            account_id TEXT PRIMARY KEY,
+            sort_order TEXT NOT NULL
+        );
+
+        -- Send-later queue
+        CREATE TABLE IF NOT EXISTS scheduled_messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id TEXT NOT NULL,
+            identity_index INTEGER NOT NULL DEFAULT 0,
+            send_at INTEGER NOT NULL,
+            to_addr TEXT NOT NULL DEFAULT '',
+            cc_addr TEXT NOT NULL DEFAULT '',
+            subject TEXT NOT NULL DEFAULT '',
+            body TEXT NOT NULL DEFAULT '',
+            in_reply_to TEXT,
+            references_list TEXT,
+            markdown INTEGER NOT NULL DEFAULT 0,
+            -- Protection of either scheme ('openpgp' or 'smime')
+            sign INTEGER NOT NULL DEFAULT 0,
+            encrypt INTEGER NOT NULL DEFAULT 0,
+            scheme TEXT NOT NULL DEFAULT 'openpgp',
+            -- Calendar reply to attach
+            calendar TEXT,
+            -- Original message of a reply (flagged ANSWERED once sent)
+            reply_to_uid INTEGER,
+            last_error TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_scheduled_send_at ON scheduled_messages(send_at);
+
+        CREATE TABLE IF NOT EXISTS scheduled_attachments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            message_id INTEGER NOT NULL REFERENCES scheduled_messages(id) ON DELETE CASCADE,
+            filename TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            data BLOB NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_scheduled_attachments_message ON scheduled_attachments(message_id);
         "#,
     )
     .execute(pool)
@@ -164,6 +206,58 @@ pub async fn init_schema(pool: &SqlitePool) -> Result<()> {
         .await
         .ok(); // Ignore error if column already exists
 
+    // Migration: Add signature/encryption status to cached bodies
+    sqlx::query("ALTER TABLE email_bodies ADD COLUMN security TEXT")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+    // Migration: Add authentication results to cached bodies
+    sqlx::query("ALTER TABLE email_bodies ADD COLUMN auth_results TEXT")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+    // Migration: Keep calendar invitations from cached bodies
+    sqlx::query("ALTER TABLE email_bodies ADD COLUMN calendar TEXT")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+    // Migration: Mailing-list headers (List-Id, List-Unsubscribe, ...)
+    for column in [
+        "list_id TEXT",
+        "list_name TEXT",
+        "list_unsubscribe TEXT",
+        "list_one_click INTEGER NOT NULL DEFAULT 0",
+        "list_archive TEXT",
+        "list_post TEXT",
+    ] {
+        sqlx::query(&format!("ALTER TABLE emails ADD COLUMN {}", column))
+            .execute(pool)
+            .await
+            .ok(); // Ignore error if column already exists
+    }
+
+    // Migration: Message size (RFC822.SIZE) for sorting by size
+    sqlx::query("ALTER TABLE emails ADD COLUMN size INTEGER NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+    // Indexes for the non-date sort orders
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_emails_account_sender
+            ON emails(account_id, COALESCE(NULLIF(from_name, ''), from_addr) COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS idx_emails_account_subject
+            ON emails(account_id, subject COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS idx_emails_account_size ON emails(account_id, size DESC);
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
     // Index on folder for cross-folder queries
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_emails_folder ON emails(folder)")
         .execute(pool)
@@ -2,5 +2,5 @@ mod db;
 mod schema;
 mod sync;
 
-pub use db::Cache;
+pub use db::{Cache, SortCursor};
 pub use sync::SyncState;
@@ -0,0 +1,53 @@
+//! Copying text to the system clipboard
+//!
+//! Uses the platform's clipboard tool when one is installed, and otherwise
+//! asks the terminal to do it with an OSC 52 escape sequence, which also
+//! works over SSH in terminals that support it.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use base64::Engine;
+
+/// Clipboard tools to try, in order
+const TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Copy text to the clipboard. Without a clipboard tool this returns the
+/// OSC 52 sequence instead, for the render thread to write since it owns
+/// the terminal.
+pub fn copy(text: &str) -> Option<String> {
+    for (program, args) in TOOLS {
+        if pipe_to(program, args, text).is_ok() {
+            return None;
+        }
+    }
+    Some(osc52(text))
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .context("no stdin")?
+        .write_all(text.as_bytes())?;
+    let status = child.wait()?;
+    anyhow::ensure!(status.success(), "{} exited with {}", program, status);
+    Ok(())
+}
+
+fn osc52(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{}\x07", encoded)
+}
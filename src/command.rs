@@ -1,9 +1,16 @@
 //! Command types and parsing for vim-style command mode
 
+use crate::mail::calendar::PartStat;
+
 /// A pending command that requires user confirmation before execution
 #[derive(Debug, Clone)]
 pub enum PendingCommand {
     Clear,
+    /// RFC 8058 one-click unsubscribe from a list
+    Unsubscribe {
+        list: String,
+        uri: String,
+    },
 }
 
 /// Result of command execution
@@ -29,7 +36,21 @@ pub enum ParsedCommand {
     Quit,
     TestCredentials,
     AddAccount,
+    Scheduled,
+    /// Sort the list (empty = list the orders)
+    Sort(String),
     Theme(String),
+    Template(String),
+    Unsubscribe,
+    ListArchive,
+    /// Answer a calendar invitation
+    Invite(PartStat),
+    /// Import contacts from a .vcf file
+    ImportContacts(String),
+    /// Export the address book as vCard (empty path = downloads folder)
+    ExportContacts(String),
+    /// Sync CardDAV address books now
+    SyncContacts,
 }
 
 /// Parse a command string into a ParsedCommand
@@ -44,6 +65,25 @@ pub fn parse_command(input: &str) -> Option<ParsedCommand> {
         }
     }
 
+    if let Some(order) = trimmed.strip_prefix("sort ") {
+        return Some(ParsedCommand::Sort(order.trim().to_string()));
+    }
+
+    if let Some(template_name) = trimmed.strip_prefix("template ") {
+        let name = template_name.trim();
+        if !name.is_empty() {
+            return Some(ParsedCommand::Template(name.to_string()));
+        }
+    }
+
+    if let Some(path) = trimmed.strip_prefix("import-contacts ") {
+        return Some(ParsedCommand::ImportContacts(path.trim().to_string()));
+    }
+
+    if let Some(path) = trimmed.strip_prefix("export-contacts ") {
+        return Some(ParsedCommand::ExportContacts(path.trim().to_string()));
+    }
+
     match trimmed {
         "clear" => Some(ParsedCommand::Clear),
         "help" | "h" | "?" => Some(ParsedCommand::Help),
@@ -51,7 +91,18 @@ pub fn parse_command(input: &str) -> Option<ParsedCommand> {
         "q" | "quit" => Some(ParsedCommand::Quit),
         "testcreds" | "test-creds" => Some(ParsedCommand::TestCredentials),
         "addaccount" | "add-account" => Some(ParsedCommand::AddAccount),
+        "scheduled" | "outbox" => Some(ParsedCommand::Scheduled),
+        "unsubscribe" | "unsub" => Some(ParsedCommand::Unsubscribe),
+        "list-archive" | "archive-list" => Some(ParsedCommand::ListArchive),
+        "accept" => Some(ParsedCommand::Invite(PartStat::Accepted)),
+        "tentative" | "maybe" => Some(ParsedCommand::Invite(PartStat::Tentative)),
+        "decline" => Some(ParsedCommand::Invite(PartStat::Declined)),
+        "import-contacts" => Some(ParsedCommand::ImportContacts(String::new())),
+        "export-contacts" => Some(ParsedCommand::ExportContacts(String::new())),
+        "sync-contacts" => Some(ParsedCommand::SyncContacts),
+        "sort" => Some(ParsedCommand::Sort(String::new())),
         "theme" | "themes" => Some(ParsedCommand::Theme(String::new())), // No arg = list themes
+        "template" | "templates" => Some(ParsedCommand::Template(String::new())), // No arg = list templates
         _ => None,
     }
 }
@@ -59,6 +110,10 @@ pub fn parse_command(input: &str) -> Option<ParsedCommand> {
 /// Get all available commands for help display
 pub fn available_commands() -> Vec<CommandHelp> {
     vec![
+        CommandHelp {
+            name: "accept",
+            description: "Accept the calendar invitation in the selected email (same as Y)",
+        },
         CommandHelp {
             name: "addaccount",
             description: "Add a new email account",
@@ -67,14 +122,50 @@ pub fn available_commands() -> Vec<CommandHelp> {
             name: "clear",
             description: "Wipe local email cache (requires confirmation)",
         },
+        CommandHelp {
+            name: "decline",
+            description: "Decline the calendar invitation in the selected email (same as N)",
+        },
+        CommandHelp {
+            name: "export-contacts [path]",
+            description: "Export all contacts as vCard 4.0 (default: Downloads/contacts.vcf)",
+        },
         CommandHelp {
             name: "help",
             description: "Show this help screen (same as \".\" key)",
         },
+        CommandHelp {
+            name: "import-contacts <path>",
+            description: "Import contacts from a .vcf file",
+        },
+        CommandHelp {
+            name: "list-archive",
+            description: "Open the mailing-list archive of the selected email in the browser",
+        },
         CommandHelp {
             name: "quit",
             description: "Exit the application",
         },
+        CommandHelp {
+            name: "scheduled",
+            description: "List, edit and cancel messages queued with send later",
+        },
+        CommandHelp {
+            name: "sort [order]",
+            description: "Sort this folder by date, date-asc, sender, subject, size, unread or flagged (same as W)",
+        },
+        CommandHelp {
+            name: "sync-contacts",
+            description: "Sync CardDAV address books now",
+        },
+        CommandHelp {
+            name: "template <name>",
+            description: "Reply to the selected email (or compose) using a template from the templates dir",
+        },
+        CommandHelp {
+            name: "tentative",
+            description: "Tentatively accept the calendar invitation (same as E)",
+        },
         CommandHelp {
             name: "testcreds",
             description: "Test credential storage backend",
@@ -83,5 +174,9 @@ pub fn available_commands() -> Vec<CommandHelp> {
             name: "theme <name>",
             description: "Switch color scheme (modern, dark, light, solarized-dark/light, tokyo-night/day, rose-pine/dawn)",
         },
+        CommandHelp {
+            name: "unsubscribe",
+            description: "Unsubscribe from the selected email's mailing list (same as U)",
+        },
     ]
 }
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::constants::MAX_UNDO_SEND_SECS;
+
 /// Helper struct for parsing old single-account config format
 #[derive(Debug, Clone, Deserialize)]
 struct LegacyConfig {
@@ -28,9 +30,24 @@ pub struct Config {
     pub ui: UiConfig,
     #[serde(default)]
     pub cache: CacheConfig,
+    /// Message composition settings
+    #[serde(default)]
+    pub compose: ComposeConfig,
     /// AI features configuration (OpenRouter)
     #[serde(default)]
     pub ai: AiConfig,
+    /// OpenPGP signing, encryption and verification
+    #[serde(default)]
+    pub pgp: PgpConfig,
+    /// S/MIME verification settings (identities are per account)
+    #[serde(default)]
+    pub smime: SmimeConfig,
+    /// CardDAV address books synced into contacts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub carddav: Vec<CardDavConfig>,
+    /// Shell commands messages can be piped to with a key
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pipe: Vec<PipeCommandConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,6 +107,103 @@ pub struct AccountConfig {
     /// Authentication method (default: password)
     #[serde(default)]
     pub auth: AuthMethod,
+    /// Compose new messages as Markdown by default (sent as text + HTML)
+    #[serde(default)]
+    pub markdown: bool,
+    /// Signature text appended below a "-- " separator when composing
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Path to a file containing the signature (used if `signature` is not set)
+    #[serde(default)]
+    pub signature_file: Option<String>,
+    /// Where the signature goes in replies and forwards
+    #[serde(default)]
+    pub signature_placement: SignaturePlacement,
+    /// Additional addresses (aliases) this account can send as
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identities: Vec<Identity>,
+    /// Organisation domains; sending to any other domain asks for confirmation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub internal_domains: Vec<String>,
+    /// authserv-ids of the servers whose Authentication-Results headers are
+    /// believed (the provider's inbound MX, e.g. "mx.google.com")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authserv_ids: Vec<String>,
+    /// S/MIME identity for signing and decrypting this account's mail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smime: Option<SmimeIdentityConfig>,
+}
+
+/// An account's S/MIME certificate and private key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SmimeIdentityConfig {
+    /// PKCS#12 (.p12/.pfx) file; its passphrase comes from the keyring
+    /// (`bltz smime`) or the BLTZ_SMIME_PASSWORD environment variable
+    pub identity: String,
+    /// Sign outgoing mail with S/MIME unless changed in the composer
+    #[serde(default)]
+    pub sign_by_default: bool,
+}
+
+/// An address an account can send as, with its own name and signature
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Identity {
+    pub email: String,
+    /// Display name used in the "From" field
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub signature_file: Option<String>,
+}
+
+impl Identity {
+    /// Resolve the signature text from `signature` or `signature_file`
+    /// Returns None if neither is set, the file can't be read, or it is empty
+    pub fn signature_text(&self) -> Option<String> {
+        let text = match (&self.signature, &self.signature_file) {
+            (Some(text), _) => text.clone(),
+            (None, Some(path)) => {
+                let path = expand_home(path);
+                match fs::read_to_string(&path) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        tracing::warn!("Failed to read signature file {}: {}", path.display(), e);
+                        return None;
+                    }
+                }
+            }
+            (None, None) => return None,
+        };
+
+        let text = text.trim_end();
+        // Drop a separator the user already put in the signature itself
+        let text = text
+            .strip_prefix("-- \n")
+            .or_else(|| text.strip_prefix("--\n"))
+            .unwrap_or(text);
+        (!text.trim().is_empty()).then(|| text.to_string())
+    }
+
+    /// Format as "Name <address>" (or just the address without a name)
+    pub fn label(&self) -> String {
+        match self.display_name {
+            Some(ref name) => format!("{} <{}>", name, self.email),
+            None => self.email.clone(),
+        }
+    }
+}
+
+/// Position of the signature relative to quoted text in replies and forwards
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignaturePlacement {
+    /// After the quoted message, at the very end of the body
+    #[default]
+    Below,
+    /// Between the reply text and the quoted message
+    Above,
 }
 
 impl AccountConfig {
@@ -111,6 +225,76 @@ impl AccountConfig {
     pub fn username_or_email(&self) -> &str {
         self.username.as_deref().unwrap_or(&self.email)
     }
+
+    /// Number of identities, including the account's own address
+    pub fn identity_count(&self) -> usize {
+        1 + self.identities.len()
+    }
+
+    /// Get an identity by index (0 = the account's own address, then aliases)
+    ///
+    /// Aliases without a signature of their own use the account's signature.
+    /// Out-of-range indices fall back to the account's own address.
+    pub fn identity(&self, index: usize) -> Identity {
+        let primary = Identity {
+            email: self.email.clone(),
+            display_name: self.display_name.clone(),
+            signature: self.signature.clone(),
+            signature_file: self.signature_file.clone(),
+        };
+        match index.checked_sub(1).and_then(|i| self.identities.get(i)) {
+            Some(alias) if alias.signature.is_none() && alias.signature_file.is_none() => {
+                Identity {
+                    signature: primary.signature,
+                    signature_file: primary.signature_file,
+                    ..alias.clone()
+                }
+            }
+            Some(alias) => alias.clone(),
+            None => primary,
+        }
+    }
+
+    /// Pick the identity whose address appears in the given recipients
+    ///
+    /// Addresses are checked in order, so callers should pass the most
+    /// specific ones (e.g. Delivered-To) first. Returns 0 if none match.
+    pub fn identity_for_recipients<'a>(
+        &self,
+        recipients: impl IntoIterator<Item = &'a str>,
+    ) -> usize {
+        for addr in recipients {
+            let addr = addr.trim();
+            if addr.eq_ignore_ascii_case(&self.email) {
+                return 0;
+            }
+            if let Some(i) = self
+                .identities
+                .iter()
+                .position(|id| id.email.eq_ignore_ascii_case(addr))
+            {
+                return i + 1;
+            }
+        }
+        0
+    }
+
+    /// All addresses this account sends as (own address first)
+    pub fn own_addresses(&self) -> Vec<&str> {
+        std::iter::once(self.email.as_str())
+            .chain(self.identities.iter().map(|id| id.email.as_str()))
+            .collect()
+    }
+}
+
+/// Expand a leading `~/` to the user's home directory
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,11 +308,31 @@ pub struct ImapConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmtpConfig {
+    /// SMTP server (unused with `transport = "command"`)
+    #[serde(default)]
     pub server: String,
     #[serde(default = "default_smtp_port")]
     pub port: u16,
     #[serde(default = "default_true")]
     pub tls: bool,
+    /// How outgoing mail is handed off
+    #[serde(default)]
+    pub transport: SendTransport,
+    /// Command line for `transport = "command"`, e.g. "msmtp -a work"
+    /// (recipients are appended as arguments; the message goes to stdin)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// How outgoing mail is handed off
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SendTransport {
+    /// Connect to the SMTP server with stored credentials
+    #[default]
+    Smtp,
+    /// Pipe the message to a local sendmail-compatible command (msmtp, sendmail)
+    Command,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,6 +358,15 @@ pub struct UiConfig {
     /// Show sent emails in inbox threads (conversation view)
     #[serde(default = "default_true")]
     pub conversation_mode: bool,
+    /// How images are drawn in the reader
+    #[serde(default)]
+    pub images: ImageProtocol,
+    /// Download images the HTML part links to (tracking pixels included)
+    #[serde(default)]
+    pub remote_images: bool,
+    /// Fold quoted text and signatures in the reader until `z` is pressed
+    #[serde(default = "default_true")]
+    pub collapse_quotes: bool,
 }
 
 fn default_dark_theme() -> ThemeVariant {
@@ -172,6 +385,19 @@ pub enum KeybindingMode {
     Arrows,
 }
 
+/// Terminal graphics protocol for images ("auto" picks one from the environment)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageProtocol {
+    #[default]
+    Auto,
+    Kitty,
+    Iterm2,
+    Sixel,
+    Halfblocks,
+    Off,
+}
+
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ThemeVariant {
@@ -215,6 +441,112 @@ impl Default for CacheConfig {
     }
 }
 
+/// Message composition settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeConfig {
+    /// Seconds a sent message is held back so it can be undone (0 = send immediately)
+    #[serde(default)]
+    pub undo_send_secs: u64,
+}
+
+impl ComposeConfig {
+    /// Undo-send window, capped at `MAX_UNDO_SEND_SECS`
+    pub fn undo_send_delay(&self) -> u64 {
+        self.undo_send_secs.min(MAX_UNDO_SEND_SECS)
+    }
+}
+
+/// OpenPGP settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PgpConfig {
+    /// Which OpenPGP implementation to use
+    #[serde(default)]
+    pub backend: PgpBackendKind,
+    /// gpg executable (default: "gpg" from PATH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpg_path: Option<String>,
+    /// Sign outgoing mail unless turned off in the composer
+    #[serde(default)]
+    pub sign_by_default: bool,
+    /// Encrypt automatically when every recipient has a usable public key
+    #[serde(default)]
+    pub auto_encrypt: bool,
+}
+
+/// OpenPGP implementation
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PgpBackendKind {
+    /// The gpg binary, using the user's GnuPG keyring
+    #[default]
+    Gpg,
+    /// No OpenPGP support
+    Off,
+}
+
+/// S/MIME settings shared by all accounts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SmimeConfig {
+    /// openssl executable (default: "openssl" from PATH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openssl_path: Option<String>,
+    /// PEM bundle of trusted CAs (default: the system store)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_file: Option<String>,
+}
+
+/// A CardDAV address book (password via `bltz carddav` or BLTZ_CARDDAV_PASSWORD)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardDavConfig {
+    /// Server root, principal or address book URL; the address book is
+    /// discovered from it (falling back to /.well-known/carddav)
+    pub url: String,
+    pub username: String,
+    /// Display name or path segment of the address book to use when the
+    /// server has several (default: the first one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addressbook: Option<String>,
+    /// Seconds between background syncs
+    #[serde(default = "default_carddav_sync_interval_secs")]
+    pub sync_interval_secs: u64,
+}
+
+/// A named shell command the message (or thread) is piped to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipeCommandConfig {
+    pub name: String,
+    /// Run with `sh -c`
+    pub command: String,
+    /// Key that runs it, e.g. "ctrl+g", "alt+p" or "P"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub input: PipeInput,
+    /// Hand the terminal to the command instead of capturing its output
+    #[serde(default)]
+    pub interactive: bool,
+}
+
+/// What is written to a piped command's stdin
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PipeInput {
+    /// The message as received
+    #[default]
+    Raw,
+    /// The text body
+    Text,
+    /// Every message of the thread, as an mbox
+    Thread,
+}
+
+impl CardDavConfig {
+    /// Key the password is stored under
+    pub fn credential_id(&self) -> String {
+        format!("{}@{}", self.username, self.url)
+    }
+}
+
 /// AI features configuration (OpenRouter integration)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiConfig {
@@ -319,6 +651,9 @@ impl Default for UiConfig {
             preview_length: default_preview_length(),
             split_ratio: default_split_ratio(),
             conversation_mode: true,
+            images: ImageProtocol::default(),
+            remote_images: false,
+            collapse_quotes: true,
         }
     }
 }
@@ -355,6 +690,10 @@ fn default_sync_interval_secs() -> u64 {
     300
 }
 
+fn default_carddav_sync_interval_secs() -> u64 {
+    900
+}
+
 fn default_prefetch_radius() -> usize {
     10
 }
@@ -367,6 +706,11 @@ impl Config {
         Ok(dir)
     }
 
+    /// Directory holding message templates (one file per template)
+    pub fn templates_dir() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("templates"))
+    }
+
     pub fn config_path() -> Result<PathBuf> {
         Ok(Self::config_dir()?.join("config.toml"))
     }
@@ -445,7 +789,12 @@ impl Config {
             notifications: NotificationConfig::default(),
             ui: legacy.ui,
             cache: legacy.cache,
+            compose: ComposeConfig::default(),
             ai: AiConfig::default(),
+            pgp: PgpConfig::default(),
+            smime: SmimeConfig::default(),
+            carddav: Vec::new(),
+            pipe: Vec::new(),
         };
 
         // Final validation: ensure at least one account exists
@@ -577,9 +926,19 @@ mod tests {
                         server: "smtp.example.com".to_string(),
                         port: 587,
                         tls: true,
+                        transport: Default::default(),
+                        command: None,
                     },
                     notifications: None,
                     auth: AuthMethod::Password,
+                    markdown: false,
+                    signature: None,
+                    signature_file: None,
+                    signature_placement: SignaturePlacement::default(),
+                    identities: Vec::new(),
+                    internal_domains: Vec::new(),
+                    authserv_ids: Vec::new(),
+                    smime: None,
                 },
                 AccountConfig {
                     email: "second@example.com".to_string(),
@@ -595,16 +954,31 @@ mod tests {
                         server: "smtp2.example.com".to_string(),
                         port: 587,
                         tls: true,
+                        transport: Default::default(),
+                        command: None,
                     },
                     notifications: None,
                     auth: AuthMethod::Password,
+                    markdown: false,
+                    signature: None,
+                    signature_file: None,
+                    signature_placement: SignaturePlacement::default(),
+                    identities: Vec::new(),
+                    internal_domains: Vec::new(),
+                    authserv_ids: Vec::new(),
+                    smime: None,
                 },
             ],
             default_account: Some(1),
             notifications: NotificationConfig::default(),
             ui: UiConfig::default(),
             cache: CacheConfig::default(),
+            compose: ComposeConfig::default(),
             ai: AiConfig::default(),
+            pgp: PgpConfig::default(),
+            smime: SmimeConfig::default(),
+            carddav: Vec::new(),
+            pipe: Vec::new(),
         };
 
         // default_account is 1, so second account should be default
@@ -639,9 +1013,19 @@ mod tests {
                 server: "smtp.example.com".to_string(),
                 port: 587,
                 tls: true,
+                transport: Default::default(),
+                command: None,
             },
             notifications: None,
             auth: AuthMethod::Password,
+            markdown: false,
+            signature: None,
+            signature_file: None,
+            signature_placement: SignaturePlacement::default(),
+            identities: Vec::new(),
+            internal_domains: Vec::new(),
+            authserv_ids: Vec::new(),
+            smime: None,
         };
 
         // name takes priority
@@ -703,6 +1087,105 @@ mod tests {
         assert_eq!(config.accounts[1].account_name(), "Work");
     }
 
+    #[test]
+    fn test_undo_send_delay() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.compose.undo_send_delay(), 0);
+
+        let config: Config = toml::from_str("[compose]\nundo_send_secs = 5").unwrap();
+        assert_eq!(config.compose.undo_send_delay(), 5);
+
+        // Capped so a typo can't hold mail back indefinitely
+        let config: Config = toml::from_str("[compose]\nundo_send_secs = 600").unwrap();
+        assert_eq!(config.compose.undo_send_delay(), MAX_UNDO_SEND_SECS);
+    }
+
+    #[test]
+    fn test_parse_signature_config() {
+        let toml = r#"
+            [[accounts]]
+            email = "work@company.com"
+            signature = "-- \nJohn Doe\nExample Corp\n"
+            signature_placement = "above"
+
+            [accounts.imap]
+            server = "imap.company.com"
+
+            [accounts.smtp]
+            server = "smtp.company.com"
+
+            [[accounts]]
+            email = "personal@example.com"
+
+            [accounts.imap]
+            server = "imap.example.com"
+
+            [accounts.smtp]
+            server = "smtp.example.com"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        let work = &config.accounts[0];
+        assert_eq!(work.signature_placement, SignaturePlacement::Above);
+        // Separator and trailing whitespace are stripped
+        assert_eq!(
+            work.identity(0).signature_text(),
+            Some("John Doe\nExample Corp".to_string())
+        );
+
+        let personal = &config.accounts[1];
+        assert_eq!(personal.signature_placement, SignaturePlacement::Below);
+        assert_eq!(personal.identity(0).signature_text(), None);
+    }
+
+    #[test]
+    fn test_identities() {
+        let toml = r#"
+            [[accounts]]
+            email = "jane@example.com"
+            display_name = "Jane Doe"
+            signature = "Jane"
+
+            [accounts.imap]
+            server = "imap.example.com"
+
+            [accounts.smtp]
+            server = "smtp.example.com"
+
+            [[accounts.identities]]
+            email = "support@example.com"
+            display_name = "Example Support"
+            signature = "The Support Team"
+
+            [[accounts.identities]]
+            email = "j@example.com"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        let account = &config.accounts[0];
+        assert_eq!(account.identity_count(), 3);
+        assert_eq!(account.identity(0).label(), "Jane Doe <jane@example.com>");
+        assert_eq!(
+            account.identity(1).signature_text(),
+            Some("The Support Team".to_string())
+        );
+        // Aliases without their own signature use the account's
+        assert_eq!(
+            account.identity(2).signature_text(),
+            Some("Jane".to_string())
+        );
+        assert_eq!(account.identity(2).label(), "j@example.com");
+        // Out of range falls back to the account's own address
+        assert_eq!(account.identity(9).email, "jane@example.com");
+
+        assert_eq!(
+            account.identity_for_recipients(["team@example.com", "Support@Example.com"]),
+            1
+        );
+        assert_eq!(account.identity_for_recipients(["j@example.com"]), 2);
+        assert_eq!(account.identity_for_recipients(["other@example.com"]), 0);
+    }
+
     #[test]
     fn test_ai_config_defaults() {
         let ai = AiConfig::default();
@@ -31,6 +31,12 @@ pub const ERROR_TTL_SECS: u64 = 5;
 /// Allows user to undo within this time window.
 pub const DELETION_DELAY_SECS: u64 = 10;
 
+/// Upper bound for the configurable undo-send window in seconds.
+pub const MAX_UNDO_SEND_SECS: u64 = 30;
+
+/// Interval in seconds between checks for scheduled messages that are due.
+pub const SCHEDULED_CHECK_INTERVAL_SECS: u64 = 30;
+
 /// Minimum terminal width to show split view (list + preview).
 /// Below this width, only the email list is shown.
 pub const MIN_SPLIT_VIEW_WIDTH: u16 = 80;
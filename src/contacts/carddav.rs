@@ -0,0 +1,788 @@
+//! CardDAV address book sync (RFC 6352, RFC 6578 sync-collection)
+//!
+//! A sync first pushes local changes to shared contacts (edits from the
+//! contacts view and deletions), each conditional on the card's last known
+//! ETag, then pulls what changed on the server since the stored sync token.
+//! When a card changed on the server in the meantime the upload fails with
+//! 412 and the server copy wins: the local edit is dropped and the card is
+//! fetched again.
+
+use anyhow::{Context, Result, bail};
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_MATCH, LOCATION};
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Url};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use super::{ContactsDb, vcard};
+use crate::config::CardDavConfig;
+use crate::credentials::CredentialStore;
+
+/// Cards fetched per addressbook-multiget request
+const MULTIGET_BATCH: usize = 50;
+/// Redirects followed during discovery (/.well-known/carddav usually redirects)
+const MAX_REDIRECTS: usize = 5;
+/// sync-collection rounds when the server truncates its answer (507)
+const MAX_SYNC_ROUNDS: usize = 20;
+
+const DAV_NS: &str = r#"xmlns:d="DAV:" xmlns:card="urn:ietf:params:xml:ns:carddav""#;
+
+/// What a sync changed locally and remotely
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub uploaded: usize,
+    /// Local changes dropped because the server copy had changed
+    pub conflicts: usize,
+}
+
+impl SyncSummary {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl std::ops::AddAssign for SyncSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.updated += other.updated;
+        self.removed += other.removed;
+        self.uploaded += other.uploaded;
+        self.conflicts += other.conflicts;
+    }
+}
+
+impl std::fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} removed, {} uploaded",
+            self.added, self.updated, self.removed, self.uploaded
+        )?;
+        if self.conflicts > 0 {
+            write!(f, ", {} conflicts (server copy kept)", self.conflicts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sync one configured address book into `db`
+pub async fn sync(config: &CardDavConfig, db: &ContactsDb) -> Result<SyncSummary> {
+    let client = DavClient::new(config)?;
+    let collection = client.discover(config).await?;
+    let key = collection.as_str();
+
+    let mut summary = SyncSummary::default();
+    push(&client, db, key, &mut summary).await?;
+    pull(&client, db, &collection, &mut summary).await?;
+    Ok(summary)
+}
+
+/// Upload edited shared contacts and delete removed ones
+async fn push(
+    client: &DavClient,
+    db: &ContactsDb,
+    collection: &str,
+    summary: &mut SyncSummary,
+) -> Result<()> {
+    for (href, etag) in db.deleted_cards(collection).await? {
+        let url = Url::parse(&href)?;
+        let status = client
+            .request("DELETE", &url)
+            .header(IF_MATCH, etag.as_deref().unwrap_or("*"))
+            .send()
+            .await
+            .with_context(|| format!("Failed to delete {}", href))?
+            .status();
+        match status {
+            s if s.is_success() || s == StatusCode::NOT_FOUND => {}
+            // Changed on the server since: the pull brings it back
+            StatusCode::PRECONDITION_FAILED => summary.conflicts += 1,
+            s => bail!("Deleting {} failed: HTTP {}", href, s),
+        }
+        db.forget_deleted(&href).await?;
+    }
+
+    for remote in db.dirty_cards(collection).await? {
+        let url = Url::parse(&remote.href)?;
+        let card = vcard::update(&remote.card, &remote.contact);
+        let response = client
+            .request("PUT", &url)
+            .header(CONTENT_TYPE, "text/vcard; charset=utf-8")
+            .header(IF_MATCH, remote.etag.as_deref().unwrap_or("*"))
+            .body(card.clone())
+            .send()
+            .await
+            .with_context(|| format!("Failed to upload {}", remote.href))?;
+        match response.status() {
+            s if s.is_success() => {
+                // Without an ETag the next pull fetches the card again
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                db.mark_uploaded(remote.contact.id, etag.as_deref(), &card)
+                    .await?;
+                summary.uploaded += 1;
+            }
+            StatusCode::PRECONDITION_FAILED => {
+                tracing::info!("CardDAV: {} changed on the server, keeping that copy", url);
+                db.discard_local_edit(remote.contact.id).await?;
+                summary.conflicts += 1;
+            }
+            s => bail!("Uploading {} failed: HTTP {}", remote.href, s),
+        }
+    }
+    Ok(())
+}
+
+/// Fetch cards that changed on the server and apply deletions
+async fn pull(
+    client: &DavClient,
+    db: &ContactsDb,
+    collection: &Url,
+    summary: &mut SyncSummary,
+) -> Result<()> {
+    let key = collection.as_str();
+    let token = db.sync_token(key).await?;
+    let changes = match client.sync_collection(collection, token.as_deref()).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            // An expired token needs a fresh sync; servers without
+            // sync-collection are listed in full
+            tracing::debug!("CardDAV sync-collection failed ({:#}), listing {}", e, key);
+            match token {
+                Some(_) => match client.sync_collection(collection, None).await {
+                    Ok(changes) => changes,
+                    Err(_) => client.list(collection).await?,
+                },
+                None => client.list(collection).await?,
+            }
+        }
+    };
+
+    let local: HashMap<String, Option<String>> = db.linked_cards(key).await?.into_iter().collect();
+    let stale: Vec<&Url> = changes
+        .changed
+        .iter()
+        .filter(|(url, etag)| etag.is_none() || local.get(url.as_str()) != Some(etag))
+        .map(|(url, _)| url)
+        .collect();
+
+    for batch in stale.chunks(MULTIGET_BATCH) {
+        for card in client.multiget(collection, batch).await? {
+            let url = collection.join(&card.href)?;
+            let Some(ref data) = card.address_data else {
+                continue;
+            };
+            match vcard::parse(data).into_iter().next() {
+                Some(contact) => {
+                    let added = db
+                        .store_remote(url.as_str(), card.etag.as_deref(), data, &contact)
+                        .await?;
+                    if added {
+                        summary.added += 1;
+                    } else {
+                        summary.updated += 1;
+                    }
+                }
+                // Cards without an email address are not kept
+                None => {
+                    if db.remove_remote(url.as_str()).await? {
+                        summary.removed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut removed = changes.removed;
+    if changes.full {
+        // A full listing reports deletions by leaving cards out
+        let present: HashSet<&str> = changes.changed.iter().map(|(u, _)| u.as_str()).collect();
+        for href in local.keys().filter(|h| !present.contains(h.as_str())) {
+            removed.push(Url::parse(href)?);
+        }
+    }
+    for url in removed {
+        if db.remove_remote(url.as_str()).await? {
+            summary.removed += 1;
+        }
+    }
+
+    db.set_sync_token(key, changes.token.as_deref()).await?;
+    Ok(())
+}
+
+/// Members of an address book that changed (or all of them, if `full`)
+#[derive(Debug, Default)]
+struct Changes {
+    changed: Vec<(Url, Option<String>)>,
+    removed: Vec<Url>,
+    token: Option<String>,
+    full: bool,
+}
+
+/// Whether a redirect from `from` to `to` may carry the credentials: same
+/// host and port, and never from https to http
+fn safe_redirect(from: &Url, to: &Url) -> bool {
+    let upgrade = from.scheme() == "http" && to.scheme() == "https";
+    let same_port = from.port_or_known_default() == to.port_or_known_default()
+        || (upgrade && to.port().is_none());
+    (from.scheme() == to.scheme() || upgrade) && same_port && from.host_str() == to.host_str()
+}
+
+struct DavClient {
+    http: Client,
+    username: String,
+    password: String,
+}
+
+impl DavClient {
+    fn new(config: &CardDavConfig) -> Result<Self> {
+        let http = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context("Failed to create HTTP client")?;
+        // Servers without authentication (e.g. Radicale with auth type
+        // "none") accept any password
+        let password = CredentialStore::new(&config.credential_id())
+            .get_carddav_password()
+            .unwrap_or_default();
+        Ok(Self {
+            http,
+            username: config.username.clone(),
+            password,
+        })
+    }
+
+    fn request(&self, method: &str, url: &Url) -> RequestBuilder {
+        let method = Method::from_bytes(method.as_bytes()).expect("valid HTTP method");
+        self.http
+            .request(method, url.clone())
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    /// Send a PROPFIND or REPORT and parse the 207 Multi-Status answer,
+    /// following redirects. Returns the URL that answered.
+    async fn dav(
+        &self,
+        method: &str,
+        url: &Url,
+        depth: &str,
+        body: String,
+    ) -> Result<(Url, MultiStatus)> {
+        let mut url = url.clone();
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self
+                .request(method, &url)
+                .header("Depth", depth)
+                .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+                .body(body.clone())
+                .send()
+                .await
+                .with_context(|| format!("{} {} failed", method, url))?;
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|v| v.to_str().ok())
+                    .context("Redirect without a location")?;
+                let target = url.join(location)?;
+                // The credentials go with every request, so only to the
+                // same server (or the same server upgraded to https)
+                if !safe_redirect(&url, &target) {
+                    bail!(
+                        "{} redirects to {}; set that as the CardDAV URL if it is trusted",
+                        url,
+                        target
+                    );
+                }
+                url = target;
+                continue;
+            }
+            if status != StatusCode::MULTI_STATUS {
+                bail!("{} {}: HTTP {}", method, url, status);
+            }
+            let text = response.text().await?;
+            return Ok((url, parse_multistatus(&text)?));
+        }
+        bail!("Too many redirects from {}", url)
+    }
+
+    /// Find the address book: the configured URL may be the address book
+    /// itself, a principal or home set, or just the server
+    async fn discover(&self, config: &CardDavConfig) -> Result<Url> {
+        let start = Url::parse(&config.url).context("Invalid CardDAV URL")?;
+        let well_known = start.join("/.well-known/carddav")?;
+
+        let mut last_error = None;
+        for url in [start, well_known] {
+            match self
+                .find_addressbook(&url, config.addressbook.as_deref())
+                .await
+            {
+                Ok(Some(book)) => return Ok(book),
+                Ok(None) => {}
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) => Err(e.context(format!("No address book found at {}", config.url))),
+            None => bail!("No address book found at {}", config.url),
+        }
+    }
+
+    async fn find_addressbook(&self, url: &Url, wanted: Option<&str>) -> Result<Option<Url>> {
+        let body = propfind_body(
+            "<d:resourcetype/><d:current-user-principal/><card:addressbook-home-set/>",
+        );
+        let (url, status) = self.dav("PROPFIND", url, "0", body.clone()).await?;
+        let Some(response) = status.responses.into_iter().next() else {
+            return Ok(None);
+        };
+        if response.is_addressbook {
+            return Ok(Some(collection_url(url)));
+        }
+
+        let home = match (response.home_set, response.principal) {
+            (Some(home), _) => url.join(&home)?,
+            (None, Some(principal)) => {
+                let principal = url.join(&principal)?;
+                let (principal, status) = self.dav("PROPFIND", &principal, "0", body).await?;
+                match status.responses.into_iter().find_map(|r| r.home_set) {
+                    Some(home) => principal.join(&home)?,
+                    None => return Ok(None),
+                }
+            }
+            (None, None) => return Ok(None),
+        };
+
+        let body = propfind_body("<d:resourcetype/><d:displayname/>");
+        let (home, status) = self.dav("PROPFIND", &home, "1", body).await?;
+        let mut books = Vec::new();
+        for response in status.responses.into_iter().filter(|r| r.is_addressbook) {
+            let url = collection_url(home.join(&response.href)?);
+            books.push((url, response.display_name));
+        }
+
+        let Some(wanted) = wanted else {
+            return Ok(books.into_iter().next().map(|(url, _)| url));
+        };
+        let found = books.into_iter().find(|(url, name)| {
+            name.as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(wanted))
+                || url.path().trim_end_matches('/').rsplit('/').next() == Some(wanted)
+        });
+        match found {
+            Some((url, _)) => Ok(Some(url)),
+            None => bail!("Address book \"{}\" not found in {}", wanted, home),
+        }
+    }
+
+    /// Changes since `token` (everything if None), following truncated answers
+    async fn sync_collection(&self, collection: &Url, token: Option<&str>) -> Result<Changes> {
+        let mut changes = Changes {
+            full: token.is_none(),
+            ..Default::default()
+        };
+        let mut token = token.map(str::to_string);
+        for _ in 0..MAX_SYNC_ROUNDS {
+            let body = format!(
+                r#"<?xml version="1.0" encoding="utf-8"?><d:sync-collection {}><d:sync-token>{}</d:sync-token><d:sync-level>1</d:sync-level><d:prop><d:getetag/></d:prop></d:sync-collection>"#,
+                DAV_NS,
+                escape(token.as_deref().unwrap_or(""))
+            );
+            let (_, status) = self.dav("REPORT", collection, "0", body).await?;
+            let mut truncated = false;
+            for response in status.responses {
+                let url = collection.join(&response.href)?;
+                if url == *collection {
+                    // 507 on the collection itself: more changes to fetch
+                    truncated = response.status == Some(507);
+                } else if response.status == Some(404) {
+                    changes.removed.push(url);
+                } else if !url.path().ends_with('/') {
+                    changes.changed.push((url, response.etag));
+                }
+            }
+            token = status.sync_token;
+            if !truncated {
+                break;
+            }
+        }
+        changes.token = token;
+        Ok(changes)
+    }
+
+    /// All members with their ETags, for servers without sync-collection
+    async fn list(&self, collection: &Url) -> Result<Changes> {
+        let body = propfind_body("<d:resourcetype/><d:getetag/>");
+        let (_, status) = self.dav("PROPFIND", collection, "1", body).await?;
+        let mut changes = Changes {
+            full: true,
+            ..Default::default()
+        };
+        for response in status.responses {
+            let url = collection.join(&response.href)?;
+            if url != *collection && !url.path().ends_with('/') {
+                changes.changed.push((url, response.etag));
+            }
+        }
+        Ok(changes)
+    }
+
+    async fn multiget(&self, collection: &Url, urls: &[&Url]) -> Result<Vec<DavResponse>> {
+        let hrefs: String = urls
+            .iter()
+            .map(|u| format!("<d:href>{}</d:href>", escape(u.path())))
+            .collect();
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><card:addressbook-multiget {}><d:prop><d:getetag/><card:address-data/></d:prop>{}</card:addressbook-multiget>"#,
+            DAV_NS, hrefs
+        );
+        let (_, status) = self.dav("REPORT", collection, "1", body).await?;
+        Ok(status.responses)
+    }
+}
+
+fn propfind_body(props: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><d:propfind {}><d:prop>{}</d:prop></d:propfind>"#,
+        DAV_NS, props
+    )
+}
+
+/// Collection URLs end with a slash so member hrefs resolve inside them
+fn collection_url(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url
+}
+
+/// One `<d:response>` of a Multi-Status answer
+#[derive(Debug, Default)]
+struct DavResponse {
+    href: String,
+    /// Status of the whole response (sync-collection reports removed members as 404)
+    status: Option<u16>,
+    etag: Option<String>,
+    address_data: Option<String>,
+    display_name: Option<String>,
+    is_addressbook: bool,
+    principal: Option<String>,
+    home_set: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct MultiStatus {
+    responses: Vec<DavResponse>,
+    sync_token: Option<String>,
+}
+
+/// Parse a 207 Multi-Status body. Elements are matched by local name since
+/// servers differ in the prefixes they bind to DAV: and the CardDAV namespace.
+fn parse_multistatus(xml: &str) -> Result<MultiStatus> {
+    let mut reader = Reader::from_str(xml);
+    let mut multistatus = MultiStatus::default();
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<DavResponse> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event().context("Malformed WebDAV response")? {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if name == "response" {
+                    current = Some(DavResponse::default());
+                }
+                stack.push(name);
+                text.clear();
+            }
+            Event::Empty(e) => {
+                if e.local_name().as_ref() == b"addressbook"
+                    && stack.last().is_some_and(|p| p == "resourcetype")
+                    && let Some(ref mut response) = current
+                {
+                    response.is_addressbook = true;
+                }
+            }
+            Event::Text(t) => text.push_str(&t.unescape().context("Malformed WebDAV response")?),
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c.into_inner())),
+            Event::End(_) => {
+                let name = stack.pop().unwrap_or_default();
+                let parent = stack.last().map(String::as_str).unwrap_or("");
+                let value = std::mem::take(&mut text);
+                let value = value.trim();
+
+                if name == "response" {
+                    multistatus.responses.extend(current.take());
+                    continue;
+                }
+                let Some(ref mut response) = current else {
+                    if name == "sync-token" && !value.is_empty() {
+                        multistatus.sync_token = Some(value.to_string());
+                    }
+                    continue;
+                };
+                let value = (!value.is_empty()).then(|| value.to_string());
+                match (name.as_str(), parent) {
+                    ("href", "response") => response.href = value.unwrap_or_default(),
+                    ("href", "current-user-principal") => response.principal = value,
+                    ("href", "addressbook-home-set") if response.home_set.is_none() => {
+                        response.home_set = value
+                    }
+                    ("status", "response") => {
+                        response.status = value
+                            .as_deref()
+                            .and_then(|s| s.split_whitespace().nth(1))
+                            .and_then(|code| code.parse().ok())
+                    }
+                    ("getetag", _) if value.is_some() => response.etag = value,
+                    ("address-data", _) if value.is_some() => response.address_data = value,
+                    ("displayname", _) if value.is_some() => response.display_name = value,
+                    ("addressbook", "resourcetype") => response.is_addressbook = true,
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(multistatus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_discovery() {
+        let xml = r#"<?xml version="1.0"?>
+            <multistatus xmlns="DAV:" xmlns:CR="urn:ietf:params:xml:ns:carddav">
+              <response>
+                <href>/</href>
+                <propstat>
+                  <prop>
+                    <resourcetype><collection/></resourcetype>
+                    <current-user-principal><href>/alice/</href></current-user-principal>
+                  </prop>
+                  <status>HTTP/1.1 200 OK</status>
+                </propstat>
+                <propstat>
+                  <prop><CR:addressbook-home-set/></prop>
+                  <status>HTTP/1.1 404 Not Found</status>
+                </propstat>
+              </response>
+            </multistatus>"#;
+        let status = parse_multistatus(xml).unwrap();
+        assert_eq!(status.responses.len(), 1);
+        let root = &status.responses[0];
+        assert_eq!(root.href, "/");
+        assert_eq!(root.principal.as_deref(), Some("/alice/"));
+        assert_eq!(root.home_set, None);
+        assert!(!root.is_addressbook);
+
+        let xml = r#"<d:multistatus xmlns:d="DAV:" xmlns:card="urn:ietf:params:xml:ns:carddav">
+              <d:response>
+                <d:href>/remote.php/dav/addressbooks/users/alice/</d:href>
+                <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/remote.php/dav/addressbooks/users/alice/company/</d:href>
+                <d:propstat><d:prop>
+                  <d:resourcetype><d:collection/><card:addressbook/></d:resourcetype>
+                  <d:displayname>Company &amp; Friends</d:displayname>
+                </d:prop></d:propstat>
+              </d:response>
+            </d:multistatus>"#;
+        let status = parse_multistatus(xml).unwrap();
+        let books: Vec<_> = status
+            .responses
+            .iter()
+            .filter(|r| r.is_addressbook)
+            .collect();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].display_name.as_deref(), Some("Company & Friends"));
+    }
+
+    #[test]
+    fn test_parse_sync_collection_and_multiget() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response>
+                <d:href>/book/a.vcf</d:href>
+                <d:propstat>
+                  <d:prop><d:getetag>"etag-a"</d:getetag></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/book/gone.vcf</d:href>
+                <d:status>HTTP/1.1 404 Not Found</d:status>
+              </d:response>
+              <d:sync-token>http://example.com/sync/42</d:sync-token>
+            </d:multistatus>"#;
+        let status = parse_multistatus(xml).unwrap();
+        assert_eq!(
+            status.sync_token.as_deref(),
+            Some("http://example.com/sync/42")
+        );
+        assert_eq!(status.responses[0].etag.as_deref(), Some("\"etag-a\""));
+        assert_eq!(status.responses[0].status, None);
+        assert_eq!(status.responses[1].href, "/book/gone.vcf");
+        assert_eq!(status.responses[1].status, Some(404));
+
+        let xml = "<d:multistatus xmlns:d=\"DAV:\" xmlns:card=\"urn:ietf:params:xml:ns:carddav\">\
+              <d:response><d:href>/book/a.vcf</d:href><d:propstat><d:prop>\
+                <d:getetag>\"etag-a\"</d:getetag>\
+                <card:address-data>BEGIN:VCARD&#13;\nVERSION:3.0&#13;\nFN:Ann &amp; Co&#13;\n\
+                EMAIL:ann@example.com&#13;\nEND:VCARD&#13;\n</card:address-data>\
+              </d:prop></d:propstat></d:response></d:multistatus>";
+        let status = parse_multistatus(xml).unwrap();
+        let data = status.responses[0].address_data.as_deref().unwrap();
+        let contact = vcard::parse(data).remove(0);
+        assert_eq!(contact.name.as_deref(), Some("Ann & Co"));
+        assert_eq!(contact.email, "ann@example.com");
+    }
+
+    #[test]
+    fn test_safe_redirect() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        let from = url("http://dav.example.com/.well-known/carddav");
+
+        assert!(safe_redirect(&from, &url("http://dav.example.com/dav/")));
+        assert!(safe_redirect(&from, &url("https://dav.example.com/dav/")));
+        assert!(!safe_redirect(&from, &url("http://evil.example.net/dav/")));
+        assert!(!safe_redirect(
+            &from,
+            &url("http://dav.example.com:8080/dav/")
+        ));
+        assert!(!safe_redirect(
+            &url("https://dav.example.com/"),
+            &url("http://dav.example.com/dav/")
+        ));
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let mut summary = SyncSummary {
+            added: 2,
+            ..Default::default()
+        };
+        summary += SyncSummary {
+            conflicts: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.to_string(),
+            "2 added, 0 updated, 0 removed, 0 uploaded, 1 conflicts (server copy kept)"
+        );
+        assert!(SyncSummary::default().is_empty());
+    }
+
+    /// Round trip against a real server. Start one with
+    /// `radicale --storage-filesystem-folder=/tmp/radicale --auth-type=none`
+    /// and run `BLTZ_CARDDAV_TEST_URL=http://localhost:5232/ cargo test -- --ignored carddav`
+    #[tokio::test]
+    #[ignore]
+    async fn test_radicale_round_trip() {
+        let Ok(server) = std::env::var("BLTZ_CARDDAV_TEST_URL") else {
+            return;
+        };
+        let server = Url::parse(&server).unwrap();
+        let user = "bltz";
+        let book = server
+            .join(&format!(
+                "/{}/test-{}/",
+                user,
+                chrono::Utc::now().timestamp_millis()
+            ))
+            .unwrap();
+        let config = CardDavConfig {
+            url: server.to_string(),
+            username: user.to_string(),
+            addressbook: book
+                .path()
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .map(str::to_string),
+            sync_interval_secs: 900,
+        };
+        let client = DavClient::new(&config).unwrap();
+
+        // Create the address book (extended MKCOL) and a card in it
+        let mkcol = format!(
+            r#"<?xml version="1.0"?><d:mkcol {}><d:set><d:prop><d:resourcetype><d:collection/><card:addressbook/></d:resourcetype></d:prop></d:set></d:mkcol>"#,
+            DAV_NS
+        );
+        let status = client
+            .request("MKCOL", &book)
+            .body(mkcol)
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert!(status.is_success(), "MKCOL failed: {}", status);
+        let card_url = book.join("dave.vcf").unwrap();
+        let put = |body: &'static str| {
+            client
+                .request("PUT", &card_url)
+                .header(CONTENT_TYPE, "text/vcard")
+                .body(body)
+                .send()
+        };
+        put("BEGIN:VCARD\r\nVERSION:3.0\r\nUID:dave\r\nFN:Dave\r\nN:;Dave;;;\r\nEMAIL:dave@example.com\r\nNOTE:keep me\r\nEND:VCARD\r\n")
+            .await
+            .unwrap();
+
+        let db = ContactsDb::open_in_memory().await.unwrap();
+        let summary = sync(&config, &db).await.unwrap();
+        assert_eq!(summary.added, 1);
+        let mut dave = db.get_by_email("dave@example.com").await.unwrap().unwrap();
+
+        // A local edit is uploaded without losing other properties
+        dave.phone = Some("+1 555 0123".to_string());
+        db.update_details(&dave).await.unwrap();
+        let summary = sync(&config, &db).await.unwrap();
+        assert_eq!(summary.uploaded, 1);
+        let card = client
+            .request("GET", &card_url)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(card.contains("+1 555 0123") && card.contains("keep me"));
+
+        // A concurrent server change wins over the local edit
+        put("BEGIN:VCARD\r\nVERSION:3.0\r\nUID:dave\r\nFN:David\r\nN:;David;;;\r\nEMAIL:dave@example.com\r\nEND:VCARD\r\n")
+            .await
+            .unwrap();
+        dave.name = Some("Dave Local".to_string());
+        db.update_details(&dave).await.unwrap();
+        let summary = sync(&config, &db).await.unwrap();
+        assert_eq!(summary.conflicts, 1);
+        let dave = db.get_by_email("dave@example.com").await.unwrap().unwrap();
+        assert_eq!(dave.name.as_deref(), Some("David"));
+
+        // Deletion on the server removes the contact
+        client.request("DELETE", &card_url).send().await.unwrap();
+        let summary = sync(&config, &db).await.unwrap();
+        assert_eq!(summary.removed, 1);
+        assert!(db.get_by_email("dave@example.com").await.unwrap().is_none());
+
+        client.request("DELETE", &book).send().await.unwrap();
+    }
+}
@@ -9,8 +9,13 @@ use std::str::FromStr;
 #[derive(Debug, Clone)]
 pub struct Contact {
     pub id: i64,
+    /// Primary address (contacts are unique by it)
     pub email: String,
     pub name: Option<String>,
+    /// Further addresses of the same person
+    pub other_emails: Vec<String>,
+    pub phone: Option<String>,
+    pub organization: Option<String>,
     pub last_contacted: Option<i64>,
     pub contact_count: i64,
 }
@@ -19,8 +24,71 @@ impl Contact {
     pub fn display_name(&self) -> &str {
         self.name.as_deref().unwrap_or(&self.email)
     }
+
+    /// Whether `address` is one of this contact's addresses
+    pub fn has_email(&self, address: &str) -> bool {
+        std::iter::once(&self.email)
+            .chain(&self.other_emails)
+            .any(|e| e.eq_ignore_ascii_case(address))
+    }
+}
+
+/// How many contacts an import created and how many it merged into existing ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+}
+
+const COLUMNS: &str =
+    "id, email, name, other_emails, phone, organization, last_contacted, contact_count";
+
+fn row_to_contact(row: &sqlx::sqlite::SqliteRow) -> Contact {
+    Contact {
+        id: row.get("id"),
+        email: row.get("email"),
+        name: row.get("name"),
+        other_emails: row
+            .get::<Option<String>, _>("other_emails")
+            .map(|s| s.lines().map(str::to_string).collect())
+            .unwrap_or_default(),
+        phone: row.get("phone"),
+        organization: row.get("organization"),
+        last_contacted: row.get("last_contacted"),
+        contact_count: row.get("contact_count"),
+    }
+}
+
+/// Additional addresses are stored one per line
+fn other_emails_column(contact: &Contact) -> Option<String> {
+    (!contact.other_emails.is_empty()).then(|| contact.other_emails.join("\n"))
 }
 
+/// A contact that lives in a CardDAV address book
+#[derive(Debug, Clone)]
+pub struct RemoteCard {
+    pub contact: Contact,
+    /// Absolute URL of the card on the server
+    pub href: String,
+    pub etag: Option<String>,
+    /// The card as last fetched, so properties bltz doesn't model survive edits
+    pub card: String,
+}
+
+const REMOTE_COLUMNS: &str = "carddav_href, carddav_etag, carddav_card";
+
+fn row_to_remote(row: &sqlx::sqlite::SqliteRow) -> RemoteCard {
+    RemoteCard {
+        contact: row_to_contact(row),
+        href: row.get("carddav_href"),
+        etag: row.get("carddav_etag"),
+        card: row
+            .get::<Option<String>, _>("carddav_card")
+            .unwrap_or_default(),
+    }
+}
+
+#[derive(Clone)]
 pub struct ContactsDb {
     pool: SqlitePool,
 }
@@ -87,6 +155,42 @@ impl ContactsDb {
         .execute(pool)
         .await?;
 
+        // Migration: vCard fields and CardDAV sync state
+        for column in [
+            "other_emails TEXT",
+            "phone TEXT",
+            "organization TEXT",
+            "carddav_href TEXT",
+            "carddav_etag TEXT",
+            "carddav_card TEXT",
+            "carddav_dirty INTEGER NOT NULL DEFAULT 0",
+        ] {
+            sqlx::query(&format!("ALTER TABLE contacts ADD COLUMN {}", column))
+                .execute(pool)
+                .await
+                .ok(); // Ignore error if column already exists
+        }
+
+        sqlx::query(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_contacts_carddav_href ON contacts(carddav_href);
+
+            -- Sync token per address book (RFC 6578)
+            CREATE TABLE IF NOT EXISTS carddav_collections (
+                url TEXT PRIMARY KEY,
+                sync_token TEXT
+            );
+
+            -- Shared contacts deleted locally, still to be deleted on the server
+            CREATE TABLE IF NOT EXISTS carddav_deleted (
+                href TEXT PRIMARY KEY,
+                etag TEXT
+            );
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         Ok(())
     }
 
@@ -117,28 +221,14 @@ impl ContactsDb {
     }
 
     pub async fn get_all(&self) -> Result<Vec<Contact>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, email, name, last_contacted, contact_count
-            FROM contacts
-            ORDER BY contact_count DESC, last_contacted DESC
-            "#,
-        )
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM contacts ORDER BY contact_count DESC, last_contacted DESC",
+            COLUMNS
+        ))
         .fetch_all(&self.pool)
         .await?;
 
-        let contacts = rows
-            .into_iter()
-            .map(|row| Contact {
-                id: row.get("id"),
-                email: row.get("email"),
-                name: row.get("name"),
-                last_contacted: row.get("last_contacted"),
-                contact_count: row.get("contact_count"),
-            })
-            .collect();
-
-        Ok(contacts)
+        Ok(rows.iter().map(row_to_contact).collect())
     }
 
     pub async fn search(&self, query: &str) -> Result<Vec<Contact>> {
@@ -149,52 +239,42 @@ impl ContactsDb {
             .replace('_', "\\_");
         let pattern = format!("%{}%", escaped);
 
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
-            SELECT id, email, name, last_contacted, contact_count
+            SELECT {}
             FROM contacts
-            WHERE email LIKE ? ESCAPE '\' OR name LIKE ? ESCAPE '\'
+            WHERE email LIKE ?1 ESCAPE '\' OR name LIKE ?1 ESCAPE '\'
+                OR other_emails LIKE ?1 ESCAPE '\' OR organization LIKE ?1 ESCAPE '\'
             ORDER BY contact_count DESC
             LIMIT 20
             "#,
-        )
-        .bind(&pattern)
+            COLUMNS
+        ))
         .bind(&pattern)
         .fetch_all(&self.pool)
         .await?;
 
-        let contacts = rows
-            .into_iter()
-            .map(|row| Contact {
-                id: row.get("id"),
-                email: row.get("email"),
-                name: row.get("name"),
-                last_contacted: row.get("last_contacted"),
-                contact_count: row.get("contact_count"),
-            })
-            .collect();
-
-        Ok(contacts)
+        Ok(rows.iter().map(row_to_contact).collect())
     }
 
     pub async fn get_by_email(&self, email: &str) -> Result<Option<Contact>> {
-        let row = sqlx::query(
-            "SELECT id, email, name, last_contacted, contact_count FROM contacts WHERE email = ?",
-        )
-        .bind(email)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = sqlx::query(&format!("SELECT {} FROM contacts WHERE email = ?", COLUMNS))
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        Ok(row.map(|row| Contact {
-            id: row.get("id"),
-            email: row.get("email"),
-            name: row.get("name"),
-            last_contacted: row.get("last_contacted"),
-            contact_count: row.get("contact_count"),
-        }))
+        Ok(row.as_ref().map(row_to_contact))
     }
 
     pub async fn delete(&self, id: i64) -> Result<()> {
+        // Shared contacts are deleted on the server at the next sync
+        sqlx::query(
+            "INSERT OR REPLACE INTO carddav_deleted (href, etag)
+             SELECT carddav_href, carddav_etag FROM contacts WHERE id = ? AND carddav_href IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
         sqlx::query("DELETE FROM contacts WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
@@ -202,15 +282,299 @@ impl ContactsDb {
         Ok(())
     }
 
-    pub async fn update_name(&self, id: i64, name: &str) -> Result<()> {
-        let name = if name.is_empty() { None } else { Some(name) };
-        sqlx::query("UPDATE contacts SET name = ? WHERE id = ?")
-            .bind(name)
+    /// Save the editable fields (name, other addresses, phone, organization).
+    /// Shared contacts are uploaded at the next sync.
+    pub async fn update_details(&self, contact: &Contact) -> Result<()> {
+        sqlx::query(
+            "UPDATE contacts SET name = ?, other_emails = ?, phone = ?, organization = ?,
+                carddav_dirty = (carddav_href IS NOT NULL)
+             WHERE id = ?",
+        )
+        .bind(&contact.name)
+        .bind(other_emails_column(contact))
+        .bind(&contact.phone)
+        .bind(&contact.organization)
+        .bind(contact.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Add imported cards, merging each into the contact that already has
+    /// one of its addresses. Imported fields win over what was harvested.
+    pub async fn import(&self, cards: &[Contact]) -> Result<ImportSummary> {
+        let mut existing = self.get_all().await?;
+        let mut summary = ImportSummary::default();
+
+        for card in cards {
+            let found = existing.iter_mut().find(|c| {
+                std::iter::once(&card.email)
+                    .chain(&card.other_emails)
+                    .any(|e| c.has_email(e))
+            });
+            match found {
+                Some(contact) => {
+                    for email in std::iter::once(&card.email).chain(&card.other_emails) {
+                        if !contact.has_email(email) {
+                            contact.other_emails.push(email.clone());
+                        }
+                    }
+                    contact.name = card.name.clone().or(contact.name.take());
+                    contact.phone = card.phone.clone().or(contact.phone.take());
+                    contact.organization =
+                        card.organization.clone().or(contact.organization.take());
+                    self.update_details(contact).await?;
+                    summary.updated += 1;
+                }
+                None => {
+                    let id = sqlx::query(
+                        "INSERT INTO contacts (email, name, other_emails, phone, organization) VALUES (?, ?, ?, ?, ?)",
+                    )
+                    .bind(&card.email)
+                    .bind(&card.name)
+                    .bind(other_emails_column(card))
+                    .bind(&card.phone)
+                    .bind(&card.organization)
+                    .execute(&self.pool)
+                    .await?
+                    .last_insert_rowid();
+                    existing.push(Contact { id, ..card.clone() });
+                    summary.added += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // === CardDAV sync state ===
+
+    pub async fn sync_token(&self, collection: &str) -> Result<Option<String>> {
+        let token = sqlx::query_scalar("SELECT sync_token FROM carddav_collections WHERE url = ?")
+            .bind(collection)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(token.flatten())
+    }
+
+    pub async fn set_sync_token(&self, collection: &str, token: Option<&str>) -> Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO carddav_collections (url, sync_token) VALUES (?, ?)")
+            .bind(collection)
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Cards of `collection` with local edits not yet uploaded
+    pub async fn dirty_cards(&self, collection: &str) -> Result<Vec<RemoteCard>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, {} FROM contacts WHERE carddav_dirty = 1 AND instr(carddav_href, ?) = 1",
+            COLUMNS, REMOTE_COLUMNS
+        ))
+        .bind(collection)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(row_to_remote).collect())
+    }
+
+    /// (href, etag) of every card of `collection` stored locally
+    pub async fn linked_cards(&self, collection: &str) -> Result<Vec<(String, Option<String>)>> {
+        let rows = sqlx::query(
+            "SELECT carddav_href, carddav_etag FROM contacts WHERE instr(carddav_href, ?) = 1",
+        )
+        .bind(collection)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("carddav_href"), row.get("carddav_etag")))
+            .collect())
+    }
+
+    /// (href, etag) of cards of `collection` deleted locally
+    pub async fn deleted_cards(&self, collection: &str) -> Result<Vec<(String, Option<String>)>> {
+        let rows = sqlx::query("SELECT href, etag FROM carddav_deleted WHERE instr(href, ?) = 1")
+            .bind(collection)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("href"), row.get("etag")))
+            .collect())
+    }
+
+    pub async fn forget_deleted(&self, href: &str) -> Result<()> {
+        sqlx::query("DELETE FROM carddav_deleted WHERE href = ?")
+            .bind(href)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a successful upload
+    pub async fn mark_uploaded(&self, id: i64, etag: Option<&str>, card: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE contacts SET carddav_etag = ?, carddav_card = ?, carddav_dirty = 0 WHERE id = ?",
+        )
+        .bind(etag)
+        .bind(card)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Drop a local edit that lost against a newer server copy; clearing
+    /// the ETag makes the next pull fetch that copy
+    pub async fn discard_local_edit(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE contacts SET carddav_etag = NULL, carddav_dirty = 0 WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
+
+    /// Store a card fetched from the server. The server copy wins over local
+    /// fields; a card that shares an address with an unlinked contact is
+    /// merged into it. Returns true if a new contact was created.
+    pub async fn store_remote(
+        &self,
+        href: &str,
+        etag: Option<&str>,
+        card: &str,
+        parsed: &Contact,
+    ) -> Result<bool> {
+        let addresses: Vec<&String> = std::iter::once(&parsed.email)
+            .chain(&parsed.other_emails)
+            .collect();
+
+        let linked = sqlx::query(&format!(
+            "SELECT {} FROM contacts WHERE carddav_href = ?",
+            COLUMNS
+        ))
+        .bind(href)
+        .fetch_optional(&self.pool)
+        .await?
+        .as_ref()
+        .map(row_to_contact);
+
+        let existing = match linked {
+            Some(contact) => Some(contact),
+            None => {
+                let mut found = None;
+                for address in &addresses {
+                    if let Some(contact) = self.find_unlinked(address).await? {
+                        found = Some(contact);
+                        break;
+                    }
+                }
+                found
+            }
+        };
+
+        let Some(mut contact) = existing else {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO contacts
+                    (email, name, other_emails, phone, organization, carddav_href, carddav_etag, carddav_card)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&parsed.email)
+            .bind(&parsed.name)
+            .bind(other_emails_column(parsed))
+            .bind(&parsed.phone)
+            .bind(&parsed.organization)
+            .bind(href)
+            .bind(etag)
+            .bind(card)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                // The address belongs to a contact linked to another card
+                tracing::warn!(
+                    "CardDAV: skipping {}, {} is already taken",
+                    href,
+                    parsed.email
+                );
+            }
+            return Ok(inserted > 0);
+        };
+
+        // Keep the local primary address while the card still lists it
+        if !addresses
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(&contact.email))
+        {
+            let taken = self
+                .get_by_email(&parsed.email)
+                .await?
+                .is_some_and(|other| other.id != contact.id);
+            if !taken {
+                contact.email = parsed.email.clone();
+            }
+        }
+        contact.other_emails = addresses
+            .iter()
+            .filter(|a| !a.eq_ignore_ascii_case(&contact.email))
+            .map(|a| a.to_string())
+            .collect();
+
+        sqlx::query(
+            "UPDATE contacts SET email = ?, name = ?, other_emails = ?, phone = ?, organization = ?,
+                carddav_href = ?, carddav_etag = ?, carddav_card = ?, carddav_dirty = 0
+             WHERE id = ?",
+        )
+        .bind(&contact.email)
+        .bind(&parsed.name)
+        .bind(other_emails_column(&contact))
+        .bind(&parsed.phone)
+        .bind(&parsed.organization)
+        .bind(href)
+        .bind(etag)
+        .bind(card)
+        .bind(contact.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(false)
+    }
+
+    /// A card was deleted on the server: drop contacts that only existed
+    /// because of it, unlink the ones harvested from mail
+    pub async fn remove_remote(&self, href: &str) -> Result<bool> {
+        let deleted =
+            sqlx::query("DELETE FROM contacts WHERE carddav_href = ? AND contact_count = 0")
+                .bind(href)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        let unlinked = sqlx::query(
+            "UPDATE contacts SET carddav_href = NULL, carddav_etag = NULL, carddav_card = NULL,
+                carddav_dirty = 0
+             WHERE carddav_href = ?",
+        )
+        .bind(href)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(deleted + unlinked > 0)
+    }
+
+    /// Contact not linked to any card that has `address`
+    async fn find_unlinked(&self, address: &str) -> Result<Option<Contact>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM contacts
+             WHERE carddav_href IS NULL
+                AND (email = ?1 COLLATE NOCASE
+                    OR instr(char(10) || lower(other_emails) || char(10), char(10) || lower(?1) || char(10)) > 0)
+             LIMIT 1",
+            COLUMNS
+        ))
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(row_to_contact))
+    }
 }
 
 #[cfg(test)]
@@ -263,4 +627,116 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].email, "alice@example.com");
     }
+
+    #[tokio::test]
+    async fn test_import_merges_by_address() {
+        let db = test_db().await;
+        db.add_or_update("alice@example.com", Some("alice"))
+            .await
+            .unwrap();
+
+        let text = "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Alice Example\r\n\
+                    EMAIL;PREF=1:alice@work.example\r\nEMAIL:Alice@Example.com\r\n\
+                    TEL:+1 555 0100\r\nORG:Acme\r\nEND:VCARD\r\n\
+                    BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Bob\r\n\
+                    EMAIL:bob@example.com\r\nEND:VCARD\r\n";
+        let summary = db.import(&super::super::vcard::parse(text)).await.unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 1,
+                updated: 1
+            }
+        );
+
+        let alice = db.get_by_email("alice@example.com").await.unwrap().unwrap();
+        assert_eq!(alice.name.as_deref(), Some("Alice Example"));
+        assert_eq!(alice.other_emails, vec!["alice@work.example"]);
+        assert_eq!(alice.phone.as_deref(), Some("+1 555 0100"));
+        assert_eq!(alice.organization.as_deref(), Some("Acme"));
+        assert_eq!(alice.contact_count, 1);
+
+        // Other addresses and organizations are searchable
+        assert_eq!(db.search("work.example").await.unwrap().len(), 1);
+        assert_eq!(
+            db.search("acme").await.unwrap()[0].email,
+            "alice@example.com"
+        );
+
+        let bob = db.get_by_email("bob@example.com").await.unwrap().unwrap();
+        assert_eq!(bob.contact_count, 0);
+        assert_eq!(db.get_all().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_remote_cards() {
+        let db = test_db().await;
+        let book = "https://dav.example.com/book/";
+        db.add_or_update("carol@example.com", Some("carol"))
+            .await
+            .unwrap();
+
+        // A shared card merges into the harvested contact with its address
+        let card = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Carol Jones\r\nEMAIL:cj@example.com\r\n\
+                    EMAIL:carol@example.com\r\nEND:VCARD\r\n";
+        let parsed = super::super::vcard::parse(card).remove(0);
+        let href = format!("{}carol.vcf", book);
+        let added = db
+            .store_remote(&href, Some("\"1\""), card, &parsed)
+            .await
+            .unwrap();
+        assert!(!added);
+        let carol = db.get_by_email("carol@example.com").await.unwrap().unwrap();
+        assert_eq!(carol.name.as_deref(), Some("Carol Jones"));
+        assert_eq!(carol.other_emails, vec!["cj@example.com"]);
+        assert_eq!(
+            db.linked_cards(book).await.unwrap(),
+            vec![(href.clone(), Some("\"1\"".to_string()))]
+        );
+
+        // Local edits of shared contacts are queued for upload
+        assert!(db.dirty_cards(book).await.unwrap().is_empty());
+        db.update_details(&carol).await.unwrap();
+        let dirty = db.dirty_cards(book).await.unwrap();
+        assert_eq!(dirty.len(), 1);
+        assert_eq!(dirty[0].card, card);
+        db.mark_uploaded(carol.id, Some("\"2\""), card)
+            .await
+            .unwrap();
+        assert!(db.dirty_cards(book).await.unwrap().is_empty());
+
+        // Server deletion keeps contacts known from mail, unlinked
+        assert!(db.remove_remote(&href).await.unwrap());
+        assert!(
+            db.get_by_email("carol@example.com")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(db.linked_cards(book).await.unwrap().is_empty());
+
+        // Cards only known from the server go away; local deletions are queued
+        let dave =
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Dave\r\nEMAIL:dave@example.com\r\nEND:VCARD\r\n";
+        let dave_href = format!("{}dave.vcf", book);
+        let parsed = super::super::vcard::parse(dave).remove(0);
+        assert!(
+            db.store_remote(&dave_href, None, dave, &parsed)
+                .await
+                .unwrap()
+        );
+        let id = db
+            .get_by_email("dave@example.com")
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+        db.delete(id).await.unwrap();
+        assert_eq!(
+            db.deleted_cards(book).await.unwrap(),
+            vec![(dave_href.clone(), None)]
+        );
+        db.forget_deleted(&dave_href).await.unwrap();
+        assert!(db.deleted_cards(book).await.unwrap().is_empty());
+    }
 }
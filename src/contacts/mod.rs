@@ -1,3 +1,5 @@
+pub mod carddav;
 mod db;
+pub mod vcard;
 
 pub use db::{Contact, ContactsDb};
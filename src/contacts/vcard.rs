@@ -0,0 +1,381 @@
+//! vCard import and export
+//!
+//! Import accepts vCard 2.1, 3.0 and 4.0 (RFC 6350), including the
+//! quoted-printable values older phones produce. Export writes vCard 4.0.
+//! Cards without an email address are skipped since contacts are keyed by it.
+
+use super::Contact;
+use crate::mail::content_line::{Property, escape, fold, parse_property, unescape, unfold};
+
+/// Parse every card in a .vcf file
+pub fn parse(text: &str) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    let mut card: Option<Vec<Property>> = None;
+
+    for line in unfold(text) {
+        let Some(prop) = parse_property(&line) else {
+            continue;
+        };
+        match prop.name.as_str() {
+            "BEGIN" if prop.value.eq_ignore_ascii_case("VCARD") => card = Some(Vec::new()),
+            "END" if prop.value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(contact) = card.take().and_then(|props| build_contact(&props)) {
+                    contacts.push(contact);
+                }
+            }
+            _ => {
+                if let Some(ref mut props) = card {
+                    props.push(prop);
+                }
+            }
+        }
+    }
+    contacts
+}
+
+/// Serialize contacts as vCard 4.0
+pub fn export(contacts: &[Contact]) -> String {
+    let mut out = String::new();
+    for contact in contacts {
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            "VERSION:4.0".to_string(),
+            "PRODID:-//bltz//bltz//EN".to_string(),
+            format!("FN:{}", escape(contact.display_name())),
+            format!("EMAIL;PREF=1:{}", contact.email),
+        ];
+        for email in &contact.other_emails {
+            lines.push(format!("EMAIL:{}", email));
+        }
+        if let Some(ref phone) = contact.phone {
+            lines.push(format!("TEL:{}", escape(phone)));
+        }
+        if let Some(ref organization) = contact.organization {
+            lines.push(format!("ORG:{}", escape(organization)));
+        }
+        lines.push("END:VCARD".to_string());
+        out.extend(lines.iter().map(|l| fold(l)));
+    }
+    out
+}
+
+/// Apply the fields bltz edits to an existing card (as stored on a CardDAV
+/// server), keeping every other property (photos, addresses, notes, ...)
+/// and the unchanged fields' original lines
+pub fn update(card: &str, contact: &Contact) -> String {
+    let Some(old) = parse(card).into_iter().next() else {
+        return export(std::slice::from_ref(contact));
+    };
+    let version_3 = card.contains("VERSION:3.0");
+    let addresses = |c: &Contact| -> Vec<String> {
+        std::iter::once(&c.email)
+            .chain(&c.other_emails)
+            .map(|e| e.to_ascii_lowercase())
+            .collect()
+    };
+    let name_changed = old.name != contact.name;
+    let emails_changed = addresses(&old) != addresses(contact);
+    let phone_changed = old.phone != contact.phone;
+    let organization_changed = old.organization != contact.organization;
+
+    let mut lines = Vec::new();
+    for line in unfold(card) {
+        let Some(prop) = parse_property(&line) else {
+            lines.push(line);
+            continue;
+        };
+        let replaced = match prop.name.as_str() {
+            "FN" => name_changed,
+            "EMAIL" => emails_changed,
+            "ORG" => organization_changed,
+            // Only the number bltz shows is replaced; other numbers stay
+            "TEL" => {
+                phone_changed
+                    && old.phone.as_deref() == Some(prop.text().trim().trim_start_matches("tel:"))
+            }
+            "END" if prop.value.eq_ignore_ascii_case("VCARD") => {
+                if name_changed {
+                    lines.push(format!("FN:{}", escape(contact.display_name())));
+                }
+                if emails_changed {
+                    let pref = if version_3 { "TYPE=pref" } else { "PREF=1" };
+                    lines.push(format!("EMAIL;{}:{}", pref, contact.email));
+                    for email in &contact.other_emails {
+                        lines.push(format!("EMAIL:{}", email));
+                    }
+                }
+                if phone_changed && let Some(ref phone) = contact.phone {
+                    lines.push(format!("TEL:{}", escape(phone)));
+                }
+                if organization_changed && let Some(ref organization) = contact.organization {
+                    lines.push(format!("ORG:{}", escape(organization)));
+                }
+                false
+            }
+            _ => false,
+        };
+        if !replaced {
+            lines.push(line);
+        }
+    }
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+impl Property {
+    /// vCard 2.1 writes bare parameters (`EMAIL;INTERNET;PREF:`), so a
+    /// parameter without a value is matched by name or by TYPE value
+    fn has(&self, name: &str, value: &str) -> bool {
+        self.params.iter().any(|(n, v)| {
+            n.eq_ignore_ascii_case(name) && v.split(',').any(|v| v.eq_ignore_ascii_case(value))
+                || v.is_empty() && n.eq_ignore_ascii_case(value)
+        })
+    }
+
+    fn is_preferred(&self) -> bool {
+        self.has("TYPE", "PREF") || self.has("PREF", "1")
+    }
+
+    fn text(&self) -> String {
+        if self.has("ENCODING", "QUOTED-PRINTABLE") {
+            unescape(&decode_quoted_printable(&self.value))
+        } else {
+            unescape(&self.value)
+        }
+    }
+}
+
+fn build_contact(props: &[Property]) -> Option<Contact> {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+
+    // The preferred address becomes the primary one
+    let mut emails: Vec<&Property> = props.iter().filter(|p| p.name == "EMAIL").collect();
+    emails.sort_by_key(|p| !p.is_preferred());
+    let mut addresses: Vec<String> = Vec::new();
+    for prop in emails {
+        let address = prop.text();
+        let address = address.trim();
+        let address = address.strip_prefix("mailto:").unwrap_or(address);
+        if !address.is_empty() && !addresses.iter().any(|a| a.eq_ignore_ascii_case(address)) {
+            addresses.push(address.to_string());
+        }
+    }
+    if addresses.is_empty() {
+        return None;
+    }
+    let email = addresses.remove(0);
+
+    // Exported nameless contacts carry their address as FN
+    let name = get("FN")
+        .map(Property::text)
+        .or_else(|| get("N").map(|p| structured_name(&p.text())))
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty() && !n.eq_ignore_ascii_case(&email));
+
+    let mut phones: Vec<&Property> = props.iter().filter(|p| p.name == "TEL").collect();
+    phones.sort_by_key(|p| !p.is_preferred());
+    let phone = phones
+        .first()
+        .map(|p| {
+            let text = p.text();
+            text.trim().trim_start_matches("tel:").to_string()
+        })
+        .filter(|p| !p.is_empty());
+
+    // ORG is "Company;Department;..."
+    let organization = get("ORG")
+        .map(|p| {
+            split_structured(&p.value)
+                .iter()
+                .map(|part| unescape(part).trim().to_string())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .filter(|o| !o.is_empty());
+
+    Some(Contact {
+        id: 0,
+        email,
+        name,
+        other_emails: addresses,
+        phone,
+        organization,
+        last_contacted: None,
+        contact_count: 0,
+    })
+}
+
+/// "Family;Given;Additional;Prefix;Suffix" -> "Prefix Given Additional Family Suffix"
+fn structured_name(value: &str) -> String {
+    let parts: Vec<&str> = value.split(';').collect();
+    let part = |i: usize| parts.get(i).copied().unwrap_or("").trim();
+    [part(3), part(1), part(2), part(0), part(4)]
+        .iter()
+        .filter(|p| !p.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split a structured value at unescaped semicolons
+fn split_structured(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let current = parts.last_mut().expect("parts is never empty");
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ';' => parts.push(String::new()),
+            _ => parts.last_mut().expect("parts is never empty").push(c),
+        }
+    }
+    parts
+}
+
+fn decode_quoted_printable(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'='
+            && let Some(hex) = value.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vcard4() {
+        let text = "BEGIN:VCARD\r\n\
+                    VERSION:4.0\r\n\
+                    FN:Alice Example\r\n\
+                    N:Example;Alice;;;\r\n\
+                    EMAIL;TYPE=work:alice@work.example\r\n\
+                    EMAIL;PREF=1:alice@example.com\r\n\
+                    TEL;VALUE=uri;TYPE=cell:tel:+1-555-0100\r\n\
+                    ORG:Acme\\, Inc.;Research\r\n\
+                    END:VCARD\r\n\
+                    BEGIN:VCARD\r\n\
+                    VERSION:4.0\r\n\
+                    FN:No Address\r\n\
+                    END:VCARD\r\n";
+
+        let contacts = parse(text);
+        assert_eq!(contacts.len(), 1);
+        let alice = &contacts[0];
+        assert_eq!(alice.email, "alice@example.com");
+        assert_eq!(alice.other_emails, vec!["alice@work.example"]);
+        assert_eq!(alice.name.as_deref(), Some("Alice Example"));
+        assert_eq!(alice.phone.as_deref(), Some("+1-555-0100"));
+        assert_eq!(alice.organization.as_deref(), Some("Acme, Inc., Research"));
+    }
+
+    #[test]
+    fn test_parse_vcard21() {
+        // Grouped properties, bare parameters, folding and quoted-printable
+        let text = "BEGIN:VCARD\n\
+                    VERSION:2.1\n\
+                    N;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:M=C3=BCller;J=C3=\n\
+                    =BCrgen\n\
+                    item1.EMAIL;INTERNET:juergen@\n example.de\n\
+                    TEL;CELL;PREF:0170 1234567\n\
+                    TEL;HOME:030 123\n\
+                    END:VCARD\n";
+
+        let contacts = parse(text);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].name.as_deref(), Some("Jürgen Müller"));
+        assert_eq!(contacts[0].email, "juergen@example.de");
+        assert_eq!(contacts[0].phone.as_deref(), Some("0170 1234567"));
+        assert!(contacts[0].other_emails.is_empty());
+    }
+
+    #[test]
+    fn test_update_keeps_other_properties() {
+        let card = "BEGIN:VCARD\r\n\
+                    VERSION:3.0\r\n\
+                    UID:1234\r\n\
+                    FN:Carol\r\n\
+                    N:;Carol;;;\r\n\
+                    EMAIL;TYPE=pref:carol@example.com\r\n\
+                    TEL;TYPE=cell:+1 555 0101\r\n\
+                    TEL;TYPE=work:+1 555 0199\r\n\
+                    ADR;TYPE=work:;;1 Main St;Springfield;;;\r\n\
+                    END:VCARD\r\n";
+        let mut contact = parse(card).remove(0);
+        assert_eq!(
+            update(card, &contact).replace("\r\n", "\n"),
+            card.replace("\r\n", "\n")
+        );
+
+        contact.name = Some("Carol Jones".to_string());
+        contact.phone = Some("+1 555 0102".to_string());
+        contact.other_emails.push("cj@home.example".to_string());
+        let updated = update(card, &contact);
+
+        assert!(updated.contains("UID:1234\r\n"));
+        assert!(updated.contains("ADR;TYPE=work:;;1 Main St;Springfield;;;\r\n"));
+        assert!(updated.contains("TEL;TYPE=work:+1 555 0199\r\n"));
+        assert!(!updated.contains("0101"));
+        assert!(updated.ends_with("END:VCARD\r\n"));
+
+        let parsed = parse(&updated).remove(0);
+        assert_eq!(parsed.name.as_deref(), Some("Carol Jones"));
+        assert_eq!(parsed.email, "carol@example.com");
+        assert_eq!(parsed.other_emails, vec!["cj@home.example"]);
+        assert!(updated.contains("TEL:+1 555 0102\r\n"));
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let contact = Contact {
+            id: 7,
+            email: "bob@example.com".to_string(),
+            name: Some("Bob; \"The Builder\"".to_string()),
+            other_emails: vec!["bob@home.example".to_string()],
+            phone: Some("+44 20 7946 0000".to_string()),
+            organization: Some("Builders, Ltd".to_string()),
+            last_contacted: Some(1),
+            contact_count: 3,
+        };
+        let nameless = Contact {
+            name: None,
+            other_emails: Vec::new(),
+            phone: None,
+            organization: None,
+            email: "x@example.com".to_string(),
+            ..contact.clone()
+        };
+
+        let text = export(&[contact.clone(), nameless]);
+        assert!(text.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(text.contains("FN:Bob\\; \"The Builder\"\r\n"));
+        assert!(text.contains("FN:x@example.com\r\n"));
+        assert!(text.lines().all(|l| l.len() <= 75));
+
+        let parsed = parse(&text);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name, contact.name);
+        assert_eq!(parsed[0].email, contact.email);
+        assert_eq!(parsed[0].other_emails, contact.other_emails);
+        assert_eq!(parsed[0].phone, contact.phone);
+        assert_eq!(parsed[0].organization, contact.organization);
+        assert_eq!(parsed[1].name, None);
+    }
+}
@@ -8,6 +8,8 @@ use std::path::PathBuf;
 use std::os::unix::fs::OpenOptionsExt;
 
 const ENV_PASSWORD: &str = "BLTZ_PASSWORD";
+const ENV_SMIME_PASSWORD: &str = "BLTZ_SMIME_PASSWORD";
+const ENV_CARDDAV_PASSWORD: &str = "BLTZ_CARDDAV_PASSWORD";
 
 /// Debug information about credential storage backends
 #[derive(Debug, Clone)]
@@ -228,7 +230,11 @@ impl CredentialStore {
         let imap_key = format!("imap:{}", self.email);
         let smtp_key = format!("smtp:{}", self.email);
         let oauth2_key = format!("oauth2:{}", self.email);
+        let smime_key = format!("smime:{}", self.email);
 
+        if let Ok(entry) = keyring::Entry::new("bltz", &smime_key) {
+            let _ = entry.delete_credential();
+        }
         if let Ok(entry) = keyring::Entry::new("bltz", &imap_key) {
             let _ = entry.delete_credential();
         }
@@ -246,6 +252,48 @@ impl CredentialStore {
         Ok(())
     }
 
+    // === S/MIME Passphrase Storage ===
+
+    /// Get the passphrase for this account's PKCS#12 identity
+    /// (None = not stored; the file may have no passphrase)
+    pub fn get_smime_passphrase(&self) -> Option<String> {
+        if let Ok(passphrase) = env::var(ENV_SMIME_PASSWORD) {
+            return Some(passphrase);
+        }
+        self.keyring_get(&format!("smime:{}", self.email))
+    }
+
+    /// Store the PKCS#12 passphrase (keyring only; never written to a file)
+    pub fn set_smime_passphrase(&self, passphrase: &str) -> Result<()> {
+        let key = format!("smime:{}", self.email);
+        if self.keyring_set(&key, passphrase) && self.keyring_get(&key).is_some() {
+            Ok(())
+        } else {
+            anyhow::bail!("Keyring unavailable; set {} instead", ENV_SMIME_PASSWORD)
+        }
+    }
+
+    // === CardDAV Password Storage ===
+    // The store is created with `CardDavConfig::credential_id()` instead of an email
+
+    /// Get the CardDAV password (None = not stored; the server may not need one)
+    pub fn get_carddav_password(&self) -> Option<String> {
+        if let Ok(password) = env::var(ENV_CARDDAV_PASSWORD) {
+            return Some(password);
+        }
+        self.keyring_get(&format!("carddav:{}", self.email))
+    }
+
+    /// Store the CardDAV password (keyring only; never written to a file)
+    pub fn set_carddav_password(&self, password: &str) -> Result<()> {
+        let key = format!("carddav:{}", self.email);
+        if self.keyring_set(&key, password) && self.keyring_get(&key).is_some() {
+            Ok(())
+        } else {
+            anyhow::bail!("Keyring unavailable; set {} instead", ENV_CARDDAV_PASSWORD)
+        }
+    }
+
     // === OAuth2 Token Storage ===
 
     fn oauth2_token_file(&self) -> PathBuf {
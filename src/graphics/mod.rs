@@ -0,0 +1,394 @@
+//! Inline images in the terminal
+//!
+//! Images are drawn with the kitty graphics protocol, the iTerm2 inline
+//! image protocol or sixel when the terminal speaks one of them, and as
+//! Unicode half blocks otherwise. The protocol comes from the environment
+//! (or the `images` setting); terminals are not queried, since replies
+//! would arrive mixed into keyboard input.
+
+mod png;
+mod sixel;
+
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+use base64::Engine;
+
+use crate::config::ImageProtocol;
+
+/// Cell size assumed when the terminal does not report its pixel size
+const DEFAULT_CELL: (u16, u16) = (10, 20);
+
+/// Largest base64 payload per kitty escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+/// How images are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+    HalfBlocks,
+}
+
+static PROTOCOL: OnceLock<Option<Protocol>> = OnceLock::new();
+
+/// Pick the protocol from the configured setting (call once at startup)
+pub fn init(setting: ImageProtocol) {
+    let protocol = match setting {
+        ImageProtocol::Auto => Some(detect()),
+        ImageProtocol::Kitty => Some(Protocol::Kitty),
+        ImageProtocol::Iterm2 => Some(Protocol::Iterm2),
+        ImageProtocol::Sixel => Some(Protocol::Sixel),
+        ImageProtocol::Halfblocks => Some(Protocol::HalfBlocks),
+        ImageProtocol::Off => None,
+    };
+    let _ = PROTOCOL.set(protocol);
+}
+
+/// The protocol in use (None = images are turned off)
+pub fn protocol() -> Option<Protocol> {
+    PROTOCOL.get().copied().flatten()
+}
+
+fn detect() -> Protocol {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+    // Multiplexers swallow graphics escapes unless specially wrapped
+    if !var("TMUX").is_empty() || term.starts_with("screen") {
+        return Protocol::HalfBlocks;
+    }
+    if !var("KITTY_WINDOW_ID").is_empty() || term.contains("kitty") || program == "ghostty" {
+        Protocol::Kitty
+    } else if program == "iTerm.app" || program == "WezTerm" {
+        Protocol::Iterm2
+    } else if term.contains("foot") || term.contains("mlterm") || term.contains("sixel") {
+        Protocol::Sixel
+    } else {
+        Protocol::HalfBlocks
+    }
+}
+
+/// Image file formats recognized by their magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Jpeg,
+    Gif,
+    Other,
+}
+
+impl Format {
+    pub fn sniff(data: &[u8]) -> Self {
+        if png::is_png(data) {
+            Format::Png
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Format::Jpeg
+        } else if data.starts_with(b"GIF8") {
+            Format::Gif
+        } else {
+            Format::Other
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Format::Png => "PNG",
+            Format::Jpeg => "JPEG",
+            Format::Gif => "GIF",
+            Format::Other => "unknown",
+        }
+    }
+}
+
+/// Decoded RGBA pixels
+#[derive(Clone, PartialEq, Eq)]
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl Pixels {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.rgba[i],
+            self.rgba[i + 1],
+            self.rgba[i + 2],
+            self.rgba[i + 3],
+        ]
+    }
+
+    /// Nearest-neighbour resize
+    pub fn resize(&self, width: u32, height: u32) -> Pixels {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            let sy = (u64::from(y) * u64::from(self.height) / u64::from(height)) as u32;
+            for x in 0..width {
+                let sx = (u64::from(x) * u64::from(self.width) / u64::from(width)) as u32;
+                rgba.extend_from_slice(&self.pixel(sx, sy));
+            }
+        }
+        Pixels {
+            width,
+            height,
+            rgba,
+        }
+    }
+}
+
+/// An image from a message: an inline (`cid:`) part, an attachment or a remote image
+pub struct InlineImage {
+    pub name: String,
+    pub format: Format,
+    pub data: Vec<u8>,
+    /// Decoded pixels (PNG only), needed for sixel and half blocks
+    pub pixels: Option<Pixels>,
+}
+
+impl fmt::Debug for InlineImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InlineImage")
+            .field("name", &self.name)
+            .field("format", &self.format)
+            .field("bytes", &self.data.len())
+            .finish()
+    }
+}
+
+impl InlineImage {
+    pub fn new(name: String, data: Vec<u8>) -> Self {
+        let format = Format::sniff(&data);
+        let pixels = (format == Format::Png)
+            .then(|| png::decode(&data))
+            .flatten();
+        Self {
+            name,
+            format,
+            data,
+            pixels,
+        }
+    }
+
+    /// Whether the data is an image at all
+    pub fn is_image(&self) -> bool {
+        self.format != Format::Other
+    }
+
+    /// Whether `protocol` can draw this image
+    pub fn drawable(&self, protocol: Protocol) -> bool {
+        match protocol {
+            Protocol::Iterm2 => self.is_image(),
+            Protocol::Kitty => self.format == Format::Png,
+            Protocol::Sixel | Protocol::HalfBlocks => self.pixels.is_some(),
+        }
+    }
+}
+
+/// Where the reader wants an image drawn, in terminal cells
+#[derive(Debug, Clone)]
+pub struct Placement {
+    pub image: Arc<InlineImage>,
+    pub x: u16,
+    pub y: u16,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl PartialEq for Placement {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.image, &other.image)
+            && (self.x, self.y, self.cols, self.rows) == (other.x, other.y, other.cols, other.rows)
+    }
+}
+
+/// Pixel size of one terminal cell
+pub fn cell_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            (size.width / size.columns).max(1),
+            (size.height / size.rows).max(1),
+        ),
+        _ => DEFAULT_CELL,
+    }
+}
+
+/// Cells an image of `width`×`height` pixels takes when shrunk (never
+/// enlarged) to fit `cols`×`rows`, keeping its aspect ratio
+pub fn fit(width: u32, height: u32, cols: u16, rows: u16, cell: (u16, u16)) -> (u16, u16) {
+    let box_w = f64::from(cols) * f64::from(cell.0);
+    let box_h = f64::from(rows) * f64::from(cell.1);
+    let scale = (box_w / f64::from(width.max(1)))
+        .min(box_h / f64::from(height.max(1)))
+        .min(1.0);
+    let cols_used = (f64::from(width) * scale / f64::from(cell.0)).ceil() as u16;
+    let rows_used = (f64::from(height) * scale / f64::from(cell.1)).ceil() as u16;
+    (
+        cols_used.clamp(1, cols.max(1)),
+        rows_used.clamp(1, rows.max(1)),
+    )
+}
+
+/// Escape sequence drawing `placement` (None if the protocol can't draw it)
+pub fn draw_sequence(protocol: Protocol, placement: &Placement) -> Option<String> {
+    let image = &placement.image;
+    if !image.drawable(protocol) {
+        return None;
+    }
+    let cell = cell_size();
+    let (width, height) = image.pixels.as_ref().map_or(
+        (
+            placement.cols as u32 * cell.0 as u32,
+            placement.rows as u32 * cell.1 as u32,
+        ),
+        |p| (p.width, p.height),
+    );
+    let (cols, rows) = fit(width, height, placement.cols, placement.rows, cell);
+    let b64 = base64::engine::general_purpose::STANDARD;
+
+    // Move to the top-left cell of the placement
+    let mut out = format!("\x1b[{};{}H", placement.y + 1, placement.x + 1);
+    match protocol {
+        Protocol::Kitty => {
+            let payload = b64.encode(&image.data);
+            let chunks: Vec<&str> = payload
+                .as_bytes()
+                .chunks(KITTY_CHUNK)
+                .map(|c| std::str::from_utf8(c).unwrap_or_default())
+                .collect();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                if i == 0 {
+                    out.push_str(&format!(
+                        "\x1b_Ga=T,f=100,t=d,q=2,C=1,c={},r={},m={};{}\x1b\\",
+                        cols, rows, more, chunk
+                    ));
+                } else {
+                    out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                }
+            }
+        }
+        Protocol::Iterm2 => {
+            out.push_str(&format!(
+                "\x1b]1337;File=name={};size={};width={};height={};preserveAspectRatio=1;inline=1:{}\x07",
+                b64.encode(&image.name),
+                image.data.len(),
+                placement.cols,
+                placement.rows,
+                b64.encode(&image.data)
+            ));
+        }
+        Protocol::Sixel => {
+            let pixels = image.pixels.as_ref()?;
+            let target = pixels.resize(
+                (u32::from(cols) * u32::from(cell.0)).min(pixels.width),
+                (u32::from(rows) * u32::from(cell.1)).min(pixels.height),
+            );
+            out.push_str(&sixel::encode(&target));
+        }
+        // Drawn by the UI as text
+        Protocol::HalfBlocks => return None,
+    }
+    Some(out)
+}
+
+/// Escape sequence removing drawn images, for protocols that keep them
+/// apart from the text (others are painted over by a full redraw)
+pub fn erase_sequence(protocol: Protocol) -> Option<&'static str> {
+    (protocol == Protocol::Kitty).then_some("\x1b_Ga=d,d=A,q=2\x1b\\")
+}
+
+/// Colours of a half-block cell: upper and lower pixel (None = transparent)
+pub type HalfBlock = (Option<[u8; 3]>, Option<[u8; 3]>);
+
+/// Lay pixels out as rows of half-block cells fitting `cols`×`rows`
+pub fn half_blocks(pixels: &Pixels, cols: u16, rows: u16) -> Vec<Vec<HalfBlock>> {
+    // Each cell shows two square-ish pixels stacked
+    let (cols, rows) = fit(pixels.width, pixels.height, cols, rows, (1, 2));
+    let scaled = pixels.resize(u32::from(cols), u32::from(rows) * 2);
+    let color = |x: u32, y: u32| {
+        let [r, g, b, a] = scaled.pixel(x, y);
+        (a >= 128).then_some([r, g, b])
+    };
+    (0..u32::from(rows))
+        .map(|row| {
+            (0..u32::from(cols))
+                .map(|x| (color(x, row * 2), color(x, row * 2 + 1)))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Pixels {
+        Pixels {
+            width,
+            height,
+            rgba: rgba.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn test_fit() {
+        // Small images are not enlarged
+        assert_eq!(fit(20, 40, 80, 24, (10, 20)), (2, 2));
+        // Wide images are limited by columns, keeping the aspect ratio
+        assert_eq!(fit(2000, 500, 50, 40, (10, 20)), (50, 7));
+        // Tall images are limited by rows
+        assert_eq!(fit(500, 4000, 80, 10, (10, 20)), (3, 10));
+    }
+
+    #[test]
+    fn test_sniff_and_decode() {
+        let png = png::tests::encode(1, 1, 2, 8, &[0, 1, 2, 3], &[]);
+        let image = InlineImage::new("a.png".to_string(), png);
+        assert_eq!(image.format, Format::Png);
+        assert!(image.drawable(Protocol::HalfBlocks));
+        assert!(image.drawable(Protocol::Kitty));
+
+        let jpeg = InlineImage::new("b.jpg".to_string(), vec![0xff, 0xd8, 0xff, 0xe0]);
+        assert_eq!(jpeg.format, Format::Jpeg);
+        assert!(jpeg.drawable(Protocol::Iterm2));
+        assert!(!jpeg.drawable(Protocol::Sixel));
+
+        assert!(!InlineImage::new("c.txt".to_string(), b"hello".to_vec()).is_image());
+    }
+
+    #[test]
+    fn test_half_blocks() {
+        let mut pixels = solid(4, 4, [255, 0, 0, 255]);
+        // Make the bottom half transparent
+        for px in pixels.rgba.chunks_exact_mut(4).skip(8) {
+            px[3] = 0;
+        }
+        let cells = half_blocks(&pixels, 10, 10);
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].len(), 4);
+        assert_eq!(cells[0][0], (Some([255, 0, 0]), Some([255, 0, 0])));
+        assert_eq!(cells[1][0], (None, None));
+    }
+
+    #[test]
+    fn test_draw_sequence() {
+        let png = png::tests::encode(1, 1, 2, 8, &[0, 1, 2, 3], &[]);
+        let placement = Placement {
+            image: Arc::new(InlineImage::new("a.png".to_string(), png)),
+            x: 4,
+            y: 2,
+            cols: 10,
+            rows: 5,
+        };
+
+        let kitty = draw_sequence(Protocol::Kitty, &placement).unwrap();
+        assert!(kitty.starts_with("\x1b[3;5H\x1b_Ga=T,f=100"));
+        let sixel = draw_sequence(Protocol::Sixel, &placement).unwrap();
+        assert!(sixel.contains("\x1bP0;1;0q"));
+        assert!(draw_sequence(Protocol::HalfBlocks, &placement).is_none());
+    }
+}
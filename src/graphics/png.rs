@@ -0,0 +1,298 @@
+//! Minimal PNG decoder
+//!
+//! Enough for screenshots and logos: every colour type and bit depth, but
+//! not Adam7 interlacing. Sixel and half-block output need pixels; kitty
+//! and iTerm2 are sent the file as is.
+
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+
+use super::Pixels;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Largest image decoded (pixels), to bound memory use
+const MAX_PIXELS: u64 = 40_000_000;
+
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+/// Decode a PNG into RGBA pixels (None if it is broken or unsupported)
+pub fn decode(data: &[u8]) -> Option<Pixels> {
+    let mut rest = data.strip_prefix(SIGNATURE)?;
+    let mut header = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut transparency: Vec<u8> = Vec::new();
+    let mut compressed = Vec::new();
+
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes(rest[..4].try_into().ok()?) as usize;
+        let kind = &rest[4..8];
+        let body = rest.get(8..8 + len)?;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(body)?),
+            b"PLTE" => palette = body.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            b"tRNS" => transparency = body.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + len..)?;
+    }
+
+    let header = header?;
+    if header.interlaced || u64::from(header.width) * u64::from(header.height) > MAX_PIXELS {
+        return None;
+    }
+    let channels = match header.color_type {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return None,
+    };
+    let bits_per_pixel = channels * header.bit_depth as usize;
+    let stride = (header.width as usize * bits_per_pixel).div_ceil(8);
+    // Filters look back one whole pixel (at least one byte)
+    let step = bits_per_pixel.div_ceil(8);
+
+    // Inflate at most one byte more than the header promises (a small IDAT
+    // can expand to gigabytes), and refuse data of any other size
+    let expected = (stride + 1) * header.height as usize;
+    let mut raw = Vec::with_capacity(expected);
+    ZlibDecoder::new(compressed.as_slice())
+        .take(expected as u64 + 1)
+        .read_to_end(&mut raw)
+        .ok()?;
+    if raw.len() != expected {
+        return None;
+    }
+
+    let mut rgba = Vec::with_capacity(header.width as usize * header.height as usize * 4);
+    let mut previous = vec![0u8; stride];
+    let mut current = vec![0u8; stride];
+    for row in raw.chunks_exact(stride + 1).take(header.height as usize) {
+        current.copy_from_slice(&row[1..]);
+        unfilter(row[0], &mut current, &previous, step)?;
+        for x in 0..header.width as usize {
+            rgba.extend_from_slice(&header.pixel(&current, x, &palette, &transparency));
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    if rgba.len() != header.width as usize * header.height as usize * 4 {
+        return None;
+    }
+
+    Some(Pixels {
+        width: header.width,
+        height: header.height,
+        rgba,
+    })
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Option<Self> {
+        if body.len() < 13 {
+            return None;
+        }
+        let header = Header {
+            width: u32::from_be_bytes(body[0..4].try_into().ok()?),
+            height: u32::from_be_bytes(body[4..8].try_into().ok()?),
+            bit_depth: body[8],
+            color_type: body[9],
+            interlaced: body[12] != 0,
+        };
+        let valid_depth = match header.color_type {
+            0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+            3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+            _ => matches!(header.bit_depth, 8 | 16),
+        };
+        (valid_depth && header.width > 0 && header.height > 0).then_some(header)
+    }
+
+    /// Sample `index` of a scanline, scaled to 8 bits
+    fn sample(&self, line: &[u8], index: usize) -> u8 {
+        match self.bit_depth {
+            8 => line[index],
+            16 => line[index * 2],
+            bits => {
+                let bits = bits as usize;
+                let byte = line[index * bits / 8];
+                let shift = 8 - bits - (index * bits) % 8;
+                let value = (byte >> shift) & ((1 << bits) - 1);
+                if self.color_type == 3 {
+                    value
+                } else {
+                    // Stretch gray levels over 0..=255
+                    value * (255 / ((1u8 << bits) - 1))
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, line: &[u8], x: usize, palette: &[[u8; 3]], trns: &[u8]) -> [u8; 4] {
+        match self.color_type {
+            0 => {
+                let v = self.sample(line, x);
+                let transparent = trns.len() >= 2 && self.bit_depth == 8 && trns[1] == v;
+                [v, v, v, if transparent { 0 } else { 255 }]
+            }
+            2 => [
+                self.sample(line, x * 3),
+                self.sample(line, x * 3 + 1),
+                self.sample(line, x * 3 + 2),
+                255,
+            ],
+            3 => {
+                let i = self.sample(line, x) as usize;
+                let [r, g, b] = palette.get(i).copied().unwrap_or_default();
+                [r, g, b, trns.get(i).copied().unwrap_or(255)]
+            }
+            4 => {
+                let v = self.sample(line, x * 2);
+                [v, v, v, self.sample(line, x * 2 + 1)]
+            }
+            _ => [
+                self.sample(line, x * 4),
+                self.sample(line, x * 4 + 1),
+                self.sample(line, x * 4 + 2),
+                self.sample(line, x * 4 + 3),
+            ],
+        }
+    }
+}
+
+/// Undo a scanline filter in place
+fn unfilter(filter: u8, line: &mut [u8], previous: &[u8], step: usize) -> Option<()> {
+    for i in 0..line.len() {
+        let left = if i >= step { line[i - step] } else { 0 };
+        let up = previous[i];
+        let up_left = if i >= step { previous[i - step] } else { 0 };
+        let predicted = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return None,
+        };
+        line[i] = line[i].wrapping_add(predicted);
+    }
+    Some(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+
+    use super::*;
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], body: &[u8]) {
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        // The decoder does not check CRCs
+        out.extend_from_slice(&[0; 4]);
+    }
+
+    /// Build a PNG from already-filtered scanlines
+    pub(in crate::graphics) fn encode(
+        width: u32,
+        height: u32,
+        color_type: u8,
+        bit_depth: u8,
+        scanlines: &[u8],
+        extra: &[(&[u8], &[u8])],
+    ) -> Vec<u8> {
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(scanlines).unwrap();
+
+        let mut png = SIGNATURE.to_vec();
+        chunk(&mut png, b"IHDR", &ihdr);
+        for (kind, body) in extra {
+            chunk(&mut png, kind, body);
+        }
+        chunk(&mut png, b"IDAT", &zlib.finish().unwrap());
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    #[test]
+    fn test_decode_rgba_with_filters() {
+        // Row 0 unfiltered, row 1 "up" filtered (identical to row 0)
+        let scanlines = [
+            0, 255, 0, 0, 255, 0, 0, 255, 128, //
+            2, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let pixels = decode(&encode(2, 2, 6, 8, &scanlines, &[])).unwrap();
+
+        assert_eq!((pixels.width, pixels.height), (2, 2));
+        assert_eq!(pixels.rgba[..8], [255, 0, 0, 255, 0, 0, 255, 128]);
+        assert_eq!(pixels.rgba[8..], pixels.rgba[..8]);
+    }
+
+    #[test]
+    fn test_decode_palette() {
+        // 4 pixels at 2 bits: indices 0, 1, 1, 0
+        let scanlines = [0, 0b0001_0100];
+        let png = encode(
+            4,
+            1,
+            3,
+            2,
+            &scanlines,
+            &[(b"PLTE", &[10, 20, 30, 200, 100, 50]), (b"tRNS", &[0])],
+        );
+        let pixels = decode(&png).unwrap();
+
+        assert_eq!(pixels.rgba[..4], [10, 20, 30, 0]);
+        assert_eq!(pixels.rgba[4..8], [200, 100, 50, 255]);
+        assert_eq!(pixels.rgba[12..], [10, 20, 30, 0]);
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert!(decode(b"GIF89a").is_none());
+        let mut png = encode(1, 1, 2, 8, &[0, 1, 2, 3], &[]);
+        png.truncate(40);
+        assert!(decode(&png).is_none());
+
+        // Image data that inflates past what the header promises
+        let png = encode(1, 1, 2, 8, &[0; 1 << 20], &[]);
+        assert!(png.len() < 4096);
+        assert!(decode(&png).is_none());
+    }
+}
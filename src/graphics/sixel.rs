@@ -0,0 +1,124 @@
+//! Sixel encoder
+//!
+//! Colours are quantized to a fixed 6×6×6 cube, which keeps encoding cheap
+//! and is plenty for screenshots at terminal resolution. Mostly transparent
+//! pixels are left unpainted.
+
+use std::fmt::Write;
+
+use super::Pixels;
+
+/// Levels per channel in the colour cube
+const LEVELS: u32 = 6;
+
+/// Encode pixels as a sixel image, drawn at the cursor
+pub fn encode(pixels: &Pixels) -> String {
+    let (width, height) = (pixels.width as usize, pixels.height as usize);
+    let index: Vec<Option<u8>> = pixels
+        .rgba
+        .chunks_exact(4)
+        .map(|p| (p[3] >= 128).then(|| quantize(p[0], p[1], p[2])))
+        .collect();
+
+    // P2=1: pixels that are not painted keep the terminal background
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    let mut used = [false; (LEVELS * LEVELS * LEVELS) as usize];
+    for i in index.iter().flatten() {
+        used[*i as usize] = true;
+    }
+    for (i, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        let [r, g, b] = cube_rgb(i as u32).map(|c| u32::from(c) * 100 / 255);
+        let _ = write!(out, "#{};2;{};{};{}", i, r, g, b);
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut in_band = [false; (LEVELS * LEVELS * LEVELS) as usize];
+        for y in rows.clone() {
+            for i in index[y * width..(y + 1) * width].iter().flatten() {
+                in_band[*i as usize] = true;
+            }
+        }
+        for (color, _) in in_band.iter().enumerate().filter(|(_, used)| **used) {
+            let _ = write!(out, "#{}", color);
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let mut bits = 0u8;
+                for (dy, y) in rows.clone().enumerate() {
+                    if index[y * width + x] == Some(color as u8) {
+                        bits |= 1 << dy;
+                    }
+                }
+                let c = 63 + bits;
+                run = match run {
+                    Some((prev, n)) if prev == c => Some((prev, n + 1)),
+                    Some((prev, n)) => {
+                        push_run(&mut out, prev, n);
+                        Some((c, 1))
+                    }
+                    None => Some((c, 1)),
+                };
+            }
+            if let Some((c, n)) = run {
+                push_run(&mut out, c, n);
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_run(out: &mut String, c: u8, n: usize) {
+    if n > 3 {
+        let _ = write!(out, "!{}{}", n, c as char);
+    } else {
+        for _ in 0..n {
+            out.push(c as char);
+        }
+    }
+}
+
+fn quantize(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| (u32::from(c) * (LEVELS - 1) + 127) / 255;
+    (level(r) * LEVELS * LEVELS + level(g) * LEVELS + level(b)) as u8
+}
+
+fn cube_rgb(index: u32) -> [u8; 3] {
+    let scale = |level: u32| (level * 255 / (LEVELS - 1)) as u8;
+    [
+        scale(index / (LEVELS * LEVELS)),
+        scale(index / LEVELS % LEVELS),
+        scale(index % LEVELS),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        // 5×2: red run, then a transparent pixel
+        let mut rgba = Vec::new();
+        for _ in 0..2 {
+            for _ in 0..4 {
+                rgba.extend_from_slice(&[255, 0, 0, 255]);
+            }
+            rgba.extend_from_slice(&[0, 0, 0, 0]);
+        }
+        let sixel = encode(&Pixels {
+            width: 5,
+            height: 2,
+            rgba,
+        });
+
+        let red = quantize(255, 0, 0);
+        assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;5;2"));
+        assert!(sixel.contains(&format!("#{};2;100;0;0", red)));
+        // Rows 0 and 1 set = 0b11 -> '?' + 3 = 'B'; the empty column is '?'
+        assert!(sixel.contains(&format!("#{}!4B?$-", red)));
+        assert!(sixel.ends_with("\x1b\\"));
+    }
+}
@@ -24,6 +24,16 @@ fn handle_key(key: KeyEvent, state: &AppState, bindings: &KeyBindings) -> InputR
         return handle_polish_preview_input(key);
     }
 
+    // Check if a piped command's output is shown
+    if is_pipe_output_mode(state) {
+        return handle_pipe_output_input(key, bindings);
+    }
+
+    // Check if we're typing a command to pipe the message to
+    if is_pipe_prompt_mode(state) {
+        return handle_pipe_prompt_input(key);
+    }
+
     // Check if we're in add account wizard mode
     if is_add_account_mode(state) {
         return handle_add_account_input(key, state);
@@ -39,21 +49,65 @@ fn handle_key(key: KeyEvent, state: &AppState, bindings: &KeyBindings) -> InputR
         return handle_contacts_input(key, bindings);
     }
 
+    // Check if we're in the send-later queue view
+    if is_scheduled_mode(state) {
+        return handle_scheduled_input(key, bindings);
+    }
+
+    // Check if we're picking a link by number in the reader
+    if is_link_hint_mode(state) {
+        return handle_link_hint_input(key);
+    }
+
     // Check if we're in attachment view (reader with attachments focused)
     if is_attachment_mode(state) {
         return handle_attachment_input(key, bindings);
     }
 
+    // Check if we're reading the raw source
+    if is_source_mode(state) {
+        return handle_source_input(key, state, bindings);
+    }
+
+    // Check if we're looking at the message's images
+    if is_image_mode(state) {
+        return handle_image_input(key, bindings);
+    }
+
     // Check if we're in help mode
     if is_help_mode(state) {
         return handle_help_input(key, bindings);
     }
 
+    // Check if we're reading a whole thread stacked in the reader
+    if is_conversation_mode(state) {
+        return handle_conversation_input(key, bindings);
+    }
+
+    // Check if we're searching within the open message
+    if is_body_find_mode(state) {
+        return handle_body_find_input(key, state, bindings);
+    }
+
     // Check if folder sidebar is focused
     if is_folder_sidebar_focused(state) {
         return handle_folder_sidebar_input(key, bindings);
     }
 
+    // Check if the send-later prompt is open in composer
+    if is_schedule_prompt_mode(state) {
+        return handle_schedule_prompt_input(key);
+    }
+
+    // Check if the From selector is open in composer
+    if is_from_picker_mode(state) {
+        return handle_from_picker_input(key, bindings);
+    }
+
+    if is_template_picker_mode(state) {
+        return handle_template_picker_input(key, bindings);
+    }
+
     // Check if autocomplete is visible in composer
     if is_autocomplete_mode(state) {
         return handle_autocomplete_input(key, bindings);
@@ -163,6 +217,12 @@ fn handle_text_input(key: KeyEvent, state: &AppState, bindings: &KeyBindings) ->
             | Action::NextField
             | Action::PrevField
             | Action::CycleSendAccount
+            | Action::ToggleMarkdown
+            | Action::ToggleMarkdownPreview
+            | Action::SelectFrom
+            | Action::SendLater
+            | Action::SelectTemplate
+            | Action::CycleProtection
             | Action::Polish => {
                 return InputResult::Action(action);
             }
@@ -223,12 +283,27 @@ fn is_contacts_mode(state: &AppState) -> bool {
     matches!(state.view, View::Contacts)
 }
 
+fn is_link_hint_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Reader { .. }) && state.reader.link_hint.is_some()
+}
+
+fn handle_link_hint_input(key: KeyEvent) -> InputResult {
+    // In link-hint mode: digits type the label, Enter follows it, Esc cancels
+    match key.code {
+        KeyCode::Char(c) if c.is_ascii_digit() => InputResult::Char(c),
+        KeyCode::Backspace => InputResult::Backspace,
+        KeyCode::Enter => InputResult::Action(Action::FollowLink),
+        KeyCode::Esc => InputResult::Action(Action::CancelLinkHint),
+        _ => InputResult::Continue,
+    }
+}
+
 fn is_attachment_mode(state: &AppState) -> bool {
     matches!(state.view, View::Reader { .. }) && state.reader.show_attachments
 }
 
 fn handle_attachment_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
-    // In attachment view: j/k navigate, Enter opens, s saves, A/Esc exits
+    // In attachment view: j/k navigate, Enter opens, s saves, i imports vCard, A/Esc exits
     if let Some(action) = bindings.get(&key) {
         match action {
             Action::Up | Action::Down => return InputResult::Action(action),
@@ -239,18 +314,182 @@ fn handle_attachment_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult
 
     match key.code {
         KeyCode::Char('s') => InputResult::Action(Action::SaveAttachment),
+        KeyCode::Char('i') => InputResult::Action(Action::ImportVcard),
         KeyCode::Enter => InputResult::Action(Action::OpenAttachment),
         KeyCode::Esc => InputResult::Action(Action::ToggleAttachments), // Close attachment view
         _ => InputResult::Continue,
     }
 }
 
+fn is_pipe_output_mode(state: &AppState) -> bool {
+    state.pipe.output.is_some()
+}
+
+fn handle_pipe_output_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In the output popup: j/k scroll, Esc/q/Enter close
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up | Action::Down | Action::PageUp | Action::PageDown => {
+                return InputResult::Action(action);
+            }
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+            InputResult::Action(Action::ClosePipeOutput)
+        }
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_pipe_prompt_mode(state: &AppState) -> bool {
+    state.pipe.prompt.is_some()
+}
+
+fn handle_pipe_prompt_input(key: KeyEvent) -> InputResult {
+    // Pipe prompt: text input, Tab switches the input, Enter runs, Esc cancels
+    match key.code {
+        KeyCode::Char(c) => InputResult::Char(c),
+        KeyCode::Backspace => InputResult::Backspace,
+        KeyCode::Tab => InputResult::Action(Action::PipeMessage),
+        KeyCode::Enter => InputResult::Action(Action::SubmitPrompt),
+        KeyCode::Esc => InputResult::Action(Action::CancelPrompt),
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_source_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Reader { .. }) && state.reader.source.is_some()
+}
+
+fn handle_source_input(key: KeyEvent, state: &AppState, bindings: &KeyBindings) -> InputResult {
+    // While typing a search term: text, Enter searches, Esc cancels
+    if state
+        .reader
+        .source
+        .as_ref()
+        .is_some_and(|s| s.find_input.is_some())
+    {
+        return match key.code {
+            KeyCode::Char(c) => InputResult::Char(c),
+            KeyCode::Backspace => InputResult::Backspace,
+            KeyCode::Enter => InputResult::Action(Action::SubmitPrompt),
+            KeyCode::Esc => InputResult::Action(Action::CancelPrompt),
+            _ => InputResult::Continue,
+        };
+    }
+
+    // In source view: scroll, Tab/H header table, / search, n/N matches,
+    // w save, | pipe, V/Esc exits
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up
+            | Action::Down
+            | Action::PageUp
+            | Action::PageDown
+            | Action::Top
+            | Action::Bottom
+            | Action::ToggleSource
+            | Action::ToggleHeaderExpand => return InputResult::Action(action),
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Tab => InputResult::Action(Action::ToggleHeaderExpand),
+        KeyCode::Char('/') => InputResult::Action(Action::Find),
+        KeyCode::Char('n') => InputResult::Action(Action::FindNext),
+        KeyCode::Char('N') => InputResult::Action(Action::FindPrev),
+        KeyCode::Char('w') => InputResult::Action(Action::SaveEml),
+        KeyCode::Char('|') => InputResult::Action(Action::PipeMessage),
+        KeyCode::Esc => InputResult::Action(Action::ToggleSource),
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_conversation_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Reader { .. }) && state.reader.conversation_shown()
+}
+
+fn handle_conversation_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In the conversation: Tab/Shift+Tab move between messages, Enter or
+    // Space expands the selected one, the rest as usual
+    match key.code {
+        KeyCode::Tab => InputResult::Action(Action::ConversationNext),
+        KeyCode::BackTab => InputResult::Action(Action::ConversationPrev),
+        KeyCode::Enter | KeyCode::Char(' ') => InputResult::Action(Action::ToggleMessage),
+        _ => match bindings.get(&key) {
+            Some(Action::Quit) => InputResult::Quit,
+            Some(action) => InputResult::Action(action),
+            None => InputResult::Continue,
+        },
+    }
+}
+
+fn is_body_find_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Reader { .. })
+        && (state.reader.find.input.is_some() || state.reader.find_has_matches())
+}
+
+fn handle_body_find_input(key: KeyEvent, state: &AppState, bindings: &KeyBindings) -> InputResult {
+    // While typing a search term: text, Enter searches, Esc cancels
+    if state.reader.find.input.is_some() {
+        return match key.code {
+            KeyCode::Char(c) => InputResult::Char(c),
+            KeyCode::Backspace => InputResult::Backspace,
+            KeyCode::Enter => InputResult::Action(Action::SubmitPrompt),
+            KeyCode::Esc => InputResult::Action(Action::CancelPrompt),
+            _ => InputResult::Continue,
+        };
+    }
+
+    // Matches highlighted: n/N jump between them, Esc clears, the rest as
+    // usual. Shift+N still declines an invitation, and Esc leaves a message
+    // opened from search in one press.
+    match key.code {
+        KeyCode::Char('n') => InputResult::Action(Action::FindNext),
+        KeyCode::Char('N') if state.reader.invite.is_none() => {
+            InputResult::Action(Action::FindPrev)
+        }
+        KeyCode::Esc if !state.reader.find.seeded => InputResult::Action(Action::CancelPrompt),
+        _ => match bindings.get(&key) {
+            Some(Action::Quit) => InputResult::Quit,
+            Some(action) => InputResult::Action(action),
+            None => InputResult::Continue,
+        },
+    }
+}
+
+fn is_image_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Reader { .. }) && state.reader.show_images
+}
+
+fn handle_image_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In image view: j/k switch images, Enter opens, I/Esc exits
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up | Action::Down | Action::ToggleImages => {
+                return InputResult::Action(action);
+            }
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Enter => InputResult::Action(Action::OpenImage),
+        KeyCode::Esc => InputResult::Action(Action::ToggleImages),
+        _ => InputResult::Continue,
+    }
+}
+
 fn is_contacts_edit_mode(state: &AppState) -> bool {
     matches!(state.view, View::Contacts) && state.contacts.editing.is_some()
 }
 
 fn handle_contacts_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
-    // In contacts view: j/k navigate, e edit, d delete, Enter compose, Esc back
+    // In contacts view: j/k navigate, e edit, d delete, Space mark, x export, Enter compose, Esc back
     if let Some(action) = bindings.get(&key) {
         match action {
             Action::Up | Action::Down => return InputResult::Action(action),
@@ -261,6 +500,8 @@ fn handle_contacts_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
 
     match key.code {
         KeyCode::Char('e') => InputResult::Action(Action::EditContact),
+        KeyCode::Char(' ') => InputResult::Action(Action::MarkContact),
+        KeyCode::Char('x') => InputResult::Action(Action::ExportContacts),
         KeyCode::Char('d') => InputResult::Action(Action::Delete),
         KeyCode::Enter => InputResult::Action(Action::Open),
         KeyCode::Esc | KeyCode::Char('q') => InputResult::Action(Action::Back),
@@ -269,16 +510,102 @@ fn handle_contacts_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
 }
 
 fn handle_contacts_edit_input(key: KeyEvent) -> InputResult {
-    // Text input for contact name editing
+    // Text input for contact editing, Tab/arrows switch fields
     match key.code {
         KeyCode::Char(c) => InputResult::Char(c),
         KeyCode::Backspace => InputResult::Backspace,
+        KeyCode::Tab | KeyCode::Down => InputResult::Action(Action::NextField),
+        KeyCode::BackTab | KeyCode::Up => InputResult::Action(Action::PrevField),
         KeyCode::Enter => InputResult::Action(Action::Open), // Save
         KeyCode::Esc => InputResult::Action(Action::Back),   // Cancel
         _ => InputResult::Continue,
     }
 }
 
+fn is_scheduled_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Scheduled)
+}
+
+fn handle_scheduled_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In send-later queue: j/k navigate, Enter edits, d/Delete cancels, Esc/q goes back
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up | Action::Down | Action::Delete => return InputResult::Action(action),
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Char('d') => InputResult::Action(Action::Delete),
+        KeyCode::Enter => InputResult::Action(Action::Open),
+        KeyCode::Esc | KeyCode::Char('q') => InputResult::Action(Action::Back),
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_schedule_prompt_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Composer { .. }) && state.composer.schedule_input.is_some()
+}
+
+fn handle_schedule_prompt_input(key: KeyEvent) -> InputResult {
+    // Send-later time prompt: text input, Enter schedules, Esc closes
+    match key.code {
+        KeyCode::Char(c) => InputResult::Char(c),
+        KeyCode::Backspace => InputResult::Backspace,
+        KeyCode::Enter => InputResult::Action(Action::ConfirmSchedule),
+        KeyCode::Esc => InputResult::Action(Action::CancelSchedule),
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_from_picker_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Composer { .. }) && state.composer.from_picker.is_some()
+}
+
+fn handle_from_picker_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In From selector: Up/Down navigate, Enter selects, Esc closes
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up => return InputResult::Action(Action::FromPickerUp),
+            Action::Down => return InputResult::Action(Action::FromPickerDown),
+            Action::SelectFrom => return InputResult::Action(Action::FromPickerClose),
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Up => InputResult::Action(Action::FromPickerUp),
+        KeyCode::Down => InputResult::Action(Action::FromPickerDown),
+        KeyCode::Enter | KeyCode::Tab => InputResult::Action(Action::FromPickerSelect),
+        KeyCode::Esc => InputResult::Action(Action::FromPickerClose),
+        _ => InputResult::Continue,
+    }
+}
+
+fn is_template_picker_mode(state: &AppState) -> bool {
+    matches!(state.view, View::Composer { .. }) && state.composer.template_picker.is_some()
+}
+
+fn handle_template_picker_input(key: KeyEvent, bindings: &KeyBindings) -> InputResult {
+    // In template picker: Up/Down navigate, Enter inserts, Esc closes
+    if let Some(action) = bindings.get(&key) {
+        match action {
+            Action::Up => return InputResult::Action(Action::TemplatePickerUp),
+            Action::Down => return InputResult::Action(Action::TemplatePickerDown),
+            Action::SelectTemplate => return InputResult::Action(Action::TemplatePickerClose),
+            _ => {}
+        }
+    }
+
+    match key.code {
+        KeyCode::Up => InputResult::Action(Action::TemplatePickerUp),
+        KeyCode::Down => InputResult::Action(Action::TemplatePickerDown),
+        KeyCode::Enter | KeyCode::Tab => InputResult::Action(Action::TemplatePickerSelect),
+        KeyCode::Esc => InputResult::Action(Action::TemplatePickerClose),
+        _ => InputResult::Continue,
+    }
+}
+
 fn is_autocomplete_mode(state: &AppState) -> bool {
     if let View::Composer { field, .. } = state.view {
         use crate::app::state::ComposerField;
@@ -367,7 +694,9 @@ fn handle_add_account_input(key: KeyEvent, state: &AppState) -> InputResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::state::Conversation;
     use crate::config::KeybindingMode;
+    use crate::mail::types::EmailBody;
 
     #[test]
     fn test_quit_action() {
@@ -379,4 +708,106 @@ mod tests {
 
         assert!(matches!(result, InputResult::Quit));
     }
+
+    #[test]
+    fn test_body_find_keys() {
+        let bindings = KeyBindings::new(&KeybindingMode::Vim);
+        let mut state = AppState {
+            view: View::Reader { uid: 1 },
+            ..Default::default()
+        };
+        let press = |c| KeyEvent::new(KeyCode::Char(c), crossterm::event::KeyModifiers::NONE);
+
+        // Typing the term takes every character
+        state.reader.find.input = Some(String::new());
+        let result = handle_key(press('n'), &state, &bindings);
+        assert!(matches!(result, InputResult::Char('n')));
+
+        // With a term highlighted, n/N jump and other keys act as usual
+        state.reader.set_body(Some(EmailBody {
+            text: Some("Your invoice is attached".to_string()),
+            ..Default::default()
+        }));
+        state.reader.find.input = None;
+        state.reader.find.query = "invoice".to_string();
+        let result = handle_key(press('n'), &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::FindNext)));
+        let result = handle_key(press('N'), &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::FindPrev)));
+        let result = handle_key(press('q'), &state, &bindings);
+        assert!(matches!(result, InputResult::Quit));
+        let esc = KeyEvent::new(KeyCode::Esc, crossterm::event::KeyModifiers::NONE);
+        let result = handle_key(esc, &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::CancelPrompt)));
+
+        // A term seeded from the inbox search: Esc goes straight back
+        state.reader.find.seeded = true;
+        let result = handle_key(esc, &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::Back)));
+
+        // Nothing matches: n is an ordinary key again
+        state.reader.find.query = "receipt".to_string();
+        assert!(!is_body_find_mode(&state));
+        let result = handle_key(press('n'), &state, &bindings);
+        assert!(!matches!(result, InputResult::Action(Action::FindNext)));
+        let result = handle_key(esc, &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::Back)));
+
+        // A match hidden in a folded quote still counts
+        state.reader.set_body(Some(EmailBody {
+            text: Some("Fine.\nOn Mon, Alice wrote:\n> the receipt\n> is here\n".to_string()),
+            ..Default::default()
+        }));
+        assert!(is_body_find_mode(&state));
+
+        // Shift+N still answers an invitation
+        state.reader.set_body(Some(EmailBody {
+            text: Some("Invoice review".to_string()),
+            calendar: Some(
+                "BEGIN:VCALENDAR\nMETHOD:REQUEST\nBEGIN:VEVENT\nUID:1\n\
+                 DTSTART:20240301T090000Z\nEND:VEVENT\nEND:VCALENDAR\n"
+                    .to_string(),
+            ),
+            ..Default::default()
+        }));
+        state.reader.find.query = "invoice".to_string();
+        let result = handle_key(press('N'), &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::DeclineInvite)));
+    }
+
+    #[test]
+    fn test_conversation_keys() {
+        let bindings = KeyBindings::new(&KeybindingMode::Vim);
+        let mut state = AppState {
+            view: View::Reader { uid: 1 },
+            ..Default::default()
+        };
+        state.reader.conversation = Some(Conversation {
+            messages: Vec::new(),
+            selected: 0,
+            scroll: 0,
+        });
+        let press = |code| KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+
+        let result = handle_key(press(KeyCode::Tab), &state, &bindings);
+        assert!(matches!(
+            result,
+            InputResult::Action(Action::ConversationNext)
+        ));
+        let result = handle_key(press(KeyCode::Enter), &state, &bindings);
+        assert!(matches!(result, InputResult::Action(Action::ToggleMessage)));
+        let result = handle_key(press(KeyCode::Char('t')), &state, &bindings);
+        assert!(matches!(
+            result,
+            InputResult::Action(Action::ToggleThreadStack)
+        ));
+
+        // A summary shown in its place takes the keys back
+        state.reader.show_summary = true;
+        let result = handle_key(press(KeyCode::Enter), &state, &bindings);
+        assert!(!matches!(
+            result,
+            InputResult::Action(Action::ToggleMessage)
+        ));
+    }
 }
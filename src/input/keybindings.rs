@@ -1,7 +1,7 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
 
-use crate::config::KeybindingMode;
+use crate::config::{KeybindingMode, PipeCommandConfig};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Action {
@@ -26,6 +26,7 @@ pub enum Action {
     Reply,
     ReplyAll,
     Forward,
+    ForwardAsAttachment,
     Compose,
     Delete,
     ToggleRead,
@@ -36,9 +37,21 @@ pub enum Action {
     Search,
     ToggleFolderSidebar,
 
+    // Mailing lists
+    Unsubscribe,
+    ReplyToList,
+    ViewLists,
+
+    // Calendar invitations
+    AcceptInvite,
+    TentativeInvite,
+    DeclineInvite,
+
     // Contacts
     OpenContacts,
     EditContact,
+    MarkContact,    // Mark contact for export
+    ExportContacts, // Export marked (or selected) contacts as vCard
 
     // Command mode
     Command,
@@ -53,6 +66,28 @@ pub enum Action {
     Send,
     Cancel,
     CycleSendAccount,
+    ToggleMarkdown,        // Toggle Markdown (text + HTML) sending
+    ToggleMarkdownPreview, // Toggle rendered Markdown preview
+    SelectFrom,            // Open From (account/identity) selector
+    SendLater,             // Open the send-later time prompt
+    SelectTemplate,        // Open the template picker
+    CycleProtection,       // Cycle OpenPGP signing/encryption
+
+    // Send-later prompt (composer)
+    ConfirmSchedule,
+    CancelSchedule,
+
+    // From selector (composer)
+    FromPickerUp,
+    FromPickerDown,
+    FromPickerSelect,
+    FromPickerClose,
+
+    // Template picker (composer)
+    TemplatePickerUp,
+    TemplatePickerDown,
+    TemplatePickerSelect,
+    TemplatePickerClose,
 
     // Autocomplete (composer)
     AutocompleteUp,
@@ -83,15 +118,48 @@ pub enum Action {
 
     // View modes
     ToggleConversationMode, // Toggle conversation view (show sent emails in threads)
+    ToggleHtml,             // Switch between the plain and HTML parts of a message
+    ToggleQuotes,           // Fold or unfold quoted text and signatures in the reader
+    CycleSort,              // Switch the list to the next sort order
+
+    // Conversation reader
+    ToggleThreadStack, // Stack every message of the thread in the reader
+    ConversationNext,  // Jump to the next message in the stack
+    ConversationPrev,  // Jump to the previous message in the stack
+    ToggleMessage,     // Expand or collapse the selected message in the stack
 
     // Attachments
     ToggleAttachments, // Toggle attachment list focus in reader
     SaveAttachment,    // Save selected attachment to disk
     OpenAttachment,    // Open selected attachment with system app
+    ImportVcard,       // Import selected vCard attachment into contacts
+    ToggleImages,      // Show the message's images in place of the body
+    OpenImage,         // Open the shown image with the system viewer
+
+    // Raw source
+    ToggleSource, // Show the raw message source and full headers
+    Find,         // Search the message or its source
+    FindNext,     // Jump to the next match
+    FindPrev,     // Jump to the previous match
+    SaveEml,      // Save the raw message as .eml
+    PipeMessage,  // Pipe the message (or thread) to a shell command
+    SubmitPrompt, // Run the search or command being typed
+    CancelPrompt, // Leave the search or command prompt
+
+    // Pipe
+    RunPipe(usize),  // Run the configured pipe command with this index
+    ClosePipeOutput, // Close the piped command's output popup
+
+    // Links
+    FollowLink,     // Pick a link in the reader by number and open it
+    CopyLink,       // Pick a link in the reader by number and copy it
+    CancelLinkHint, // Leave link-hint mode
 }
 
 pub struct KeyBindings {
     bindings: HashMap<KeyEvent, Action>,
+    /// Names of the configured pipe commands, by `RunPipe` index
+    pipe_names: Vec<String>,
 }
 
 /// A displayable keybinding entry
@@ -108,13 +176,50 @@ impl KeyBindings {
             KeybindingMode::Vim => Self::vim_bindings(),
             KeybindingMode::Arrows => Self::arrow_bindings(),
         };
-        Self { bindings }
+        Self {
+            bindings,
+            pipe_names: Vec::new(),
+        }
+    }
+
+    /// Bind the configured pipe commands that have a key (overriding
+    /// built-in bindings of the same key)
+    pub fn bind_pipe_commands(&mut self, commands: &[PipeCommandConfig]) {
+        self.pipe_names = commands.iter().map(|c| c.name.clone()).collect();
+        for (index, command) in commands.iter().enumerate() {
+            let Some(ref spec) = command.key else {
+                continue;
+            };
+            let Some(event) = parse_key(spec) else {
+                tracing::warn!("Pipe command {:?}: unknown key {:?}", command.name, spec);
+                continue;
+            };
+            self.bindings.insert(event, Action::RunPipe(index));
+            // Some terminals send uppercase letters without SHIFT
+            if let KeyCode::Char(c) = event.code
+                && event.modifiers == KeyModifiers::SHIFT
+            {
+                self.bindings.insert(
+                    KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+                    Action::RunPipe(index),
+                );
+            }
+        }
     }
 
     pub fn get(&self, event: &KeyEvent) -> Option<Action> {
         self.bindings.get(event).copied()
     }
 
+    /// Display string for the key bound to an action (the shortest if there are several)
+    pub fn key_for(&self, action: Action) -> Option<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(event, _)| format_key_event(event))
+            .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+    }
+
     /// Get all keybindings as displayable entries grouped by category
     pub fn all_bindings(&self) -> Vec<KeybindingEntry> {
         let mut entries: Vec<_> = self
@@ -122,7 +227,15 @@ impl KeyBindings {
             .iter()
             .map(|(event, action)| KeybindingEntry {
                 key: format_key_event(event),
-                description: action_description(action),
+                description: match action {
+                    Action::RunPipe(index) => format!(
+                        "Pipe to {}",
+                        self.pipe_names
+                            .get(*index)
+                            .map_or("command", String::as_str)
+                    ),
+                    _ => action_description(action),
+                },
                 category: action_category(action),
             })
             .collect();
@@ -159,6 +272,7 @@ impl KeyBindings {
         map.insert(key('r'), Action::Reply);
         map.insert(key('a'), Action::ReplyAll);
         map.insert(key('f'), Action::Forward);
+        map.insert(shift_key('F'), Action::ForwardAsAttachment);
         map.insert(key('c'), Action::Compose);
         map.insert(key('d'), Action::Delete);
         map.insert(key('m'), Action::ToggleRead);
@@ -170,6 +284,17 @@ impl KeyBindings {
         map.insert(key('/'), Action::Search);
         map.insert(key('b'), Action::ToggleFolderSidebar);
         map.insert(key(':'), Action::Command);
+        map.insert(key('|'), Action::PipeMessage);
+
+        // Mailing lists
+        map.insert(shift_key('U'), Action::Unsubscribe);
+        map.insert(shift_key('L'), Action::ReplyToList);
+        map.insert(shift_key('M'), Action::ViewLists);
+
+        // Calendar invitations
+        map.insert(shift_key('Y'), Action::AcceptInvite);
+        map.insert(shift_key('E'), Action::TentativeInvite);
+        map.insert(shift_key('N'), Action::DeclineInvite);
 
         // Account switching (] = next, [ = prev)
         map.insert(key(']'), Action::NextAccount);
@@ -182,6 +307,12 @@ impl KeyBindings {
         map.insert(shift_key_code(KeyCode::BackTab), Action::PrevField);
         map.insert(ctrl_key('s'), Action::Send);
         map.insert(ctrl_key('a'), Action::CycleSendAccount);
+        map.insert(ctrl_key('e'), Action::ToggleMarkdown);
+        map.insert(ctrl_key('o'), Action::ToggleMarkdownPreview);
+        map.insert(ctrl_key('l'), Action::SelectFrom);
+        map.insert(ctrl_key('w'), Action::SendLater);
+        map.insert(ctrl_key('g'), Action::SelectTemplate);
+        map.insert(ctrl_key('k'), Action::CycleProtection);
 
         // Undo
         map.insert(key('u'), Action::Undo);
@@ -193,6 +324,14 @@ impl KeyBindings {
 
         // Preview
         map.insert(shift_key('H'), Action::ToggleHeaderExpand);
+        map.insert(key('v'), Action::ToggleHtml);
+        map.insert(key('z'), Action::ToggleQuotes);
+        map.insert(key('t'), Action::ToggleThreadStack);
+        map.insert(shift_key('V'), Action::ToggleSource);
+        map.insert(
+            KeyEvent::new(KeyCode::Char('V'), KeyModifiers::NONE),
+            Action::ToggleSource,
+        );
 
         // Help
         map.insert(key('.'), Action::Help);
@@ -204,6 +343,11 @@ impl KeyBindings {
             KeyEvent::new(KeyCode::Char('C'), KeyModifiers::NONE),
             Action::ToggleConversationMode,
         );
+        map.insert(shift_key('W'), Action::CycleSort);
+        map.insert(
+            KeyEvent::new(KeyCode::Char('W'), KeyModifiers::NONE),
+            Action::CycleSort,
+        );
 
         // Attachments (Reader view)
         // Note: Some terminals send uppercase 'A' without SHIFT modifier, so we handle both
@@ -212,6 +356,19 @@ impl KeyBindings {
             KeyEvent::new(KeyCode::Char('A'), KeyModifiers::NONE),
             Action::ToggleAttachments,
         );
+        map.insert(shift_key('I'), Action::ToggleImages);
+        map.insert(
+            KeyEvent::new(KeyCode::Char('I'), KeyModifiers::NONE),
+            Action::ToggleImages,
+        );
+
+        // Links (Reader view)
+        map.insert(key('o'), Action::FollowLink);
+        map.insert(shift_key('O'), Action::CopyLink);
+        map.insert(
+            KeyEvent::new(KeyCode::Char('O'), KeyModifiers::NONE),
+            Action::CopyLink,
+        );
 
         map
     }
@@ -237,6 +394,7 @@ impl KeyBindings {
         map.insert(ctrl_key('r'), Action::Reply);
         map.insert(shift_key('A'), Action::ReplyAll);
         map.insert(ctrl_key('f'), Action::Forward);
+        map.insert(shift_key('F'), Action::ForwardAsAttachment);
         map.insert(ctrl_key('n'), Action::Compose);
         map.insert(key_code(KeyCode::Delete), Action::Delete);
         map.insert(ctrl_key('u'), Action::ToggleRead);
@@ -251,6 +409,17 @@ impl KeyBindings {
         map.insert(key('p'), Action::ToggleFolderSidebar);
         map.insert(key_code(KeyCode::F(2)), Action::ToggleFolderSidebar);
         map.insert(key(':'), Action::Command);
+        map.insert(key('|'), Action::PipeMessage);
+
+        // Mailing lists
+        map.insert(shift_key('U'), Action::Unsubscribe);
+        map.insert(shift_key('L'), Action::ReplyToList);
+        map.insert(shift_key('M'), Action::ViewLists);
+
+        // Calendar invitations
+        map.insert(shift_key('Y'), Action::AcceptInvite);
+        map.insert(shift_key('E'), Action::TentativeInvite);
+        map.insert(shift_key('N'), Action::DeclineInvite);
 
         // Account switching (] = next, [ = prev, or Ctrl+Right/Left)
         map.insert(key(']'), Action::NextAccount);
@@ -266,6 +435,12 @@ impl KeyBindings {
         map.insert(ctrl_key('s'), Action::Send);
         map.insert(ctrl_key('c'), Action::Cancel);
         map.insert(key_code(KeyCode::F(4)), Action::CycleSendAccount);
+        map.insert(ctrl_key('e'), Action::ToggleMarkdown);
+        map.insert(ctrl_key('o'), Action::ToggleMarkdownPreview);
+        map.insert(ctrl_key('l'), Action::SelectFrom);
+        map.insert(ctrl_key('w'), Action::SendLater);
+        map.insert(ctrl_key('g'), Action::SelectTemplate);
+        map.insert(ctrl_key('k'), Action::CycleProtection);
 
         // Undo
         map.insert(ctrl_key('z'), Action::Undo);
@@ -277,20 +452,88 @@ impl KeyBindings {
 
         // Preview
         map.insert(shift_key('H'), Action::ToggleHeaderExpand);
+        map.insert(key('v'), Action::ToggleHtml);
+        map.insert(key('z'), Action::ToggleQuotes);
+        map.insert(key('t'), Action::ToggleThreadStack);
+        map.insert(shift_key('V'), Action::ToggleSource);
 
         // Help
         map.insert(key('.'), Action::Help);
 
         // View modes
         map.insert(key_code(KeyCode::F(9)), Action::ToggleConversationMode);
+        map.insert(key_code(KeyCode::F(10)), Action::CycleSort);
 
         // Attachments (Reader view)
         map.insert(ctrl_key('a'), Action::ToggleAttachments);
+        map.insert(shift_key('I'), Action::ToggleImages);
+
+        // Links (Reader view)
+        map.insert(key('o'), Action::FollowLink);
+        map.insert(shift_key('O'), Action::CopyLink);
 
         map
     }
 }
 
+/// Parse a key from config, e.g. "ctrl+g", "alt+p", "P", "space" or "f10"
+pub fn parse_key(spec: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let parts: Vec<&str> = spec.split('+').collect();
+    // A trailing "+" is the plus key itself
+    let (name, prefixes) = match parts.as_slice() {
+        [rest @ .., "", ""] => ("+", rest),
+        [rest @ .., last] => (*last, rest),
+        [] => return None,
+    };
+    for modifier in prefixes {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+    }
+
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            if c.is_ascii_uppercase() {
+                modifiers |= KeyModifiers::SHIFT;
+            }
+            if modifiers.contains(KeyModifiers::SHIFT) && c.is_ascii_lowercase() {
+                KeyCode::Char(c.to_ascii_uppercase())
+            } else {
+                KeyCode::Char(c)
+            }
+        }
+        _ => match name.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "esc" | "escape" => KeyCode::Esc,
+            f => KeyCode::F(
+                f.strip_prefix('f')?
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=24).contains(n))?,
+            ),
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
 fn key(c: char) -> KeyEvent {
     KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
 }
@@ -372,6 +615,7 @@ fn action_description(action: &Action) -> String {
         Action::Reply => "Reply to email".to_string(),
         Action::ReplyAll => "Reply all".to_string(),
         Action::Forward => "Forward email".to_string(),
+        Action::ForwardAsAttachment => "Forward as attachment".to_string(),
         Action::Compose => "Compose new email".to_string(),
         Action::Delete => "Delete email".to_string(),
         Action::ToggleRead => "Toggle read/unread".to_string(),
@@ -381,8 +625,16 @@ fn action_description(action: &Action) -> String {
         Action::ToggleThread => "Toggle thread expansion".to_string(),
         Action::Search => "Search emails".to_string(),
         Action::ToggleFolderSidebar => "Toggle folder sidebar".to_string(),
+        Action::Unsubscribe => "Unsubscribe from mailing list".to_string(),
+        Action::ReplyToList => "Reply to mailing list".to_string(),
+        Action::ViewLists => "Group mailing lists".to_string(),
+        Action::AcceptInvite => "Accept invitation".to_string(),
+        Action::TentativeInvite => "Tentatively accept invitation".to_string(),
+        Action::DeclineInvite => "Decline invitation".to_string(),
         Action::OpenContacts => "Open contacts".to_string(),
         Action::EditContact => "Edit contact".to_string(),
+        Action::MarkContact => "Mark contact".to_string(),
+        Action::ExportContacts => "Export contacts as vCard".to_string(),
         Action::Command => "Enter command mode".to_string(),
         Action::ExecuteCommand => "Execute command".to_string(),
         Action::ConfirmCommand => "Confirm command".to_string(),
@@ -393,6 +645,22 @@ fn action_description(action: &Action) -> String {
         Action::Send => "Send email".to_string(),
         Action::Cancel => "Cancel".to_string(),
         Action::CycleSendAccount => "Cycle send account".to_string(),
+        Action::ToggleMarkdown => "Toggle Markdown".to_string(),
+        Action::ToggleMarkdownPreview => "Toggle Markdown preview".to_string(),
+        Action::SelectFrom => "Choose From identity".to_string(),
+        Action::SendLater => "Send later".to_string(),
+        Action::SelectTemplate => "Insert template".to_string(),
+        Action::CycleProtection => "Cycle signing/encryption".to_string(),
+        Action::ConfirmSchedule => "Send later: confirm".to_string(),
+        Action::CancelSchedule => "Send later: cancel".to_string(),
+        Action::FromPickerUp => "From selector: previous".to_string(),
+        Action::FromPickerDown => "From selector: next".to_string(),
+        Action::FromPickerSelect => "From selector: select".to_string(),
+        Action::FromPickerClose => "From selector: close".to_string(),
+        Action::TemplatePickerUp => "Template picker: previous".to_string(),
+        Action::TemplatePickerDown => "Template picker: next".to_string(),
+        Action::TemplatePickerSelect => "Template picker: insert".to_string(),
+        Action::TemplatePickerClose => "Template picker: close".to_string(),
         Action::AutocompleteUp => "Autocomplete: previous".to_string(),
         Action::AutocompleteDown => "Autocomplete: next".to_string(),
         Action::AutocompleteSelect => "Autocomplete: select".to_string(),
@@ -407,11 +675,34 @@ fn action_description(action: &Action) -> String {
         Action::AcceptPolish => "Accept polished text".to_string(),
         Action::RejectPolish => "Reject polished text".to_string(),
         Action::ToggleHeaderExpand => "Expand/collapse headers".to_string(),
+        Action::ToggleHtml => "Switch plain text / HTML part".to_string(),
+        Action::ToggleQuotes => "Fold / unfold quotes and signature".to_string(),
+        Action::ToggleThreadStack => "Read the whole thread / single message".to_string(),
+        Action::ConversationNext => "Next message in thread".to_string(),
+        Action::ConversationPrev => "Previous message in thread".to_string(),
+        Action::ToggleMessage => "Expand / collapse message".to_string(),
         Action::Help => "Toggle help".to_string(),
         Action::ToggleConversationMode => "Toggle conversation view".to_string(),
+        Action::CycleSort => "Cycle sort order".to_string(),
         Action::ToggleAttachments => "Toggle attachments list".to_string(),
         Action::SaveAttachment => "Save attachment".to_string(),
+        Action::ImportVcard => "Import vCard into contacts".to_string(),
         Action::OpenAttachment => "Open attachment".to_string(),
+        Action::ToggleImages => "Show images".to_string(),
+        Action::OpenImage => "Open image".to_string(),
+        Action::FollowLink => "Open link by number".to_string(),
+        Action::CopyLink => "Copy link by number".to_string(),
+        Action::CancelLinkHint => "Cancel link selection".to_string(),
+        Action::ToggleSource => "View raw source and headers".to_string(),
+        Action::Find => "Search the message".to_string(),
+        Action::FindNext => "Next match".to_string(),
+        Action::FindPrev => "Previous match".to_string(),
+        Action::SaveEml => "Save as .eml".to_string(),
+        Action::PipeMessage => "Pipe message to command".to_string(),
+        Action::SubmitPrompt => "Run search or command".to_string(),
+        Action::CancelPrompt => "Cancel prompt".to_string(),
+        Action::RunPipe(_) => "Run pipe command".to_string(),
+        Action::ClosePipeOutput => "Close command output".to_string(),
     }
 }
 
@@ -435,6 +726,7 @@ fn action_category(action: &Action) -> &'static str {
         | Action::Reply
         | Action::ReplyAll
         | Action::Forward
+        | Action::ForwardAsAttachment
         | Action::Compose
         | Action::Delete
         | Action::ToggleRead
@@ -446,9 +738,20 @@ fn action_category(action: &Action) -> &'static str {
         | Action::ToggleFolderSidebar
         | Action::Undo
         | Action::OpenContacts
-        | Action::ToggleHeaderExpand => "Actions",
+        | Action::ToggleHeaderExpand
+        | Action::ToggleHtml
+        | Action::ToggleQuotes
+        | Action::CycleSort
+        | Action::ToggleThreadStack
+        | Action::ConversationNext
+        | Action::ConversationPrev
+        | Action::ToggleMessage => "Actions",
 
-        Action::EditContact => "Contacts",
+        Action::EditContact | Action::MarkContact | Action::ExportContacts => "Contacts",
+
+        Action::Unsubscribe | Action::ReplyToList | Action::ViewLists => "Mailing lists",
+
+        Action::AcceptInvite | Action::TentativeInvite | Action::DeclineInvite => "Calendar",
 
         Action::Command
         | Action::ExecuteCommand
@@ -461,6 +764,22 @@ fn action_category(action: &Action) -> &'static str {
         | Action::Send
         | Action::Cancel
         | Action::CycleSendAccount
+        | Action::ToggleMarkdown
+        | Action::ToggleMarkdownPreview
+        | Action::SelectFrom
+        | Action::SendLater
+        | Action::SelectTemplate
+        | Action::CycleProtection
+        | Action::ConfirmSchedule
+        | Action::CancelSchedule
+        | Action::FromPickerUp
+        | Action::FromPickerDown
+        | Action::FromPickerSelect
+        | Action::FromPickerClose
+        | Action::TemplatePickerUp
+        | Action::TemplatePickerDown
+        | Action::TemplatePickerSelect
+        | Action::TemplatePickerClose
         | Action::AutocompleteUp
         | Action::AutocompleteDown
         | Action::AutocompleteSelect
@@ -478,9 +797,25 @@ fn action_category(action: &Action) -> &'static str {
 
         Action::ToggleConversationMode => "Actions",
 
-        Action::ToggleAttachments | Action::SaveAttachment | Action::OpenAttachment => {
-            "Attachments"
-        }
+        Action::ToggleAttachments
+        | Action::SaveAttachment
+        | Action::OpenAttachment
+        | Action::ImportVcard
+        | Action::ToggleImages
+        | Action::OpenImage => "Attachments",
+
+        Action::FollowLink | Action::CopyLink | Action::CancelLinkHint => "Links",
+
+        Action::ToggleSource
+        | Action::Find
+        | Action::FindNext
+        | Action::FindPrev
+        | Action::SaveEml
+        | Action::PipeMessage
+        | Action::SubmitPrompt
+        | Action::CancelPrompt
+        | Action::RunPipe(_)
+        | Action::ClosePipeOutput => "Actions",
     }
 }
 
@@ -490,13 +825,16 @@ fn category_order(category: &str) -> u8 {
         "Navigation" => 0,
         "Actions" => 1,
         "Attachments" => 2,
-        "Accounts" => 3,
-        "Contacts" => 4,
-        "AI" => 5,
-        "Commands" => 6,
-        "Composer" => 7,
-        "Wizard" => 8,
-        "Help" => 9,
+        "Links" => 3,
+        "Mailing lists" => 4,
+        "Calendar" => 5,
+        "Accounts" => 6,
+        "Contacts" => 7,
+        "AI" => 8,
+        "Commands" => 9,
+        "Composer" => 10,
+        "Wizard" => 11,
+        "Help" => 12,
         _ => 99,
     }
 }
@@ -522,4 +860,41 @@ mod tests {
         assert_eq!(bindings.get(&key_code(KeyCode::Up)), Some(Action::Up));
         assert_eq!(bindings.get(&ctrl_key('q')), Some(Action::Quit));
     }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("ctrl+g"), Some(ctrl_key('g')));
+        assert_eq!(parse_key("P"), Some(shift_key('P')));
+        assert_eq!(parse_key("shift+p"), Some(shift_key('P')));
+        assert_eq!(
+            parse_key("alt+space"),
+            Some(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::ALT))
+        );
+        assert_eq!(parse_key("F10"), Some(key_code(KeyCode::F(10))));
+        assert_eq!(parse_key("ctrl++"), Some(ctrl_key('+')));
+        assert_eq!(parse_key("hyper+x"), None);
+        assert_eq!(parse_key("f99"), None);
+        assert_eq!(parse_key("bogus"), None);
+    }
+
+    #[test]
+    fn test_bind_pipe_commands() {
+        let mut bindings = KeyBindings::new(&KeybindingMode::Vim);
+        let command = |key: &str| PipeCommandConfig {
+            name: "Apply".to_string(),
+            command: "git am".to_string(),
+            key: Some(key.to_string()),
+            input: Default::default(),
+            interactive: false,
+        };
+        bindings.bind_pipe_commands(&[command("ctrl+g"), command("nope+x")]);
+
+        assert_eq!(bindings.get(&ctrl_key('g')), Some(Action::RunPipe(0)));
+        assert!(
+            bindings
+                .all_bindings()
+                .iter()
+                .any(|e| e.description == "Pipe to Apply")
+        );
+    }
 }
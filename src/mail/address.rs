@@ -0,0 +1,387 @@
+//! RFC 5322 address lists
+//!
+//! The To/Cc fields are free text, so they can't simply be split on commas:
+//! display names may be quoted (`"Doe, John" <john@example.com>`), carry
+//! comments (`john@example.com (John Doe)`), or be grouped
+//! (`Team: a@example.com, b@example.com;`). This module splits a field into
+//! its mailboxes, keeping the byte range of each so the composer can
+//! highlight the ones that don't parse.
+
+use std::fmt;
+use std::ops::Range;
+
+use anyhow::{Result, anyhow};
+use lettre::address::AddressError;
+use lettre::message::Mailbox;
+
+/// Characters that force a display name to be quoted
+const SPECIALS: &[char] = &[
+    '(', ')', '<', '>', '[', ']', ':', ';', '@', '\\', ',', '.', '"',
+];
+
+/// A single mailbox: an address with an optional display name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub name: Option<String>,
+    pub email: String,
+}
+
+impl Address {
+    /// Domain part of the address
+    pub fn domain(&self) -> &str {
+        self.email.rsplit_once('@').map_or("", |(_, domain)| domain)
+    }
+
+    /// Mailbox for sending, with an internationalized domain converted to ASCII
+    pub fn to_mailbox(&self) -> Result<Mailbox> {
+        let (user, domain) = self
+            .email
+            .rsplit_once('@')
+            .ok_or_else(|| anyhow!("Invalid address: {}", self.email))?;
+        let domain = idna::domain_to_ascii(domain)
+            .map_err(|_| anyhow!("Invalid domain in address: {}", self.email))?;
+        let address = lettre::Address::new(user, domain)
+            .map_err(|e| anyhow!("Invalid address {}: {}", self.email, e))?;
+        Ok(Mailbox::new(self.name.clone(), address))
+    }
+
+    /// Whether the address belongs to one of `domains` (or a subdomain)
+    pub fn is_in_domains(&self, domains: &[String]) -> bool {
+        let domain = self.domain().to_lowercase();
+        domains.iter().any(|d| {
+            let d = d.trim().trim_start_matches('@').to_lowercase();
+            domain == d || domain.ends_with(&format!(".{}", d))
+        })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) if name.contains(SPECIALS) => {
+                let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{}\" <{}>", escaped, self.email)
+            }
+            Some(ref name) => write!(f, "{} <{}>", name, self.email),
+            None => f.write_str(&self.email),
+        }
+    }
+}
+
+/// One mailbox in an address field, as typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Byte range of the mailbox within the field
+    pub range: Range<usize>,
+    pub parsed: std::result::Result<Address, &'static str>,
+}
+
+/// Split a field into its mailboxes and parse each one
+///
+/// Group names are dropped and their members listed like any other
+/// mailbox; empty entries (e.g. a trailing comma) are skipped.
+pub fn parse_entries(input: &str) -> Vec<Entry> {
+    split(input)
+        .into_iter()
+        .filter_map(|range| {
+            let text = &input[range.clone()];
+            let trimmed = text.trim_start();
+            let start = range.start + (text.len() - trimmed.len());
+            let end = start + trimmed.trim_end().len();
+            (start < end).then(|| Entry {
+                range: start..end,
+                parsed: parse_mailbox(&input[start..end]),
+            })
+        })
+        .collect()
+}
+
+/// Parse a field, failing on the first invalid mailbox
+pub fn parse_list(input: &str) -> Result<Vec<Address>> {
+    parse_entries(input)
+        .into_iter()
+        .map(|entry| {
+            entry
+                .parsed
+                .map_err(|e| anyhow!("Invalid address \"{}\": {}", &input[entry.range], e))
+        })
+        .collect()
+}
+
+/// The valid mailboxes in a field, ignoring anything that doesn't parse
+pub fn valid_addresses(input: &str) -> Vec<Address> {
+    parse_entries(input)
+        .into_iter()
+        .filter_map(|entry| entry.parsed.ok())
+        .collect()
+}
+
+/// Byte offset where the mailbox being typed at the end of the field starts
+pub fn current_entry_start(input: &str) -> usize {
+    let start = split(input).last().map_or(0, |range| range.start);
+    start + (input[start..].len() - input[start..].trim_start().len())
+}
+
+/// Byte ranges of the mailboxes in a field (untrimmed, possibly empty)
+fn split(input: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut comment_depth = 0usize;
+    let mut in_angle = false;
+    let mut in_literal = false;
+
+    for (i, c) in input.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if quoted || comment_depth > 0 => escaped = true,
+            '"' if comment_depth == 0 => quoted = !quoted,
+            _ if quoted => {}
+            '(' => comment_depth += 1,
+            ')' if comment_depth > 0 => comment_depth -= 1,
+            _ if comment_depth > 0 => {}
+            '<' => in_angle = true,
+            '>' => in_angle = false,
+            '[' => in_literal = true,
+            ']' => in_literal = false,
+            _ if in_angle || in_literal => {}
+            // Mailboxes are separated by commas; a group ends with a semicolon
+            ',' | ';' => {
+                ranges.push(start..i);
+                start = i + 1;
+            }
+            // "Group name:" - the members follow
+            ':' => start = i + 1,
+            _ => {}
+        }
+    }
+    ranges.push(start..input.len());
+    ranges
+}
+
+/// Parse `addr`, `Name <addr>`, `"Quoted, Name" <addr>` or `addr (Name)`
+fn parse_mailbox(text: &str) -> std::result::Result<Address, &'static str> {
+    // Strip comments; an old-style "addr (Name)" comment supplies the name
+    let mut plain = String::with_capacity(text.len());
+    let mut comment = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    for c in text.chars() {
+        if escaped {
+            escaped = false;
+            if depth > 0 {
+                comment.push(c);
+            } else {
+                plain.push(c);
+            }
+            continue;
+        }
+        match c {
+            '\\' if quoted || depth > 0 => {
+                escaped = true;
+                if depth == 0 {
+                    plain.push(c);
+                }
+            }
+            '"' if depth == 0 => {
+                quoted = !quoted;
+                plain.push(c);
+            }
+            '(' if !quoted => {
+                if depth > 0 {
+                    comment.push(c);
+                }
+                depth += 1;
+            }
+            ')' if !quoted && depth > 0 => {
+                depth -= 1;
+                if depth > 0 {
+                    comment.push(c);
+                }
+            }
+            _ if depth > 0 => comment.push(c),
+            _ => plain.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote");
+    }
+    if depth > 0 {
+        return Err("unterminated comment");
+    }
+
+    let plain = plain.trim();
+    let (name, addr) = match find_unquoted(plain, |c| c == '<') {
+        Some(open) => {
+            let rest = &plain[open + 1..];
+            let close = rest.find('>').ok_or("missing closing >")?;
+            if !rest[close + 1..].trim().is_empty() {
+                return Err("unexpected text after >");
+            }
+            (display_name(&plain[..open]), rest[..close].trim())
+        }
+        None if plain.contains('>') => return Err("missing opening <"),
+        None => (None, plain),
+    };
+    if find_unquoted(addr, char::is_whitespace).is_some() {
+        return Err("put the address in <> after the name");
+    }
+
+    let name = name.or_else(|| display_name(&comment));
+    Ok(Address {
+        name,
+        email: validate_addr_spec(addr)?.to_string(),
+    })
+}
+
+/// Check a bare `local@domain`, allowing internationalized domains
+fn validate_addr_spec(addr: &str) -> std::result::Result<&str, &'static str> {
+    if addr.is_empty() {
+        return Err("missing address");
+    }
+    let (user, domain) = addr.rsplit_once('@').ok_or("missing @")?;
+    if user.is_empty() {
+        return Err("missing name before @");
+    }
+    if domain.is_empty() {
+        return Err("missing domain");
+    }
+    if !domain.contains('.') && !domain.starts_with('[') {
+        return Err("incomplete domain");
+    }
+    match lettre::Address::new(user, domain) {
+        Ok(_) => Ok(addr),
+        Err(AddressError::InvalidUser) => Err("invalid characters before @"),
+        Err(AddressError::InvalidDomain) => Err("invalid domain"),
+        Err(_) => Err("invalid address"),
+    }
+}
+
+/// Unquote and tidy a display name; None if empty
+fn display_name(text: &str) -> Option<String> {
+    let text = text.trim();
+    let name = match text
+        .strip_prefix('"')
+        .and_then(|inner| inner.strip_suffix('"'))
+    {
+        Some(inner) => {
+            let mut name = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => name.extend(chars.next()),
+                    _ => name.push(c),
+                }
+            }
+            name
+        }
+        None => text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace('"', ""),
+    };
+    (!name.trim().is_empty()).then(|| name.trim().to_string())
+}
+
+/// Byte offset of the first character matching `pred` outside double quotes
+fn find_unquoted(text: &str, pred: impl Fn(char) -> bool) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quoted {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && pred(c) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emails(input: &str) -> Vec<String> {
+        parse_list(input)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.email)
+            .collect()
+    }
+
+    #[test]
+    fn test_quoted_names_with_commas() {
+        let list = parse_list(r#""Doe, John" <john@x.com>, jane@x.com"#).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name.as_deref(), Some("Doe, John"));
+        assert_eq!(list[0].email, "john@x.com");
+        assert_eq!(list[1].name, None);
+        assert_eq!(list[0].to_string(), r#""Doe, John" <john@x.com>"#);
+    }
+
+    #[test]
+    fn test_groups_and_comments() {
+        assert_eq!(
+            emails("Team: a@x.com, B <b@x.com>;, c@y.org (Carol Smith)"),
+            ["a@x.com", "b@x.com", "c@y.org"]
+        );
+        assert!(parse_list("undisclosed-recipients:;").unwrap().is_empty());
+        let list = parse_list("c@y.org (Carol Smith)").unwrap();
+        assert_eq!(list[0].name.as_deref(), Some("Carol Smith"));
+    }
+
+    #[test]
+    fn test_invalid_entries_are_located() {
+        let input = "ok@x.com, John john@x.com, bad@, x@y.com,";
+        let entries = parse_entries(input);
+        assert_eq!(entries.len(), 4);
+        assert!(entries[0].parsed.is_ok());
+        assert_eq!(&input[entries[1].range.clone()], "John john@x.com");
+        assert!(entries[1].parsed.is_err());
+        assert_eq!(entries[2].parsed, Err("missing domain"));
+        assert!(entries[3].parsed.is_ok());
+        assert!(parse_list(input).is_err());
+        assert_eq!(
+            parse_entries(r#""Unterminated <a@x.com>"#)[0].parsed,
+            Err("unterminated quote")
+        );
+    }
+
+    #[test]
+    fn test_idn_domain() {
+        let list = parse_list("Jörg <jörg@bücher.example>").unwrap();
+        assert_eq!(list[0].domain(), "bücher.example");
+        let mailbox = list[0].to_mailbox().unwrap();
+        assert_eq!(mailbox.email.domain(), "xn--bcher-kva.example");
+    }
+
+    #[test]
+    fn test_current_entry_start() {
+        assert_eq!(current_entry_start("jo"), 0);
+        let input = r#""Doe, John" <john@x.com>, ja"#;
+        assert_eq!(&input[current_entry_start(input)..], "ja");
+        assert_eq!(current_entry_start("a@x.com, "), 9);
+    }
+
+    #[test]
+    fn test_domain_matching() {
+        let internal = vec!["example.com".to_string()];
+        let address = |email: &str| Address {
+            name: None,
+            email: email.to_string(),
+        };
+        assert!(address("a@example.com").is_in_domains(&internal));
+        assert!(address("a@mail.Example.com").is_in_domains(&internal));
+        assert!(!address("a@notexample.com").is_in_domains(&internal));
+    }
+}
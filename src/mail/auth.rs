@@ -0,0 +1,420 @@
+//! Sender authentication results (RFC 8601 Authentication-Results, RFC 8617 ARC)
+//!
+//! The receiving server records its DKIM, SPF and DMARC checks in trace
+//! headers. Anyone can add such a header before the message reaches that
+//! server, so only headers stamped with an authserv-id the account trusts
+//! are believed. The From address is also checked for display names and
+//! domains made to look like someone else's.
+
+use crate::config::AccountConfig;
+
+pub const HEADER: &str = "Authentication-Results";
+pub const ARC_HEADER: &str = "ARC-Authentication-Results";
+
+/// Outcome of one authentication method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    SoftFail,
+    Neutral,
+    None,
+    TempError,
+    PermError,
+}
+
+impl Verdict {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s.to_ascii_lowercase().as_str() {
+            "pass" => Self::Pass,
+            // "policy": the signature was valid but rejected by local policy
+            "fail" | "hardfail" | "policy" => Self::Fail,
+            "softfail" => Self::SoftFail,
+            "neutral" => Self::Neutral,
+            "none" => Self::None,
+            "temperror" => Self::TempError,
+            "permerror" => Self::PermError,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+            Self::SoftFail => "softfail",
+            Self::Neutral => "neutral",
+            Self::None => "none",
+            Self::TempError => "temperror",
+            Self::PermError => "permerror",
+        }
+    }
+}
+
+/// What an account trusts when judging senders
+#[derive(Debug, Clone, Default)]
+pub struct AuthPolicy {
+    /// authserv-ids whose results are believed (lowercase)
+    pub authserv_ids: Vec<String>,
+    /// Domains senders might try to imitate
+    pub internal_domains: Vec<String>,
+}
+
+impl AuthPolicy {
+    pub fn for_account(account: &AccountConfig) -> Self {
+        Self {
+            authserv_ids: account
+                .authserv_ids
+                .iter()
+                .map(|id| id.trim().to_lowercase())
+                .collect(),
+            internal_domains: account.internal_domains.clone(),
+        }
+    }
+
+    fn trusts(&self, authserv_id: &str) -> bool {
+        self.authserv_ids.iter().any(|id| id == authserv_id)
+    }
+}
+
+/// Verdicts from the trusted server plus reasons to distrust the sender
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthSummary {
+    /// A trusted Authentication-Results header was found
+    pub trusted: bool,
+    pub dkim: Option<Verdict>,
+    pub spf: Option<Verdict>,
+    pub dmarc: Option<Verdict>,
+    /// Shown as a banner above the headers
+    pub warnings: Vec<String>,
+}
+
+/// One parsed Authentication-Results or ARC-Authentication-Results header
+#[derive(Debug)]
+struct AuthResults {
+    authserv_id: String,
+    arc: bool,
+    /// (method, verdict) in header order
+    results: Vec<(String, Verdict)>,
+}
+
+impl AuthResults {
+    /// Result for `method`; with several (e.g. two DKIM signatures) any pass wins
+    fn verdict(&self, method: &str) -> Option<Verdict> {
+        let mut verdicts = self
+            .results
+            .iter()
+            .filter(|(m, _)| m == method)
+            .map(|&(_, v)| v);
+        let first = verdicts.next()?;
+        Some(if verdicts.any(|v| v == Verdict::Pass) {
+            Verdict::Pass
+        } else {
+            first
+        })
+    }
+}
+
+/// Judge a message from its stored `Name: value` authentication headers
+pub fn evaluate(
+    headers: &[String],
+    policy: &AuthPolicy,
+    from_addr: &str,
+    from_name: Option<&str>,
+) -> AuthSummary {
+    let trusted: Vec<AuthResults> = headers
+        .iter()
+        .filter_map(|line| parse_header(line))
+        .filter(|r| policy.trusts(&r.authserv_id))
+        .collect();
+    // The topmost header from our own server beats an ARC copy of it
+    let results = trusted.iter().find(|r| !r.arc).or(trusted.first());
+
+    let mut summary = AuthSummary::default();
+    if let Some(results) = results {
+        summary.trusted = true;
+        summary.dkim = results.verdict("dkim");
+        summary.spf = results.verdict("spf");
+        summary.dmarc = results.verdict("dmarc");
+    }
+
+    let domain = domain_of(from_addr);
+    if summary.dmarc == Some(Verdict::Fail) {
+        summary.warnings.push(format!(
+            "{} failed DMARC: this message may not be from who it claims",
+            domain
+        ));
+    }
+    summary.warnings.extend(lookalike_warnings(
+        from_addr,
+        from_name,
+        &policy.internal_domains,
+    ));
+    summary
+}
+
+/// Parse one stored header line, `Name: value` with folding already removed
+fn parse_header(line: &str) -> Option<AuthResults> {
+    let (name, value) = line.split_once(':')?;
+    let arc = name.trim().eq_ignore_ascii_case(ARC_HEADER);
+    if !arc && !name.trim().eq_ignore_ascii_case(HEADER) {
+        return None;
+    }
+
+    let segments = split_segments(value);
+    let mut segments = segments.iter().map(|s| s.trim());
+    let mut first = segments.next()?;
+    // ARC-Authentication-Results starts with the instance tag
+    if arc && first.starts_with("i=") {
+        first = segments.next()?;
+    }
+    let authserv_id = first.split_whitespace().next()?.to_lowercase();
+
+    let results = segments
+        .filter_map(|segment| {
+            // method[/version]=result, then properties we don't need
+            let (method, verdict) = segment.split_whitespace().next()?.split_once('=')?;
+            let method = method.split('/').next()?.trim().to_lowercase();
+            Some((method, Verdict::parse(verdict.trim())?))
+        })
+        .collect();
+
+    Some(AuthResults {
+        authserv_id,
+        arc,
+        results,
+    })
+}
+
+/// Split a header value on `;`, dropping (comments) and ignoring separators
+/// inside "quoted strings"
+fn split_segments(value: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for c in value.chars() {
+        let current = segments.last_mut().expect("never empty");
+        if escaped {
+            if depth == 0 {
+                current.push(c);
+            }
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if quoted || depth > 0 => escaped = true,
+            '"' if depth == 0 => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            '(' if !quoted => depth += 1,
+            ')' if !quoted && depth > 0 => depth -= 1,
+            ';' if !quoted && depth == 0 => segments.push(String::new()),
+            _ if depth > 0 => {}
+            _ => current.push(c),
+        }
+    }
+    segments
+}
+
+fn domain_of(address: &str) -> String {
+    address
+        .rsplit_once('@')
+        .map_or("", |(_, domain)| domain)
+        .trim_end_matches('>')
+        .to_lowercase()
+}
+
+/// Display names that show a different address or domain, and From domains
+/// spelled to look like an internal one
+fn lookalike_warnings(
+    from_addr: &str,
+    from_name: Option<&str>,
+    internal_domains: &[String],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let domain = domain_of(from_addr);
+    if domain.is_empty() {
+        return warnings;
+    }
+
+    if let Some(name) = from_name {
+        let claimed = name
+            .split(|c: char| c.is_whitespace() || "<>()[]\"',;:|".contains(c))
+            .map(|token| token.trim_matches('.').to_lowercase())
+            .find(|token| {
+                let claimed_domain = token.rsplit_once('@').map_or(token.as_str(), |(_, d)| d);
+                looks_like_domain(claimed_domain) && !same_site(claimed_domain, &domain)
+            });
+        if let Some(claimed) = claimed {
+            warnings.push(format!(
+                "Sender name shows {} but the address is {}",
+                claimed, from_addr
+            ));
+        }
+    }
+
+    let is_internal = internal_domains
+        .iter()
+        .any(|d| same_site(&d.trim().trim_start_matches('@').to_lowercase(), &domain));
+    if !is_internal {
+        let skeleton_of_domain = skeleton(&domain);
+        if let Some(imitated) = internal_domains
+            .iter()
+            .map(|d| d.trim().trim_start_matches('@').to_lowercase())
+            .find(|d| skeleton(d) == skeleton_of_domain)
+        {
+            warnings.push(format!(
+                "{} imitates the internal domain {}",
+                domain, imitated
+            ));
+        }
+    }
+    warnings
+}
+
+/// Whether a word from a display name reads as a domain ("paypal.com")
+fn looks_like_domain(token: &str) -> bool {
+    let labels: Vec<&str> = token.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(char::is_alphabetic))
+}
+
+/// Equal, or one is a subdomain of the other
+fn same_site(a: &str, b: &str) -> bool {
+    a == b || a.ends_with(&format!(".{}", b)) || b.ends_with(&format!(".{}", a))
+}
+
+/// Fold characters that are easily mistaken for each other so that
+/// "cornpany.com", "c0mpany.com" and "cоmpany.com" (Cyrillic о) compare equal
+fn skeleton(domain: &str) -> String {
+    let (unicode, _) = idna::domain_to_unicode(domain);
+    let folded: String = unicode
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'а' | 'α' => 'a',
+            'с' | 'ϲ' => 'c',
+            'е' | 'ε' | '3' => 'e',
+            'һ' => 'h',
+            'і' | 'ι' | 'ӏ' | 'i' | '1' | '|' => 'l',
+            'ј' => 'j',
+            'о' | 'ο' | '0' => 'o',
+            'р' | 'ρ' => 'p',
+            'ѕ' | '5' => 's',
+            'х' | 'χ' => 'x',
+            'у' | 'γ' => 'y',
+            'ν' => 'v',
+            other => other,
+        })
+        .collect();
+    folded
+        .replace("rn", "m")
+        .replace("vv", "w")
+        .replace("cl", "d")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AuthPolicy {
+        AuthPolicy {
+            authserv_ids: vec!["mx.company.com".to_string()],
+            internal_domains: vec!["company.com".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_trusted_results() {
+        let headers = vec![
+            "Authentication-Results: mx.company.com; \
+             dkim=pass (2048-bit key) header.d=example.com header.s=s1; \
+             spf=softfail smtp.mailfrom=example.com; dmarc=pass header.from=example.com"
+                .to_string(),
+            // Added before the message reached us; must be ignored
+            "Authentication-Results: mx.company.com.evil.net; dmarc=fail".to_string(),
+        ];
+        let summary = evaluate(&headers, &policy(), "bob@example.com", Some("Bob"));
+        assert!(summary.trusted);
+        assert_eq!(summary.dkim, Some(Verdict::Pass));
+        assert_eq!(summary.spf, Some(Verdict::SoftFail));
+        assert_eq!(summary.dmarc, Some(Verdict::Pass));
+        assert!(summary.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_untrusted_headers_are_ignored() {
+        let headers =
+            vec!["Authentication-Results: attacker.example; dkim=pass; dmarc=pass".to_string()];
+        let summary = evaluate(&headers, &policy(), "bob@example.com", None);
+        assert!(!summary.trusted);
+        assert_eq!(summary.dkim, None);
+    }
+
+    #[test]
+    fn test_arc_and_dmarc_failure() {
+        let headers = vec![
+            "ARC-Authentication-Results: i=1; mx.company.com; dkim=fail; \
+             dkim=pass header.d=lists.example; spf=pass; dmarc=fail (p=reject) header.from=company.com"
+                .to_string(),
+        ];
+        let summary = evaluate(&headers, &policy(), "ceo@company.com", Some("CEO"));
+        assert!(summary.trusted);
+        assert_eq!(summary.dkim, Some(Verdict::Pass));
+        assert_eq!(summary.dmarc, Some(Verdict::Fail));
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].contains("company.com failed DMARC"));
+    }
+
+    #[test]
+    fn test_quoted_separators_and_comments() {
+        let headers = vec![
+            "Authentication-Results: mx.company.com (version 1; dmarc=pass); \
+             spf=fail reason=\"x; dmarc=pass\"; dkim=none"
+                .to_string(),
+        ];
+        let summary = evaluate(&headers, &policy(), "a@example.com", None);
+        assert_eq!(summary.spf, Some(Verdict::Fail));
+        assert_eq!(summary.dkim, Some(Verdict::None));
+        assert_eq!(summary.dmarc, None);
+    }
+
+    #[test]
+    fn test_lookalike_display_name() {
+        let warnings = lookalike_warnings("attacker@evil.net", Some("ceo@company.com"), &[]);
+        assert_eq!(
+            warnings,
+            ["Sender name shows ceo@company.com but the address is attacker@evil.net"]
+        );
+        let warnings =
+            lookalike_warnings("service@mail.paypal.com", Some("PayPal.com Support"), &[]);
+        assert!(warnings.is_empty());
+        assert!(lookalike_warnings("jr@example.com", Some("Smith, J. Jr."), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_lookalike_domain() {
+        let internal = ["company.com".to_string()];
+        for domain in [
+            "cornpany.com",
+            "c0mpany.com",
+            "cоmpany.com",
+            "xn--cmpany-wqf.com",
+        ] {
+            let warnings = lookalike_warnings(&format!("ceo@{}", domain), None, &internal);
+            assert_eq!(warnings.len(), 1, "{}", domain);
+            assert!(warnings[0].contains("imitates the internal domain company.com"));
+        }
+        assert!(lookalike_warnings("ceo@company.com", None, &internal).is_empty());
+        assert!(lookalike_warnings("ceo@eu.company.com", None, &internal).is_empty());
+        assert!(lookalike_warnings("ceo@example.com", None, &internal).is_empty());
+    }
+}
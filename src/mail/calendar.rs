@@ -0,0 +1,781 @@
+//! Calendar invitations
+//!
+//! Meeting invites are iCalendar objects (RFC 5545) carried in a
+//! text/calendar part; the METHOD says whether it is a request, a reply or
+//! a cancellation (iTIP, RFC 5546). Answering one sends a METHOD:REPLY object
+//! back to the organizer by mail (iMIP, RFC 6047).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+
+use super::content_line::{Property, escape, fold, parse_property, unescape, unfold};
+
+/// iTIP method of a calendar object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Method {
+    Request,
+    Reply,
+    Cancel,
+    Publish,
+    Other(String),
+}
+
+impl Method {
+    fn parse(value: &str) -> Self {
+        match value.to_ascii_uppercase().as_str() {
+            "REQUEST" => Self::Request,
+            "REPLY" => Self::Reply,
+            "CANCEL" => Self::Cancel,
+            "PUBLISH" => Self::Publish,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// An attendee's answer (PARTSTAT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartStat {
+    NeedsAction,
+    Accepted,
+    Tentative,
+    Declined,
+    Delegated,
+}
+
+impl PartStat {
+    fn parse(value: &str) -> Self {
+        match value.to_ascii_uppercase().as_str() {
+            "ACCEPTED" => Self::Accepted,
+            "TENTATIVE" => Self::Tentative,
+            "DECLINED" => Self::Declined,
+            "DELEGATED" => Self::Delegated,
+            _ => Self::NeedsAction,
+        }
+    }
+
+    /// Value for the PARTSTAT parameter
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NeedsAction => "NEEDS-ACTION",
+            Self::Accepted => "ACCEPTED",
+            Self::Tentative => "TENTATIVE",
+            Self::Declined => "DECLINED",
+            Self::Delegated => "DELEGATED",
+        }
+    }
+
+    /// Subject prefix for a reply ("Accepted: Team sync")
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Accepted => "Accepted",
+            Self::Tentative => "Tentatively accepted",
+            Self::Declined => "Declined",
+            Self::Delegated => "Delegated",
+            Self::NeedsAction => "No response",
+        }
+    }
+}
+
+/// Someone named by ORGANIZER or ATTENDEE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub email: String,
+    /// Common name (CN parameter)
+    pub name: Option<String>,
+}
+
+impl Person {
+    pub fn display(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.email)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee {
+    pub person: Person,
+    pub status: PartStat,
+    /// OPT-PARTICIPANT etc. (None = required)
+    pub optional: bool,
+}
+
+/// Start or end of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum When {
+    /// All-day (VALUE=DATE)
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+impl When {
+    /// The instant this refers to; dates start at local midnight
+    pub fn instant(self) -> DateTime<Utc> {
+        match self {
+            Self::Time(t) => t,
+            Self::Date(d) => local_instant(d.and_hms_opt(0, 0, 0).unwrap_or_default()),
+        }
+    }
+
+    fn to_ical(self) -> String {
+        match self {
+            Self::Date(d) => format!(";VALUE=DATE:{}", d.format("%Y%m%d")),
+            Self::Time(t) => format!(":{}", t.format("%Y%m%dT%H%M%SZ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub uid: String,
+    pub sequence: u32,
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub start: When,
+    pub end: When,
+    pub organizer: Option<Person>,
+    pub attendees: Vec<Attendee>,
+    /// The occurrence of a recurring event this instance replaces
+    pub recurrence_id: Option<When>,
+    /// Raw RRULE of a recurring event
+    pub rrule: Option<String>,
+    /// STATUS:CANCELLED
+    pub cancelled: bool,
+}
+
+impl Event {
+    pub fn title(&self) -> &str {
+        self.summary.as_deref().unwrap_or("(no title)")
+    }
+
+    /// Whether the two events share any time
+    pub fn overlaps(&self, other: &Event) -> bool {
+        self.start.instant() < other.end.instant() && other.start.instant() < self.end.instant()
+    }
+
+    /// The attendee entry for one of `addresses`
+    pub fn attendee<'a>(&self, addresses: impl IntoIterator<Item = &'a str>) -> Option<&Attendee> {
+        let addresses: Vec<&str> = addresses.into_iter().collect();
+        self.attendees.iter().find(|a| {
+            addresses
+                .iter()
+                .any(|addr| a.person.email.eq_ignore_ascii_case(addr))
+        })
+    }
+}
+
+/// A calendar object with its first event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invite {
+    pub method: Method,
+    pub event: Event,
+}
+
+impl Invite {
+    /// An invitation that can be answered
+    pub fn is_request(&self) -> bool {
+        self.method == Method::Request && !self.event.cancelled
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.method == Method::Cancel || self.event.cancelled
+    }
+}
+
+/// One STANDARD or DAYLIGHT block of a VTIMEZONE
+#[derive(Debug, Default)]
+struct TzRule {
+    start: Option<NaiveDateTime>,
+    offset: i32,
+    /// (month, nth weekday) from a yearly RRULE
+    yearly: Option<(u32, i8, Weekday)>,
+}
+
+/// Parse an iCalendar object; None if it has no usable VEVENT
+pub fn parse(text: &str) -> Option<Invite> {
+    let mut method = Method::Publish;
+    let mut stack: Vec<String> = Vec::new();
+    let mut events: Vec<Vec<Property>> = Vec::new();
+    let mut zones: HashMap<String, Vec<TzRule>> = HashMap::new();
+    let mut zone_id = String::new();
+
+    for line in unfold(text) {
+        let Some(prop) = parse_property(&line) else {
+            continue;
+        };
+        match prop.name.as_str() {
+            "BEGIN" => {
+                let component = prop.value.to_ascii_uppercase();
+                match component.as_str() {
+                    "VEVENT" if stack.last().is_some_and(|c| c == "VCALENDAR") => {
+                        events.push(Vec::new())
+                    }
+                    "STANDARD" | "DAYLIGHT" => zones
+                        .entry(zone_id.clone())
+                        .or_default()
+                        .push(TzRule::default()),
+                    _ => {}
+                }
+                stack.push(component);
+                continue;
+            }
+            "END" => {
+                stack.pop();
+                continue;
+            }
+            _ => {}
+        }
+        match stack.last().map(String::as_str) {
+            Some("VCALENDAR") if prop.name == "METHOD" => method = Method::parse(&prop.value),
+            Some("VEVENT") if stack.len() == 2 => {
+                if let Some(event) = events.last_mut() {
+                    event.push(prop);
+                }
+            }
+            Some("VTIMEZONE") if prop.name == "TZID" => zone_id = prop.value,
+            Some("STANDARD" | "DAYLIGHT") => {
+                if let Some(rule) = zones.get_mut(&zone_id).and_then(|r| r.last_mut()) {
+                    apply_tz_property(rule, &prop);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Prefer the master event over overridden occurrences
+    let props = events
+        .iter()
+        .find(|e| !e.iter().any(|p| p.name == "RECURRENCE-ID"))
+        .or_else(|| events.first())?;
+    let event = build_event(props, &zones)?;
+    Some(Invite { method, event })
+}
+
+fn build_event(props: &[Property], zones: &HashMap<String, Vec<TzRule>>) -> Option<Event> {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+    let text = |name: &str| {
+        get(name)
+            .map(|p| unescape(&p.value).trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let start = parse_when(get("DTSTART")?, zones)?;
+    let end = get("DTEND")
+        .and_then(|p| parse_when(p, zones))
+        .or_else(|| {
+            let duration = parse_duration(&get("DURATION")?.value)?;
+            Some(match start {
+                When::Time(t) => When::Time(t + duration),
+                When::Date(d) => When::Date(d + duration),
+            })
+        })
+        .unwrap_or(match start {
+            // RFC 5545 3.6.1: a date lasts one day, a date-time is instantaneous
+            When::Date(d) => When::Date(d + Duration::days(1)),
+            time => time,
+        });
+
+    Some(Event {
+        uid: get("UID").map(|p| p.value.clone()).unwrap_or_default(),
+        sequence: get("SEQUENCE")
+            .and_then(|p| p.value.trim().parse().ok())
+            .unwrap_or(0),
+        summary: text("SUMMARY"),
+        location: text("LOCATION"),
+        description: text("DESCRIPTION"),
+        start,
+        end,
+        organizer: get("ORGANIZER").and_then(person),
+        attendees: props
+            .iter()
+            .filter(|p| p.name == "ATTENDEE")
+            .filter_map(|p| {
+                Some(Attendee {
+                    person: person(p)?,
+                    status: PartStat::parse(p.param("PARTSTAT").unwrap_or("")),
+                    optional: p
+                        .param("ROLE")
+                        .is_some_and(|r| r.eq_ignore_ascii_case("OPT-PARTICIPANT")),
+                })
+            })
+            .collect(),
+        recurrence_id: get("RECURRENCE-ID").and_then(|p| parse_when(p, zones)),
+        rrule: get("RRULE").map(|p| p.value.clone()),
+        cancelled: get("STATUS").is_some_and(|p| p.value.eq_ignore_ascii_case("CANCELLED")),
+    })
+}
+
+fn person(prop: &Property) -> Option<Person> {
+    let value = prop.value.trim();
+    let email = match value.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &value[7..],
+        _ => value,
+    };
+    if !email.contains('@') {
+        return None;
+    }
+    Some(Person {
+        email: email.to_string(),
+        name: prop
+            .param("CN")
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string),
+    })
+}
+
+fn parse_when(prop: &Property, zones: &HashMap<String, Vec<TzRule>>) -> Option<When> {
+    let value = prop.value.trim();
+    let is_date = prop
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || value.len() == 8;
+    if is_date {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(When::Date);
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(When::Time(naive.and_utc()));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let instant = match prop.param("TZID") {
+        Some(tzid) => zone_instant(naive, tzid, zones),
+        // Floating time: the same wall-clock time wherever the reader is
+        None => local_instant(naive),
+    };
+    Some(When::Time(instant))
+}
+
+/// Resolve a TZID: an IANA name, else the object's own VTIMEZONE
+/// (Outlook uses Windows zone names), else local time
+fn zone_instant(
+    naive: NaiveDateTime,
+    tzid: &str,
+    zones: &HashMap<String, Vec<TzRule>>,
+) -> DateTime<Utc> {
+    if let Ok(tz) = chrono_tz::Tz::from_str(tzid.trim_start_matches('/')) {
+        return resolve(&tz, naive);
+    }
+    match zones
+        .get(tzid)
+        .and_then(|rules| vtimezone_offset(rules, naive))
+    {
+        Some(offset) => resolve(&offset, naive),
+        None => local_instant(naive),
+    }
+}
+
+fn local_instant(naive: NaiveDateTime) -> DateTime<Utc> {
+    resolve(&Local, naive)
+}
+
+/// Wall-clock time in `tz`; times skipped by a DST jump move forward an hour
+fn resolve<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| naive.and_utc())
+}
+
+fn apply_tz_property(rule: &mut TzRule, prop: &Property) {
+    match prop.name.as_str() {
+        "DTSTART" => {
+            rule.start = NaiveDateTime::parse_from_str(prop.value.trim(), "%Y%m%dT%H%M%S").ok()
+        }
+        "TZOFFSETTO" => rule.offset = parse_utc_offset(&prop.value).unwrap_or(0),
+        "RRULE" => rule.yearly = parse_yearly_rule(&prop.value),
+        _ => {}
+    }
+}
+
+/// `+0100` / `-0530` → seconds east of UTC
+fn parse_utc_offset(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let hours: i32 = digits.get(..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..4)?.parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// `FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU` → (3, -1, Sunday)
+fn parse_yearly_rule(value: &str) -> Option<(u32, i8, Weekday)> {
+    let parts: HashMap<String, &str> = value
+        .split(';')
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), v))
+        .collect();
+    if !parts.get("FREQ")?.eq_ignore_ascii_case("YEARLY") {
+        return None;
+    }
+    let month: u32 = parts.get("BYMONTH")?.parse().ok()?;
+    let byday = parts.get("BYDAY")?;
+    let day = byday.get(byday.len().checked_sub(2)?..)?;
+    let nth = match &byday[..byday.len() - 2] {
+        "" => 1,
+        n => n.trim_start_matches('+').parse().ok()?,
+    };
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    Some((month, nth, weekday))
+}
+
+/// Offset in force at `naive`: the rule whose latest onset is closest before it
+fn vtimezone_offset(rules: &[TzRule], naive: NaiveDateTime) -> Option<FixedOffset> {
+    rules
+        .iter()
+        .filter_map(|rule| Some((last_onset(rule, naive)?, rule.offset)))
+        .max_by_key(|(onset, _)| *onset)
+        .and_then(|(_, offset)| FixedOffset::east_opt(offset))
+}
+
+fn last_onset(rule: &TzRule, naive: NaiveDateTime) -> Option<NaiveDateTime> {
+    let start = rule.start?;
+    let Some((month, nth, weekday)) = rule.yearly else {
+        return (start <= naive).then_some(start);
+    };
+    [naive.year(), naive.year() - 1]
+        .into_iter()
+        .filter_map(|year| {
+            nth_weekday(year, month, nth, weekday)?
+                .and_time(start.time())
+                .into()
+        })
+        .find(|onset| *onset <= naive && *onset >= start)
+}
+
+/// The nth (negative: from the end) weekday of a month
+fn nth_weekday(year: i32, month: u32, nth: i8, weekday: Weekday) -> Option<NaiveDate> {
+    if nth >= 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth.max(1) as u8)
+    } else {
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let last = next_month.pred_opt()?;
+        let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        last.checked_sub_signed(Duration::days(i64::from(back) + 7 * (i64::from(-nth) - 1)))
+    }
+}
+
+/// `P1W`, `PT1H30M`, `-P1D` → duration
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim_start_matches('+')),
+    };
+    let rest = rest.strip_prefix(['P', 'p'])?;
+    let mut total = 0i64;
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += n * match unit {
+                    'W' => 7 * 86400,
+                    'D' => 86400,
+                    'H' => 3600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(Duration::seconds(sign * total))
+}
+
+/// Build the METHOD:REPLY object answering `invite` as `attendee`
+pub fn reply(invite: &Invite, attendee: &Person, status: PartStat, now: DateTime<Utc>) -> String {
+    let event = &invite.event;
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "PRODID:-//bltz//bltz//EN".to_string(),
+        "VERSION:2.0".to_string(),
+        "METHOD:REPLY".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("SEQUENCE:{}", event.sequence),
+        format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+    ];
+    if let Some(recurrence_id) = event.recurrence_id {
+        lines.push(format!("RECURRENCE-ID{}", recurrence_id.to_ical()));
+    }
+    lines.push(format!("DTSTART{}", event.start.to_ical()));
+    lines.push(format!("DTEND{}", event.end.to_ical()));
+    if let Some(ref summary) = event.summary {
+        lines.push(format!("SUMMARY:{}", escape(summary)));
+    }
+    if let Some(ref organizer) = event.organizer {
+        lines.push(format!("ORGANIZER{}", person_value(organizer)));
+    }
+    lines.push(format!(
+        "ATTENDEE;PARTSTAT={}{}",
+        status.as_str(),
+        person_value(attendee)
+    ));
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|l| fold(l)).collect::<Vec<_>>().join("")
+}
+
+/// `;CN="Name":mailto:address`
+fn person_value(person: &Person) -> String {
+    match person.name {
+        Some(ref name) => format!(
+            ";CN=\"{}\":mailto:{}",
+            name.replace(['"', '\r', '\n'], ""),
+            person.email
+        ),
+        None => format!(":mailto:{}", person.email),
+    }
+}
+
+/// Events among `others` that overlap `event`, sorted by start
+///
+/// Several messages may carry versions of the same event (updates and
+/// cancellations), so only the latest one per UID counts. Events the user
+/// declined and events sharing `event`'s UID are left out.
+pub fn conflicts(event: &Event, others: Vec<Invite>, own: &[&str]) -> Vec<Event> {
+    let mut latest: HashMap<(String, Option<When>), Invite> = HashMap::new();
+    for other in others {
+        let key = (other.event.uid.clone(), other.event.recurrence_id);
+        match latest.get(&key) {
+            // Same sequence: a cancellation supersedes the request it cancels
+            Some(seen)
+                if seen.event.sequence > other.event.sequence
+                    || seen.event.sequence == other.event.sequence && !other.is_cancelled() => {}
+            _ => {
+                latest.insert(key, other);
+            }
+        }
+    }
+
+    let mut found: Vec<Event> = latest
+        .into_values()
+        .filter(|o| matches!(o.method, Method::Request | Method::Publish) && !o.is_cancelled())
+        .map(|o| o.event)
+        .filter(|o| o.uid != event.uid && o.overlaps(event))
+        .filter(|o| {
+            o.attendee(own.iter().copied())
+                .is_none_or(|a| a.status != PartStat::Declined)
+        })
+        .collect();
+    found.sort_by_key(|o| o.start.instant());
+    found
+}
+
+/// METHOD of a serialized calendar object (for the Content-Type parameter)
+pub fn method_of(text: &str) -> Option<String> {
+    unfold(text)
+        .iter()
+        .filter_map(|l| parse_property(l))
+        .find(|p| p.name == "METHOD")
+        .map(|p| p.value.trim().to_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTLOOK: &str = "BEGIN:VCALENDAR\r\n\
+        METHOD:REQUEST\r\n\
+        PRODID:Microsoft Exchange Server 2010\r\n\
+        VERSION:2.0\r\n\
+        BEGIN:VTIMEZONE\r\n\
+        TZID:W. Europe Standard Time\r\n\
+        BEGIN:STANDARD\r\n\
+        DTSTART:16010101T030000\r\n\
+        TZOFFSETFROM:+0200\r\n\
+        TZOFFSETTO:+0100\r\n\
+        RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=-1SU;BYMONTH=10\r\n\
+        END:STANDARD\r\n\
+        BEGIN:DAYLIGHT\r\n\
+        DTSTART:16010101T020000\r\n\
+        TZOFFSETFROM:+0100\r\n\
+        TZOFFSETTO:+0200\r\n\
+        RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=-1SU;BYMONTH=3\r\n\
+        END:DAYLIGHT\r\n\
+        END:VTIMEZONE\r\n\
+        BEGIN:VEVENT\r\n\
+        ORGANIZER;CN=\"Alice Example\":mailto:alice@example.com\r\n\
+        ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE;CN=Bob:mailto:\r\n \
+        bob@example.com\r\n\
+        ATTENDEE;ROLE=OPT-PARTICIPANT;PARTSTAT=ACCEPTED:mailto:carol@example.com\r\n\
+        SUMMARY;LANGUAGE=en-US:Planning\\, Q3\r\n\
+        DTSTART;TZID=W. Europe Standard Time:20240715T100000\r\n\
+        DTEND;TZID=W. Europe Standard Time:20240715T110000\r\n\
+        UID:040000008200E00074C5B7101A82E008\r\n\
+        SEQUENCE:2\r\n\
+        LOCATION:Room 4\r\n\
+        DESCRIPTION:Agenda:\\nBudget\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    fn utc(s: &str) -> When {
+        When::Time(DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn test_parse_outlook_request() {
+        let invite = parse(OUTLOOK).unwrap();
+        assert!(invite.is_request());
+        let event = &invite.event;
+        assert_eq!(event.title(), "Planning, Q3");
+        assert_eq!(event.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.description.as_deref(), Some("Agenda:\nBudget"));
+        assert_eq!(event.sequence, 2);
+        // Summer time from the VTIMEZONE: UTC+2
+        assert_eq!(event.start, utc("2024-07-15T08:00:00Z"));
+        assert_eq!(event.end, utc("2024-07-15T09:00:00Z"));
+        let organizer = event.organizer.as_ref().unwrap();
+        assert_eq!(organizer.email, "alice@example.com");
+        assert_eq!(organizer.display(), "Alice Example");
+        assert_eq!(event.attendees.len(), 2);
+        let bob = event.attendee(["BOB@example.com"]).unwrap();
+        assert_eq!(bob.status, PartStat::NeedsAction);
+        assert!(!bob.optional);
+        assert!(event.attendees[1].optional);
+    }
+
+    #[test]
+    fn test_vtimezone_winter_offset() {
+        let winter = OUTLOOK.replace("20240715T1", "20240115T1");
+        let event = parse(&winter).unwrap().event;
+        assert_eq!(event.start, utc("2024-01-15T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_iana_zone_and_duration() {
+        let text = "BEGIN:VCALENDAR\nMETHOD:REQUEST\nBEGIN:VEVENT\nUID:1\n\
+            DTSTART;TZID=America/New_York:20240301T090000\nDURATION:PT1H30M\n\
+            END:VEVENT\nEND:VCALENDAR\n";
+        let event = parse(text).unwrap().event;
+        assert_eq!(event.start, utc("2024-03-01T14:00:00Z"));
+        assert_eq!(event.end, utc("2024-03-01T15:30:00Z"));
+    }
+
+    #[test]
+    fn test_all_day_and_overlap() {
+        let text = |uid: &str, start: &str| {
+            format!(
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:{}\nDTSTART:{}\nDTEND:{}\nEND:VEVENT\nEND:VCALENDAR\n",
+                uid,
+                start,
+                start.replace("T10", "T11")
+            )
+        };
+        let a = parse(&text("a", "20240301T100000Z")).unwrap().event;
+        let b = parse(&text("b", "20240301T103000Z")).unwrap().event;
+        let c = parse(&text("c", "20240301T110000Z")).unwrap().event;
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+
+        let all_day = parse("BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:d\nDTSTART;VALUE=DATE:20240301\nEND:VEVENT\nEND:VCALENDAR")
+            .unwrap()
+            .event;
+        assert!(matches!(all_day.start, When::Date(_)));
+        assert_eq!(
+            all_day.end,
+            When::Date(NaiveDate::from_ymd_opt(2024, 3, 2).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let invite = |uid: &str, seq: u32, start: &str, extra: &str| {
+            parse(&format!(
+                "BEGIN:VCALENDAR\nMETHOD:REQUEST\nBEGIN:VEVENT\nUID:{}\nSEQUENCE:{}\n\
+                 DTSTART:{}\nDURATION:PT1H\n{}END:VEVENT\nEND:VCALENDAR\n",
+                uid, seq, start, extra
+            ))
+            .unwrap()
+        };
+        let mine = invite("mine", 0, "20240301T100000Z", "").event;
+        let others = vec![
+            invite("mine", 1, "20240301T100000Z", ""),
+            invite("moved", 0, "20240301T103000Z", ""),
+            invite("moved", 1, "20240301T150000Z", ""),
+            invite("later", 0, "20240301T110000Z", ""),
+            invite("cancelled", 0, "20240301T100000Z", ""),
+            invite("cancelled", 0, "20240301T100000Z", "STATUS:CANCELLED\n"),
+            invite(
+                "declined",
+                0,
+                "20240301T100000Z",
+                "ATTENDEE;PARTSTAT=DECLINED:mailto:Me@example.com\n",
+            ),
+            invite("clash", 2, "20240301T093000Z", ""),
+        ];
+
+        let found = conflicts(&mine, others, &["me@example.com"]);
+        let uids: Vec<&str> = found.iter().map(|e| e.uid.as_str()).collect();
+        assert_eq!(uids, ["clash"]);
+    }
+
+    #[test]
+    fn test_cancel_and_invalid() {
+        let cancel = OUTLOOK.replace("METHOD:REQUEST", "METHOD:CANCEL");
+        let invite = parse(&cancel).unwrap();
+        assert!(invite.is_cancelled());
+        assert!(!invite.is_request());
+        assert!(parse("BEGIN:VCALENDAR\nEND:VCALENDAR").is_none());
+    }
+
+    #[test]
+    fn test_reply() {
+        let invite = parse(OUTLOOK).unwrap();
+        let me = Person {
+            email: "bob@example.com".to_string(),
+            name: Some("Bob".to_string()),
+        };
+        let now = DateTime::parse_from_rfc3339("2024-07-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let ics = reply(&invite, &me, PartStat::Accepted, now);
+        assert!(ics.contains("METHOD:REPLY\r\n"));
+        assert!(ics.contains("UID:040000008200E00074C5B7101A82E008\r\n"));
+        assert!(ics.contains("SEQUENCE:2\r\n"));
+        assert!(ics.contains("DTSTAMP:20240701T120000Z\r\n"));
+        assert!(ics.contains("DTSTART:20240715T080000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Planning\\, Q3\r\n"));
+        assert!(ics.contains("ORGANIZER;CN=\"Alice Example\":mailto:alice@example.com\r\n"));
+        assert!(ics.contains("ATTENDEE;PARTSTAT=ACCEPTED;CN=\"Bob\":mailto:bob@example.com\r\n"));
+        assert!(ics.lines().all(|l| l.len() <= 75));
+        assert_eq!(method_of(&ics).as_deref(), Some("REPLY"));
+
+        // The reply parses back with the new status
+        let parsed = parse(&ics).unwrap();
+        assert_eq!(parsed.method, Method::Reply);
+        assert_eq!(parsed.event.attendees[0].status, PartStat::Accepted);
+        assert_eq!(parsed.event.start, invite.event.start);
+    }
+}
@@ -0,0 +1,172 @@
+//! Content lines shared by iCalendar (RFC 5545) and vCard (RFC 6350)
+//!
+//! Both formats are lists of `NAME;PARAM=value:value` lines, folded at 75
+//! octets, with the same TEXT escaping. vCard 2.1 quirks (groups, bare
+//! parameters, quoted-printable soft breaks) are accepted here too since
+//! they can't appear in a valid iCalendar object.
+
+/// One content line: `group.NAME;PARAM=value:value`
+#[derive(Debug)]
+pub struct Property {
+    /// Upper-cased, without the group prefix
+    pub name: String,
+    /// Upper-cased names; a bare parameter has an empty value
+    pub params: Vec<(String, String)>,
+    /// Still escaped (see `unescape`)
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Join folded lines: a leading space or tab continues the previous line,
+/// and so does a trailing `=` in a vCard 2.1 quoted-printable value
+pub fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut soft_break = false;
+    for line in text.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if soft_break && let Some(last) = lines.last_mut() {
+            last.pop(); // the '='
+            last.push_str(line);
+        } else if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else {
+            lines.push(line.to_string());
+        }
+        let last = lines.last().map(String::as_str).unwrap_or("");
+        soft_break = last.ends_with('=') && last.to_ascii_uppercase().contains("QUOTED-PRINTABLE");
+    }
+    lines
+}
+
+pub fn parse_property(line: &str) -> Option<Property> {
+    // Parameters end at the first colon outside a quoted parameter value
+    let mut in_quotes = false;
+    let mut sections: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut value_start = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                sections.push(&line[start..i]);
+                start = i + 1;
+            }
+            ':' if !in_quotes => {
+                sections.push(&line[start..i]);
+                value_start = Some(i + 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value = line[value_start?..].to_string();
+    let mut sections = sections.into_iter();
+    let name = sections.next()?.trim();
+    // Strip the group prefix ("item1.EMAIL")
+    let name = name.rsplit('.').next().unwrap_or(name).to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = sections
+        .map(|s| match s.split_once('=') {
+            Some((k, v)) => (
+                k.trim().to_ascii_uppercase(),
+                v.trim().trim_matches('"').to_string(),
+            ),
+            None => (s.trim().to_ascii_uppercase(), String::new()),
+        })
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+/// Undo TEXT escaping (RFC 5545 3.3.11, RFC 6350 3.4)
+pub fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets and terminate it with CRLF
+pub fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_property() {
+        let prop =
+            parse_property(r#"ATTENDEE;CN="Doe; John";RSVP=TRUE:mailto:john@example.com"#).unwrap();
+        assert_eq!(prop.name, "ATTENDEE");
+        assert_eq!(prop.param("cn"), Some("Doe; John"));
+        assert_eq!(prop.param("RSVP"), Some("TRUE"));
+        assert_eq!(prop.value, "mailto:john@example.com");
+
+        // vCard groups and bare parameters
+        let prop = parse_property("item1.email;INTERNET;pref:a@example.com").unwrap();
+        assert_eq!(prop.name, "EMAIL");
+        assert_eq!(prop.param("INTERNET"), Some(""));
+        assert_eq!(prop.param("PREF"), Some(""));
+
+        assert!(parse_property("no colon").is_none());
+        assert!(parse_property(":value").is_none());
+    }
+
+    #[test]
+    fn test_fold_round_trip() {
+        let line = format!("SUMMARY:{}", escape(&"Planning, review; notes\n".repeat(8)));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+
+        let lines = unfold(&folded);
+        assert_eq!(lines, vec![line.clone()]);
+        let prop = parse_property(&lines[0]).unwrap();
+        assert_eq!(unescape(&prop.value), "Planning, review; notes\n".repeat(8));
+    }
+}
@@ -0,0 +1,85 @@
+//! Helpers for the external tools that do our cryptography (gpg, openssl)
+
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::{Context, Result, anyhow};
+
+/// Run `command` with `data` on stdin and collect its output
+pub fn run_piped(command: &mut Command, data: &[u8]) -> Result<Output> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    // Write from another thread so a full stdout pipe can't deadlock us
+    let mut stdin = child.stdin.take().context("stdin unavailable")?;
+    let input = data.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output()?;
+    // The tool may stop reading early (e.g. bad input); its exit status says why
+    let _ = writer.join();
+    Ok(output)
+}
+
+/// Temporary file removed on drop, for tools that only read some inputs
+/// from files (detached signatures, signed content, certificates)
+pub struct TempFile {
+    pub path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new(contents: &[u8]) -> Result<Self> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "bltz-{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        // Owner only: these can hold decrypted message content
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&path)
+            .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+        file.write_all(contents)?;
+        Ok(Self { path })
+    }
+
+    /// Path as a command line argument
+    pub fn arg(&self) -> Result<&str> {
+        self.path.to_str().context("Temporary path is not UTF-8")
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = TempFile::new(b"secret").unwrap();
+        let mode = std::fs::metadata(&file.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read(&file.path).unwrap(), b"secret");
+    }
+}
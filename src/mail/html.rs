@@ -0,0 +1,297 @@
+//! HTML part rendering
+//!
+//! Lays HTML out as text at a given width, keeping emphasis, headings,
+//! code and table borders as styled runs so the UI can map them to its
+//! theme. Each link gets a `[N]` marker matching its footnote number.
+
+use bitflags::bitflags;
+use html2text::render::RichAnnotation;
+
+use super::links::{self, Link};
+
+/// Narrowest width html2text is asked to lay out
+const MIN_WIDTH: usize = 20;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct TextStyle: u8 {
+        const BOLD = 0b0000_0001;
+        const ITALIC = 0b0000_0010;
+        const STRIKE = 0b0000_0100;
+        const CODE = 0b0000_1000;
+        const LINK = 0b0001_0000;
+        /// `[N]` marker after a link
+        const MARKER = 0b0010_0000;
+        const HEADING = 0b0100_0000;
+        /// Table borders
+        const RULE = 0b1000_0000;
+    }
+}
+
+/// A stretch of text in one style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+pub type StyledLine = Vec<Run>;
+
+/// HTML laid out as styled lines, with the links and images it contains
+#[derive(Debug, Clone, Default)]
+pub struct Rendered {
+    pub lines: Vec<StyledLine>,
+    pub links: Vec<Link>,
+    /// `src` of each `<img>`, in document order
+    pub images: Vec<String>,
+}
+
+impl Rendered {
+    /// The rendered text without styling
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.lines {
+            for run in line {
+                text.push_str(&run.text);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Lay HTML out at `width` columns
+pub fn render(html: &str, width: usize) -> Option<Rendered> {
+    let html = bold_as_strong(html);
+    let tagged = html2text::config::rich()
+        .lines_from_read(html.as_bytes(), width.max(MIN_WIDTH))
+        .ok()?;
+
+    let mut rendered = Rendered::default();
+    // Link being rendered: (url, text so far, line the run last touched)
+    let mut open: Option<(String, String, usize)> = None;
+
+    for line in &tagged {
+        rendered.lines.push(Vec::new());
+        let row = rendered.lines.len() - 1;
+        for piece in line.tagged_strings() {
+            let url = piece.tag.iter().find_map(|a| match a {
+                RichAnnotation::Link(url) => Some(url.as_str()),
+                _ => None,
+            });
+            for tag in &piece.tag {
+                if let RichAnnotation::Image(src) = tag
+                    && !rendered.images.contains(src)
+                {
+                    rendered.images.push(src.clone());
+                }
+            }
+            if open.as_ref().map(|(u, ..)| u.as_str()) != url
+                && let Some((url, text, end)) = open.take()
+            {
+                close_link(&mut rendered, end, url, &text);
+            }
+            if let Some(url) = url {
+                let (_, text, end) =
+                    open.get_or_insert_with(|| (url.to_string(), String::new(), row));
+                if *end != row && !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(piece.s.trim());
+                *end = row;
+            }
+            push_styled(&mut rendered.lines[row], &piece.s, style_of(&piece.tag));
+        }
+        mark_heading(&mut rendered.lines[row]);
+    }
+    if let Some((url, text, end)) = open.take() {
+        close_link(&mut rendered, end, url, &text);
+    }
+
+    Some(rendered)
+}
+
+/// Append the marker for a finished link and record the link
+fn close_link(rendered: &mut Rendered, row: usize, url: String, text: &str) {
+    if !links::is_followable(&url) {
+        return;
+    }
+    let number = match rendered.links.iter().position(|l| l.url == url) {
+        Some(i) => i + 1,
+        None => {
+            let text = text.trim();
+            let text = (!text.is_empty() && text != url).then(|| text.to_string());
+            rendered.links.push(Link { text, url });
+            rendered.links.len()
+        }
+    };
+    push_run(
+        &mut rendered.lines[row],
+        &format!("[{}]", number),
+        TextStyle::MARKER,
+    );
+}
+
+fn style_of(tags: &[RichAnnotation]) -> TextStyle {
+    let mut style = TextStyle::empty();
+    for tag in tags {
+        style |= match tag {
+            RichAnnotation::Strong => TextStyle::BOLD,
+            RichAnnotation::Emphasis | RichAnnotation::Image(_) => TextStyle::ITALIC,
+            RichAnnotation::Strikeout => TextStyle::STRIKE,
+            RichAnnotation::Code | RichAnnotation::Preformat(_) => TextStyle::CODE,
+            RichAnnotation::Link(_) => TextStyle::LINK,
+            _ => TextStyle::empty(),
+        };
+    }
+    style
+}
+
+/// Push text, splitting out the box-drawing characters html2text draws
+/// table borders with
+fn push_styled(line: &mut StyledLine, text: &str, style: TextStyle) {
+    let is_rule = |c: char| ('\u{2500}'..='\u{257f}').contains(&c);
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let rule = is_rule(c);
+        let end = rest
+            .find(|c: char| is_rule(c) != rule)
+            .unwrap_or(rest.len());
+        let part_style = if rule { TextStyle::RULE } else { style };
+        push_run(line, &rest[..end], part_style);
+        rest = &rest[end..];
+    }
+}
+
+/// html2text only knows `<strong>`, but mail is full of `<b>`
+fn bold_as_strong(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(i) = rest.find('<') {
+        out.push_str(&rest[..i]);
+        let tag = &rest[i + 1..];
+        let name_at = usize::from(tag.starts_with('/'));
+        let is_b = tag[name_at..].starts_with(['b', 'B'])
+            && tag[name_at + 1..].starts_with(|c: char| c == '>' || c.is_ascii_whitespace());
+        if is_b {
+            out.push_str(if name_at == 1 { "</strong" } else { "<strong" });
+            rest = &tag[name_at + 1..];
+        } else {
+            out.push('<');
+            rest = tag;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// html2text prefixes headings with `#`s; style the whole line
+fn mark_heading(line: &mut StyledLine) {
+    let is_heading = line.first().is_some_and(|run| {
+        let hashes = run.text.chars().take_while(|&c| c == '#').count();
+        hashes > 0 && run.text[hashes..].starts_with(' ')
+    });
+    if is_heading {
+        for run in line {
+            run.style |= TextStyle::HEADING;
+        }
+    }
+}
+
+fn push_run(line: &mut StyledLine, text: &str, style: TextStyle) {
+    if text.is_empty() {
+        return;
+    }
+    match line.last_mut() {
+        Some(last) if last.style == style => last.text.push_str(text),
+        _ => line.push(Run {
+            text: text.to_string(),
+            style,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styled(rendered: &Rendered, style: TextStyle) -> Vec<String> {
+        rendered
+            .lines
+            .iter()
+            .flatten()
+            .filter(|run| run.style.contains(style))
+            .map(|run| run.text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_numbers_links() {
+        let html = r##"<p>See <a href="https://example.com/a">the docs</a> and
+            <a href="https://example.com/b">this</a>, or <a href="https://example.com/a">again</a>.
+            <a href="#top">Top</a></p>"##;
+        let rendered = render(html, 80).unwrap();
+        let text = rendered.text();
+
+        assert!(text.contains("the docs[1]"));
+        assert!(text.contains("this[2]"));
+        assert!(text.contains("again[1]"));
+        assert!(!text.contains("Top["), "fragment links are not listed");
+        assert_eq!(
+            styled(&rendered, TextStyle::MARKER),
+            vec!["[1]", "[2]", "[1]"]
+        );
+        assert_eq!(
+            rendered.links,
+            vec![
+                Link {
+                    text: Some("the docs".to_string()),
+                    url: "https://example.com/a".to_string(),
+                },
+                Link {
+                    text: Some("this".to_string()),
+                    url: "https://example.com/b".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_follows_width() {
+        let html = format!("<p>{}</p>", "word ".repeat(40));
+        let narrow = render(&html, 30).unwrap();
+        let wide = render(&html, 120).unwrap();
+
+        assert!(narrow.lines.len() > wide.lines.len());
+        assert!(narrow.text().lines().all(|l| l.chars().count() <= 30));
+    }
+
+    #[test]
+    fn test_render_styles() {
+        let html = "<h2>Title</h2><p><b>bold</b> <i>soft</i> <code>x = 1</code></p>\
+                    <table border=1><tr><td>a</td><td>b</td></tr><tr><td>c</td><td>d</td></tr></table>";
+        let rendered = render(html, 60).unwrap();
+
+        assert!(
+            styled(&rendered, TextStyle::HEADING)
+                .concat()
+                .contains("Title")
+        );
+        assert_eq!(styled(&rendered, TextStyle::BOLD), vec!["bold"]);
+        assert_eq!(styled(&rendered, TextStyle::ITALIC), vec!["soft"]);
+        assert_eq!(styled(&rendered, TextStyle::CODE), vec!["x = 1"]);
+        assert!(!styled(&rendered, TextStyle::RULE).is_empty());
+    }
+
+    #[test]
+    fn test_render_collects_images() {
+        let html = r#"<img src="cid:logo@x" alt="Logo"><p>Hi</p>
+            <img src="https://t.example/p.gif" alt="."><img src="cid:logo@x" alt="Logo">"#;
+        let rendered = render(html, 60).unwrap();
+
+        assert_eq!(
+            rendered.images,
+            vec!["cid:logo@x", "https://t.example/p.gif"]
+        );
+    }
+}
@@ -785,6 +785,63 @@ async fn handle_command(
             )
             .await;
         }
+        ImapCommand::FetchRaw { uid, folder } => {
+            let body_cache_key = folder_cache_key(account_id, &folder);
+
+            // First try the cache
+            if let Ok(Some(raw)) = cache.get_raw_message(&body_cache_key, uid).await {
+                event_tx.send(ImapEvent::RawFetched { uid, raw }).await.ok();
+                return;
+            }
+
+            // Save original folder for restoration after operation
+            let original_folder = current_folder.clone();
+            let needs_folder_switch = folder != *current_folder;
+
+            if needs_folder_switch && let Err(e) = client.select_folder(&folder).await {
+                event_tx
+                    .send(ImapEvent::RawFetchFailed {
+                        uid,
+                        error: format!("Failed to select folder: {}", e),
+                    })
+                    .await
+                    .ok();
+                return;
+            }
+
+            match client.fetch_raw(uid).await {
+                Ok(raw) => {
+                    // Cache the raw message alongside the parsed body for future use
+                    let body = crate::mail::parser::parse_body(&raw);
+                    if let Err(e) = cache
+                        .insert_email_body_with_raw(&body_cache_key, uid, &body, &raw)
+                        .await
+                    {
+                        tracing::warn!("Failed to cache raw message for UID {}: {}", uid, e);
+                    }
+                    event_tx.send(ImapEvent::RawFetched { uid, raw }).await.ok();
+                }
+                Err(e) => {
+                    event_tx
+                        .send(ImapEvent::RawFetchFailed {
+                            uid,
+                            error: e.to_string(),
+                        })
+                        .await
+                        .ok();
+                }
+            }
+
+            // Switch back to original folder (with recovery on failure)
+            restore_folder_after_operation(
+                client,
+                current_folder,
+                &original_folder,
+                needs_folder_switch,
+                event_tx,
+            )
+            .await;
+        }
         ImapCommand::Shutdown => {
             // Handled in the main loop
         }
@@ -1002,6 +1059,9 @@ impl ImapClient {
             // Sync flags for existing emails first
             self.sync_flags(cache, &cache_key).await?;
 
+            // Fill in sizes for emails cached before sizes were stored
+            self.backfill_sizes(cache, &cache_key).await?;
+
             // Detect server-side deletions by comparing server UIDs with cached UIDs
             let server_uids = self.fetch_all_uids().await?;
             let deleted = cache.delete_emails_not_in(&cache_key, &server_uids).await?;
@@ -1113,4 +1173,40 @@ impl ImapClient {
 
         Ok(())
     }
+
+    /// Fetch RFC822.SIZE for cached emails that don't have a size yet
+    async fn backfill_sizes(&mut self, cache: &Cache, account_id: &str) -> Result<()> {
+        let uids = cache.get_uids_without_size(account_id).await?;
+        if uids.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!("Fetching sizes for {} cached emails", uids.len());
+
+        for chunk in uids.chunks(FLAG_SYNC_BATCH_SIZE) {
+            let uid_set = chunk
+                .iter()
+                .map(|uid| uid.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let session = self.session()?;
+            let mut messages = session
+                .uid_fetch(&uid_set, "(UID RFC822.SIZE)")
+                .await
+                .context("Failed to fetch sizes")?;
+
+            let mut sizes = Vec::with_capacity(chunk.len());
+            while let Some(result) = messages.next().await {
+                let fetch = result.context("Failed to fetch message size")?;
+                if let (Some(uid), Some(size)) = (fetch.uid, fetch.size) {
+                    sizes.push((uid, size));
+                }
+            }
+
+            cache.update_sizes(account_id, &sizes).await?;
+        }
+
+        Ok(())
+    }
 }
@@ -223,7 +223,7 @@ impl ImapClient {
         let mut messages = session
             .uid_fetch(
                 sequence,
-                "(UID FLAGS BODY.PEEK[HEADER] BODY.PEEK[TEXT]<0.200>)",
+                "(UID FLAGS RFC822.SIZE BODY.PEEK[HEADER] BODY.PEEK[TEXT]<0.200>)",
             )
             .await
             .context("Failed to fetch messages")?;
@@ -238,6 +238,7 @@ impl ImapClient {
         }
 
         // Sort by date descending
+        #[allow(clippy::unnecessary_sort_by)]
         headers.sort_by(|a, b| b.date.cmp(&a.date));
 
         tracing::info!("Fetched {} email headers", headers.len());
@@ -476,5 +477,7 @@ pub(crate) fn parse_fetch(fetch: &Fetch) -> Option<EmailHeader> {
     raw.extend_from_slice(b"\r\n\r\n");
     raw.extend_from_slice(body_preview);
 
-    parse_envelope(uid, &raw, flags)
+    let mut header = parse_envelope(uid, &raw, flags)?;
+    header.size = fetch.size.unwrap_or(0);
+    Some(header)
 }
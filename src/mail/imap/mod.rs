@@ -189,6 +189,11 @@ pub enum ImapCommand {
         folder: String,
         attachment_index: usize,
     },
+    /// Fetch the full raw message (RFC 5322 source)
+    FetchRaw {
+        uid: u32,
+        folder: String,
+    },
     Shutdown,
 }
 
@@ -251,6 +256,16 @@ pub enum ImapEvent {
         attachment_index: usize,
         error: String,
     },
+    /// Raw message fetched successfully
+    RawFetched {
+        uid: u32,
+        raw: Vec<u8>,
+    },
+    /// Raw message fetch failed
+    RawFetchFailed {
+        uid: u32,
+        error: String,
+    },
     Error(ImapError),
 }
 
@@ -131,6 +131,7 @@ pub async fn parallel_fetch_all_headers(
     }
 
     // Step 5: Sort all headers by date descending
+    #[allow(clippy::unnecessary_sort_by)]
     all_headers.sort_by(|a, b| b.date.cmp(&a.date));
 
     tracing::info!(
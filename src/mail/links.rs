@@ -0,0 +1,241 @@
+//! Links in message bodies
+//!
+//! HTML parts mark each link with a `[N]` (see `html::render`), and plain
+//! text parts are scanned for bare URLs, so every link can be listed under
+//! the body and followed by its number. The host shown next to each link
+//! comes from the parsed URL, not from the text the sender chose to display.
+
+use super::html;
+use super::types::EmailBody;
+
+/// URL schemes worth listing and opening
+const SCHEMES: [&str; 3] = ["https://", "http://", "mailto:"];
+
+/// A link found in a message body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// Text the link was displayed as (None when it was the URL itself)
+    pub text: Option<String>,
+    pub url: String,
+}
+
+impl Link {
+    /// Host the link really points to
+    pub fn domain(&self) -> Option<String> {
+        if let Some(rest) = strip_scheme(&self.url, "mailto:") {
+            let address = rest.split('?').next().unwrap_or_default();
+            return address
+                .rsplit_once('@')
+                .map(|(_, host)| host.to_ascii_lowercase());
+        }
+        let url = reqwest::Url::parse(&self.url).ok()?;
+        url.host_str().map(str::to_ascii_lowercase)
+    }
+
+    /// Whether the text reads like an address on a different host than the target
+    pub fn is_deceptive(&self) -> bool {
+        let Some(shown) = self.text.as_deref().and_then(text_domain) else {
+            return false;
+        };
+        let Some(target) = self.domain() else {
+            return true;
+        };
+        let target = target.strip_prefix("www.").unwrap_or(&target);
+        target != shown && !target.ends_with(&format!(".{}", shown))
+    }
+}
+
+/// Find bare URLs in plain text
+pub fn scan_text(text: &str) -> Vec<Link> {
+    let mut links: Vec<Link> = Vec::new();
+    for word in text.split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"')) {
+        let Some(start) = SCHEMES
+            .iter()
+            .filter_map(|s| find_ignore_case(word, s))
+            .min()
+        else {
+            continue;
+        };
+        let url = trim_url(&word[start..]);
+        if is_followable(url) && !links.iter().any(|l| l.url == url) {
+            links.push(Link {
+                text: None,
+                url: url.to_string(),
+            });
+        }
+    }
+    links
+}
+
+/// Every link in the body, numbered in the order the displayed part shows them
+pub fn collect(body: &EmailBody, html_shown: bool) -> Vec<Link> {
+    let html_links = body
+        .html
+        .as_deref()
+        .and_then(|html| html::render(html, 80))
+        .map(|rendered| rendered.links)
+        .unwrap_or_default();
+    let text_links = body.text.as_deref().map(scan_text).unwrap_or_default();
+    let (mut links, rest) = if html_shown {
+        (html_links, text_links)
+    } else {
+        (text_links, html_links)
+    };
+    for link in rest {
+        if !links.iter().any(|l| l.url == link.url) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+pub(super) fn is_followable(url: &str) -> bool {
+    SCHEMES
+        .iter()
+        .any(|s| strip_scheme(url, s).is_some_and(|rest| rest.len() > 1))
+}
+
+fn strip_scheme<'a>(url: &'a str, scheme: &str) -> Option<&'a str> {
+    url.get(..scheme.len())
+        .filter(|head| head.eq_ignore_ascii_case(scheme))
+        .map(|_| &url[scheme.len()..])
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .char_indices()
+        .map(|(i, _)| i)
+        .find(|&i| strip_scheme(&haystack[i..], needle).is_some())
+}
+
+/// Drop punctuation that ends the sentence rather than the URL
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', ']', '*']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(inner) if inner.matches('(').count() <= inner.matches(')').count() => inner,
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// Host named by link text that looks like a URL or bare domain
+fn text_domain(text: &str) -> Option<String> {
+    let text = text.trim().to_ascii_lowercase();
+    let rest = SCHEMES
+        .iter()
+        .find_map(|s| strip_scheme(&text, s))
+        .unwrap_or(&text);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.split(':').next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let plausible = host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    // A TLD is never numeric-only, which rules out prices and version numbers
+    let tld = host.rsplit('.').next()?;
+    (plausible && tld.chars().any(|c| c.is_ascii_alphabetic())).then(|| host.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(text: Option<&str>, url: &str) -> Link {
+        Link {
+            text: text.map(str::to_string),
+            url: url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_scan_text() {
+        let links = scan_text(
+            "Read https://example.com/x.html. Also (https://en.wikipedia.org/wiki/Rust_(language))\n\
+             <mailto:list@example.org?subject=hi> and https://example.com/x.html again, ftp://no",
+        );
+        let urls: Vec<_> = links.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/x.html",
+                "https://en.wikipedia.org/wiki/Rust_(language)",
+                "mailto:list@example.org?subject=hi",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_follows_displayed_part() {
+        let body = EmailBody {
+            text: Some("Home: https://example.com/".to_string()),
+            html: Some(
+                r#"<a href="https://example.com/">Home</a> <a href="https://b.example/">B</a>"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let urls: Vec<_> = collect(&body, false).into_iter().map(|l| l.url).collect();
+        assert_eq!(urls, vec!["https://example.com/", "https://b.example/"]);
+
+        let body = EmailBody {
+            text: Some("Also https://c.example/ and https://b.example/".to_string()),
+            ..body
+        };
+        let urls: Vec<_> = collect(&body, true).into_iter().map(|l| l.url).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/",
+                "https://b.example/",
+                "https://c.example/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_domain() {
+        assert_eq!(
+            link(None, "https://Example.COM/path").domain().as_deref(),
+            Some("example.com")
+        );
+        // Userinfo does not hide the real host
+        assert_eq!(
+            link(None, "https://bank.com@evil.example/login")
+                .domain()
+                .as_deref(),
+            Some("evil.example")
+        );
+        assert_eq!(
+            link(None, "mailto:Me@Example.org?subject=x")
+                .domain()
+                .as_deref(),
+            Some("example.org")
+        );
+    }
+
+    #[test]
+    fn test_is_deceptive() {
+        assert!(link(Some("www.bank.com"), "https://evil.example/").is_deceptive());
+        assert!(
+            link(
+                Some("https://bank.com/login"),
+                "https://bank.com.evil.example/"
+            )
+            .is_deceptive()
+        );
+        assert!(!link(Some("bank.com"), "https://www.bank.com/login").is_deceptive());
+        assert!(!link(Some("bank.com"), "https://secure.bank.com/").is_deceptive());
+        assert!(!link(Some("Click here"), "https://evil.example/").is_deceptive());
+        assert!(!link(Some("Version 1.2"), "https://evil.example/").is_deceptive());
+    }
+}
@@ -0,0 +1,309 @@
+//! Mailing-list headers and unsubscribing
+//!
+//! Lists identify themselves with List-Id (RFC 2919) and advertise
+//! List-Unsubscribe, List-Archive and List-Post URIs (RFC 2369).
+//! Unsubscribing prefers RFC 8058 one-click, an HTTPS POST that needs no
+//! browser; otherwise a mailto: URI becomes a message for the user to send.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use mail_parser::Message;
+
+use super::types::{ComposeEmail, ListInfo};
+
+/// Body of an RFC 8058 one-click unsubscribe request
+const ONE_CLICK_BODY: &str = "List-Unsubscribe=One-Click";
+
+/// HTTP client for one-click requests, built on first use (None = failed)
+static CLIENT: OnceLock<Option<reqwest::Client>> = OnceLock::new();
+
+/// Read the list headers of a message (None if it has none)
+pub fn parse(message: &Message) -> Option<ListInfo> {
+    let header = |name: &str| message.header_raw(name).map(unfold);
+
+    let (name, id) = header("List-Id")
+        .map(|v| parse_list_id(&v))
+        .unwrap_or_default();
+    let unsubscribe = header("List-Unsubscribe")
+        .map(|v| angle_uris(&v))
+        .unwrap_or_default();
+    let one_click =
+        header("List-Unsubscribe-Post").is_some_and(|v| v.eq_ignore_ascii_case(ONE_CLICK_BODY));
+    let archive = header("List-Archive").and_then(|v| angle_uris(&v).into_iter().next());
+    // "List-Post: NO" marks an announce-only list
+    let post = header("List-Post")
+        .and_then(|v| angle_uris(&v).into_iter().find(|u| is_scheme(u, "mailto")))
+        .and_then(|uri| mailto(&uri).map(|m| m.to));
+
+    if id.is_none() && unsubscribe.is_empty() && archive.is_none() && post.is_none() {
+        return None;
+    }
+    Some(ListInfo {
+        id,
+        name,
+        unsubscribe,
+        one_click,
+        archive,
+        post,
+    })
+}
+
+/// Collapse folding whitespace in a raw header value
+fn unfold(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `Description <list.id>` → (description, id); the brackets are optional
+fn parse_list_id(value: &str) -> (Option<String>, Option<String>) {
+    let (name, id) = match (value.find('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => (&value[..start], &value[start + 1..end]),
+        _ => ("", value),
+    };
+    let name = name.trim().trim_matches('"').trim();
+    let id = id.trim().to_lowercase();
+    (
+        (!name.is_empty()).then(|| name.to_string()),
+        (!id.is_empty()).then_some(id),
+    )
+}
+
+/// The `<uri>` entries of an RFC 2369 header, whitespace removed
+fn angle_uris(value: &str) -> Vec<String> {
+    value
+        .split('<')
+        .skip(1)
+        .filter_map(|part| part.split_once('>'))
+        .map(|(uri, _)| uri.split_whitespace().collect::<String>())
+        .filter(|uri| !uri.is_empty())
+        .collect()
+}
+
+fn is_scheme(uri: &str, scheme: &str) -> bool {
+    uri.split_once(':')
+        .is_some_and(|(s, _)| s.eq_ignore_ascii_case(scheme))
+}
+
+/// Whether a header URI is a web page (safe to hand to the browser; other
+/// schemes such as file: or custom handlers are not)
+pub fn is_web(uri: &str) -> bool {
+    is_scheme(uri, "https") || is_scheme(uri, "http")
+}
+
+/// How to leave a list
+#[derive(Debug, Clone)]
+pub enum Unsubscribe {
+    /// RFC 8058 POST to this HTTPS URI
+    OneClick(String),
+    /// Send this message
+    Mail(Box<ComposeEmail>),
+    /// Only a web page is offered; open it in the browser
+    Web(String),
+}
+
+/// Best way to unsubscribe: one-click, then mail, then the web page
+pub fn unsubscribe_method(list: &ListInfo) -> Option<Unsubscribe> {
+    let https = list.unsubscribe.iter().find(|u| is_scheme(u, "https"));
+    if list.one_click
+        && let Some(uri) = https
+    {
+        return Some(Unsubscribe::OneClick(uri.clone()));
+    }
+    if let Some(mail) = list
+        .unsubscribe
+        .iter()
+        .filter(|u| is_scheme(u, "mailto"))
+        .find_map(|u| mailto(u))
+    {
+        return Some(Unsubscribe::Mail(Box::new(mail)));
+    }
+    https
+        .or_else(|| list.unsubscribe.iter().find(|u| is_scheme(u, "http")))
+        .map(|uri| Unsubscribe::Web(uri.clone()))
+}
+
+/// Turn an RFC 6068 mailto: URI into a message
+pub fn mailto(uri: &str) -> Option<ComposeEmail> {
+    let (scheme, rest) = uri.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case("mailto") {
+        return None;
+    }
+    let (to, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut email = ComposeEmail {
+        to: percent_decode(to),
+        ..ComposeEmail::new()
+    };
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value);
+        match key.to_ascii_lowercase().as_str() {
+            "to" if email.to.is_empty() => email.to = value,
+            "to" => email.to = format!("{}, {}", email.to, value),
+            "cc" => email.cc = value,
+            "subject" => email.subject = value,
+            "body" => email.body = value,
+            _ => {}
+        }
+    }
+    if email.to.trim().is_empty() {
+        return None;
+    }
+    if email.subject.is_empty() {
+        email.subject = "unsubscribe".to_string();
+    }
+    Some(email)
+}
+
+/// Decode %XX escapes ('+' stays literal in URIs)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Send an RFC 8058 one-click unsubscribe request
+///
+/// The request carries no cookies or credentials, as the RFC requires.
+pub async fn one_click(uri: &str) -> Result<()> {
+    let client = CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .inspect_err(|e| tracing::warn!("Failed to create HTTP client: {}", e))
+                .ok()
+        })
+        .as_ref()
+        .context("Failed to create HTTP client")?;
+    let response = client
+        .post(uri)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .body(ONE_CLICK_BODY)
+        .send()
+        .await
+        .context("Unsubscribe request failed")?;
+    let status = response.status();
+    // Some senders answer with a redirect to a confirmation page
+    if !status.is_success() && !status.is_redirection() {
+        bail!("Unsubscribe request failed: HTTP {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mail_parser::MessageParser;
+
+    fn parse_raw(raw: &str) -> Option<ListInfo> {
+        parse(&MessageParser::default().parse(raw.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn test_parse_list_headers() {
+        let list = parse_raw(
+            "From: news@example.com\r\n\
+             List-Id: \"Example News\" <News.Example.com>\r\n\
+             List-Unsubscribe: <mailto:leave@example.com?subject=bye>,\r\n\
+             \t<https://example.com/unsub?u=1&l=2>\r\n\
+             List-Unsubscribe-Post: List-Unsubscribe=One-Click\r\n\
+             List-Archive: <https://lists.example.com/archive/>\r\n\
+             List-Post: <mailto:news@lists.example.com>\r\n\
+             Subject: Hi\r\n\r\nBody",
+        )
+        .unwrap();
+        assert_eq!(list.id.as_deref(), Some("news.example.com"));
+        assert_eq!(list.name.as_deref(), Some("Example News"));
+        assert_eq!(list.label(), Some("Example News"));
+        assert_eq!(
+            list.unsubscribe,
+            [
+                "mailto:leave@example.com?subject=bye",
+                "https://example.com/unsub?u=1&l=2"
+            ]
+        );
+        assert!(list.one_click);
+        assert_eq!(
+            list.archive.as_deref(),
+            Some("https://lists.example.com/archive/")
+        );
+        assert_eq!(list.post.as_deref(), Some("news@lists.example.com"));
+        assert!(matches!(
+            unsubscribe_method(&list),
+            Some(Unsubscribe::OneClick(uri)) if uri == "https://example.com/unsub?u=1&l=2"
+        ));
+    }
+
+    #[test]
+    fn test_no_list_headers() {
+        assert_eq!(
+            parse_raw("From: a@example.com\r\nSubject: Hi\r\n\r\nBody"),
+            None
+        );
+        let list = parse_raw("List-Id: <dev.example.org>\r\nList-Post: NO\r\n\r\nBody").unwrap();
+        assert_eq!(list.label(), Some("dev"));
+        assert_eq!(list.post, None);
+    }
+
+    #[test]
+    fn test_unsubscribe_fallbacks() {
+        let mut list = ListInfo {
+            unsubscribe: vec![
+                "https://example.com/u".to_string(),
+                "mailto:leave%2Bme@example.com?Subject=unsubscribe%20me&body=now".to_string(),
+            ],
+            ..Default::default()
+        };
+        let Some(Unsubscribe::Mail(mail)) = unsubscribe_method(&list) else {
+            panic!("expected a mail unsubscribe");
+        };
+        assert_eq!(mail.to, "leave+me@example.com");
+        assert_eq!(mail.subject, "unsubscribe me");
+        assert_eq!(mail.body, "now");
+
+        list.unsubscribe.pop();
+        assert!(matches!(
+            unsubscribe_method(&list),
+            Some(Unsubscribe::Web(uri)) if uri == "https://example.com/u"
+        ));
+        list.unsubscribe.clear();
+        assert!(unsubscribe_method(&list).is_none());
+    }
+
+    #[test]
+    fn test_mailto_defaults() {
+        let mail = mailto("mailto:off@example.com").unwrap();
+        assert_eq!(mail.subject, "unsubscribe");
+        assert!(mailto("mailto:?subject=x").is_none());
+        assert!(mailto("https://example.com").is_none());
+    }
+
+    #[test]
+    fn test_is_web() {
+        assert!(is_web("HTTPS://lists.example.com/archive/"));
+        assert!(!is_web("file:///etc/passwd"));
+        assert!(!is_web("smb://host/share"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Markdown rendering for outgoing mail
+//!
+//! The composer body is plain text that may optionally be treated as
+//! CommonMark. When enabled, the message is sent as multipart/alternative
+//! with the original text and an HTML rendering of it.
+
+use pulldown_cmark::{Event, Options, Parser, html};
+
+/// Render a Markdown body to an HTML document suitable for the text/html part.
+///
+/// Single newlines are kept as line breaks (mail authors don't expect
+/// paragraph reflow), and `>` quoted reply text becomes `<blockquote>`.
+pub fn render_html(body: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+
+    // Treat soft breaks as hard breaks so line-oriented mail text keeps its shape
+    let parser = Parser::new_ext(body, options).map(|event| match event {
+        Event::SoftBreak => Event::HardBreak,
+        // Raw HTML in the body is shown literally rather than injected
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+
+    let mut content = String::with_capacity(body.len() * 3 / 2);
+    html::push_html(&mut content, parser);
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        STYLE, content
+    )
+}
+
+/// Minimal inline stylesheet so quotes and code look reasonable in most clients
+const STYLE: &str = "blockquote { margin: 0 0 0 0.8ex; border-left: 2px solid #ccc; padding-left: 1ex; color: #555; }
+pre { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }
+code { font-family: monospace; }
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_lists_and_code() {
+        let html = render_html("Hello\n\n- one\n- two\n\n```\nlet x = 1;\n```\n");
+        assert!(html.contains("<li>one</li>"));
+        assert!(html.contains("<pre><code>let x = 1;"));
+    }
+
+    #[test]
+    fn test_quoted_reply_becomes_blockquote() {
+        let body = "Thanks!\n\nOn Jan 01, 2024 at 12:00, Alice wrote:\n> first line\n> second line";
+        let html = render_html(body);
+        assert!(html.contains("<blockquote>"));
+        assert!(html.contains("first line<br />\nsecond line"));
+    }
+
+    #[test]
+    fn test_single_newlines_are_preserved() {
+        let html = render_html("line one\nline two");
+        assert!(html.contains("line one<br />"));
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped() {
+        let html = render_html("<script>alert(1)</script>");
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+}
@@ -1,6 +1,20 @@
+pub mod address;
+pub mod auth;
+pub mod calendar;
+pub mod content_line;
+mod external;
+pub mod html;
 pub mod imap;
+pub mod links;
+pub mod list;
+pub mod markdown;
 pub mod parser;
+pub mod pgp;
+pub mod quotes;
+pub mod signature;
+pub mod smime;
 pub mod smtp;
+pub mod template;
 pub mod thread;
 pub mod types;
 
@@ -11,4 +25,4 @@ pub use imap::{
     spawn_imap_actor,
 };
 pub use smtp::SmtpClient;
-pub use thread::{EmailThread, ThreadId, group_into_threads, merge_into_threads};
+pub use thread::{EmailThread, ThreadId, group_into_threads, merge_into_threads, sort_threads};
@@ -1,6 +1,15 @@
-use mail_parser::{MessageParser, MimeHeaders, PartType};
+use mail_parser::{
+    Addr, Address, HeaderValue, Message, MessageParser, MessagePart, MimeHeaders, PartType,
+};
 
-use super::types::{Attachment, EmailBody, EmailFlags, EmailHeader};
+use super::auth;
+use super::list;
+use super::pgp::{self, PgpBackend};
+use super::smime::{self, SmimeBackend};
+use super::types::{
+    Attachment, ComposeAttachment, EmailBody, EmailFlags, EmailHeader, Scheme, SecurityInfo,
+    SignatureState, SignatureStatus, Trust,
+};
 
 pub fn parse_envelope(uid: u32, raw: &[u8], flags: EmailFlags) -> Option<EmailHeader> {
     let message = match MessageParser::default().parse(raw) {
@@ -61,7 +70,7 @@ pub fn parse_envelope(uid: u32, raw: &[u8], flags: EmailFlags) -> Option<EmailHe
 
     let message_id = message.message_id().map(|s| s.to_string());
 
-    let has_attachments = message.attachments().count() > 0;
+    let has_attachments = user_attachments(&message).next().is_some();
 
     let preview = extract_preview(&message, 100);
 
@@ -93,18 +102,336 @@ pub fn parse_envelope(uid: u32, raw: &[u8], flags: EmailFlags) -> Option<EmailHe
         in_reply_to,
         references,
         folder: None, // Set by caller when storing
+        list: list::parse(&message),
+        size: 0, // Set by caller from RFC822.SIZE
     })
 }
 
+/// Parse a fetched body, checking signatures. Encrypted messages are kept
+/// as ciphertext: decrypting can ask for a passphrase, so it waits until
+/// the message is opened (`decrypt_body`).
 pub fn parse_body(raw: &[u8]) -> EmailBody {
+    let crypto = Crypto {
+        pgp: pgp::backend(),
+        smime: smime::backend(),
+        decrypt: false,
+    };
+    parse_body_with(raw, crypto)
+}
+
+/// Parse a body, decrypting it if it is encrypted (blocks on the crypto
+/// tools, so run it off the event loop)
+pub fn decrypt_body(raw: &[u8]) -> EmailBody {
+    let crypto = Crypto {
+        pgp: pgp::backend(),
+        smime: smime::backend(),
+        decrypt: true,
+    };
+    parse_body_with(raw, crypto)
+}
+
+/// Backends used to check signatures and decrypt while parsing
+#[derive(Clone, Copy, Default)]
+struct Crypto<'a> {
+    pgp: Option<&'a dyn PgpBackend>,
+    smime: Option<&'a dyn SmimeBackend>,
+    /// Decrypt encrypted messages (else leave them locked)
+    decrypt: bool,
+}
+
+/// Parse a body, verifying and decrypting PGP/MIME and S/MIME
+fn parse_body_with(raw: &[u8], crypto: Crypto) -> EmailBody {
     let Some(message) = MessageParser::default().parse(raw) else {
         return EmailBody::default();
     };
 
-    let text = extract_text_body(&message);
-    let html = extract_html_body(&message);
+    let mut body = match secure_parts(&message) {
+        Some(SecureParts::PgpEncrypted(_)) if !crypto.decrypt => locked_body(Scheme::OpenPgp, raw),
+        Some(SecureParts::SmimeEnveloped(_)) if !crypto.decrypt => locked_body(Scheme::Smime, raw),
+        Some(SecureParts::PgpEncrypted(ciphertext)) => decrypt_pgp(ciphertext, crypto),
+        Some(SecureParts::SmimeEnveloped(enveloped)) => decrypt_smime(enveloped, crypto),
+        Some(SecureParts::SmimeOpaqueSigned(signed)) => verify_opaque(signed, crypto),
+        Some(SecureParts::Signed {
+            scheme,
+            data,
+            signature,
+        }) => {
+            let mut body = plain_body(&message);
+            let data = &raw[data.0.min(raw.len())..data.1.min(raw.len())];
+            body.security = Some(verify_signature(scheme, data, signature, crypto));
+            body
+        }
+        None => plain_body(&message),
+    };
+    // Trace headers live on the outer message, not any decrypted entity
+    body.auth_results = auth_headers(&message, raw);
+    body
+}
+
+/// The message's authentication headers as unfolded `Name: value` lines
+fn auth_headers(message: &Message, raw: &[u8]) -> Vec<String> {
+    message
+        .headers()
+        .iter()
+        .filter(|h| {
+            h.name().eq_ignore_ascii_case(auth::HEADER)
+                || h.name().eq_ignore_ascii_case(auth::ARC_HEADER)
+        })
+        .filter_map(|h| {
+            let value = raw.get(h.offset_start as usize..h.offset_end as usize)?;
+            let value = String::from_utf8_lossy(value);
+            Some(format!(
+                "{}: {}",
+                h.name(),
+                value.split_whitespace().collect::<Vec<_>>().join(" ")
+            ))
+        })
+        .collect()
+}
+
+fn plain_body(message: &Message) -> EmailBody {
+    EmailBody {
+        text: extract_text_body(message),
+        html: extract_html_body(message),
+        security: None,
+        auth_results: Vec::new(),
+        calendar: extract_calendar(message),
+        ciphertext: None,
+    }
+}
+
+/// The pieces of a signed or encrypted message (RFC 3156 / RFC 8551)
+enum SecureParts<'a> {
+    /// multipart/encrypted: the ciphertext part's contents
+    PgpEncrypted(&'a [u8]),
+    /// application/pkcs7-mime enveloped-data
+    SmimeEnveloped(&'a [u8]),
+    /// application/pkcs7-mime signed-data (content inside the signature)
+    SmimeOpaqueSigned(&'a [u8]),
+    /// multipart/signed: byte range of the signed entity (headers included)
+    /// and the detached signature
+    Signed {
+        scheme: Scheme,
+        data: (usize, usize),
+        signature: &'a [u8],
+    },
+}
+
+fn is_mime_type(value: &str, types: &[&str]) -> bool {
+    types.iter().any(|t| value.eq_ignore_ascii_case(t))
+}
+
+const PKCS7_MIME: &[&str] = &["application/pkcs7-mime", "application/x-pkcs7-mime"];
+const PKCS7_SIGNATURE: &[&str] = &[
+    "application/pkcs7-signature",
+    "application/x-pkcs7-signature",
+];
+
+fn secure_parts<'a>(message: &'a Message) -> Option<SecureParts<'a>> {
+    let root = message.root_part();
+    let content_type = root.content_type()?;
+    let mime_type = format!(
+        "{}/{}",
+        content_type.ctype(),
+        content_type.subtype().unwrap_or_default()
+    );
+
+    if is_mime_type(&mime_type, PKCS7_MIME) {
+        // smime-type is optional; a ".p7s"-style signed-data is the rarer case
+        let smime_type = content_type.attribute("smime-type").unwrap_or_default();
+        return Some(if smime_type.eq_ignore_ascii_case("signed-data") {
+            SecureParts::SmimeOpaqueSigned(root.contents())
+        } else {
+            SecureParts::SmimeEnveloped(root.contents())
+        });
+    }
+
+    let PartType::Multipart(ref children) = root.body else {
+        return None;
+    };
+    let first = message.part(*children.first()?)?;
+    let second = message.part(*children.get(1)?)?;
+    let protocol = content_type.attribute("protocol").unwrap_or_default();
+
+    if mime_type.eq_ignore_ascii_case("multipart/encrypted")
+        && protocol.eq_ignore_ascii_case("application/pgp-encrypted")
+    {
+        // The first part only holds "Version: 1"
+        return Some(SecureParts::PgpEncrypted(second.contents()));
+    }
+    if mime_type.eq_ignore_ascii_case("multipart/signed") {
+        let scheme = if protocol.eq_ignore_ascii_case("application/pgp-signature") {
+            Scheme::OpenPgp
+        } else if is_mime_type(protocol, PKCS7_SIGNATURE) {
+            Scheme::Smime
+        } else {
+            return None;
+        };
+        // The part's range stops before the CRLF preceding the boundary,
+        // which is exactly what RFC 3156 and RFC 8551 say was signed
+        return Some(SecureParts::Signed {
+            scheme,
+            data: (first.offset_header as usize, first.offset_end as usize),
+            signature: second.contents(),
+        });
+    }
+    None
+}
+
+/// Parts that carry signatures or ciphertext rather than user content
+fn is_security_part(part: &MessagePart) -> bool {
+    part.content_type().is_some_and(|ct| {
+        let mime_type = format!("{}/{}", ct.ctype(), ct.subtype().unwrap_or_default());
+        is_mime_type(&mime_type, PKCS7_MIME)
+            || is_mime_type(&mime_type, PKCS7_SIGNATURE)
+            || is_mime_type(
+                &mime_type,
+                &["application/pgp-signature", "application/pgp-encrypted"],
+            )
+    })
+}
+
+/// Attachments the user sent, leaving out signatures and encrypted payloads
+fn user_attachments<'a>(message: &'a Message<'a>) -> impl Iterator<Item = &'a MessagePart<'a>> {
+    let encrypted = matches!(
+        secure_parts(message),
+        Some(SecureParts::PgpEncrypted(_) | SecureParts::SmimeEnveloped(_))
+    );
+    message
+        .attachments()
+        .filter(move |part| !encrypted && !is_security_part(part))
+}
+
+fn decrypt_pgp(ciphertext: &[u8], crypto: Crypto) -> EmailBody {
+    let Some(backend) = crypto.pgp else {
+        return failed_body(
+            Scheme::OpenPgp,
+            true,
+            "OpenPGP support is turned off".to_string(),
+        );
+    };
+    match backend.decrypt(ciphertext) {
+        Ok(decrypted) => {
+            // The plaintext is a MIME entity, possibly signed itself
+            let mut body = parse_body_with(&decrypted.plaintext, crypto);
+            let inner = body.security.take().unwrap_or_default();
+            body.security = Some(SecurityInfo {
+                scheme: Scheme::OpenPgp,
+                encrypted: true,
+                signature: decrypted.signature.or(inner.signature),
+                error: inner.error,
+            });
+            body
+        }
+        Err(e) => failed_body(Scheme::OpenPgp, true, e.to_string()),
+    }
+}
+
+fn decrypt_smime(enveloped: &[u8], crypto: Crypto) -> EmailBody {
+    let Some(backend) = crypto.smime else {
+        return failed_body(
+            Scheme::Smime,
+            true,
+            "S/MIME support is unavailable".to_string(),
+        );
+    };
+    match backend.decrypt(enveloped) {
+        Ok(plaintext) => {
+            // Usually signed first, then encrypted
+            let mut body = parse_body_with(&plaintext, crypto);
+            let inner = body.security.take().unwrap_or_default();
+            body.security = Some(SecurityInfo {
+                scheme: Scheme::Smime,
+                encrypted: true,
+                ..inner
+            });
+            body
+        }
+        Err(e) => failed_body(Scheme::Smime, true, e.to_string()),
+    }
+}
+
+fn verify_opaque(signed: &[u8], crypto: Crypto) -> EmailBody {
+    let result = match crypto.smime {
+        Some(backend) => backend.verify_opaque(signed),
+        None => Err(anyhow::anyhow!("S/MIME support is unavailable")),
+    };
+    match result {
+        Ok((content, status)) => {
+            let mut body = parse_body_with(&content, crypto);
+            body.security = Some(SecurityInfo {
+                scheme: Scheme::Smime,
+                signature: Some(status),
+                ..Default::default()
+            });
+            body
+        }
+        Err(e) => failed_body(Scheme::Smime, false, e.to_string()),
+    }
+}
+
+fn verify_signature(scheme: Scheme, data: &[u8], signature: &[u8], crypto: Crypto) -> SecurityInfo {
+    let result = match scheme {
+        Scheme::OpenPgp => crypto
+            .pgp
+            .ok_or_else(|| anyhow::anyhow!("OpenPGP support is turned off"))
+            .and_then(|backend| backend.verify(data, signature)),
+        Scheme::Smime => crypto
+            .smime
+            .ok_or_else(|| anyhow::anyhow!("S/MIME support is unavailable"))
+            .and_then(|backend| backend.verify(data, signature)),
+    };
+    match result {
+        Ok(status) => SecurityInfo {
+            scheme,
+            signature: Some(status),
+            ..Default::default()
+        },
+        Err(e) => SecurityInfo {
+            scheme,
+            encrypted: false,
+            signature: Some(SignatureStatus {
+                state: SignatureState::Error,
+                signer: None,
+                trust: Trust::Unknown,
+            }),
+            error: Some(e.to_string()),
+        },
+    }
+}
 
-    EmailBody { text, html }
+/// Body of an encrypted message left for `decrypt_body`
+fn locked_body(scheme: Scheme, raw: &[u8]) -> EmailBody {
+    EmailBody {
+        text: None,
+        html: None,
+        security: Some(SecurityInfo {
+            scheme,
+            encrypted: true,
+            signature: None,
+            error: None,
+        }),
+        auth_results: Vec::new(),
+        calendar: None,
+        ciphertext: Some(raw.into()),
+    }
+}
+
+/// Body of a message that couldn't be decrypted or unwrapped
+fn failed_body(scheme: Scheme, encrypted: bool, error: String) -> EmailBody {
+    EmailBody {
+        text: None,
+        html: None,
+        security: Some(SecurityInfo {
+            scheme,
+            encrypted,
+            signature: None,
+            error: Some(error),
+        }),
+        auth_results: Vec::new(),
+        calendar: None,
+        ciphertext: None,
+    }
 }
 
 /// Parse attachment metadata from raw email
@@ -113,8 +440,7 @@ pub fn parse_attachments(raw: &[u8]) -> Vec<Attachment> {
         return Vec::new();
     };
 
-    message
-        .attachments()
+    user_attachments(&message)
         .enumerate()
         .map(|(i, part)| {
             let filename = part
@@ -150,7 +476,7 @@ pub fn parse_attachments(raw: &[u8]) -> Vec<Attachment> {
 /// Extract binary data for a specific attachment by index
 pub fn extract_attachment_data(raw: &[u8], index: usize) -> Option<Vec<u8>> {
     let message = MessageParser::default().parse(raw)?;
-    let part = message.attachments().nth(index)?;
+    let part = user_attachments(&message).nth(index)?;
 
     match &part.body {
         PartType::Binary(data) => Some(data.to_vec()),
@@ -160,6 +486,132 @@ pub fn extract_attachment_data(raw: &[u8], index: usize) -> Option<Vec<u8>> {
     }
 }
 
+/// Extract every attachment with its data, ready to re-attach when forwarding
+pub fn extract_attachments_for_forward(raw: &[u8]) -> Vec<ComposeAttachment> {
+    parse_attachments(raw)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, attachment)| {
+            Some(ComposeAttachment {
+                data: extract_attachment_data(raw, index)?,
+                filename: attachment.filename,
+                mime_type: attachment.mime_type,
+            })
+        })
+        .collect()
+}
+
+/// Extract the image parts (inline `cid:` images and image attachments) as
+/// (name, data), those referenced from `html_images` first in that order
+pub fn extract_images(raw: &[u8], html_images: &[String]) -> Vec<(String, Vec<u8>)> {
+    let position = |attachment: &Attachment| {
+        attachment.content_id.as_deref().and_then(|cid| {
+            let cid = cid.trim_matches(['<', '>']);
+            html_images.iter().position(|src| {
+                src.strip_prefix("cid:")
+                    .is_some_and(|s| s.eq_ignore_ascii_case(cid))
+            })
+        })
+    };
+    let mut images: Vec<_> = parse_attachments(raw)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, a)| a.mime_type.starts_with("image/"))
+        .collect();
+    // Unreferenced images keep their order after the referenced ones
+    images.sort_by_key(|(_, a)| position(a).unwrap_or(usize::MAX));
+    images
+        .into_iter()
+        .filter_map(|(index, attachment)| {
+            Some((attachment.filename, extract_attachment_data(raw, index)?))
+        })
+        .collect()
+}
+
+/// Extract the addresses a message was delivered to (Delivered-To / X-Original-To)
+///
+/// These name the actual mailbox or alias that received the message, which
+/// may not appear in To/Cc (e.g. Bcc or list delivery).
+pub fn parse_delivered_to(raw: &[u8]) -> Vec<String> {
+    let Some(message) = MessageParser::default().parse_headers(raw) else {
+        return Vec::new();
+    };
+
+    message
+        .headers_raw()
+        .filter(|(name, _)| {
+            name.eq_ignore_ascii_case("Delivered-To") || name.eq_ignore_ascii_case("X-Original-To")
+        })
+        .map(|(_, value)| {
+            value
+                .trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string()
+        })
+        .filter(|addr| !addr.is_empty())
+        .collect()
+}
+
+/// Every header field in order, with encoded words and addresses decoded
+///
+/// Structured fields the parser keeps no text for (Received, Content-Type,
+/// ...) are shown as written, unfolded onto one line.
+pub fn parse_headers(raw: &[u8]) -> Vec<(String, String)> {
+    let Some(message) = MessageParser::default().parse_headers(raw) else {
+        return Vec::new();
+    };
+
+    message
+        .headers()
+        .iter()
+        .map(|header| {
+            let written = || {
+                let bytes = raw
+                    .get(header.offset_start as usize..header.offset_end as usize)
+                    .unwrap_or_default();
+                unfold(&String::from_utf8_lossy(bytes))
+            };
+            let value = match &header.value {
+                HeaderValue::Text(text) => unfold(text),
+                HeaderValue::TextList(list) => unfold(&list.join(", ")),
+                HeaderValue::Address(address) => format_address(address),
+                HeaderValue::DateTime(date) => date.to_rfc822(),
+                _ => written(),
+            };
+            (header.name().to_string(), value)
+        })
+        .collect()
+}
+
+fn format_address(address: &Address) -> String {
+    let addr = |a: &Addr| match (&a.name, &a.address) {
+        (Some(name), Some(address)) => format!("{} <{}>", name, address),
+        (Some(name), None) => name.to_string(),
+        (None, Some(address)) => address.to_string(),
+        (None, None) => String::new(),
+    };
+    match address {
+        Address::List(list) => list.iter().map(addr).collect::<Vec<_>>().join(", "),
+        Address::Group(groups) => groups
+            .iter()
+            .map(|group| {
+                let members = group.addresses.iter().map(addr).collect::<Vec<_>>();
+                match &group.name {
+                    Some(name) => format!("{}: {};", name, members.join(", ")),
+                    None => members.join(", "),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Join a folded header value onto one line
+fn unfold(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 fn extract_text_body(message: &mail_parser::Message) -> Option<String> {
     // First try to get text body parts
     for part in message.text_bodies() {
@@ -184,6 +636,24 @@ fn extract_text_body(message: &mail_parser::Message) -> Option<String> {
     None
 }
 
+/// The first text/calendar part, inline or attached (`.ics` files included)
+fn extract_calendar(message: &mail_parser::Message) -> Option<String> {
+    message
+        .parts
+        .iter()
+        .find(|part| {
+            part.content_type().is_some_and(|ct| {
+                ct.ctype().eq_ignore_ascii_case("text")
+                    && ct
+                        .subtype()
+                        .is_some_and(|s| s.eq_ignore_ascii_case("calendar"))
+                    || ct.ctype().eq_ignore_ascii_case("application")
+                        && ct.subtype().is_some_and(|s| s.eq_ignore_ascii_case("ics"))
+            })
+        })
+        .map(|part| String::from_utf8_lossy(part.contents()).into_owned())
+}
+
 fn extract_html_body(message: &mail_parser::Message) -> Option<String> {
     for part in message.html_bodies() {
         if let PartType::Html(html) = &part.body {
@@ -252,4 +722,364 @@ mod tests {
         assert!(body.text.is_some());
         assert!(body.text.unwrap().contains("Hello"));
     }
+
+    #[test]
+    fn test_parse_headers() {
+        let raw = b"Received: from mx.example.com\r\n\tby mail.example.org; Mon, 1 Jan 2024 10:00:00 +0000\r\n\
+From: =?UTF-8?Q?J=C3=BCrgen?= <j@example.com>\r\n\
+Subject: =?UTF-8?B?R3LDvMOfZQ==?=\r\n\
+DKIM-Signature: v=1; a=rsa-sha256;\r\n b=abc\r\n\
+\r\n\
+Body\r\n";
+        let headers = parse_headers(raw);
+        let names: Vec<&str> = headers.iter().map(|(name, _)| name.as_str()).collect();
+
+        assert_eq!(names, ["Received", "From", "Subject", "DKIM-Signature"]);
+        assert_eq!(
+            headers[0].1,
+            "from mx.example.com by mail.example.org; Mon, 1 Jan 2024 10:00:00 +0000"
+        );
+        assert_eq!(headers[1].1, "J\u{fc}rgen <j@example.com>");
+        assert_eq!(headers[2].1, "Gr\u{fc}\u{df}e");
+        assert_eq!(headers[3].1, "v=1; a=rsa-sha256; b=abc");
+    }
+
+    #[test]
+    fn test_extract_images() {
+        let raw = b"From: a@example.com\r\n\
+                    Subject: Screenshots\r\n\
+                    MIME-Version: 1.0\r\n\
+                    Content-Type: multipart/related; boundary=\"b\"\r\n\
+                    \r\n\
+                    --b\r\n\
+                    Content-Type: text/html\r\n\
+                    \r\n\
+                    <img src=\"cid:second\"><img src=\"cid:first\">\r\n\
+                    --b\r\n\
+                    Content-Type: image/png; name=\"one.png\"\r\n\
+                    Content-ID: <first>\r\n\
+                    Content-Transfer-Encoding: base64\r\n\
+                    \r\n\
+                    AQID\r\n\
+                    --b\r\n\
+                    Content-Type: application/pdf; name=\"doc.pdf\"\r\n\
+                    Content-Disposition: attachment; filename=\"doc.pdf\"\r\n\
+                    \r\n\
+                    %PDF\r\n\
+                    --b\r\n\
+                    Content-Type: image/png; name=\"two.png\"\r\n\
+                    Content-ID: <second>\r\n\
+                    Content-Transfer-Encoding: base64\r\n\
+                    \r\n\
+                    BAUG\r\n\
+                    --b--\r\n";
+
+        let html = vec!["cid:second".to_string(), "cid:first".to_string()];
+        let images = extract_images(raw, &html);
+        assert_eq!(
+            images,
+            vec![
+                ("two.png".to_string(), vec![4, 5, 6]),
+                ("one.png".to_string(), vec![1, 2, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_delivered_to() {
+        let raw = b"Delivered-To: support@example.com\r\n\
+                    X-Original-To: <alias@example.com>\r\n\
+                    From: sender@example.com\r\n\
+                    To: team@example.com\r\n\
+                    Subject: Help\r\n\
+                    \r\n\
+                    Body";
+
+        assert_eq!(
+            parse_delivered_to(raw),
+            vec!["support@example.com", "alias@example.com"]
+        );
+        assert!(parse_delivered_to(b"From: a@b.c\r\n\r\nBody").is_empty());
+    }
+
+    #[test]
+    fn test_parse_auth_results() {
+        let raw = b"Authentication-Results: mx.example.com;\r\n\
+                    \tdkim=pass header.d=example.org;\r\n\
+                    \tspf=pass smtp.mailfrom=example.org\r\n\
+                    ARC-Authentication-Results: i=1; mx.example.com; dmarc=pass\r\n\
+                    Received-SPF: pass\r\n\
+                    From: sender@example.org\r\n\
+                    Subject: Hi\r\n\
+                    \r\n\
+                    Body";
+
+        assert_eq!(
+            parse_body(raw).auth_results,
+            vec![
+                "Authentication-Results: mx.example.com; dkim=pass header.d=example.org; \
+                 spf=pass smtp.mailfrom=example.org",
+                "ARC-Authentication-Results: i=1; mx.example.com; dmarc=pass",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar_part() {
+        let raw = b"From: organizer@example.com\r\n\
+                    Subject: Invitation: Sync\r\n\
+                    MIME-Version: 1.0\r\n\
+                    Content-Type: multipart/alternative; boundary=\"b\"\r\n\
+                    \r\n\
+                    --b\r\n\
+                    Content-Type: text/plain\r\n\
+                    \r\n\
+                    You have been invited\r\n\
+                    --b\r\n\
+                    Content-Type: text/calendar; charset=utf-8; method=REQUEST\r\n\
+                    \r\n\
+                    BEGIN:VCALENDAR\r\n\
+                    METHOD:REQUEST\r\n\
+                    END:VCALENDAR\r\n\
+                    --b--\r\n";
+
+        let body = parse_body(raw);
+        assert_eq!(
+            body.text.as_deref().map(str::trim),
+            Some("You have been invited")
+        );
+        let calendar = body.calendar.unwrap();
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nMETHOD:REQUEST"));
+        assert!(parse_body(b"Subject: Hi\r\n\r\nBody").calendar.is_none());
+    }
+
+    /// Backend that "decrypts" by returning a fixed plaintext and checks
+    /// that the signed data is what RFC 3156 specifies
+    struct FakePgp;
+
+    impl PgpBackend for FakePgp {
+        fn decrypt(&self, ciphertext: &[u8]) -> anyhow::Result<pgp::Decrypted> {
+            if ciphertext.starts_with(b"-----BEGIN PGP MESSAGE-----") {
+                Ok(pgp::Decrypted {
+                    plaintext: b"Content-Type: text/plain\r\n\r\nSecret text".to_vec(),
+                    signature: None,
+                })
+            } else {
+                anyhow::bail!("No secret key")
+            }
+        }
+
+        fn verify(&self, data: &[u8], signature: &[u8]) -> anyhow::Result<SignatureStatus> {
+            assert_eq!(data, b"Content-Type: text/plain\r\n\r\nHello\r\nworld");
+            assert!(signature.starts_with(b"-----BEGIN PGP SIGNATURE-----"));
+            Ok(SignatureStatus {
+                state: SignatureState::Good,
+                signer: Some("Jane <jane@example.com>".to_string()),
+                trust: Trust::Full,
+            })
+        }
+
+        fn sign(&self, _: &[u8], _: &str) -> anyhow::Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        fn encrypt(&self, _: &[u8], _: &[String], _: Option<&str>) -> anyhow::Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        fn has_public_key(&self, _: &str) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_parse_pgp_signed() {
+        let raw = b"From: jane@example.com\r\n\
+                    Content-Type: multipart/signed; boundary=\"XX\"; protocol=\"application/pgp-signature\"; micalg=pgp-sha256\r\n\
+                    \r\n\
+                    --XX\r\n\
+                    Content-Type: text/plain\r\n\
+                    \r\n\
+                    Hello\r\n\
+                    world\r\n\
+                    --XX\r\n\
+                    Content-Type: application/pgp-signature\r\n\
+                    \r\n\
+                    -----BEGIN PGP SIGNATURE-----\r\n\
+                    abc\r\n\
+                    -----END PGP SIGNATURE-----\r\n\
+                    \r\n\
+                    --XX--\r\n";
+
+        let body = parse_body_with(raw, fake_crypto());
+        assert!(body.text.unwrap().contains("Hello"));
+        let security = body.security.unwrap();
+        assert!(!security.encrypted);
+        assert_eq!(security.signature.unwrap().state, SignatureState::Good);
+
+        // Without a backend the signature is reported as unchecked
+        let security = parse_body_with(raw, Crypto::default()).security.unwrap();
+        assert_eq!(security.signature.unwrap().state, SignatureState::Error);
+        assert!(security.error.is_some());
+    }
+
+    #[test]
+    fn test_parse_pgp_encrypted() {
+        let message = |ciphertext: &str| {
+            format!(
+                "From: jane@example.com\r\n\
+                 Content-Type: multipart/encrypted; boundary=\"XX\"; protocol=\"application/pgp-encrypted\"\r\n\
+                 \r\n\
+                 --XX\r\n\
+                 Content-Type: application/pgp-encrypted\r\n\
+                 \r\n\
+                 Version: 1\r\n\
+                 --XX\r\n\
+                 Content-Type: application/octet-stream\r\n\
+                 \r\n\
+                 {}\r\n\
+                 --XX--\r\n",
+                ciphertext
+            )
+        };
+
+        let raw = message("-----BEGIN PGP MESSAGE-----\r\nxyz\r\n-----END PGP MESSAGE-----");
+        let body = parse_body_with(raw.as_bytes(), fake_crypto());
+        assert_eq!(body.text.as_deref(), Some("Secret text"));
+        let security = body.security.unwrap();
+        assert!(security.encrypted);
+        assert_eq!(security.error, None);
+
+        let body = parse_body_with(message("garbage").as_bytes(), fake_crypto());
+        assert_eq!(body.text, None);
+        let security = body.security.unwrap();
+        assert!(security.encrypted);
+        assert_eq!(security.error.as_deref(), Some("No secret key"));
+
+        // When fetching, the ciphertext is kept for later
+        let crypto = Crypto {
+            decrypt: false,
+            ..fake_crypto()
+        };
+        let body = parse_body_with(raw.as_bytes(), crypto);
+        assert!(body.is_locked());
+        assert_eq!(body.text, None);
+        assert!(body.security.unwrap().encrypted);
+        assert_eq!(body.ciphertext.as_deref(), Some(raw.as_bytes()));
+    }
+
+    fn fake_crypto() -> Crypto<'static> {
+        Crypto {
+            pgp: Some(&FakePgp),
+            smime: Some(&FakeSmime),
+            decrypt: true,
+        }
+    }
+
+    /// Backend that accepts DER "signatures" equal to b"sig" and
+    /// "decrypts" b"enveloped" to a fixed signed entity
+    struct FakeSmime;
+
+    impl SmimeBackend for FakeSmime {
+        fn verify(&self, data: &[u8], signature: &[u8]) -> anyhow::Result<SignatureStatus> {
+            assert!(data.starts_with(b"Content-Type: text/plain"));
+            Ok(SignatureStatus {
+                state: if signature == b"sig" {
+                    SignatureState::Good
+                } else {
+                    SignatureState::Bad
+                },
+                signer: Some("Partner <p@partner.example>".to_string()),
+                trust: Trust::Full,
+            })
+        }
+
+        fn verify_opaque(&self, _: &[u8]) -> anyhow::Result<(Vec<u8>, SignatureStatus)> {
+            unimplemented!()
+        }
+
+        fn decrypt(&self, enveloped: &[u8]) -> anyhow::Result<Vec<u8>> {
+            anyhow::ensure!(enveloped == b"enveloped", "Not encrypted to us");
+            Ok(smime_signed("c2ln").into_bytes())
+        }
+
+        fn sign(&self, _: &[u8], _: &str) -> anyhow::Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        fn has_identity(&self, _: &str) -> bool {
+            false
+        }
+    }
+
+    /// multipart/signed with a base64 S/MIME signature
+    fn smime_signed(signature_b64: &str) -> String {
+        format!(
+            "Content-Type: multipart/signed; protocol=\"application/pkcs7-signature\"; micalg=sha-256; boundary=\"SS\"\r\n\
+             \r\n\
+             --SS\r\n\
+             Content-Type: text/plain\r\n\
+             \r\n\
+             Quarterly figures\r\n\
+             --SS\r\n\
+             Content-Type: application/pkcs7-signature; name=smime.p7s\r\n\
+             Content-Transfer-Encoding: base64\r\n\
+             Content-Disposition: attachment; filename=smime.p7s\r\n\
+             \r\n\
+             {}\r\n\
+             --SS--\r\n",
+            signature_b64
+        )
+    }
+
+    #[test]
+    fn test_parse_smime_signed() {
+        let raw = format!("From: p@partner.example\r\n{}", smime_signed("c2ln"));
+        let body = parse_body_with(raw.as_bytes(), fake_crypto());
+        assert!(body.text.unwrap().contains("Quarterly figures"));
+        let security = body.security.unwrap();
+        assert_eq!(security.scheme, Scheme::Smime);
+        assert_eq!(security.signature.unwrap().state, SignatureState::Good);
+
+        // smime.p7s isn't offered as an attachment
+        assert!(parse_attachments(raw.as_bytes()).is_empty());
+        let header = parse_envelope(1, raw.as_bytes(), EmailFlags::empty()).unwrap();
+        assert!(!header.has_attachments);
+
+        let tampered = format!("From: p@partner.example\r\n{}", smime_signed("YmFk"));
+        let security = parse_body_with(tampered.as_bytes(), fake_crypto())
+            .security
+            .unwrap();
+        assert_eq!(security.signature.unwrap().state, SignatureState::Bad);
+    }
+
+    #[test]
+    fn test_parse_smime_enveloped() {
+        let message = |payload_b64: &str| {
+            format!(
+                "From: p@partner.example\r\n\
+                 Content-Type: application/pkcs7-mime; smime-type=enveloped-data; name=smime.p7m\r\n\
+                 Content-Transfer-Encoding: base64\r\n\
+                 Content-Disposition: attachment; filename=smime.p7m\r\n\
+                 \r\n\
+                 {}\r\n",
+                payload_b64
+            )
+        };
+
+        let raw = message("ZW52ZWxvcGVk");
+        let body = parse_body_with(raw.as_bytes(), fake_crypto());
+        assert!(body.text.unwrap().contains("Quarterly figures"));
+        let security = body.security.unwrap();
+        assert_eq!(security.scheme, Scheme::Smime);
+        assert!(security.encrypted);
+        assert_eq!(security.signature.unwrap().state, SignatureState::Good);
+        assert!(parse_attachments(raw.as_bytes()).is_empty());
+
+        let security = parse_body_with(message("b3RoZXI=").as_bytes(), fake_crypto())
+            .security
+            .unwrap();
+        assert!(security.encrypted);
+        assert_eq!(security.error.as_deref(), Some("Not encrypted to us"));
+    }
 }
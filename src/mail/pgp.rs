@@ -0,0 +1,355 @@
+//! OpenPGP support (RFC 3156 PGP/MIME)
+//!
+//! Cryptography is delegated to a `PgpBackend`. The only implementation
+//! today drives the `gpg` binary, so keys, trust and passphrases all come
+//! from the user's GnuPG setup (gpg-agent handles pinentry).
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow, bail};
+
+use super::external::{TempFile, run_piped};
+use super::types::{SignatureState, SignatureStatus, Trust};
+use crate::config::{PgpBackendKind, PgpConfig};
+
+/// `micalg` parameter matching the digest `sign` uses
+pub const MICALG: &str = "pgp-sha256";
+
+/// Output of a successful decryption
+#[derive(Debug, Clone)]
+pub struct Decrypted {
+    /// The decrypted MIME entity
+    pub plaintext: Vec<u8>,
+    /// Signature found inside the encrypted data (combined sign + encrypt)
+    pub signature: Option<SignatureStatus>,
+}
+
+/// An OpenPGP implementation
+pub trait PgpBackend: Send + Sync {
+    /// Decrypt an armored or binary OpenPGP message
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Decrypted>;
+    /// Check a detached signature over `data`
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<SignatureStatus>;
+    /// Create an armored detached signature with `signer`'s secret key
+    fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>>;
+    /// Encrypt (and optionally sign) `data` to every recipient, armored
+    fn encrypt(&self, data: &[u8], recipients: &[String], signer: Option<&str>) -> Result<Vec<u8>>;
+    /// Whether a valid public key usable for encryption exists for `address`
+    fn has_public_key(&self, address: &str) -> bool;
+}
+
+static BACKEND: OnceLock<Option<Box<dyn PgpBackend>>> = OnceLock::new();
+
+/// Set up the configured backend (call once at startup)
+pub fn init(config: &PgpConfig) {
+    let backend: Option<Box<dyn PgpBackend>> = match config.backend {
+        PgpBackendKind::Gpg => Some(Box::new(Gpg::new(
+            config.gpg_path.clone().unwrap_or_else(|| "gpg".to_string()),
+        ))),
+        PgpBackendKind::Off => None,
+    };
+    let _ = BACKEND.set(backend);
+}
+
+/// The configured backend, if OpenPGP is enabled
+pub fn backend() -> Option<&'static dyn PgpBackend> {
+    BACKEND.get().and_then(|b| b.as_deref())
+}
+
+/// Backend driving the `gpg` command line tool
+pub struct Gpg {
+    program: String,
+}
+
+/// Result of one gpg invocation
+struct GpgOutput {
+    ok: bool,
+    stdout: Vec<u8>,
+    /// `[GNUPG:]` lines from the status file
+    status: String,
+    /// Human-readable messages (may quote message data, never parsed)
+    stderr: String,
+}
+
+/// What gpg reported on its status channel
+#[derive(Debug, Default)]
+struct GpgStatus {
+    signature: Option<SignatureStatus>,
+    decryption_ok: bool,
+    no_secret_key: bool,
+}
+
+impl Gpg {
+    pub fn new(program: String) -> Self {
+        Self { program }
+    }
+
+    /// Run gpg with `data` on stdin
+    ///
+    /// Status lines go to a file of their own: on stderr they would mix
+    /// with messages that can echo user IDs and file names from the message,
+    /// which could then pass for status lines.
+    fn run(&self, args: &[&str], data: &[u8]) -> Result<GpgOutput> {
+        let status_file = TempFile::new(b"")?;
+        let output = run_piped(
+            Command::new(&self.program)
+                .args(["--batch", "--no-tty", "--status-file", status_file.arg()?])
+                .args(args),
+            data,
+        )?;
+        let status = std::fs::read(&status_file.path).unwrap_or_default();
+        Ok(GpgOutput {
+            ok: output.status.success(),
+            stdout: output.stdout,
+            status: String::from_utf8_lossy(&status).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+impl PgpBackend for Gpg {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Decrypted> {
+        let output = self.run(&["--decrypt"], ciphertext)?;
+        let status = parse_status(&output.status);
+        if !status.decryption_ok {
+            if status.no_secret_key {
+                bail!("No secret key to decrypt this message");
+            }
+            bail!("Decryption failed: {}", last_error(&output.stderr));
+        }
+        if !output.ok && status.signature.is_none() {
+            bail!("Decryption failed: {}", last_error(&output.stderr));
+        }
+        Ok(Decrypted {
+            plaintext: output.stdout,
+            signature: status.signature,
+        })
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<SignatureStatus> {
+        let sig_file = TempFile::new(signature)?;
+        let output = self.run(&["--verify", sig_file.arg()?, "-"], data)?;
+        parse_status(&output.status)
+            .signature
+            .ok_or_else(|| anyhow!("Verification failed: {}", last_error(&output.stderr)))
+    }
+
+    fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>> {
+        let output = self.run(
+            &[
+                "--armor",
+                "--detach-sign",
+                "--digest-algo",
+                "SHA256",
+                "--local-user",
+                signer,
+            ],
+            data,
+        )?;
+        if !output.ok {
+            bail!(
+                "Signing as {} failed: {}",
+                signer,
+                last_error(&output.stderr)
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    fn encrypt(&self, data: &[u8], recipients: &[String], signer: Option<&str>) -> Result<Vec<u8>> {
+        let mut args = vec!["--armor", "--encrypt"];
+        if let Some(signer) = signer {
+            args.extend(["--sign", "--digest-algo", "SHA256", "--local-user", signer]);
+        }
+        for recipient in recipients {
+            args.extend(["--recipient", recipient.as_str()]);
+        }
+        let output = self.run(&args, data)?;
+        if !output.ok {
+            bail!("Encryption failed: {}", last_error(&output.stderr));
+        }
+        Ok(output.stdout)
+    }
+
+    fn has_public_key(&self, address: &str) -> bool {
+        // "<addr>" makes gpg match the email part of user IDs exactly
+        let pattern = format!("<{}>", address);
+        match self.run(&["--with-colons", "--list-keys", &pattern], b"") {
+            Ok(output) if output.ok => has_usable_key(&String::from_utf8_lossy(&output.stdout)),
+            _ => false,
+        }
+    }
+}
+
+/// Interpret gpg's `[GNUPG:]` status lines
+fn parse_status(output: &str) -> GpgStatus {
+    let mut status = GpgStatus::default();
+    let mut trust = Trust::Unknown;
+
+    for line in output.lines() {
+        let Some(rest) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let mut fields = rest.splitn(3, ' ');
+        let keyword = fields.next().unwrap_or_default();
+        let key_id = fields.next().unwrap_or_default();
+        let user_id = fields.next().filter(|s| !s.is_empty());
+
+        let state = match keyword {
+            "GOODSIG" => SignatureState::Good,
+            "BADSIG" => SignatureState::Bad,
+            "EXPKEYSIG" => SignatureState::ExpiredKey,
+            "REVKEYSIG" => SignatureState::RevokedKey,
+            "ERRSIG" => {
+                // ERRSIG <keyid> <algo> <hash> <class> <time> <rc>; rc 9 = no public key
+                if rest.split(' ').nth(6) == Some("9") {
+                    SignatureState::UnknownKey
+                } else {
+                    SignatureState::Error
+                }
+            }
+            "TRUST_UNDEFINED" => {
+                trust = Trust::Unknown;
+                continue;
+            }
+            "TRUST_NEVER" => {
+                trust = Trust::Never;
+                continue;
+            }
+            "TRUST_MARGINAL" => {
+                trust = Trust::Marginal;
+                continue;
+            }
+            "TRUST_FULLY" => {
+                trust = Trust::Full;
+                continue;
+            }
+            "TRUST_ULTIMATE" => {
+                trust = Trust::Ultimate;
+                continue;
+            }
+            "DECRYPTION_OKAY" => {
+                status.decryption_ok = true;
+                continue;
+            }
+            "NO_SECKEY" => {
+                status.no_secret_key = true;
+                continue;
+            }
+            _ => continue,
+        };
+
+        // Only the first signature is reported; ERRSIG has no user ID
+        if status.signature.is_none() {
+            let signer = if keyword == "ERRSIG" {
+                Some(format!("key {}", key_id))
+            } else {
+                user_id.map(str::to_string)
+            };
+            status.signature = Some(SignatureStatus {
+                state,
+                signer,
+                trust: Trust::Unknown,
+            });
+        }
+    }
+
+    if let Some(ref mut signature) = status.signature {
+        signature.trust = trust;
+    }
+    status
+}
+
+/// Whether `--with-colons` key listing has a valid key that can encrypt
+fn has_usable_key(listing: &str) -> bool {
+    listing.lines().any(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        // Validity: m(arginal), f(ull), u(ltimate); uppercase E = key can encrypt
+        fields.first() == Some(&"pub")
+            && fields.get(1).is_some_and(|v| matches!(*v, "m" | "f" | "u"))
+            && fields.get(11).is_some_and(|caps| caps.contains('E'))
+    })
+}
+
+/// Last human-readable gpg message, for error reports
+fn last_error(stderr: &str) -> String {
+    stderr
+        .lines()
+        .rev()
+        .find(|line| !line.starts_with("[GNUPG:]") && !line.trim().is_empty())
+        .map(|line| line.trim_start_matches("gpg: ").to_string())
+        .unwrap_or_else(|| "unknown gpg error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_good_signature() {
+        let status = parse_status(
+            "[GNUPG:] NEWSIG\n\
+             [GNUPG:] GOODSIG 0123456789ABCDEF Jane Doe <jane@example.com>\n\
+             [GNUPG:] VALIDSIG ABC\n\
+             [GNUPG:] TRUST_FULLY 0 pgp\n",
+        );
+        let signature = status.signature.unwrap();
+        assert_eq!(signature.state, SignatureState::Good);
+        assert_eq!(
+            signature.signer.as_deref(),
+            Some("Jane Doe <jane@example.com>")
+        );
+        assert_eq!(signature.trust, Trust::Full);
+    }
+
+    #[test]
+    fn test_parse_missing_key_and_decryption() {
+        let status = parse_status(
+            "gpg: encrypted with rsa3072 key\n\
+             [GNUPG:] DECRYPTION_OKAY\n\
+             [GNUPG:] ERRSIG 0123456789ABCDEF 1 8 00 1700000000 9 -\n\
+             [GNUPG:] NO_PUBKEY 0123456789ABCDEF\n",
+        );
+        assert!(status.decryption_ok);
+        let signature = status.signature.unwrap();
+        assert_eq!(signature.state, SignatureState::UnknownKey);
+        assert_eq!(signature.signer.as_deref(), Some("key 0123456789ABCDEF"));
+        assert_eq!(signature.trust, Trust::Unknown);
+    }
+
+    #[test]
+    fn test_parse_bad_signature_and_no_seckey() {
+        let status = parse_status(
+            "[GNUPG:] NO_SECKEY 0123456789ABCDEF\n\
+             [GNUPG:] BADSIG 0123456789ABCDEF Mallory <m@example.com>\n",
+        );
+        assert!(status.no_secret_key);
+        assert!(!status.decryption_ok);
+        assert_eq!(status.signature.unwrap().state, SignatureState::Bad);
+    }
+
+    #[test]
+    fn test_usable_key_listing() {
+        let valid = "tru::1:1700000000:0:3:1:5\n\
+                     pub:u:3072:1:0123456789ABCDEF:1700000000:::u:::scESC::::::23::0:\n\
+                     uid:u::::1700000000::HASH::Jane <jane@example.com>::::::::::0:\n";
+        assert!(has_usable_key(valid));
+        // Unknown validity (not certified)
+        assert!(!has_usable_key(&valid.replace("pub:u:", "pub:-:")));
+        // Revoked
+        assert!(!has_usable_key(&valid.replace("pub:u:", "pub:r:")));
+        // Signing-only key
+        assert!(!has_usable_key(&valid.replace("scESC", "scSC")));
+    }
+
+    #[test]
+    fn test_last_error() {
+        assert_eq!(
+            last_error(
+                "[GNUPG:] FAILURE sign 17\ngpg: signing failed: No secret key\n[GNUPG:] X\n"
+            ),
+            "signing failed: No secret key"
+        );
+    }
+}
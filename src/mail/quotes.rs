@@ -0,0 +1,263 @@
+//! Quoted history and signature detection in message bodies
+//!
+//! Works on display lines, so the same rules cover plain text and HTML
+//! laid out as text (where blockquotes come out as `>` lines).
+
+/// Blocks shorter than this are left in place (folding them saves nothing)
+const MIN_BLOCK_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    /// `>` lines, with the "On … wrote:" line before them, or everything
+    /// after an Outlook "Original Message" separator
+    Quote,
+    /// Everything from a "-- " separator to the next quoted block
+    Signature,
+}
+
+/// A run of lines that can be folded away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Block {
+    pub kind: BlockKind,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Block {
+    pub fn contains(&self, line: usize) -> bool {
+        (self.start..self.start + self.len).contains(&line)
+    }
+}
+
+/// A displayed row: a body line, or a folded block in place of its lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Row {
+    Line(usize),
+    Folded(Block),
+}
+
+impl Row {
+    /// First body line the row shows
+    pub fn first_line(&self) -> usize {
+        match *self {
+            Row::Line(line) => line,
+            Row::Folded(block) => block.start,
+        }
+    }
+
+    /// Whether the row shows (or folds away) body line `line`
+    pub fn covers(&self, line: usize) -> bool {
+        match *self {
+            Row::Line(l) => l == line,
+            Row::Folded(block) => block.contains(line),
+        }
+    }
+}
+
+/// Find the quoted blocks and signature in `lines`, in order
+pub fn detect<S: AsRef<str>>(lines: &[S]) -> Vec<Block> {
+    let lines: Vec<&str> = lines.iter().map(AsRef::as_ref).collect();
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if is_original_message(&lines, i) {
+            // Outlook quotes by appending the whole previous message
+            blocks.push(Block {
+                kind: BlockKind::Quote,
+                start: i,
+                len: lines.len() - i,
+            });
+            break;
+        }
+        if let Some(start) = quote_start(&lines, i) {
+            let end = quote_end(&lines, start);
+            blocks.push(Block {
+                kind: BlockKind::Quote,
+                start: i,
+                len: end - i,
+            });
+            i = end;
+        } else if lines[i].trim_end() == "--" {
+            // The signature runs until quoted history (top posting) or the end
+            let end = (i + 1..lines.len())
+                .find(|&j| is_original_message(&lines, j) || quote_start(&lines, j).is_some())
+                .unwrap_or(lines.len());
+            let end = trim_blank(&lines, i, end);
+            blocks.push(Block {
+                kind: BlockKind::Signature,
+                start: i,
+                len: end - i,
+            });
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    blocks.retain(|b| b.len >= MIN_BLOCK_LINES);
+    blocks
+}
+
+/// Rows to display `line_count` lines with `folded` blocks collapsed
+pub fn rows(line_count: usize, folded: &[Block]) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(line_count);
+    let mut blocks = folded.iter().peekable();
+    let mut line = 0;
+    while line < line_count {
+        match blocks.next_if(|b| b.start == line) {
+            Some(block) => {
+                rows.push(Row::Folded(*block));
+                line += block.len;
+            }
+            None => {
+                rows.push(Row::Line(line));
+                line += 1;
+            }
+        }
+    }
+    rows
+}
+
+fn is_quoted(line: &str) -> bool {
+    line.trim_start().starts_with('>')
+}
+
+/// Where the `>` lines start if a quote starts at line `i`: right there,
+/// or after an attribution line ("On Mon, Jan 1, Bob wrote:", possibly
+/// wrapped over two lines) and blank lines
+fn quote_start(lines: &[&str], i: usize) -> Option<usize> {
+    if is_quoted(lines[i]) {
+        return Some(i);
+    }
+    let attribution_len = if is_attribution(lines[i]) {
+        1
+    } else if lines[i].trim_start().starts_with("On ")
+        && lines.get(i + 1).is_some_and(|next| is_attribution(next))
+    {
+        2
+    } else {
+        return None;
+    };
+    let next = (i + attribution_len..lines.len()).find(|&j| !lines[j].trim().is_empty())?;
+    is_quoted(lines[next]).then_some(next)
+}
+
+fn is_attribution(line: &str) -> bool {
+    let line = line.trim();
+    ["wrote:", "schrieb:", "a écrit :", "a écrit:", "escribió:"]
+        .iter()
+        .any(|end| line.ends_with(end))
+}
+
+/// End of the `>` run starting at `start` (blank lines inside it included)
+fn quote_end(lines: &[&str], start: usize) -> usize {
+    let mut end = start;
+    let mut j = start;
+    while j < lines.len() {
+        if is_quoted(lines[j]) {
+            end = j + 1;
+        } else if !lines[j].trim().is_empty() {
+            break;
+        }
+        j += 1;
+    }
+    end
+}
+
+/// "-----Original Message-----", or Outlook's underscore rule before "From:"
+fn is_original_message(lines: &[&str], i: usize) -> bool {
+    let line = lines[i].trim();
+    let squashed: String = line
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    if squashed.starts_with("-----originalmessage") {
+        return true;
+    }
+    line.len() >= 20
+        && line.chars().all(|c| c == '_')
+        && lines
+            .get(i + 1)
+            .is_some_and(|next| next.trim_start().starts_with("From:"))
+}
+
+/// Pull `end` back over blank lines (but not before `start + 1`)
+fn trim_blank(lines: &[&str], start: usize, mut end: usize) -> usize {
+    while end > start + 1 && lines[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(BlockKind, usize, usize)> {
+        let lines: Vec<&str> = text.lines().collect();
+        detect(&lines)
+            .into_iter()
+            .map(|b| (b.kind, b.start, b.len))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_quotes_and_signature() {
+        let text = "Sounds good.\n\
+                    \n\
+                    -- \n\
+                    Bob\n\
+                    Example Inc.\n\
+                    \n\
+                    On Mon, Jan 1, 2024 at 10:00 AM Alice <a@example.com>\n\
+                    wrote:\n\
+                    > Shall we meet?\n\
+                    >\n\
+                    > Alice\n";
+
+        assert_eq!(
+            kinds(text),
+            vec![(BlockKind::Signature, 2, 3), (BlockKind::Quote, 6, 5)]
+        );
+    }
+
+    #[test]
+    fn test_detect_keeps_short_inline_quotes() {
+        let text = "> one question?\n\
+                    Answer.\n\
+                    > another\n\
+                    >\n\
+                    > and more\n\
+                    Second answer.";
+
+        assert_eq!(kinds(text), vec![(BlockKind::Quote, 2, 3)]);
+    }
+
+    #[test]
+    fn test_detect_outlook_history() {
+        let text = "Fine by me.\n\
+                    \n\
+                    -----Original Message-----\n\
+                    From: Alice\n\
+                    Sent: Monday\n\
+                    \n\
+                    Shall we meet?";
+
+        assert_eq!(kinds(text), vec![(BlockKind::Quote, 2, 5)]);
+    }
+
+    #[test]
+    fn test_rows() {
+        let block = Block {
+            kind: BlockKind::Quote,
+            start: 1,
+            len: 3,
+        };
+
+        assert_eq!(
+            rows(5, &[block]),
+            vec![Row::Line(0), Row::Folded(block), Row::Line(4)]
+        );
+        assert_eq!(rows(2, &[]), vec![Row::Line(0), Row::Line(1)]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Signature insertion for outgoing mail
+//!
+//! Signatures are placed below the standard "-- " separator so receiving
+//! clients can recognise (and trim) them. The inserted block is tracked on
+//! the `ComposeEmail` so it can be swapped when the sending account changes.
+
+use crate::config::SignaturePlacement;
+
+/// Standard signature separator line (note the trailing space)
+pub const SEPARATOR: &str = "-- ";
+
+/// Marker line that starts a forwarded message in composer bodies
+const FORWARD_MARKER: &str = "---------- Forwarded message ----------";
+
+/// Format a signature as the block inserted into the body
+fn block(signature: &str) -> String {
+    format!("{}\n{}", SEPARATOR, signature)
+}
+
+/// Insert a signature into a body
+///
+/// `Above` places it before the quoted or forwarded message when there is
+/// one; otherwise (and for `Below`) it goes at the end of the body.
+pub fn insert(body: &str, signature: &str, placement: SignaturePlacement) -> String {
+    let block = block(signature);
+
+    if placement == SignaturePlacement::Above
+        && let Some(idx) = quote_start(body)
+    {
+        let (head, quote) = body.split_at(idx);
+        return format!("{}\n\n{}\n\n{}", head.trim_end_matches('\n'), block, quote);
+    }
+
+    format!("{}\n\n{}", body.trim_end_matches('\n'), block)
+}
+
+/// Remove a previously inserted signature block
+///
+/// Returns None if the block is no longer present (e.g. the user edited it).
+pub fn remove(body: &str, signature: &str) -> Option<String> {
+    let block = format!("\n\n{}", block(signature));
+    let idx = body.rfind(&block)?;
+    let mut result = String::with_capacity(body.len());
+    result.push_str(&body[..idx]);
+    result.push_str(&body[idx + block.len()..]);
+    Some(result)
+}
+
+/// Replace one signature with another (either may be absent)
+///
+/// Returns None if the old signature can't be found, so that a hand-edited
+/// signature is never duplicated.
+pub fn replace(
+    body: &str,
+    old: Option<&str>,
+    new: Option<&str>,
+    placement: SignaturePlacement,
+) -> Option<String> {
+    let stripped = match old {
+        Some(old) => remove(body, old)?,
+        None => body.to_string(),
+    };
+    Some(match new {
+        Some(new) => insert(&stripped, new, placement),
+        None => stripped,
+    })
+}
+
+/// Find the byte offset where the quoted or forwarded part of a body begins
+fn quote_start(body: &str) -> Option<usize> {
+    let mut offset = 0;
+    let mut lines = body.split_inclusive('\n').peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_end();
+        let is_attribution =
+            trimmed.ends_with(" wrote:") && lines.peek().is_some_and(|next| next.starts_with('>'));
+        if is_attribution || trimmed == FORWARD_MARKER || trimmed.starts_with('>') {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLY: &str = "\n\nOn Jan 01, 2024 at 12:00, Alice wrote:\n> hello\n> there";
+
+    #[test]
+    fn test_insert_new_message() {
+        let body = insert("", "Bob\nExample Corp", SignaturePlacement::Below);
+        assert_eq!(body, "\n\n-- \nBob\nExample Corp");
+    }
+
+    #[test]
+    fn test_insert_below_quote() {
+        let body = insert(REPLY, "Bob", SignaturePlacement::Below);
+        assert!(body.ends_with("> there\n\n-- \nBob"));
+    }
+
+    #[test]
+    fn test_insert_above_quote() {
+        let body = insert(REPLY, "Bob", SignaturePlacement::Above);
+        assert_eq!(
+            body,
+            "\n\n-- \nBob\n\nOn Jan 01, 2024 at 12:00, Alice wrote:\n> hello\n> there"
+        );
+    }
+
+    #[test]
+    fn test_insert_above_forward() {
+        let body = insert(
+            "FYI\n\n---------- Forwarded message ----------\nFrom: Alice",
+            "Bob",
+            SignaturePlacement::Above,
+        );
+        assert!(body.starts_with("FYI\n\n-- \nBob\n\n---------- Forwarded"));
+    }
+
+    #[test]
+    fn test_replace_swaps_signature() {
+        for placement in [SignaturePlacement::Below, SignaturePlacement::Above] {
+            let body = insert(REPLY, "Work", placement);
+            let swapped = replace(&body, Some("Work"), Some("Home"), placement).unwrap();
+            assert_eq!(swapped, insert(REPLY, "Home", placement));
+        }
+    }
+
+    #[test]
+    fn test_replace_removes_and_adds() {
+        let body = insert("Hi", "Work", SignaturePlacement::Below);
+        assert_eq!(
+            replace(&body, Some("Work"), None, SignaturePlacement::Below).unwrap(),
+            "Hi"
+        );
+        assert_eq!(
+            replace("Hi", None, Some("Home"), SignaturePlacement::Below).unwrap(),
+            "Hi\n\n-- \nHome"
+        );
+    }
+
+    #[test]
+    fn test_replace_keeps_edited_signature() {
+        let body = "Hi\n\n-- \nWork (edited)";
+        assert!(
+            replace(
+                body,
+                Some("Work Inc"),
+                Some("Home"),
+                SignaturePlacement::Below
+            )
+            .is_none()
+        );
+    }
+}
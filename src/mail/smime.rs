@@ -0,0 +1,392 @@
+//! S/MIME support (RFC 8551)
+//!
+//! CMS operations are delegated to the `openssl` binary. Each account can
+//! have a PKCS#12 identity (certificate + private key) used for signing
+//! its outgoing mail and decrypting mail sent to it; verification only
+//! needs the trusted CA bundle.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+use anyhow::{Result, anyhow, bail};
+
+use super::external::{TempFile, run_piped};
+use super::types::{SignatureState, SignatureStatus, Trust};
+use crate::config::{Config, expand_home};
+use crate::credentials::CredentialStore;
+
+/// `micalg` parameter matching the digest `sign` uses
+pub const MICALG: &str = "sha-256";
+
+/// Environment variable the PKCS#12 passphrase is handed to openssl in
+const PASS_ENV: &str = "BLTZ_SMIME_PASS";
+
+/// An S/MIME implementation
+pub trait SmimeBackend: Send + Sync {
+    /// Check a detached DER signature over `data`
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<SignatureStatus>;
+    /// Check opaque signed-data, returning the MIME entity it wraps
+    fn verify_opaque(&self, signed: &[u8]) -> Result<(Vec<u8>, SignatureStatus)>;
+    /// Decrypt enveloped-data with whichever configured identity it was sent to
+    fn decrypt(&self, enveloped: &[u8]) -> Result<Vec<u8>>;
+    /// Create a detached DER signature with `signer`'s identity
+    fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>>;
+    /// Whether an identity is configured for `address`
+    fn has_identity(&self, address: &str) -> bool;
+}
+
+static BACKEND: OnceLock<Box<dyn SmimeBackend>> = OnceLock::new();
+
+/// Set up the backend with every account's identity (call once at startup)
+pub fn init(config: &Config) {
+    let identities = config
+        .accounts
+        .iter()
+        .filter_map(|account| {
+            let smime = account.smime.as_ref()?;
+            Some(Identity {
+                email: account.email.clone(),
+                path: expand_home(&smime.identity),
+                passphrase: CredentialStore::new(&account.email).get_smime_passphrase(),
+            })
+        })
+        .collect();
+    let backend = Openssl {
+        program: config
+            .smime
+            .openssl_path
+            .clone()
+            .unwrap_or_else(|| "openssl".to_string()),
+        ca_file: config.smime.ca_file.as_deref().map(expand_home),
+        identities,
+    };
+    let _ = BACKEND.set(Box::new(backend));
+}
+
+/// The S/MIME backend (None before `init`)
+pub fn backend() -> Option<&'static dyn SmimeBackend> {
+    BACKEND.get().map(|b| b.as_ref())
+}
+
+/// A PKCS#12 file and the address it belongs to
+struct Identity {
+    email: String,
+    path: PathBuf,
+    passphrase: Option<String>,
+}
+
+/// Backend driving `openssl cms`
+pub struct Openssl {
+    program: String,
+    ca_file: Option<PathBuf>,
+    identities: Vec<Identity>,
+}
+
+impl Openssl {
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(args);
+        command
+    }
+
+    /// Command reading `identity`'s PKCS#12 file with its passphrase
+    fn identity_command(&self, identity: &Identity, args: &[&str]) -> Result<Command> {
+        let path = identity
+            .path
+            .to_str()
+            .ok_or_else(|| anyhow!("Identity path is not UTF-8"))?;
+        let mut command = self.command(args);
+        command
+            .args(["-passin", &format!("env:{}", PASS_ENV)])
+            .env(PASS_ENV, identity.passphrase.as_deref().unwrap_or_default());
+        if args.first() == Some(&"pkcs12") {
+            command.args(["-in", path]);
+        } else {
+            command.args(["-inkey", path]);
+        }
+        Ok(command)
+    }
+
+    fn identity(&self, address: &str) -> Option<&Identity> {
+        self.identities
+            .iter()
+            .find(|i| i.email.eq_ignore_ascii_case(address))
+    }
+
+    /// Run `cms -verify`, first against the trusted CAs and then, if only
+    /// the certificate chain failed, without chain validation
+    fn verify_cms(&self, extra: &[&str], input: &[u8]) -> Result<(Vec<u8>, SignatureStatus)> {
+        let signers = TempFile::new(b"")?;
+        let run = |check_chain: bool| -> Result<Output> {
+            let mut command = self.command(&["cms", "-verify", "-inform", "DER", "-binary"]);
+            command.args(["-signer", signers.arg()?]).args(extra);
+            if !check_chain {
+                command.arg("-noverify");
+            } else if let Some(ref ca_file) = self.ca_file {
+                command.arg("-CAfile").arg(ca_file);
+            }
+            run_piped(&mut command, input)
+        };
+
+        let output = run(true)?;
+        if output.status.success() {
+            let signer = self.signer_name(&signers);
+            return Ok((
+                output.stdout,
+                status(SignatureState::Good, signer, Trust::Full),
+            ));
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let state = match classify_failure(&stderr) {
+            Failure::Certificate => {
+                // The signature itself may still be intact
+                let unchecked = run(false)?;
+                if unchecked.status.success() {
+                    let state = if stderr.contains("certificate has expired") {
+                        SignatureState::ExpiredKey
+                    } else if stderr.contains("certificate revoked") {
+                        SignatureState::RevokedKey
+                    } else {
+                        SignatureState::Good
+                    };
+                    let signer = self.signer_name(&signers);
+                    return Ok((unchecked.stdout, status(state, signer, Trust::Unknown)));
+                }
+                SignatureState::Bad
+            }
+            Failure::Content => SignatureState::Bad,
+            Failure::Other => bail!("Verification failed: {}", last_error(&stderr)),
+        };
+        Ok((
+            Vec::new(),
+            status(state, self.signer_name(&signers), Trust::Unknown),
+        ))
+    }
+
+    /// "Name <address>" of the first certificate openssl wrote out
+    fn signer_name(&self, signers: &TempFile) -> Option<String> {
+        let path = signers.arg().ok()?;
+        let output = run_piped(
+            &mut self.command(&["x509", "-noout", "-email", "-subject", "-in", path]),
+            b"",
+        )
+        .ok()?;
+        output
+            .status
+            .success()
+            .then(|| describe_certificate(&String::from_utf8_lossy(&output.stdout)))
+            .flatten()
+    }
+}
+
+impl SmimeBackend for Openssl {
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<SignatureStatus> {
+        let content = TempFile::new(data)?;
+        let (_, status) = self.verify_cms(&["-content", content.arg()?], signature)?;
+        Ok(status)
+    }
+
+    fn verify_opaque(&self, signed: &[u8]) -> Result<(Vec<u8>, SignatureStatus)> {
+        self.verify_cms(&[], signed)
+    }
+
+    fn decrypt(&self, enveloped: &[u8]) -> Result<Vec<u8>> {
+        if self.identities.is_empty() {
+            bail!("No S/MIME identity configured");
+        }
+        let mut last = None;
+        for identity in &self.identities {
+            let mut command =
+                self.identity_command(identity, &["cms", "-decrypt", "-inform", "DER"])?;
+            let output = run_piped(&mut command, enveloped)?;
+            if output.status.success() {
+                return Ok(output.stdout);
+            }
+            last = Some(last_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        bail!(
+            "Not encrypted to any configured S/MIME identity ({})",
+            last.unwrap_or_default()
+        )
+    }
+
+    fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>> {
+        let identity = self
+            .identity(signer)
+            .ok_or_else(|| anyhow!("No S/MIME identity for {}", signer))?;
+
+        // -signer can't read an encrypted PKCS#12 file, so pull out the
+        // certificate (and any CA chain to include) first
+        let extract = |which: &str| -> Result<Vec<u8>> {
+            let mut command = self.identity_command(identity, &["pkcs12", which, "-nokeys"])?;
+            let output = run_piped(&mut command, b"")?;
+            if !output.status.success() {
+                bail!(
+                    "Failed to open {}: {}",
+                    identity.path.display(),
+                    last_error(&String::from_utf8_lossy(&output.stderr))
+                );
+            }
+            Ok(output.stdout)
+        };
+        let cert = TempFile::new(&extract("-clcerts")?)?;
+        let chain = extract("-cacerts")?;
+        let chain = (!chain.is_empty())
+            .then(|| TempFile::new(&chain))
+            .transpose()?;
+
+        let mut command = self.identity_command(
+            identity,
+            &[
+                "cms", "-sign", "-binary", "-md", "sha256", "-outform", "DER",
+            ],
+        )?;
+        command.args(["-signer", cert.arg()?]);
+        if let Some(ref chain) = chain {
+            command.args(["-certfile", chain.arg()?]);
+        }
+        let output = run_piped(&mut command, data)?;
+        if !output.status.success() {
+            bail!(
+                "S/MIME signing as {} failed: {}",
+                signer,
+                last_error(&String::from_utf8_lossy(&output.stderr))
+            );
+        }
+        Ok(output.stdout)
+    }
+
+    fn has_identity(&self, address: &str) -> bool {
+        self.identity(address).is_some()
+    }
+}
+
+fn status(state: SignatureState, signer: Option<String>, trust: Trust) -> SignatureStatus {
+    SignatureStatus {
+        state,
+        signer,
+        trust,
+    }
+}
+
+/// Why `cms -verify` failed
+#[derive(Debug, PartialEq, Eq)]
+enum Failure {
+    /// The certificate chain couldn't be validated
+    Certificate,
+    /// The content doesn't match the signature
+    Content,
+    /// Anything else (malformed input, missing tool...)
+    Other,
+}
+
+fn classify_failure(stderr: &str) -> Failure {
+    if stderr.contains("certificate verify error") {
+        Failure::Certificate
+    } else if stderr.contains("content verify error")
+        || stderr.contains("signature failure")
+        || stderr.contains("Verification failure")
+    {
+        Failure::Content
+    } else {
+        Failure::Other
+    }
+}
+
+/// Turn `x509 -email -subject` output into "CN <address>"
+fn describe_certificate(output: &str) -> Option<String> {
+    let email = output
+        .lines()
+        .map(str::trim)
+        .find(|line| line.contains('@') && !line.contains('='));
+    let common_name = output
+        .lines()
+        .find_map(|line| line.strip_prefix("subject="))
+        .and_then(|subject| {
+            subject.split(',').find_map(|rdn| {
+                let (key, value) = rdn.split_once('=')?;
+                (key.trim() == "CN").then(|| value.trim().to_string())
+            })
+        });
+    match (common_name, email) {
+        (Some(name), Some(email)) if name != email => Some(format!("{} <{}>", name, email)),
+        (_, Some(email)) => Some(email.to_string()),
+        (Some(name), None) => Some(name),
+        (None, None) => None,
+    }
+}
+
+/// Last openssl error line, without the error-stack prefix
+fn last_error(stderr: &str) -> String {
+    stderr
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| {
+            // "40B7...:error:1700009E:CMS routines:func:reason:file:line:"
+            let parts: Vec<&str> = line.split(':').collect();
+            if parts.len() > 5 && parts[1] == "error" {
+                parts[5].to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .unwrap_or_else(|| "unknown openssl error".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_certificate() {
+        assert_eq!(
+            describe_certificate(
+                "me@example.com\nsubject=CN=Jane Doe, emailAddress=me@example.com\n"
+            )
+            .as_deref(),
+            Some("Jane Doe <me@example.com>")
+        );
+        assert_eq!(
+            describe_certificate("subject=C = DE, CN = Example Signing\n").as_deref(),
+            Some("Example Signing")
+        );
+        assert_eq!(describe_certificate("subject=O=Org\n"), None);
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert_eq!(
+            classify_failure(
+                "CMS Verification failure\n4047:error:17000064:CMS routines:cms_signerinfo_verify_cert:certificate verify error:crypto/cms/cms_smime.c:290:Verify error: unable to get local issuer certificate\n"
+            ),
+            Failure::Certificate
+        );
+        assert_eq!(
+            classify_failure(
+                "CMS Verification failure\n4057:error:1700006D:CMS routines:CMS_verify:content verify error:crypto/cms/cms_smime.c:462:\n"
+            ),
+            Failure::Content
+        );
+        assert_eq!(
+            classify_failure("Error reading SMIME Content Info\n"),
+            Failure::Other
+        );
+    }
+
+    #[test]
+    fn test_last_error() {
+        assert_eq!(
+            last_error(
+                "x\n40B7:error:1400006B:UI routines:UI_process:processing error:crypto/ui/ui_lib.c:553:while reading strings\n"
+            ),
+            "processing error"
+        );
+        assert_eq!(
+            last_error("Could not find signer certificate\n"),
+            "Could not find signer certificate"
+        );
+    }
+}
@@ -1,19 +1,32 @@
-use anyhow::{Context, Result};
-use lettre::message::Mailbox;
-use lettre::message::header::ContentType;
+use std::process::Stdio;
+
+use anyhow::{Context, Result, bail};
+use lettre::message::header::{ContentDisposition, ContentTransferEncoding, ContentType};
+use lettre::message::{Attachment, Body, Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::{Credentials, Mechanism};
 use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
 
 use crate::config::{AuthMethod, SmtpConfig};
 
-use super::types::ComposeEmail;
+use super::pgp::{self, PgpBackend};
+use super::smime::{self, SmimeBackend};
+use super::types::{ComposeAttachment, ComposeEmail, Protection, Scheme};
+use super::{address, calendar, markdown};
 
 pub struct SmtpClient {
-    transport: AsyncSmtpTransport<Tokio1Executor>,
+    transport: Transport,
     from_email: String,
     from_name: Option<String>,
 }
 
+enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    /// Program and arguments of a sendmail-compatible command
+    Command(Vec<String>),
+}
+
 impl SmtpClient {
     #[allow(dead_code)]
     pub async fn new(
@@ -63,7 +76,30 @@ impl SmtpClient {
             .build();
 
         Ok(Self {
-            transport,
+            transport: Transport::Smtp(transport),
+            from_email: from_email.to_string(),
+            from_name: from_name.map(|s| s.to_string()),
+        })
+    }
+
+    /// Client that pipes messages to a local command (msmtp, sendmail)
+    /// instead of connecting to a server; no credentials are involved
+    pub fn with_command(
+        config: &SmtpConfig,
+        from_email: &str,
+        from_name: Option<&str>,
+    ) -> Result<Self> {
+        let command = config
+            .command
+            .as_deref()
+            .context("transport = \"command\" needs a command line in [accounts.smtp]")?;
+        let argv = split_command(command)?;
+        if argv.is_empty() {
+            bail!("Send command is empty");
+        }
+
+        Ok(Self {
+            transport: Transport::Command(argv),
             from_email: from_email.to_string(),
             from_name: from_name.map(|s| s.to_string()),
         })
@@ -84,28 +120,14 @@ impl SmtpClient {
             .from(from_mailbox)
             .subject(&compose.subject);
 
-        // Add To recipients (handle comma-separated list and trailing commas)
-        for to_addr in compose.to.split(',') {
-            let to_addr = to_addr.trim();
-            if !to_addr.is_empty() {
-                let to_mailbox = to_addr
-                    .parse::<Mailbox>()
-                    .context(format!("Invalid recipient address: {}", to_addr))?;
-                builder = builder.to(to_mailbox);
-            }
+        // Recipients are RFC 5322 address lists (quoted names may contain commas)
+        let to = address::parse_list(&compose.to).context("Invalid To field")?;
+        let cc = address::parse_list(&compose.cc).context("Invalid Cc field")?;
+        for address in &to {
+            builder = builder.to(address.to_mailbox()?);
         }
-
-        // Add CC recipients if present
-        if !compose.cc.is_empty() {
-            for cc_addr in compose.cc.split(',') {
-                let cc_addr = cc_addr.trim();
-                if !cc_addr.is_empty() {
-                    let cc_mailbox = cc_addr
-                        .parse::<Mailbox>()
-                        .context(format!("Invalid CC address: {}", cc_addr))?;
-                    builder = builder.cc(cc_mailbox);
-                }
-            }
+        for address in &cc {
+            builder = builder.cc(address.to_mailbox()?);
         }
 
         if let Some(ref reply_to) = compose.in_reply_to {
@@ -116,21 +138,313 @@ impl SmtpClient {
             builder = builder.references(references.clone());
         }
 
-        let message = builder
-            .header(ContentType::TEXT_PLAIN)
-            .body(compose.body.clone())
-            .context("Failed to build email message")?;
+        // Markdown bodies go out as multipart/alternative (original text + rendered HTML)
+        let mut alternative = compose.markdown.then(|| {
+            let html = markdown::render_html(&compose.body);
+            MultiPart::alternative_plain_html(compose.body.clone(), html)
+        });
 
-        self.transport
-            .send(message)
-            .await
-            .context("Failed to send email")?;
+        // iMIP (RFC 6047): the iCalendar object is the last alternative
+        if let Some(ref calendar) = compose.calendar {
+            let parts = alternative.unwrap_or_else(|| {
+                MultiPart::alternative().singlepart(SinglePart::plain(compose.body.clone()))
+            });
+            alternative = Some(parts.singlepart(calendar_part(calendar)?));
+        }
+
+        let mut content = if compose.attachments.is_empty() {
+            match alternative {
+                Some(alternative) => Content::Multi(alternative),
+                None => Content::Single(SinglePart::plain(compose.body.clone())),
+            }
+        } else {
+            // Attachments need a multipart/mixed wrapper around the text part(s)
+            let mut mixed = match alternative {
+                Some(alternative) => MultiPart::mixed().multipart(alternative),
+                None => MultiPart::mixed().singlepart(SinglePart::plain(compose.body.clone())),
+            };
+            for attachment in &compose.attachments {
+                mixed = mixed.singlepart(attachment_part(attachment));
+            }
+            Content::Multi(mixed)
+        };
+
+        if !compose.protection.is_off() {
+            let protection = compose.protection;
+            let signer = self.from_email.clone();
+            content = match protection.scheme {
+                Scheme::OpenPgp => {
+                    let backend = pgp::backend().context("OpenPGP support is turned off")?;
+                    // Encrypt to ourselves too so the sent copy stays readable
+                    let recipients: Vec<String> = to
+                        .iter()
+                        .chain(&cc)
+                        .map(|a| a.email.clone())
+                        .chain(std::iter::once(self.from_email.clone()))
+                        .collect();
+                    // gpg may wait on pinentry; keep it off the async workers
+                    tokio::task::spawn_blocking(move || {
+                        protect(backend, content, protection, &signer, &recipients)
+                    })
+                    .await??
+                }
+                Scheme::Smime => {
+                    let backend = smime::backend().context("S/MIME support is unavailable")?;
+                    tokio::task::spawn_blocking(move || smime_sign(backend, content, &signer))
+                        .await??
+                }
+            };
+        }
+
+        let message = match content {
+            Content::Single(part) => builder.singlepart(part),
+            Content::Multi(part) => builder.multipart(part),
+        }
+        .context("Failed to build email message")?;
+
+        match self.transport {
+            Transport::Smtp(ref transport) => {
+                transport
+                    .send(message)
+                    .await
+                    .context("Failed to send email")?;
+            }
+            Transport::Command(ref argv) => pipe_to_command(argv, &message).await?,
+        }
 
         tracing::info!("Email sent to {} (cc: {})", compose.to, compose.cc);
         Ok(())
     }
 }
 
+/// text/calendar part carrying the iTIP method from the object itself
+fn calendar_part(calendar: &str) -> Result<SinglePart> {
+    let method = calendar::method_of(calendar).unwrap_or_else(|| "PUBLISH".to_string());
+    let content_type =
+        ContentType::parse(&format!("text/calendar; charset=utf-8; method={}", method))
+            .context("Invalid calendar method")?;
+    Ok(SinglePart::builder()
+        .header(content_type)
+        .body(calendar.to_string()))
+}
+
+/// Run a sendmail-compatible command with the recipients as arguments and
+/// the message on stdin; a non-zero exit status becomes an error carrying stderr
+///
+/// The recipients follow `--` so an address starting with `-` cannot be
+/// read as an option. They come from the envelope rather than `-t`, which
+/// would miss Bcc (the header is not in the formatted message).
+async fn pipe_to_command(argv: &[String], message: &Message) -> Result<()> {
+    let recipients: Vec<String> = message
+        .envelope()
+        .to()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .arg("--")
+        .args(&recipients)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run {}", argv[0]))?;
+
+    let mut stdin = child.stdin.take().context("Failed to open command stdin")?;
+    let written = stdin.write_all(&message.formatted()).await;
+    drop(stdin); // Close stdin so the command sees the end of the message
+
+    let output = child
+        .wait_with_output()
+        .await
+        .with_context(|| format!("Failed to run {}", argv[0]))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+
+    if !output.status.success() {
+        let status = match output.status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "a signal".to_string(),
+        };
+        if stderr.is_empty() {
+            bail!("{} failed with {}", argv[0], status);
+        }
+        bail!("{} failed with {}: {}", argv[0], status, stderr);
+    }
+    // The command may exit before reading everything (e.g. a broken pipe)
+    written.with_context(|| format!("Failed to write message to {}", argv[0]))?;
+    if !stderr.is_empty() {
+        tracing::warn!("{}: {}", argv[0], stderr);
+    }
+    Ok(())
+}
+
+/// Split a command line into program and arguments
+///
+/// Supports single and double quotes and backslash escapes, but no other
+/// shell syntax; the command is run directly, not through a shell.
+fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => match chars.next() {
+                Some(next @ ('"' | '\\')) => current.push(next),
+                Some(next) => {
+                    current.push('\\');
+                    current.push(next);
+                }
+                None => bail!("Unterminated quote in send command"),
+            },
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '\\') => {
+                current.extend(chars.next());
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        bail!("Unterminated quote in send command");
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Top-level body of an outgoing message
+enum Content {
+    Single(SinglePart),
+    Multi(MultiPart),
+}
+
+impl Content {
+    fn formatted(&self) -> Vec<u8> {
+        match self {
+            Self::Single(part) => part.formatted(),
+            Self::Multi(part) => part.formatted(),
+        }
+    }
+}
+
+/// Wrap `content` in RFC 3156 multipart/signed or multipart/encrypted
+fn protect(
+    backend: &dyn PgpBackend,
+    content: Content,
+    protection: Protection,
+    signer: &str,
+    recipients: &[String],
+) -> Result<Content> {
+    let entity = content.formatted();
+
+    if protection.encrypt {
+        let signer = protection.sign.then_some(signer);
+        let ciphertext = backend.encrypt(&entity, recipients, signer)?;
+        let control = SinglePart::builder()
+            .header(ContentType::parse("application/pgp-encrypted").expect("valid MIME type"))
+            .body(String::from("Version: 1\r\n"));
+        let data = SinglePart::builder()
+            .header(
+                ContentType::parse("application/octet-stream; name=\"encrypted.asc\"")
+                    .expect("valid MIME type"),
+            )
+            .body(ciphertext);
+        return Ok(Content::Multi(
+            MultiPart::encrypted("application/pgp-encrypted".to_string())
+                .singlepart(control)
+                .singlepart(data),
+        ));
+    }
+
+    let signature = backend.sign(signed_data(&entity), signer)?;
+    let signature = SinglePart::builder()
+        .header(
+            ContentType::parse("application/pgp-signature; name=\"signature.asc\"")
+                .expect("valid MIME type"),
+        )
+        .body(signature);
+    Ok(signed(
+        content,
+        signature,
+        "application/pgp-signature",
+        pgp::MICALG,
+    ))
+}
+
+/// Wrap `content` in an RFC 8551 multipart/signed with a detached CMS signature
+fn smime_sign(backend: &dyn SmimeBackend, content: Content, signer: &str) -> Result<Content> {
+    let entity = content.formatted();
+    let signature = backend.sign(signed_data(&entity), signer)?;
+    let signature = SinglePart::builder()
+        .header(
+            ContentType::parse("application/pkcs7-signature; name=\"smime.p7s\"")
+                .expect("valid MIME type"),
+        )
+        .header(ContentDisposition::attachment("smime.p7s"))
+        .body(Body::new(signature));
+    Ok(signed(
+        content,
+        signature,
+        "application/pkcs7-signature",
+        smime::MICALG,
+    ))
+}
+
+/// The bytes a detached signature covers: the entity up to, not including,
+/// the CRLF that precedes the next boundary
+fn signed_data(entity: &[u8]) -> &[u8] {
+    entity.strip_suffix(b"\r\n").unwrap_or(entity)
+}
+
+/// Pair `content` with its detached signature in a multipart/signed
+fn signed(content: Content, signature: SinglePart, protocol: &str, micalg: &str) -> Content {
+    let signed = MultiPart::signed(protocol.to_string(), micalg.to_string());
+    Content::Multi(match content {
+        Content::Single(part) => signed.singlepart(part).singlepart(signature),
+        Content::Multi(part) => signed.multipart(part).singlepart(signature),
+    })
+}
+
+/// Build the MIME part for an outgoing attachment
+fn attachment_part(attachment: &ComposeAttachment) -> SinglePart {
+    let content_type = ContentType::parse(&attachment.mime_type).unwrap_or_else(|_| {
+        ContentType::parse("application/octet-stream").expect("valid MIME type")
+    });
+
+    let body = if attachment.mime_type.eq_ignore_ascii_case("message/rfc822") {
+        // RFC 2046 only allows 7bit/8bit/binary for message/rfc822, so keep it unencoded
+        Body::new_with_encoding(attachment.data.clone(), ContentTransferEncoding::SevenBit)
+            .or_else(|data| Body::new_with_encoding(data, ContentTransferEncoding::EightBit))
+            .unwrap_or_else(Body::new)
+    } else {
+        Body::new(attachment.data.clone())
+    };
+
+    Attachment::new(attachment.filename.clone()).body(body, content_type)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +456,246 @@ mod tests {
         assert!(compose.subject.is_empty());
         assert!(compose.body.is_empty());
     }
+
+    #[test]
+    fn test_forwarded_message_is_not_reencoded() {
+        let raw = b"From: a@example.com\r\nSubject: Hi\r\n\r\nHello\r\n".to_vec();
+        let part = attachment_part(&ComposeAttachment::message("Hi", raw));
+        let formatted = String::from_utf8(part.formatted()).unwrap();
+        assert!(formatted.contains("Content-Type: message/rfc822"));
+        assert!(formatted.contains("Content-Transfer-Encoding: 7bit"));
+        assert!(formatted.contains("filename=\"Hi.eml\""));
+        assert!(formatted.contains("Subject: Hi"));
+    }
+
+    #[test]
+    fn test_binary_attachment_is_base64() {
+        let part = attachment_part(&ComposeAttachment {
+            filename: "invoice.pdf".to_string(),
+            mime_type: "application/pdf".to_string(),
+            data: vec![0x25, 0x50, 0x44, 0x46, 0x00, 0xff, 0xfe],
+        });
+        let formatted = String::from_utf8(part.formatted()).unwrap();
+        assert!(formatted.contains("Content-Type: application/pdf"));
+        assert!(formatted.contains("Content-Transfer-Encoding: base64"));
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"msmtp -a work --logfile "/tmp/my log" 'it''s' a\ b"#).unwrap(),
+            [
+                "msmtp",
+                "-a",
+                "work",
+                "--logfile",
+                "/tmp/my log",
+                "its",
+                "a b"
+            ]
+        );
+        assert_eq!(
+            split_command(r#"x "a \"q\" \n""#).unwrap(),
+            ["x", r#"a "q" \n"#]
+        );
+        assert_eq!(split_command(r#"x """#).unwrap(), ["x", ""]);
+        assert!(split_command("x 'open").is_err());
+    }
+
+    fn command_client(command: &str) -> SmtpClient {
+        let config = SmtpConfig {
+            server: String::new(),
+            port: 587,
+            tls: true,
+            transport: crate::config::SendTransport::Command,
+            command: Some(command.to_string()),
+        };
+        SmtpClient::with_command(&config, "me@example.com", Some("Me")).unwrap()
+    }
+
+    fn test_email() -> ComposeEmail {
+        ComposeEmail {
+            to: r#""Doe, John" <john@example.com>"#.to_string(),
+            cc: "carol@example.org".to_string(),
+            subject: "Hello".to_string(),
+            body: "Hi there".to_string(),
+            ..ComposeEmail::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_command_transport_pipes_message() {
+        let out = std::env::temp_dir().join(format!("bltz-sendmail-{}", std::process::id()));
+        // The script writes its arguments (`--` and the recipients), then the message
+        let client = command_client(&format!(
+            r#"sh -c 'echo "$@" > "$0"; cat >> "$0"' {}"#,
+            out.display()
+        ));
+        client.send(&test_email()).await.unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        std::fs::remove_file(&out).ok();
+        let mut lines = written.lines();
+        assert_eq!(lines.next(), Some("-- john@example.com carol@example.org"));
+        assert!(written.contains("Subject: Hello"));
+        assert!(written.contains("From: Me <me@example.com>"));
+        assert!(written.contains("Hi there"));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_alternative() {
+        let out = std::env::temp_dir().join(format!("bltz-imip-{}", std::process::id()));
+        let client = command_client(&format!(r#"sh -c 'cat > "$0"' {}"#, out.display()));
+        let email = ComposeEmail {
+            calendar: Some("BEGIN:VCALENDAR\r\nMETHOD:REPLY\r\nEND:VCALENDAR\r\n".to_string()),
+            ..test_email()
+        };
+        client.send(&email).await.unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        std::fs::remove_file(&out).ok();
+        assert!(written.contains("multipart/alternative"));
+        assert!(written.contains("Content-Type: text/calendar; charset=utf-8; method=REPLY"));
+        assert!(written.find("Hi there") < written.find("BEGIN:VCALENDAR"));
+    }
+
+    #[tokio::test]
+    async fn test_command_transport_reports_failure() {
+        let client =
+            command_client(r#"sh -c 'cat > /dev/null; echo "relay refused" >&2; exit 75'"#);
+        let err = client.send(&test_email()).await.unwrap_err().to_string();
+        assert_eq!(err, "sh failed with exit code 75: relay refused");
+
+        let client = command_client("/nonexistent/sendmail");
+        let err = client.send(&test_email()).await.unwrap_err().to_string();
+        assert!(err.contains("Failed to run /nonexistent/sendmail"));
+    }
+
+    /// Records what it was asked to sign and returns a fixed signature
+    struct RecordingSigner(std::sync::Mutex<Vec<u8>>);
+
+    impl PgpBackend for RecordingSigner {
+        fn decrypt(&self, _: &[u8]) -> Result<pgp::Decrypted> {
+            unimplemented!()
+        }
+
+        fn verify(&self, _: &[u8], _: &[u8]) -> Result<crate::mail::types::SignatureStatus> {
+            unimplemented!()
+        }
+
+        fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>> {
+            assert_eq!(signer, "me@example.com");
+            *self.0.lock().unwrap() = data.to_vec();
+            Ok(b"-----BEGIN PGP SIGNATURE-----\r\nsig\r\n-----END PGP SIGNATURE-----\r\n".to_vec())
+        }
+
+        fn encrypt(
+            &self,
+            data: &[u8],
+            recipients: &[String],
+            signer: Option<&str>,
+        ) -> Result<Vec<u8>> {
+            assert_eq!(recipients, ["bob@example.com", "me@example.com"]);
+            assert_eq!(signer, Some("me@example.com"));
+            assert!(data.starts_with(b"Content-Type: text/plain"));
+            Ok(b"-----BEGIN PGP MESSAGE-----\r\nct\r\n-----END PGP MESSAGE-----\r\n".to_vec())
+        }
+
+        fn has_public_key(&self, _: &str) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_protect_signed() {
+        let backend = RecordingSigner(Default::default());
+        let content = Content::Single(SinglePart::plain("Hi there".to_string()));
+        let signed = protect(
+            &backend,
+            content,
+            Protection::PGP_SIGN,
+            "me@example.com",
+            &[],
+        )
+        .unwrap();
+        let formatted = String::from_utf8(signed.formatted()).unwrap();
+        assert!(formatted.contains("multipart/signed"));
+        assert!(formatted.contains("protocol=\"application/pgp-signature\""));
+        assert!(formatted.contains("micalg=\"pgp-sha256\""));
+
+        // The signed bytes sit between the boundary line and the CRLF before the next one
+        let data = String::from_utf8(backend.0.lock().unwrap().clone()).unwrap();
+        assert!(data.starts_with("Content-Type: text/plain"));
+        assert!(data.ends_with("Hi there"));
+        assert!(formatted.contains(&format!("\r\n{}\r\n--", data)));
+    }
+
+    #[test]
+    fn test_protect_encrypted() {
+        let backend = RecordingSigner(Default::default());
+        let content = Content::Single(SinglePart::plain("Hi there".to_string()));
+        let both = Protection::PGP_SIGN_ENCRYPT;
+        let recipients = ["bob@example.com".to_string(), "me@example.com".to_string()];
+        let encrypted = protect(&backend, content, both, "me@example.com", &recipients).unwrap();
+        let formatted = String::from_utf8(encrypted.formatted()).unwrap();
+        assert!(formatted.contains("multipart/encrypted"));
+        assert!(formatted.contains("protocol=\"application/pgp-encrypted\""));
+        assert!(formatted.contains("Version: 1"));
+        assert!(formatted.contains("-----BEGIN PGP MESSAGE-----"));
+        assert!(!formatted.contains("Hi there"));
+    }
+
+    struct FixedSmimeSigner;
+
+    impl SmimeBackend for FixedSmimeSigner {
+        fn verify(&self, _: &[u8], _: &[u8]) -> Result<crate::mail::types::SignatureStatus> {
+            unimplemented!()
+        }
+
+        fn verify_opaque(
+            &self,
+            _: &[u8],
+        ) -> Result<(Vec<u8>, crate::mail::types::SignatureStatus)> {
+            unimplemented!()
+        }
+
+        fn decrypt(&self, _: &[u8]) -> Result<Vec<u8>> {
+            unimplemented!()
+        }
+
+        fn sign(&self, data: &[u8], signer: &str) -> Result<Vec<u8>> {
+            assert_eq!(signer, "me@example.com");
+            assert!(data.ends_with(b"Hi there"));
+            Ok(vec![0x30, 0x82, 0x00, 0xff])
+        }
+
+        fn has_identity(&self, _: &str) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_smime_signed() {
+        let content = Content::Single(SinglePart::plain("Hi there".to_string()));
+        let signed = smime_sign(&FixedSmimeSigner, content, "me@example.com").unwrap();
+        let formatted = String::from_utf8(signed.formatted()).unwrap();
+        assert!(formatted.contains("multipart/signed"));
+        assert!(formatted.contains("protocol=\"application/pkcs7-signature\""));
+        assert!(formatted.contains("micalg=\"sha-256\""));
+        assert!(formatted.contains("filename=\"smime.p7s\""));
+        // The DER signature is binary, so it has to travel base64-encoded
+        assert!(formatted.contains("MIIA/w=="));
+    }
+
+    #[test]
+    fn test_command_transport_requires_command() {
+        let config = SmtpConfig {
+            server: String::new(),
+            port: 587,
+            tls: true,
+            transport: crate::config::SendTransport::Command,
+            command: None,
+        };
+        assert!(SmtpClient::with_command(&config, "me@example.com", None).is_err());
+    }
 }
@@ -0,0 +1,351 @@
+//! Message templates (canned responses)
+//!
+//! Templates are plain text files in the templates directory. Optional
+//! `Subject:`, `To:` and `Cc:` header lines at the top are separated from
+//! the body by a blank line:
+//!
+//! ```text
+//! Subject: Re: {{subject}}
+//! Cc: support-lead@example.com
+//!
+//! Hi {{sender_first_name}},
+//!
+//! Thanks for getting in touch...
+//! ```
+//!
+//! `{{name}}` placeholders are filled from the message being replied to and
+//! the sending identity; unknown placeholders are left untouched.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use super::types::{ComposeEmail, EmailHeader};
+
+/// A parsed template file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    /// File name without extension (used by `:template <name>`)
+    pub name: String,
+    pub subject: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
+    pub body: String,
+}
+
+/// Values substituted for placeholders
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub sender_name: String,
+    pub sender_first_name: String,
+    pub sender_email: String,
+    pub subject: String,
+    pub date: String,
+    pub my_name: String,
+    pub my_email: String,
+}
+
+impl TemplateContext {
+    /// Build a context from the message being replied to (if any) and our identity
+    pub fn new(original: Option<&EmailHeader>, my_name: Option<&str>, my_email: &str) -> Self {
+        let mut ctx = Self {
+            my_name: my_name.unwrap_or(my_email).to_string(),
+            my_email: my_email.to_string(),
+            ..Default::default()
+        };
+        if let Some(original) = original {
+            ctx.sender_name = original.display_from().to_string();
+            ctx.sender_first_name = first_name(original);
+            ctx.sender_email = original.from_addr.clone();
+            ctx.subject = strip_reply_prefix(&original.subject).to_string();
+            ctx.date = chrono::DateTime::from_timestamp(original.date, 0)
+                .map(|dt| dt.with_timezone(&Local).format("%b %d, %Y").to_string())
+                .unwrap_or_default();
+        }
+        ctx
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        Some(match key {
+            "sender_name" => &self.sender_name,
+            "sender_first_name" => &self.sender_first_name,
+            "sender_email" => &self.sender_email,
+            "subject" => &self.subject,
+            "date" => &self.date,
+            "my_name" => &self.my_name,
+            "my_email" => &self.my_email,
+            _ => return None,
+        })
+    }
+
+    /// Replace `{{placeholder}}`s in `text`
+    pub fn fill(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let key = rest[start + 2..start + 2 + len].trim();
+            result.push_str(&rest[..start]);
+            match self.get(key) {
+                Some(value) => result.push_str(value),
+                None => result.push_str(&rest[start..start + len + 4]),
+            }
+            rest = &rest[start + len + 4..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Sender's first name, falling back to the address's local part
+fn first_name(original: &EmailHeader) -> String {
+    if let Some(name) = original.from_name.as_deref() {
+        // "Last, First" is common in corporate directories
+        let name = name.split_once(',').map_or(name, |(_, first)| first);
+        if let Some(first) = name.split_whitespace().next() {
+            return first.trim_matches('"').to_string();
+        }
+    }
+    original
+        .from_addr
+        .split(['@', '.', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Drop any number of Re:/Fwd:/Fw: prefixes, in any case
+fn strip_reply_prefix(subject: &str) -> &str {
+    let mut subject = subject.trim();
+    while let Some(rest) = ["re:", "fwd:", "fw:"].iter().find_map(|p| {
+        subject
+            .get(..p.len())
+            .filter(|head| head.eq_ignore_ascii_case(p))
+            .map(|_| &subject[p.len()..])
+    }) {
+        subject = rest.trim_start();
+    }
+    subject
+}
+
+impl Template {
+    /// Parse a template file's contents
+    pub fn parse(name: &str, content: &str) -> Self {
+        let mut template = Self {
+            name: name.to_string(),
+            subject: None,
+            to: None,
+            cc: None,
+            body: String::new(),
+        };
+
+        let mut body_start = 0;
+        for line in content.split_inclusive('\n') {
+            let trimmed = line.trim_end();
+            if trimmed.is_empty() {
+                // Blank line ends the headers (only if there were any)
+                if body_start > 0 {
+                    body_start += line.len();
+                }
+                break;
+            }
+            let Some((key, value)) = trimmed.split_once(':') else {
+                break;
+            };
+            let value = Some(value.trim().to_string());
+            match key.to_ascii_lowercase().as_str() {
+                "subject" => template.subject = value,
+                "to" => template.to = value,
+                "cc" => template.cc = value,
+                _ => break,
+            }
+            body_start += line.len();
+        }
+
+        if body_start == 0 {
+            // No headers: reset anything picked up from a body line
+            template.subject = None;
+            template.to = None;
+            template.cc = None;
+        }
+        template.body = content[body_start..].trim_end().to_string();
+        template
+    }
+
+    /// Fill in the template and apply it to a message being composed
+    ///
+    /// The subject is replaced, To/Cc addresses are added to any already
+    /// there, and the body goes above the existing text (quote, signature).
+    pub fn apply(&self, email: &mut ComposeEmail, ctx: &TemplateContext) {
+        if let Some(ref subject) = self.subject {
+            email.subject = ctx.fill(subject);
+        }
+        if let Some(ref to) = self.to {
+            add_addresses(&mut email.to, &ctx.fill(to));
+        }
+        if let Some(ref cc) = self.cc {
+            add_addresses(&mut email.cc, &ctx.fill(cc));
+        }
+
+        let body = ctx.fill(&self.body);
+        email.body = if email.body.is_empty() || email.body.starts_with('\n') {
+            format!("{}{}", body, email.body)
+        } else {
+            format!("{}\n\n{}", body, email.body)
+        };
+    }
+}
+
+/// Append comma-separated addresses to a field, skipping ones already present
+fn add_addresses(field: &mut String, addresses: &str) {
+    for address in addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        let present = field
+            .split(',')
+            .any(|existing| existing.trim().eq_ignore_ascii_case(address));
+        if present {
+            continue;
+        }
+        let trimmed = field.trim_end().trim_end_matches(',').trim_end();
+        *field = if trimmed.is_empty() {
+            address.to_string()
+        } else {
+            format!("{}, {}", trimmed, address)
+        };
+    }
+}
+
+/// Load every template in `dir`, sorted by name
+///
+/// A missing directory simply means there are no templates.
+pub fn load_all(dir: &Path) -> Result<Vec<Template>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut templates = Vec::new();
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read templates directory {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !path.is_file() || name.starts_with('.') {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read template {}", path.display()))?;
+        templates.push(Template::parse(name, &content));
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::types::EmailFlags;
+
+    fn original() -> EmailHeader {
+        EmailHeader {
+            uid: 1,
+            message_id: None,
+            subject: "Re: Order #42".to_string(),
+            from_addr: "jane.doe@example.com".to_string(),
+            from_name: Some("Jane Doe".to_string()),
+            to_addr: None,
+            cc_addr: None,
+            date: 1_700_000_000,
+            flags: EmailFlags::empty(),
+            has_attachments: false,
+            preview: None,
+            body_cached: false,
+            in_reply_to: None,
+            references: Vec::new(),
+            folder: None,
+            list: None,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_headers_and_body() {
+        let template = Template::parse(
+            "thanks",
+            "Subject: Re: {{subject}}\nCc: lead@example.com\n\nHi {{sender_first_name}},\n\nThanks!\n",
+        );
+        assert_eq!(template.subject.as_deref(), Some("Re: {{subject}}"));
+        assert_eq!(template.cc.as_deref(), Some("lead@example.com"));
+        assert_eq!(template.to, None);
+        assert_eq!(template.body, "Hi {{sender_first_name}},\n\nThanks!");
+    }
+
+    #[test]
+    fn test_parse_body_only() {
+        let template = Template::parse("note", "Note: this is the body\nsecond line");
+        assert_eq!(template.subject, None);
+        assert_eq!(template.body, "Note: this is the body\nsecond line");
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let mut header = original();
+        // Midday UTC, so the local date is Nov 14 in nearly every zone
+        header.date = 1_699_963_200;
+        let ctx = TemplateContext::new(Some(&header), Some("Bob Smith"), "bob@example.com");
+        let date = chrono::DateTime::from_timestamp(header.date, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            .format("%b %d, %Y")
+            .to_string();
+        assert_eq!(
+            ctx.fill("Hi {{sender_first_name}}, re \"{{ subject }}\" on {{date}}. {{unknown}} -- {{my_name}}"),
+            format!("Hi Jane, re \"Order #42\" on {}. {{{{unknown}}}} -- Bob Smith", date)
+        );
+        assert_eq!(ctx.fill("unterminated {{subject"), "unterminated {{subject");
+    }
+
+    #[test]
+    fn test_strip_reply_prefix() {
+        assert_eq!(strip_reply_prefix("RE: re:Fwd: FW: Order #42"), "Order #42");
+        assert_eq!(
+            strip_reply_prefix("Regarding the order"),
+            "Regarding the order"
+        );
+        assert_eq!(strip_reply_prefix("Ré: résumé"), "Ré: résumé");
+    }
+
+    #[test]
+    fn test_first_name_fallbacks() {
+        let mut header = original();
+        header.from_name = Some("Doe, Jane".to_string());
+        assert_eq!(first_name(&header), "Jane");
+        header.from_name = None;
+        assert_eq!(first_name(&header), "jane");
+    }
+
+    #[test]
+    fn test_apply_to_reply() {
+        let template = Template::parse(
+            "t",
+            "To: jane.doe@example.com, billing@example.com\nCc: lead@example.com\n\nHi {{sender_first_name}},",
+        );
+        let mut email = ComposeEmail::reply_to(&original(), "hello");
+        let ctx = TemplateContext::new(Some(&original()), None, "bob@example.com");
+        template.apply(&mut email, &ctx);
+
+        assert_eq!(email.to, "jane.doe@example.com, billing@example.com");
+        assert_eq!(email.cc, "lead@example.com");
+        assert_eq!(email.subject, "Re: Order #42");
+        assert!(email.body.starts_with("Hi Jane,\n\nOn "));
+        assert!(email.body.ends_with("> hello"));
+    }
+}
@@ -1,16 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use super::types::{EmailFlags, EmailHeader};
+use super::types::{EmailFlags, EmailHeader, SortOrder};
 
 pub type ThreadId = String;
 
+/// Longest gap between two messages that are grouped by subject alone
+const MAX_THREAD_GAP_SECS: i64 = 7 * 24 * 60 * 60; // 7 days
+
 /// Email thread using indices into the emails array (avoids cloning).
-/// Emails are sorted by date ascending within the thread.
+/// Emails are in reply-tree order: each after its parent, replies to the
+/// same message by date.
 #[derive(Debug, Clone)]
 pub struct EmailThread {
     pub id: ThreadId,
-    /// Indices into the AppState.emails array, sorted by date ascending
+    /// Indices into the AppState.emails array, in reply-tree order
     pub email_indices: Vec<usize>,
+    /// Reply depth of each email in `email_indices` (0 = top of the tree)
+    pub depths: Vec<usize>,
     /// Pre-computed metadata (avoids repeated iteration)
     pub unread_count: usize,
     pub total_count: usize,
@@ -21,6 +27,29 @@ pub struct EmailThread {
 }
 
 impl EmailThread {
+    /// Thread of `(email index, depth)` pairs given in reply-tree order
+    fn new(id: ThreadId, nodes: Vec<(usize, usize)>, emails: &[EmailHeader]) -> Self {
+        let (email_indices, depths): (Vec<usize>, Vec<usize>) = nodes.into_iter().unzip();
+        let latest_idx = email_indices
+            .iter()
+            .copied()
+            .max_by_key(|&i| emails[i].date)
+            .unwrap_or_default();
+        EmailThread {
+            id,
+            unread_count: email_indices
+                .iter()
+                .filter(|&&i| !emails[i].flags.contains(EmailFlags::SEEN))
+                .count(),
+            total_count: email_indices.len(),
+            latest_date: emails[latest_idx].date,
+            has_attachments: email_indices.iter().any(|&i| emails[i].has_attachments),
+            latest_idx,
+            email_indices,
+            depths,
+        }
+    }
+
     /// Check if thread has any unread emails
     pub fn has_unread(&self) -> bool {
         self.unread_count > 0
@@ -59,175 +88,321 @@ impl EmailThread {
     pub fn len(&self) -> usize {
         self.email_indices.len()
     }
-}
 
-/// Group emails into threads using a hybrid algorithm:
-/// 1. Message-ID based linking (in_reply_to/references)
-/// 2. Subject-based fallback for emails without threading headers
-///
-/// Takes a slice reference to avoid cloning the entire vector at the call site.
-pub fn group_into_threads(emails: &[EmailHeader]) -> Vec<EmailThread> {
-    if emails.is_empty() {
-        return Vec::new();
+    /// For the email at `pos`, whether the tree continues below it at each
+    /// level from the top down to its own: what the tree guides need
+    /// ("│" for an ancestor with later replies, "├─" or "└─" for itself)
+    pub fn branches(&self, pos: usize) -> Vec<bool> {
+        let Some(&depth) = self.depths.get(pos) else {
+            return Vec::new();
+        };
+        let mut open = vec![false; depth + 1];
+        // Levels at or above `floor` are settled by a row seen already
+        let mut floor = depth + 1;
+        for &d in &self.depths[pos + 1..] {
+            if d < floor {
+                open[d] = true;
+                floor = d;
+            }
+            if floor == 0 {
+                break;
+            }
+        }
+        open
     }
 
-    // Build message-id index
-    let mut by_message_id: HashMap<String, usize> = HashMap::new();
-    for (i, email) in emails.iter().enumerate() {
-        if let Some(ref mid) = email.message_id {
-            by_message_id.insert(mid.clone(), i);
+    /// Add a reply to the email at position `parent`, among its other
+    /// replies by date
+    fn insert_reply(&mut self, parent: usize, email_idx: usize, emails: &[EmailHeader]) {
+        let depth = self.depths[parent] + 1;
+        let date = emails[email_idx].date;
+        let mut pos = parent + 1;
+        while pos < self.len() && self.depths[pos] >= depth {
+            if self.depths[pos] == depth && emails[self.email_indices[pos]].date > date {
+                break;
+            }
+            pos += 1;
+        }
+        self.email_indices.insert(pos, email_idx);
+        self.depths.insert(pos, depth);
+
+        // Update metadata
+        let email = &emails[email_idx];
+        self.total_count += 1;
+        if !email.flags.contains(EmailFlags::SEEN) {
+            self.unread_count += 1;
+        }
+        if email.has_attachments {
+            self.has_attachments = true;
+        }
+        if email.date > self.latest_date {
+            self.latest_date = email.date;
+            self.latest_idx = email_idx;
         }
     }
+}
 
-    // Build parent links: find which email each email replies to
-    let mut parent: Vec<Option<usize>> = vec![None; emails.len()];
-    for (i, email) in emails.iter().enumerate() {
-        // First try in_reply_to
-        if let Some(ref reply_to) = email.in_reply_to
-            && let Some(&parent_idx) = by_message_id.get(reply_to)
-            && parent_idx != i
-        {
-            parent[i] = Some(parent_idx);
-            continue;
+/// A node of the reply tree: a message, or an empty placeholder for one
+/// that is referenced but not loaded (deleted, or in another folder)
+#[derive(Debug, Default)]
+struct Container {
+    /// Message-ID (or subject key) the container stands for
+    id: Option<String>,
+    email: Option<usize>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// Containers with the Message-ID table of the JWZ algorithm
+#[derive(Debug, Default)]
+struct Tree {
+    nodes: Vec<Container>,
+    by_id: HashMap<String, usize>,
+}
+
+impl Tree {
+    fn add(&mut self, id: Option<String>) -> usize {
+        self.nodes.push(Container {
+            id,
+            ..Default::default()
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Container for a Message-ID, created empty the first time
+    fn container(&mut self, id: &str) -> usize {
+        if let Some(&node) = self.by_id.get(id) {
+            return node;
         }
-        // Fallback: try references (last one is most immediate parent)
-        for ref_id in email.references.iter().rev() {
-            if let Some(&parent_idx) = by_message_id.get(ref_id)
-                && parent_idx != i
-            {
-                parent[i] = Some(parent_idx);
-                break;
+        let node = self.add(Some(id.to_string()));
+        self.by_id.insert(id.to_string(), node);
+        node
+    }
+
+    /// Whether `node` is `other` or one of its ancestors
+    fn is_ancestor(&self, node: usize, mut other: usize) -> bool {
+        loop {
+            if node == other {
+                return true;
+            }
+            match self.nodes[other].parent {
+                Some(parent) => other = parent,
+                None => return false,
             }
         }
     }
 
-    // Find roots using union-find with path compression
-    let mut root: Vec<usize> = (0..emails.len()).collect();
-    for (i, r) in root.iter_mut().enumerate() {
-        *r = find_root_compressed(&mut parent, i);
+    /// Move `child` under `parent` (or to the top when None)
+    fn set_parent(&mut self, child: usize, parent: Option<usize>) {
+        if let Some(old) = self.nodes[child].parent.take() {
+            self.nodes[old].children.retain(|&c| c != child);
+        }
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(child);
+            self.nodes[child].parent = Some(parent);
+        }
     }
 
-    // Group by root, with subject-based fallback for orphans
-    let mut thread_groups: HashMap<ThreadId, Vec<usize>> = HashMap::new();
-    let mut subject_groups: HashMap<String, Vec<usize>> = HashMap::new();
+    /// Drop empty containers and move their children up. An empty root
+    /// stays when it holds several children (replies to a missing message).
+    /// Returns what takes the place of `node`.
+    fn prune(&mut self, node: usize, root: bool) -> Vec<usize> {
+        let children: Vec<usize> = std::mem::take(&mut self.nodes[node].children)
+            .into_iter()
+            .flat_map(|child| self.prune(child, false))
+            .collect();
+        if self.nodes[node].email.is_none() && (!root || children.len() < 2) {
+            return children;
+        }
+        for &child in &children {
+            self.nodes[child].parent = Some(node);
+        }
+        self.nodes[node].children = children;
+        vec![node]
+    }
 
-    for (i, email) in emails.iter().enumerate() {
-        if root[i] == i && parent[i].is_none() {
-            // This is a root with no parent - check if we can group by subject
-            let normalized = normalize_subject(&email.subject);
-            subject_groups.entry(normalized).or_default().push(i);
-        } else {
-            // Part of a message-id based thread
-            let root_email = &emails[root[i]];
-            let thread_id = root_email
-                .message_id
-                .clone()
-                .unwrap_or_else(|| format!("uid:{}", root_email.uid));
-            thread_groups.entry(thread_id).or_default().push(i);
+    /// Date of the message, or of the earliest one under a placeholder
+    fn date(&self, node: usize, emails: &[EmailHeader]) -> i64 {
+        match self.nodes[node].email {
+            Some(i) => emails[i].date,
+            None => self.nodes[node]
+                .children
+                .iter()
+                .map(|&child| self.date(child, emails))
+                .min()
+                .unwrap_or(i64::MAX),
         }
     }
 
-    // Merge subject groups that have more than one email AND are within date proximity
-    // (7 days max between emails to be considered part of same thread)
-    const MAX_THREAD_GAP_SECS: i64 = 7 * 24 * 60 * 60; // 7 days
+    /// Messages under `node` as `(email index, depth)`, each after its
+    /// parent and siblings by date; placeholders take no level of their own
+    fn flatten(
+        &self,
+        node: usize,
+        depth: usize,
+        emails: &[EmailHeader],
+        out: &mut Vec<(usize, usize)>,
+    ) {
+        let depth = match self.nodes[node].email {
+            Some(i) => {
+                out.push((i, depth));
+                depth + 1
+            }
+            None => depth,
+        };
+        let mut children = self.nodes[node].children.clone();
+        children.sort_by_key(|&child| self.date(child, emails));
+        for child in children {
+            self.flatten(child, depth, emails, out);
+        }
+    }
+}
+
+/// Ancestor Message-IDs of an email, oldest first, its parent last
+fn references(email: &EmailHeader) -> impl Iterator<Item = &str> {
+    let in_reply_to = email
+        .in_reply_to
+        .as_deref()
+        .filter(|id| email.references.last().map(String::as_str) != Some(*id));
+    email
+        .references
+        .iter()
+        .map(String::as_str)
+        .chain(in_reply_to)
+}
+
+/// Whether the subject carries a reply prefix ("Re:", "Aw:", ...)
+fn is_reply(subject: &str) -> bool {
+    normalize_subject(subject) != subject.trim().to_lowercase()
+}
 
-    for (subject, mut indices) in subject_groups {
-        if indices.len() > 1 {
-            // Sort by date to check proximity
-            indices.sort_by_key(|&i| emails[i].date);
+/// Group emails into threads with the JWZ algorithm
+/// (<https://www.jwz.org/doc/threading.html>):
+/// 1. Message-ID links (References/In-Reply-To) build a reply tree, with
+///    empty placeholders for referenced messages that are not loaded, so
+///    replies to a missing message stay together
+/// 2. Placeholders are pruned, keeping only the ones that hold siblings
+/// 3. Messages without any threading headers are grouped by subject when
+///    they are close in time
+///
+/// Takes a slice reference to avoid cloning the entire vector at the call site.
+pub fn group_into_threads(emails: &[EmailHeader]) -> Vec<EmailThread> {
+    if emails.is_empty() {
+        return Vec::new();
+    }
 
-            // Split into sub-groups based on date proximity
-            let mut current_group = vec![indices[0]];
-            for &i in &indices[1..] {
-                let prev_date = emails[*current_group.last().unwrap()].date;
-                let curr_date = emails[i].date;
-                if curr_date - prev_date <= MAX_THREAD_GAP_SECS {
-                    // Within proximity, add to current group
-                    current_group.push(i);
+    let mut tree = Tree::default();
+    for (i, email) in emails.iter().enumerate() {
+        // A duplicate Message-ID gets a container of its own
+        let node = match email.message_id.as_deref() {
+            Some(mid) => {
+                let node = tree.container(mid);
+                if tree.nodes[node].email.is_some() {
+                    tree.add(None)
                 } else {
-                    // Gap too large, flush current group and start new one
-                    if current_group.len() > 1 {
-                        let thread_id =
-                            format!("subj:{}:{}", subject, emails[current_group[0]].date);
-                        thread_groups
-                            .entry(thread_id)
-                            .or_default()
-                            .extend(current_group);
-                    } else {
-                        // Single email - keep as its own thread
-                        let idx = current_group[0];
-                        let email = &emails[idx];
-                        let thread_id = email
-                            .message_id
-                            .clone()
-                            .unwrap_or_else(|| format!("uid:{}", email.uid));
-                        thread_groups.entry(thread_id).or_default().push(idx);
-                    }
-                    current_group = vec![i];
+                    node
                 }
             }
-            // Flush remaining group
-            if current_group.len() > 1 {
-                let thread_id = format!("subj:{}:{}", subject, emails[current_group[0]].date);
-                thread_groups
-                    .entry(thread_id)
-                    .or_default()
-                    .extend(current_group);
-            } else {
-                let idx = current_group[0];
-                let email = &emails[idx];
-                let thread_id = email
-                    .message_id
-                    .clone()
-                    .unwrap_or_else(|| format!("uid:{}", email.uid));
-                thread_groups.entry(thread_id).or_default().push(idx);
+            None => tree.add(None),
+        };
+        tree.nodes[node].email = Some(i);
+
+        // Link the References chain, keeping links made earlier and never
+        // making a loop
+        let mut previous: Option<usize> = None;
+        for id in references(email) {
+            let current = tree.container(id);
+            if let Some(previous) = previous
+                && tree.nodes[current].parent.is_none()
+                && !tree.is_ancestor(current, previous)
+            {
+                tree.set_parent(current, Some(previous));
             }
-        } else {
-            // Single email - keep as its own thread
-            let i = indices[0];
-            let email = &emails[i];
-            let thread_id = email
-                .message_id
-                .clone()
-                .unwrap_or_else(|| format!("uid:{}", email.uid));
-            thread_groups.entry(thread_id).or_default().push(i);
+            previous = Some(current);
         }
+        // The message's own headers have the last word on its parent
+        let parent = previous.filter(|&p| !tree.is_ancestor(node, p));
+        tree.set_parent(node, parent);
     }
 
-    // Build EmailThread objects using indices (no cloning!)
-    let mut threads: Vec<EmailThread> = thread_groups
-        .into_iter()
-        .map(|(id, mut indices)| {
-            // Sort indices by date ascending within thread
-            indices.sort_by_key(|&i| emails[i].date);
+    let tops: Vec<usize> = (0..tree.nodes.len())
+        .filter(|&n| tree.nodes[n].parent.is_none())
+        .collect();
+    let mut roots: Vec<usize> = Vec::with_capacity(tops.len());
+    for top in tops {
+        roots.extend(tree.prune(top, true));
+    }
+    for &root in &roots {
+        tree.nodes[root].parent = None;
+    }
 
-            // Compute metadata without cloning
-            let unread_count = indices
+    // Messages that name no parent are grouped by subject when close in
+    // time; replies are threaded by their headers alone, so unrelated
+    // "Re: Meeting" mails stay apart
+    let mut by_subject: HashMap<String, Vec<usize>> = HashMap::new();
+    for &root in &roots {
+        if let Some(i) = tree.nodes[root].email
+            && references(&emails[i]).next().is_none()
+        {
+            let subject = normalize_subject(&emails[i].subject);
+            if !subject.is_empty() {
+                by_subject.entry(subject).or_default().push(root);
+            }
+        }
+    }
+    for (subject, mut group) in by_subject {
+        group.sort_by_key(|&n| tree.date(n, emails));
+        let runs: Vec<Vec<usize>> = group
+            .chunk_by(|&a, &b| tree.date(b, emails) - tree.date(a, emails) <= MAX_THREAD_GAP_SECS)
+            .map(<[usize]>::to_vec)
+            .collect();
+        for run in runs {
+            if run.len() < 2 {
+                continue;
+            }
+            // Under the first message that is not a reply, else a placeholder
+            let parent = run
                 .iter()
-                .filter(|&&i| !emails[i].flags.contains(EmailFlags::SEEN))
-                .count();
-
-            let has_attachments = indices.iter().any(|&i| emails[i].has_attachments);
-            // Safety: indices is always non-empty because thread_groups only contains
-            // entries that were populated via push() or extend() with non-empty data.
-            // Using unwrap_or with first index as fallback for defensive programming.
-            let latest_idx = indices.last().copied().unwrap_or(indices[0]);
-            let latest_date = emails[latest_idx].date;
-            let total_count = indices.len();
-
-            EmailThread {
-                id,
-                email_indices: indices,
-                unread_count,
-                total_count,
-                latest_date,
-                has_attachments,
-                latest_idx,
+                .copied()
+                .find(|&n| {
+                    tree.nodes[n]
+                        .email
+                        .is_some_and(|i| !is_reply(&emails[i].subject))
+                })
+                .unwrap_or_else(|| {
+                    let id = format!("subj:{}:{}", subject, tree.date(run[0], emails));
+                    let placeholder = tree.add(Some(id));
+                    roots.push(placeholder);
+                    placeholder
+                });
+            for &node in &run {
+                if node != parent {
+                    tree.set_parent(node, Some(parent));
+                }
             }
+        }
+    }
+
+    let mut threads: Vec<EmailThread> = roots
+        .into_iter()
+        .filter(|&root| tree.nodes[root].parent.is_none())
+        .map(|root| {
+            let container = &tree.nodes[root];
+            let id = match container.email {
+                Some(i) => emails[i]
+                    .message_id
+                    .clone()
+                    .unwrap_or_else(|| format!("uid:{}", emails[i].uid)),
+                None => container.id.clone().unwrap_or_default(),
+            };
+            let mut nodes = Vec::new();
+            tree.flatten(root, 0, emails, &mut nodes);
+            EmailThread::new(id, nodes, emails)
         })
         .collect();
 
     // Sort threads by latest date descending
+    #[allow(clippy::unnecessary_sort_by)]
     threads.sort_unstable_by(|a, b| b.latest_date.cmp(&a.latest_date));
 
     threads
@@ -236,8 +411,9 @@ pub fn group_into_threads(emails: &[EmailHeader]) -> Vec<EmailThread> {
 /// Merge new emails into existing threads efficiently.
 /// Returns true if incremental merge was possible, false if full rebuild is needed.
 ///
-/// Incremental merge is possible when none of the new emails are parents of existing emails.
-/// This is the common case for pagination (loading older emails).
+/// Incremental merge is possible when every new email either replies to a
+/// loaded email or starts a thread of its own. This is the common case for
+/// pagination (loading older emails).
 pub fn merge_into_threads(
     threads: &mut Vec<EmailThread>,
     all_emails: &[EmailHeader],
@@ -247,205 +423,121 @@ pub fn merge_into_threads(
         return true; // Nothing to merge
     }
 
+    let existing = &all_emails[..new_email_start_idx];
     let new_emails = &all_emails[new_email_start_idx..];
-    if new_emails.is_empty() {
-        return true;
-    }
 
-    // Build index of message-ids that existing threads are looking for (as parents)
-    let mut wanted_parents: std::collections::HashSet<&str> = std::collections::HashSet::new();
-    for email in &all_emails[..new_email_start_idx] {
-        if let Some(ref reply_to) = email.in_reply_to {
-            wanted_parents.insert(reply_to);
+    // Message-ids that existing emails reference (as parents or ancestors)
+    let wanted: HashSet<&str> = existing.iter().flat_map(references).collect();
+    let new_ids: HashSet<&str> = new_emails
+        .iter()
+        .filter_map(|e| e.message_id.as_deref())
+        .collect();
+
+    // Build message-id index for existing emails (for finding parents of new emails)
+    let existing_by_message_id: HashMap<&str, usize> = existing
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| e.message_id.as_deref().map(|mid| (mid, i)))
+        .collect();
+
+    // Decide where each new email goes before touching any thread
+    let mut replies: Vec<(usize, usize)> = Vec::new();
+    let mut singles: Vec<usize> = Vec::new();
+    let mut unthreaded_subjects: HashSet<String> = HashSet::new();
+    for (offset, email) in new_emails.iter().enumerate() {
+        let email_idx = new_email_start_idx + offset;
+
+        // New email is a parent of an existing email - need full rebuild
+        if email
+            .message_id
+            .as_deref()
+            .is_some_and(|mid| wanted.contains(mid))
+        {
+            return false;
         }
-        for ref_id in &email.references {
-            wanted_parents.insert(ref_id);
+
+        // Nearest loaded ancestor
+        let mut ancestors: Vec<&str> = references(email).collect();
+        ancestors.reverse();
+        if let Some(&parent_idx) = ancestors
+            .iter()
+            .find_map(|id| existing_by_message_id.get(id))
+        {
+            replies.push((parent_idx, email_idx));
+            continue;
         }
-    }
 
-    // Check if any new email is a wanted parent
-    for email in new_emails {
-        if let Some(ref mid) = email.message_id
-            && wanted_parents.contains(mid.as_str())
+        // Siblings under a missing message, or replies among the new
+        // emails, need the placeholders of a full rebuild
+        if ancestors
+            .iter()
+            .any(|id| wanted.contains(id) || new_ids.contains(id))
         {
-            // New email is a parent of existing email - need full rebuild
             return false;
         }
-    }
 
-    // Build message-id index for existing emails (for finding parents of new emails)
-    let mut existing_by_message_id: std::collections::HashMap<&str, usize> =
-        std::collections::HashMap::new();
-    for (i, email) in all_emails[..new_email_start_idx].iter().enumerate() {
-        if let Some(ref mid) = email.message_id {
-            existing_by_message_id.insert(mid, i);
+        // Same for subject grouping of emails without threading headers
+        if ancestors.is_empty() {
+            let subject = normalize_subject(&email.subject);
+            if !subject.is_empty() && !unthreaded_subjects.insert(subject) {
+                return false;
+            }
         }
+        singles.push(email_idx);
+    }
+    if !unthreaded_subjects.is_empty()
+        && existing.iter().any(|e| {
+            references(e).next().is_none()
+                && unthreaded_subjects.contains(&normalize_subject(&e.subject))
+        })
+    {
+        return false;
     }
-
-    // Build thread-id to thread-index map (using owned strings to avoid borrow issues)
-    let thread_by_id: std::collections::HashMap<String, usize> = threads
-        .iter()
-        .enumerate()
-        .map(|(idx, thread)| (thread.id.clone(), idx))
-        .collect();
 
     // Build email_idx -> thread_idx map for O(1) parent lookup (instead of O(n) linear search)
-    let mut email_to_thread: std::collections::HashMap<usize, usize> =
-        std::collections::HashMap::with_capacity(new_email_start_idx);
+    let mut email_to_thread: HashMap<usize, usize> = HashMap::with_capacity(new_email_start_idx);
     for (thread_idx, thread) in threads.iter().enumerate() {
         for &email_idx in &thread.email_indices {
             email_to_thread.insert(email_idx, thread_idx);
         }
     }
 
-    // Process new emails - collect updates to apply later
-    struct ThreadUpdate {
-        thread_idx: usize,
-        email_idx: usize,
-    }
-    let mut thread_updates: Vec<ThreadUpdate> = Vec::new();
-    let mut subject_groups: std::collections::HashMap<String, Vec<usize>> =
-        std::collections::HashMap::new();
-
-    for (offset, email) in new_emails.iter().enumerate() {
-        let email_idx = new_email_start_idx + offset;
-
-        // Try to find parent in existing emails
-        let parent_idx = email
-            .in_reply_to
-            .as_ref()
-            .and_then(|r| existing_by_message_id.get(r.as_str()).copied())
-            .or_else(|| {
-                email
-                    .references
-                    .iter()
-                    .rev()
-                    .find_map(|r| existing_by_message_id.get(r.as_str()).copied())
-            });
-
-        if let Some(parent_idx) = parent_idx {
-            // Find which thread the parent belongs to - O(1) HashMap lookup instead of O(n) linear search
-            let parent_thread_idx = email_to_thread.get(&parent_idx).copied();
-
-            if let Some(thread_idx) = parent_thread_idx {
-                thread_updates.push(ThreadUpdate {
-                    thread_idx,
-                    email_idx,
-                });
-                continue;
-            }
-        }
-
-        // No parent found - group by subject or create new thread
-        let normalized = normalize_subject(&email.subject);
-        subject_groups
-            .entry(normalized)
-            .or_default()
-            .push(email_idx);
-    }
-
-    // Apply thread updates
-    for update in thread_updates {
-        let email = &all_emails[update.email_idx];
-        let thread = &mut threads[update.thread_idx];
-        // Insert at correct position to maintain date order
-        let insert_pos = thread
-            .email_indices
-            .iter()
-            .position(|&idx| all_emails[idx].date > email.date)
-            .unwrap_or(thread.email_indices.len());
-        thread.email_indices.insert(insert_pos, update.email_idx);
-
-        // Update metadata
-        thread.total_count += 1;
-        if !email.flags.contains(EmailFlags::SEEN) {
-            thread.unread_count += 1;
-        }
-        if email.has_attachments {
-            thread.has_attachments = true;
-        }
-        if email.date > thread.latest_date {
-            thread.latest_date = email.date;
-            thread.latest_idx = update.email_idx;
-        }
+    for (parent_idx, email_idx) in replies {
+        let Some(&thread_idx) = email_to_thread.get(&parent_idx) else {
+            return false;
+        };
+        let thread = &mut threads[thread_idx];
+        let Some(parent) = thread.email_indices.iter().position(|&i| i == parent_idx) else {
+            return false;
+        };
+        thread.insert_reply(parent, email_idx, all_emails);
     }
 
-    // Create new threads from subject groups
-    let mut new_threads: Vec<EmailThread> = Vec::new();
-    for (subject, indices) in subject_groups {
-        // Check if there's an existing thread with this subject
-        let thread_id = format!("subj:{}", subject);
-        if let Some(&thread_idx) = thread_by_id.get(&thread_id) {
-            // Add to existing subject-based thread
-            let thread = &mut threads[thread_idx];
-            for &email_idx in &indices {
-                let email = &all_emails[email_idx];
-                let insert_pos = thread
-                    .email_indices
-                    .iter()
-                    .position(|&idx| all_emails[idx].date > email.date)
-                    .unwrap_or(thread.email_indices.len());
-                thread.email_indices.insert(insert_pos, email_idx);
-                thread.total_count += 1;
-                if !email.flags.contains(EmailFlags::SEEN) {
-                    thread.unread_count += 1;
-                }
-                if email.has_attachments {
-                    thread.has_attachments = true;
-                }
-                if email.date > thread.latest_date {
-                    thread.latest_date = email.date;
-                    thread.latest_idx = email_idx;
-                }
-            }
-        } else {
-            // Create new thread(s)
-            for &email_idx in &indices {
-                let email = &all_emails[email_idx];
-                let id = email
-                    .message_id
-                    .clone()
-                    .unwrap_or_else(|| format!("uid:{}", email.uid));
-                new_threads.push(EmailThread {
-                    id,
-                    email_indices: vec![email_idx],
-                    unread_count: if email.flags.contains(EmailFlags::SEEN) {
-                        0
-                    } else {
-                        1
-                    },
-                    total_count: 1,
-                    latest_date: email.date,
-                    has_attachments: email.has_attachments,
-                    latest_idx: email_idx,
-                });
-            }
-        }
+    for email_idx in singles {
+        let email = &all_emails[email_idx];
+        let id = email
+            .message_id
+            .clone()
+            .unwrap_or_else(|| format!("uid:{}", email.uid));
+        threads.push(EmailThread::new(id, vec![(email_idx, 0)], all_emails));
     }
 
-    // Append new threads and re-sort by latest date
-    threads.extend(new_threads);
+    // Re-sort by latest date
+    #[allow(clippy::unnecessary_sort_by)]
     threads.sort_unstable_by(|a, b| b.latest_date.cmp(&a.latest_date));
 
     true
 }
 
-/// Find root with path compression for O(log n) amortized lookups
-fn find_root_compressed(parent: &mut [Option<usize>], mut i: usize) -> usize {
-    // First pass: find the root
-    let mut current = i;
-    while let Some(p) = parent[current] {
-        current = p;
-    }
-    let root = current;
-
-    // Second pass: path compression - point all non-root nodes directly to root
-    while let Some(p) = parent[i] {
-        parent[i] = Some(root);
-        i = p;
+/// Order threads for the list: newest activity first by default, else by
+/// where their first message sits in `emails` (which the cache returned in
+/// `order`)
+pub fn sort_threads(threads: &mut [EmailThread], order: SortOrder) {
+    if order == SortOrder::DateDesc {
+        threads.sort_unstable_by_key(|t| std::cmp::Reverse(t.latest_date));
+    } else {
+        threads.sort_unstable_by_key(|t| t.email_indices.iter().min().copied());
     }
-
-    root
 }
 
 /// Normalize subject for grouping: strip Re:/Fwd:/Fw: prefixes and lowercase
@@ -506,6 +598,8 @@ mod tests {
             in_reply_to: in_reply_to.map(|s| s.to_string()),
             references: Vec::new(),
             folder: None,
+            list: None,
+            size: 0,
         }
     }
 
@@ -592,6 +686,29 @@ mod tests {
         assert_eq!(threads[2].latest_date, 1000);
     }
 
+    #[test]
+    fn test_sort_threads_keeps_list_order() {
+        // The list came sorted by subject; a reply doesn't move its thread up
+        let emails = vec![
+            make_email(1, "Alpha", Some("a@test"), None, 1000),
+            make_email(2, "Beta", Some("b@test"), None, 3000),
+            make_email(3, "Re: Alpha", Some("c@test"), Some("a@test"), 5000),
+            make_email(4, "Gamma", Some("d@test"), None, 4000),
+        ];
+
+        let mut threads = group_into_threads(&emails);
+        sort_threads(&mut threads, SortOrder::Subject);
+        let firsts: Vec<u32> = threads
+            .iter()
+            .map(|t| emails[t.email_indices[0]].uid)
+            .collect();
+        assert_eq!(firsts, vec![1, 2, 4]);
+
+        sort_threads(&mut threads, SortOrder::DateDesc);
+        let latest: Vec<i64> = threads.iter().map(|t| t.latest_date).collect();
+        assert_eq!(latest, vec![5000, 4000, 3000]);
+    }
+
     #[test]
     fn test_thread_unread_count() {
         let mut email1 = make_email(1, "Hello", Some("msg1@test"), None, 1000);
@@ -706,4 +823,123 @@ mod tests {
         assert_eq!(thread.latest(&emails).uid, 3);
         assert_eq!(thread.latest_date, 3000);
     }
+
+    #[test]
+    fn test_missing_parent_keeps_siblings_together() {
+        // Both reply to a message that is not loaded (e.g. it lives in Sent)
+        let emails = vec![
+            make_email(1, "Re: Plan", Some("b@test"), Some("a@test"), 2000),
+            make_email(2, "Re: Plan", Some("c@test"), Some("a@test"), 3000),
+        ];
+
+        let threads = group_into_threads(&emails);
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, "a@test");
+        assert_eq!(threads[0].email_indices, vec![0, 1]);
+        assert_eq!(threads[0].depths, vec![0, 0]);
+    }
+
+    #[test]
+    fn test_unrelated_replies_with_same_subject_stay_apart() {
+        let emails = vec![
+            make_email(1, "Re: Meeting", Some("b@test"), Some("a@test"), 1000),
+            make_email(2, "Re: Meeting", Some("y@test"), Some("x@test"), 2000),
+        ];
+
+        let threads = group_into_threads(&emails);
+        assert_eq!(threads.len(), 2);
+    }
+
+    #[test]
+    fn test_reply_tree_order_and_depth() {
+        // a ── b ── d
+        //  └── c
+        let mut d = make_email(4, "Re: Hi", Some("d@test"), Some("b@test"), 4000);
+        d.references = vec!["a@test".to_string(), "b@test".to_string()];
+        let emails = vec![
+            make_email(1, "Hi", Some("a@test"), None, 1000),
+            make_email(2, "Re: Hi", Some("b@test"), Some("a@test"), 2000),
+            make_email(3, "Re: Hi", Some("c@test"), Some("a@test"), 3000),
+            d,
+        ];
+
+        let threads = group_into_threads(&emails);
+        assert_eq!(threads.len(), 1);
+        let thread = &threads[0];
+        let uids: Vec<u32> = thread.emails(&emails).map(|e| e.uid).collect();
+        assert_eq!(uids, vec![1, 2, 4, 3]);
+        assert_eq!(thread.depths, vec![0, 1, 2, 1]);
+        assert_eq!(thread.latest(&emails).uid, 4);
+
+        // b has a later sibling (c), d is the last reply under b
+        assert_eq!(thread.branches(1), vec![false, true]);
+        assert_eq!(thread.branches(2), vec![false, true, false]);
+        assert_eq!(thread.branches(3), vec![false, false]);
+    }
+
+    #[test]
+    fn test_references_link_through_missing_message() {
+        // c's References name a and the missing b, so c goes under a
+        let mut c = make_email(3, "Re: Hi", Some("c@test"), Some("b@test"), 3000);
+        c.references = vec!["a@test".to_string(), "b@test".to_string()];
+        let emails = vec![make_email(1, "Hi", Some("a@test"), None, 1000), c];
+
+        let threads = group_into_threads(&emails);
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].depths, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_reference_loop_is_ignored() {
+        let emails = vec![
+            make_email(1, "Loop", Some("a@test"), Some("b@test"), 1000),
+            make_email(2, "Loop", Some("b@test"), Some("a@test"), 2000),
+        ];
+
+        let threads = group_into_threads(&emails);
+        assert_eq!(threads.iter().map(EmailThread::len).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_merge_reply_into_tree() {
+        let mut emails = vec![
+            make_email(1, "Hi", Some("a@test"), None, 1000),
+            make_email(2, "Re: Hi", Some("b@test"), Some("a@test"), 3000),
+        ];
+        let mut threads = group_into_threads(&emails);
+        emails.push(make_email(
+            3,
+            "Re: Hi",
+            Some("c@test"),
+            Some("a@test"),
+            2000,
+        ));
+
+        assert!(merge_into_threads(&mut threads, &emails, 2));
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].email_indices, vec![0, 2, 1]);
+        assert_eq!(threads[0].depths, vec![0, 1, 1]);
+        assert_eq!(threads[0].total_count, 3);
+    }
+
+    #[test]
+    fn test_merge_sibling_of_missing_parent_needs_rebuild() {
+        let mut emails = vec![make_email(
+            1,
+            "Re: Plan",
+            Some("b@test"),
+            Some("a@test"),
+            2000,
+        )];
+        let mut threads = group_into_threads(&emails);
+        emails.push(make_email(
+            2,
+            "Re: Plan",
+            Some("c@test"),
+            Some("a@test"),
+            1000,
+        ));
+
+        assert!(!merge_into_threads(&mut threads, &emails, 1));
+    }
 }
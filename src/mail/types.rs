@@ -1,6 +1,12 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
+use super::address::{self, Address};
+use super::html;
+
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
     pub struct EmailFlags: u32 {
@@ -31,6 +37,134 @@ pub struct EmailHeader {
     pub references: Vec<String>,
     /// Folder this email belongs to (e.g., "INBOX", "Sent")
     pub folder: Option<String>,
+    /// Mailing-list headers, if the message came through a list
+    pub list: Option<ListInfo>,
+    /// RFC822.SIZE in bytes (0 if the server did not say)
+    pub size: u32,
+}
+
+/// Order of the inbox list, applied in the cache queries that page it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    DateDesc,
+    DateAsc,
+    Sender,
+    Subject,
+    Size,
+    UnreadFirst,
+    FlaggedFirst,
+}
+
+impl SortOrder {
+    pub const ALL: [Self; 7] = [
+        Self::DateDesc,
+        Self::DateAsc,
+        Self::Sender,
+        Self::Subject,
+        Self::Size,
+        Self::UnreadFirst,
+        Self::FlaggedFirst,
+    ];
+
+    /// Name used by `:sort` and stored per folder
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DateDesc => "date",
+            Self::DateAsc => "date-asc",
+            Self::Sender => "sender",
+            Self::Subject => "subject",
+            Self::Size => "size",
+            Self::UnreadFirst => "unread",
+            Self::FlaggedFirst => "flagged",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::DateDesc => "newest first",
+            Self::DateAsc => "oldest first",
+            Self::Sender => "by sender",
+            Self::Subject => "by subject",
+            Self::Size => "largest first",
+            Self::UnreadFirst => "unread first",
+            Self::FlaggedFirst => "starred first",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "newest" | "date-desc" => Some(Self::DateDesc),
+            "oldest" | "asc" => Some(Self::DateAsc),
+            "from" => Some(Self::Sender),
+            "starred" => Some(Self::FlaggedFirst),
+            _ => Self::ALL.into_iter().find(|order| order.name() == name),
+        }
+    }
+
+    /// Compare emails the way the cache orders them (to merge lists read
+    /// from two folders). NOCASE folds ASCII only, and so does this.
+    pub fn compare(self, a: &EmailHeader, b: &EmailHeader) -> Ordering {
+        let sender = |e: &EmailHeader| {
+            e.from_name
+                .as_deref()
+                .filter(|name| !name.is_empty())
+                .unwrap_or(&e.from_addr)
+                .to_ascii_lowercase()
+        };
+        let by_key = match self {
+            Self::DateDesc | Self::DateAsc => Ordering::Equal,
+            Self::Sender => sender(a).cmp(&sender(b)),
+            Self::Subject => a
+                .subject
+                .to_ascii_lowercase()
+                .cmp(&b.subject.to_ascii_lowercase()),
+            Self::Size => b.size.cmp(&a.size),
+            Self::UnreadFirst => a.is_seen().cmp(&b.is_seen()),
+            Self::FlaggedFirst => b.is_flagged().cmp(&a.is_flagged()),
+        };
+        let by_date = (b.date, b.uid).cmp(&(a.date, a.uid));
+        let by_date = if self == Self::DateAsc {
+            by_date.reverse()
+        } else {
+            by_date
+        };
+        by_key.then(by_date)
+    }
+
+    /// Next order in the keybinding's cycle
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&o| o == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Mailing-list headers (RFC 2919 List-Id, RFC 2369 List-*, RFC 8058)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListInfo {
+    /// List identifier, e.g. "dev.lists.example.org"
+    pub id: Option<String>,
+    /// Description from List-Id, e.g. "Example developers"
+    pub name: Option<String>,
+    /// List-Unsubscribe URIs (mailto: and https:), in header order
+    pub unsubscribe: Vec<String>,
+    /// List-Unsubscribe-Post asks for RFC 8058 one-click unsubscribe
+    pub one_click: bool,
+    /// List-Archive URI
+    pub archive: Option<String>,
+    /// List-Post address for replies to the list
+    pub post: Option<String>,
+}
+
+impl ListInfo {
+    /// Short name for badges: the description, else the first label of the id
+    pub fn label(&self) -> Option<&str> {
+        self.name
+            .as_deref()
+            .filter(|n| !n.is_empty())
+            .or_else(|| self.id.as_deref().and_then(|id| id.split('.').next()))
+    }
 }
 
 impl EmailHeader {
@@ -62,26 +196,210 @@ impl EmailHeader {
 pub struct EmailBody {
     pub text: Option<String>,
     pub html: Option<String>,
+    /// Signature and encryption status (None = not a signed or encrypted message)
+    pub security: Option<SecurityInfo>,
+    /// Authentication-Results / ARC-Authentication-Results headers as
+    /// unfolded `Name: value` lines, topmost first
+    pub auth_results: Vec<String>,
+    /// First text/calendar part (an iCalendar invitation, reply or cancellation)
+    pub calendar: Option<String>,
+    /// Raw message of an encrypted body that isn't decrypted yet (that
+    /// happens when the message is opened, see `parser::decrypt_body`)
+    pub ciphertext: Option<Arc<[u8]>>,
+}
+
+/// What was found when verifying and decrypting a message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecurityInfo {
+    pub scheme: Scheme,
+    /// The message was encrypted (and decrypted, unless `error` is set)
+    pub encrypted: bool,
+    pub signature: Option<SignatureStatus>,
+    /// Why the message couldn't be decrypted or checked
+    pub error: Option<String>,
+}
+
+/// Message security standard
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scheme {
+    /// RFC 3156 PGP/MIME
+    #[default]
+    OpenPgp,
+    /// RFC 8551 S/MIME (CMS, X.509 certificates)
+    Smime,
+}
+
+impl Scheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenPgp => "PGP",
+            Self::Smime => "S/MIME",
+        }
+    }
+
+    /// Name stored in the cache
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::OpenPgp => "openpgp",
+            Self::Smime => "smime",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "smime" => Self::Smime,
+            _ => Self::OpenPgp,
+        }
+    }
+}
+
+/// Result of checking a message signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureStatus {
+    pub state: SignatureState,
+    /// Signer's user ID, or the key ID if the key isn't known
+    pub signer: Option<String>,
+    pub trust: Trust,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureState {
+    Good,
+    Bad,
+    /// Signed with a key we don't have
+    UnknownKey,
+    ExpiredKey,
+    RevokedKey,
+    /// Verification couldn't be completed
+    Error,
+}
+
+/// How much the signing key is trusted (OpenPGP validity)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trust {
+    #[default]
+    Unknown,
+    Never,
+    Marginal,
+    Full,
+    Ultimate,
+}
+
+impl SecurityInfo {
+    /// Serialize for the body cache
+    pub fn to_cache_string(&self) -> String {
+        let mut fields = Vec::new();
+        if self.scheme == Scheme::Smime {
+            fields.push("scheme=smime".to_string());
+        }
+        if self.encrypted {
+            fields.push("encrypted=1".to_string());
+        }
+        if let Some(ref sig) = self.signature {
+            let state = match sig.state {
+                SignatureState::Good => "good",
+                SignatureState::Bad => "bad",
+                SignatureState::UnknownKey => "unknown",
+                SignatureState::ExpiredKey => "expired",
+                SignatureState::RevokedKey => "revoked",
+                SignatureState::Error => "error",
+            };
+            let trust = match sig.trust {
+                Trust::Unknown => "unknown",
+                Trust::Never => "never",
+                Trust::Marginal => "marginal",
+                Trust::Full => "full",
+                Trust::Ultimate => "ultimate",
+            };
+            fields.push(format!("sig={}", state));
+            fields.push(format!("trust={}", trust));
+            if let Some(ref signer) = sig.signer {
+                fields.push(format!("signer={}", signer.replace('\n', " ")));
+            }
+        }
+        if let Some(ref error) = self.error {
+            fields.push(format!("error={}", error.replace('\n', " ")));
+        }
+        fields.join("\n")
+    }
+
+    /// Parse what `to_cache_string` produced
+    pub fn from_cache_string(s: &str) -> Self {
+        let mut info = Self::default();
+        let mut state = None;
+        let mut trust = Trust::Unknown;
+        let mut signer = None;
+        for line in s.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "scheme" if value == "smime" => info.scheme = Scheme::Smime,
+                "encrypted" => info.encrypted = value == "1",
+                "sig" => {
+                    state = Some(match value {
+                        "good" => SignatureState::Good,
+                        "bad" => SignatureState::Bad,
+                        "unknown" => SignatureState::UnknownKey,
+                        "expired" => SignatureState::ExpiredKey,
+                        "revoked" => SignatureState::RevokedKey,
+                        _ => SignatureState::Error,
+                    })
+                }
+                "trust" => {
+                    trust = match value {
+                        "never" => Trust::Never,
+                        "marginal" => Trust::Marginal,
+                        "full" => Trust::Full,
+                        "ultimate" => Trust::Ultimate,
+                        _ => Trust::Unknown,
+                    }
+                }
+                "signer" => signer = Some(value.to_string()),
+                "error" => info.error = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        info.signature = state.map(|state| SignatureStatus {
+            state,
+            signer,
+            trust,
+        });
+        info
+    }
 }
 
 impl EmailBody {
+    /// Encrypted and waiting to be decrypted
+    pub fn is_locked(&self) -> bool {
+        self.ciphertext.is_some()
+    }
+
     /// Get displayable text content
-    /// Returns plain text if available, otherwise strips HTML tags from HTML content
+    /// Returns plain text if available, otherwise renders the HTML content as
+    /// text with a `[N]` marker after each link (see `links::collect`)
     pub fn display_text(&self) -> String {
-        if let Some(ref text) = self.text {
+        if let Some(ref html) = self.html
+            && self.shows_html(false)
+        {
+            html::render(html, 80)
+                .map(|rendered| rendered.text())
+                .unwrap_or_else(|| html.to_string())
+        } else if let Some(ref text) = self.text {
             text.clone()
-        } else if let Some(ref html) = self.html {
-            // Strip HTML tags for display
-            strip_html_tags(html)
+        } else if self.is_locked() {
+            "[Encrypted message: decrypted when opened]".to_string()
         } else {
             "[No content]".to_string()
         }
     }
-}
 
-/// Convert HTML to readable plain text
-fn strip_html_tags(html: &str) -> String {
-    html2text::from_read(html.as_bytes(), 80).unwrap_or_else(|_| html.to_string())
+    /// Whether the reader shows the HTML part: when asked to, or when the
+    /// plain part is missing or blank
+    pub fn shows_html(&self, prefer_html: bool) -> bool {
+        self.html.is_some()
+            && (prefer_html || self.text.as_deref().is_none_or(|t| t.trim().is_empty()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -94,8 +412,110 @@ pub struct ComposeEmail {
     pub references: Option<String>,
     /// Index of the account to send from (None = use currently active account)
     pub from_account_index: Option<usize>,
+    /// Identity within the sending account (0 = the account's own address)
+    pub identity_index: usize,
     /// UID of the email being replied to (for setting ANSWERED flag after send)
     pub reply_to_uid: Option<u32>,
+    /// Treat the body as Markdown and send multipart/alternative with an HTML part
+    pub markdown: bool,
+    /// Signature currently inserted in the body (swapped when the account changes)
+    pub signature: Option<String>,
+    /// Files attached to the message (e.g. carried over when forwarding)
+    pub attachments: Vec<ComposeAttachment>,
+    /// OpenPGP signing and encryption requested for this message
+    pub protection: Protection,
+    /// iCalendar object sent as a text/calendar alternative (iMIP invitation replies)
+    pub calendar: Option<String>,
+}
+
+/// Signing and encryption requested for an outgoing message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Protection {
+    pub sign: bool,
+    pub encrypt: bool,
+    pub scheme: Scheme,
+}
+
+impl Protection {
+    pub const PGP_SIGN: Self = Self {
+        sign: true,
+        encrypt: false,
+        scheme: Scheme::OpenPgp,
+    };
+    pub const PGP_ENCRYPT: Self = Self {
+        sign: false,
+        encrypt: true,
+        scheme: Scheme::OpenPgp,
+    };
+    pub const PGP_SIGN_ENCRYPT: Self = Self {
+        sign: true,
+        encrypt: true,
+        scheme: Scheme::OpenPgp,
+    };
+    pub const SMIME_SIGN: Self = Self {
+        sign: true,
+        encrypt: false,
+        scheme: Scheme::Smime,
+    };
+
+    pub fn is_off(self) -> bool {
+        !self.sign && !self.encrypt
+    }
+
+    /// Next setting in the composer's cycle: off, then the PGP settings
+    /// (sign, encrypt, both) and S/MIME signing when available
+    pub fn cycle(self, pgp: bool, smime: bool) -> Self {
+        let mut options = vec![Self::default()];
+        if pgp {
+            options.extend([Self::PGP_SIGN, Self::PGP_ENCRYPT, Self::PGP_SIGN_ENCRYPT]);
+        }
+        if smime {
+            options.push(Self::SMIME_SIGN);
+        }
+        let next = options.iter().position(|&p| p == self).map_or(0, |i| i + 1);
+        options.get(next).copied().unwrap_or_default()
+    }
+
+    pub fn label(self) -> Option<String> {
+        let what = match (self.sign, self.encrypt) {
+            (false, false) => return None,
+            (true, false) => "signed",
+            (false, true) => "encrypted",
+            (true, true) => "signed + encrypted",
+        };
+        Some(format!("{} {}", self.scheme.name(), what))
+    }
+}
+
+/// A file attached to an outgoing message
+#[derive(Debug, Clone)]
+pub struct ComposeAttachment {
+    pub filename: String,
+    /// MIME type (e.g., "application/pdf", "message/rfc822")
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl ComposeAttachment {
+    /// Wrap a complete message as a message/rfc822 attachment
+    pub fn message(subject: &str, raw: Vec<u8>) -> Self {
+        let name: String = subject
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == ' ' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let name = name.trim();
+        Self {
+            filename: format!("{}.eml", if name.is_empty() { "message" } else { name }),
+            mime_type: "message/rfc822".to_string(),
+            data: raw,
+        }
+    }
 }
 
 impl ComposeEmail {
@@ -108,7 +528,13 @@ impl ComposeEmail {
             in_reply_to: None,
             references: None,
             from_account_index: None,
+            identity_index: 0,
             reply_to_uid: None,
+            markdown: false,
+            signature: None,
+            attachments: Vec::new(),
+            protection: Protection::default(),
+            calendar: None,
         }
     }
 
@@ -160,12 +586,19 @@ impl ComposeEmail {
             in_reply_to: original.message_id.clone(),
             references,
             from_account_index: None,
+            identity_index: 0,
             reply_to_uid: None, // Set by caller with the original email's UID
+            markdown: false,
+            signature: None,
+            attachments: Vec::new(),
+            protection: Protection::default(),
+            calendar: None,
         }
     }
 
     /// Create a reply-all email that includes original sender and all CC recipients
-    pub fn reply_all(original: &EmailHeader, original_body: &str, my_email: &str) -> Self {
+    /// Addresses in `my_addresses` (our own address and aliases) are left out
+    pub fn reply_all(original: &EmailHeader, original_body: &str, my_addresses: &[&str]) -> Self {
         let subject = if original.subject.starts_with("Re:") {
             original.subject.clone()
         } else {
@@ -200,37 +633,34 @@ impl ComposeEmail {
         let to = original.from_addr.clone();
 
         // CC: original To recipients (excluding ourselves) + original CC recipients
-        let mut cc_addrs: Vec<String> = Vec::new();
-
-        // Add original To recipients (could be multiple comma-separated)
-        if let Some(ref to_addr) = original.to_addr {
-            for addr in to_addr.split(',') {
-                let addr = addr.trim();
-                // Skip our own email
-                if !addr.eq_ignore_ascii_case(my_email) && !addr.is_empty() {
-                    cc_addrs.push(addr.to_string());
-                }
-            }
-        }
-
-        // Add original CC recipients (could be multiple comma-separated)
-        if let Some(ref cc_addr) = original.cc_addr {
-            for addr in cc_addr.split(',') {
-                let addr = addr.trim();
-                // Skip our own email and avoid duplicates
-                if !addr.eq_ignore_ascii_case(my_email)
-                    && !addr.is_empty()
-                    && !cc_addrs.iter().any(|a| a.eq_ignore_ascii_case(addr))
-                {
-                    cc_addrs.push(addr.to_string());
-                }
+        let is_mine = |addr: &Address| {
+            my_addresses
+                .iter()
+                .any(|me| addr.email.eq_ignore_ascii_case(me))
+        };
+        let mut cc_addrs: Vec<Address> = Vec::new();
+        let recipients = original
+            .to_addr
+            .iter()
+            .chain(original.cc_addr.iter())
+            .flat_map(|field| address::valid_addresses(field));
+        for addr in recipients {
+            // Skip our own addresses, the original sender (already in To) and duplicates
+            if !is_mine(&addr)
+                && !addr.email.eq_ignore_ascii_case(&original.from_addr)
+                && !cc_addrs
+                    .iter()
+                    .any(|a| a.email.eq_ignore_ascii_case(&addr.email))
+            {
+                cc_addrs.push(addr);
             }
         }
 
-        // Remove the original sender from CC (they're already in To)
-        cc_addrs.retain(|addr| !addr.eq_ignore_ascii_case(&original.from_addr));
-
-        let cc = cc_addrs.join(", ");
+        let cc = cc_addrs
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
 
         Self {
             to,
@@ -240,16 +670,18 @@ impl ComposeEmail {
             in_reply_to: original.message_id.clone(),
             references,
             from_account_index: None,
+            identity_index: 0,
             reply_to_uid: None, // Set by caller with the original email's UID
+            markdown: false,
+            signature: None,
+            attachments: Vec::new(),
+            protection: Protection::default(),
+            calendar: None,
         }
     }
 
     pub fn forward(original: &EmailHeader, original_body: &str) -> Self {
-        let subject = if original.subject.to_lowercase().starts_with("fwd:") {
-            original.subject.clone()
-        } else {
-            format!("Fwd: {}", original.subject)
-        };
+        let subject = forward_subject(&original.subject);
 
         let body = format!(
             "\n\n---------- Forwarded message ----------\n\
@@ -273,17 +705,57 @@ impl ComposeEmail {
             in_reply_to: None, // Forward is not a reply
             references: None,
             from_account_index: None,
+            identity_index: 0,
             reply_to_uid: None, // Forward doesn't set ANSWERED flag
+            markdown: false,
+            signature: None,
+            attachments: Vec::new(),
+            protection: Protection::default(),
+            calendar: None,
+        }
+    }
+
+    /// Forward the original message as a message/rfc822 attachment
+    pub fn forward_as_attachment(original: &EmailHeader, raw: Vec<u8>) -> Self {
+        Self {
+            subject: forward_subject(&original.subject),
+            attachments: vec![ComposeAttachment::message(&original.subject, raw)],
+            protection: Protection::default(),
+            ..Self::new()
         }
     }
 }
 
+/// Add a "Fwd: " prefix unless the subject already has one
+fn forward_subject(subject: &str) -> String {
+    if subject.to_lowercase().starts_with("fwd:") {
+        subject.to_string()
+    } else {
+        format!("Fwd: {}", subject)
+    }
+}
+
 impl Default for ComposeEmail {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// A message waiting in the send-later queue
+#[derive(Debug, Clone)]
+pub struct ScheduledMessage {
+    /// Database ID
+    pub id: i64,
+    /// Account the message is sent from
+    pub account_id: String,
+    /// When to send (Unix timestamp)
+    pub send_at: i64,
+    /// The message itself (`from_account_index` is resolved from `account_id` at send time)
+    pub email: ComposeEmail,
+    /// Error from the last send attempt (not retried automatically once set)
+    pub last_error: Option<String>,
+}
+
 /// Email attachment metadata
 #[derive(Debug, Clone)]
 pub struct Attachment {
@@ -341,3 +813,103 @@ impl Attachment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_order_names() {
+        for order in SortOrder::ALL {
+            assert_eq!(SortOrder::parse(order.name()), Some(order));
+        }
+        assert_eq!(SortOrder::parse("Oldest"), Some(SortOrder::DateAsc));
+        assert_eq!(SortOrder::parse("starred"), Some(SortOrder::FlaggedFirst));
+        assert_eq!(SortOrder::parse("random"), None);
+        assert_eq!(SortOrder::FlaggedFirst.next(), SortOrder::DateDesc);
+    }
+
+    #[test]
+    fn test_reply_all_keeps_quoted_names_together() {
+        let original = EmailHeader {
+            uid: 1,
+            message_id: None,
+            subject: "Plans".to_string(),
+            from_addr: "boss@x.com".to_string(),
+            from_name: None,
+            to_addr: Some(r#"me@x.com, "Doe, John" <john@x.com>"#.to_string()),
+            cc_addr: Some("Boss <boss@x.com>, JOHN@x.com, carol@y.org".to_string()),
+            date: 0,
+            flags: EmailFlags::empty(),
+            has_attachments: false,
+            preview: None,
+            body_cached: false,
+            in_reply_to: None,
+            references: Vec::new(),
+            folder: None,
+            list: None,
+            size: 0,
+        };
+        let reply = ComposeEmail::reply_all(&original, "", &["me@x.com"]);
+        assert_eq!(reply.to, "boss@x.com");
+        assert_eq!(reply.cc, r#""Doe, John" <john@x.com>, carol@y.org"#);
+    }
+
+    #[test]
+    fn test_security_info_cache_round_trip() {
+        let info = SecurityInfo {
+            scheme: Scheme::Smime,
+            encrypted: true,
+            signature: Some(SignatureStatus {
+                state: SignatureState::ExpiredKey,
+                signer: Some("Jane <jane@example.com>".to_string()),
+                trust: Trust::Marginal,
+            }),
+            error: None,
+        };
+        assert_eq!(
+            SecurityInfo::from_cache_string(&info.to_cache_string()),
+            info
+        );
+
+        let failed = SecurityInfo {
+            error: Some("No secret key".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            SecurityInfo::from_cache_string(&failed.to_cache_string()),
+            failed
+        );
+    }
+
+    #[test]
+    fn test_protection_cycle() {
+        let labels = |pgp: bool, smime: bool| {
+            let mut protection = Protection::default();
+            let mut labels = Vec::new();
+            loop {
+                protection = protection.cycle(pgp, smime);
+                match protection.label() {
+                    Some(label) => labels.push(label),
+                    None => return labels,
+                }
+            }
+        };
+        assert_eq!(
+            labels(true, true),
+            [
+                "PGP signed",
+                "PGP encrypted",
+                "PGP signed + encrypted",
+                "S/MIME signed"
+            ]
+        );
+        assert_eq!(labels(false, true), ["S/MIME signed"]);
+        assert!(labels(false, false).is_empty());
+        // A setting that is no longer available starts the cycle over
+        assert_eq!(
+            Protection::SMIME_SIGN.cycle(true, false),
+            Protection::default()
+        );
+    }
+}
@@ -3,16 +3,19 @@ mod actor;
 mod ai;
 mod app;
 mod cache;
+mod clipboard;
 mod command;
 mod config;
 mod constants;
 mod contacts;
 mod credentials;
+mod graphics;
 mod input;
 mod mail;
 #[cfg(feature = "notifications")]
 mod notification;
 mod oauth2;
+mod pipe;
 mod ui;
 
 use anyhow::Result;
@@ -128,6 +131,8 @@ Usage: bltz [command]
 Commands:
     (none)      Start the email client
     setup       Configure email account and credentials
+    smime       Store the passphrases of configured S/MIME identities
+    carddav     Store the passwords of configured CardDAV address books
     help        Show this help message
 
 Configuration file: ~/.config/bltz/config.toml
@@ -271,15 +276,30 @@ async fn run_setup() -> Result<()> {
                 server: smtp_server,
                 port: 587,
                 tls: true,
+                transport: Default::default(),
+                command: None,
             },
             notifications: None,
             auth: config::AuthMethod::Password,
+            markdown: false,
+            signature: None,
+            signature_file: None,
+            signature_placement: config::SignaturePlacement::default(),
+            identities: Vec::new(),
+            internal_domains: Vec::new(),
+            authserv_ids: Vec::new(),
+            smime: None,
         }],
         default_account: Some(0),
         notifications: config::NotificationConfig::default(),
         ui: config::UiConfig::default(),
         cache: config::CacheConfig::default(),
+        compose: config::ComposeConfig::default(),
         ai: config::AiConfig::default(),
+        pgp: config::PgpConfig::default(),
+        smime: config::SmimeConfig::default(),
+        carddav: Vec::new(),
+        pipe: Vec::new(),
     };
 
     // Save config
@@ -303,6 +323,53 @@ async fn run_setup() -> Result<()> {
     Ok(())
 }
 
+/// Prompt for the passphrase of each account's PKCS#12 identity
+fn run_smime_setup() -> Result<()> {
+    use std::io::{self, Write};
+
+    let config = Config::load()?;
+    let mut found = false;
+    for account in &config.accounts {
+        let Some(ref smime) = account.smime else {
+            continue;
+        };
+        found = true;
+        print!(
+            "Passphrase for {} ({}, empty for none): ",
+            account.email, smime.identity
+        );
+        io::stdout().flush()?;
+        let passphrase = rpassword_read()?;
+        println!();
+        CredentialStore::new(&account.email).set_smime_passphrase(&passphrase)?;
+        println!("Stored.");
+    }
+    if !found {
+        println!("No account has an [accounts.smime] identity configured.");
+    }
+    Ok(())
+}
+
+/// Prompt for the password of each CardDAV address book
+fn run_carddav_setup() -> Result<()> {
+    use std::io::{self, Write};
+
+    let config = Config::load()?;
+    if config.carddav.is_empty() {
+        println!("No [[carddav]] address book is configured.");
+        return Ok(());
+    }
+    for book in &config.carddav {
+        print!("Password for {} at {}: ", book.username, book.url);
+        io::stdout().flush()?;
+        let password = rpassword_read()?;
+        println!();
+        CredentialStore::new(&book.credential_id()).set_carddav_password(&password)?;
+        println!("Stored.");
+    }
+    Ok(())
+}
+
 fn rpassword_read() -> Result<String> {
     use std::io;
 
@@ -369,6 +436,8 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Some("setup") => run_setup().await,
+        Some("smime") => run_smime_setup(),
+        Some("carddav") => run_carddav_setup(),
         Some(cmd) => {
             eprintln!("Unknown command: {}", cmd);
             print_usage();
@@ -391,6 +460,11 @@ async fn main() -> Result<()> {
             // Initialize theme from config, with system dark mode detection
             let theme = resolve_theme(&config);
             crate::ui::theme::init_theme(theme);
+            crate::graphics::init(config.ui.images);
+
+            // OpenPGP backend used when parsing and sending mail
+            crate::mail::pgp::init(&config.pgp);
+            crate::mail::smime::init(&config);
 
             // Get the default account
             let account = config.default_account().ok_or_else(|| {
@@ -405,7 +479,9 @@ async fn main() -> Result<()> {
                 if let Err(e) = creds.get_imap_password() {
                     eprintln!("IMAP credential error: {}", e);
                 }
-                if let Err(e) = creds.get_smtp_password() {
+                if account.smtp.transport == config::SendTransport::Smtp
+                    && let Err(e) = creds.get_smtp_password()
+                {
                     eprintln!("SMTP credential error: {}", e);
                 }
 
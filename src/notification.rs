@@ -65,7 +65,9 @@ fn send_notification(summary: &str, body: Option<&str>) -> Result<(), notify_rus
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AuthMethod, ImapConfig, NotificationConfig, SmtpConfig};
+    use crate::config::{
+        AuthMethod, ImapConfig, NotificationConfig, SignaturePlacement, SmtpConfig,
+    };
 
     fn test_account(notifications: Option<bool>) -> AccountConfig {
         AccountConfig {
@@ -82,9 +84,19 @@ mod tests {
                 server: "smtp.example.com".to_string(),
                 port: 587,
                 tls: true,
+                transport: Default::default(),
+                command: None,
             },
             notifications,
             auth: AuthMethod::Password,
+            markdown: false,
+            signature: None,
+            signature_file: None,
+            signature_placement: SignaturePlacement::default(),
+            identities: Vec::new(),
+            internal_domains: Vec::new(),
+            authserv_ids: Vec::new(),
+            smime: None,
         }
     }
 
@@ -98,7 +110,12 @@ mod tests {
             },
             ui: Default::default(),
             cache: Default::default(),
+            compose: Default::default(),
             ai: Default::default(),
+            pgp: Default::default(),
+            smime: Default::default(),
+            carddav: Vec::new(),
+            pipe: Vec::new(),
         }
     }
 
@@ -0,0 +1,134 @@
+//! Running shell commands with a message on their standard input
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Longest a piped command may run before it is killed
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a piped command printed
+pub struct PipeOutput {
+    pub success: bool,
+    /// Exit code (None if killed by a signal)
+    pub code: Option<i32>,
+    /// Standard output followed by standard error
+    pub output: String,
+}
+
+/// Run `command` with `sh -c`, writing `input` to its stdin
+pub async fn run(command: &str, input: Vec<u8>) -> Result<PipeOutput> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start sh")?;
+
+    // Feed stdin concurrently so a command that writes a lot before reading
+    // everything can't deadlock; a command that stops reading early is fine
+    let mut stdin = child.stdin.take().context("No stdin")?;
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(&input).await;
+    });
+
+    let output = tokio::time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .context("Command timed out")?
+        .context("Failed to run command")?;
+    writer.abort();
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(PipeOutput {
+        success: output.status.success(),
+        code: output.status.code(),
+        output: text,
+    })
+}
+
+/// A message for an mbox: envelope sender, date (Unix time) and raw source
+pub struct MboxMessage<'a> {
+    pub sender: &'a str,
+    pub date: i64,
+    pub raw: &'a [u8],
+}
+
+/// Join messages into an mbox (mboxrd: body lines starting with `From `,
+/// after any number of `>`, get one more `>`)
+pub fn mbox(messages: &[MboxMessage]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for message in messages {
+        let date = chrono::DateTime::from_timestamp(message.date, 0).unwrap_or_default();
+        let sender = if message.sender.is_empty() {
+            "MAILER-DAEMON"
+        } else {
+            message.sender
+        };
+        out.extend_from_slice(
+            format!("From {} {}\n", sender, date.format("%a %b %e %H:%M:%S %Y")).as_bytes(),
+        );
+        for line in message.raw.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let quotes = line.iter().take_while(|b| **b == b'>').count();
+            if line[quotes..].starts_with(b"From ") {
+                out.push(b'>');
+            }
+            out.extend_from_slice(line);
+            out.push(b'\n');
+        }
+        // The raw message ended with a newline: keep one blank separator line
+        if !message.raw.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbox() {
+        let first = b"Subject: a\r\n\r\nFrom here on\r\n>From quoted\r\n";
+        let second = b"Subject: b\n\nbody";
+        let mbox = mbox(&[
+            MboxMessage {
+                sender: "a@example.com",
+                date: 1_704_103_200,
+                raw: first,
+            },
+            MboxMessage {
+                sender: "",
+                date: 0,
+                raw: second,
+            },
+        ]);
+
+        assert_eq!(
+            String::from_utf8(mbox).unwrap(),
+            "From a@example.com Mon Jan  1 10:00:00 2024\n\
+             Subject: a\n\n>From here on\n>>From quoted\n\n\
+             From MAILER-DAEMON Thu Jan  1 00:00:00 1970\n\
+             Subject: b\n\nbody\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let result = run("tr a-z A-Z; echo oops >&2; exit 3", b"hello\n".to_vec())
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.code, Some(3));
+        assert_eq!(result.output, "HELLO\noops\n");
+    }
+}
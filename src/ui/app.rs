@@ -9,10 +9,12 @@ use ratatui::widgets::Block;
 use crate::app::state::{AppState, View};
 
 use super::add_account::render_add_account;
+use super::components::{render_pipe_output, render_pipe_prompt};
 use super::composer::render_composer;
 use super::contacts::render_contacts;
 use super::inbox::render_inbox;
 use super::reader::render_reader;
+use super::scheduled::render_scheduled;
 use super::theme::Theme;
 
 pub fn render(frame: &mut Frame, state: &AppState) {
@@ -27,5 +29,14 @@ pub fn render(frame: &mut Frame, state: &AppState) {
         View::Composer { email, field } => render_composer(frame, state, email, *field),
         View::AddAccount { step, data } => render_add_account(frame, state, step, data),
         View::Contacts => render_contacts(frame, state),
+        View::Scheduled => render_scheduled(frame, state),
+    }
+
+    // Pipe popups sit above every view
+    if let Some(ref output) = state.pipe.output {
+        render_pipe_output(frame, frame.area(), output);
+    }
+    if let Some(ref prompt) = state.pipe.prompt {
+        render_pipe_prompt(frame, frame.area(), prompt);
     }
 }
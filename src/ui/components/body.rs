@@ -0,0 +1,59 @@
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use super::quotes::render_quoted_text;
+use crate::app::state::RenderedBody;
+use crate::mail::html::TextStyle;
+use crate::ui::theme::Theme;
+use crate::ui::widgets::highlight;
+
+/// Renders a message body: plain text with quote bars, or styled HTML lines,
+/// highlighting matches of `query` (if not empty)
+pub fn render_body_lines<'a>(body: &'a RenderedBody, query: &str) -> Vec<Line<'a>> {
+    match body {
+        RenderedBody::Text(text) => render_quoted_text(text, query),
+        RenderedBody::Html(lines) => lines
+            .iter()
+            .map(|line| {
+                Line::from(
+                    line.iter()
+                        .flat_map(|run| {
+                            let style = span_style(run.style);
+                            if query.is_empty() {
+                                vec![Span::styled(run.text.as_str(), style)]
+                            } else {
+                                highlight(&run.text, query, style)
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn span_style(style: TextStyle) -> Style {
+    let mut base = if style.contains(TextStyle::HEADING) {
+        Theme::text_accent().add_modifier(Modifier::BOLD)
+    } else if style.contains(TextStyle::LINK) {
+        Theme::text_link()
+    } else if style.contains(TextStyle::MARKER) {
+        Theme::text_accent()
+    } else if style.contains(TextStyle::CODE) {
+        Theme::text_secondary()
+    } else if style.contains(TextStyle::RULE) {
+        Theme::text_muted()
+    } else {
+        Theme::text()
+    };
+    if style.contains(TextStyle::BOLD) {
+        base = base.add_modifier(Modifier::BOLD);
+    }
+    if style.contains(TextStyle::ITALIC) {
+        base = base.add_modifier(Modifier::ITALIC);
+    }
+    if style.contains(TextStyle::STRIKE) {
+        base = base.add_modifier(Modifier::CROSSED_OUT);
+    }
+    base
+}
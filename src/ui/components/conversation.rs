@@ -0,0 +1,118 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+
+use super::quotes::{folded_placeholder, render_quoted_text};
+use crate::app::state::{Conversation, ConversationMessage, ConversationRow};
+use crate::mail::quotes::Row;
+use crate::ui::theme::Theme;
+use crate::ui::widgets::{format_date, sanitize_text, spinner_char};
+
+/// Every message of the thread stacked: a header line per message, with the
+/// body under the expanded ones
+pub fn render_conversation(
+    frame: &mut Frame,
+    area: Rect,
+    conversation: &Conversation,
+    show_quotes: bool,
+) {
+    let rows = conversation.rows(show_quotes);
+    let visible: Vec<ConversationRow> = rows
+        .into_iter()
+        .skip(conversation.scroll)
+        .take(area.height as usize)
+        .collect();
+
+    // Sanitized body text of the visible rows (the styled lines borrow it)
+    let texts: Vec<String> = visible
+        .iter()
+        .map(|row| match *row {
+            ConversationRow::Body(n, Row::Line(line)) => conversation.messages[n]
+                .lines
+                .get(line)
+                .map(|text| sanitize_text(text))
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .collect();
+
+    let lines: Vec<Line> = visible
+        .iter()
+        .zip(&texts)
+        .map(|(row, text)| match *row {
+            ConversationRow::Header(n) => header_line(
+                &conversation.messages[n],
+                n == conversation.selected,
+                area.width as usize,
+            ),
+            ConversationRow::Body(_, Row::Line(_)) => {
+                indent(render_quoted_text(text, "").pop().unwrap_or_default())
+            }
+            ConversationRow::Body(_, Row::Folded(block)) => {
+                indent(folded_placeholder(block, false))
+            }
+            ConversationRow::Loading(_) => Line::styled(
+                format!("  {} Loading...", spinner_char()),
+                Theme::text_muted(),
+            ),
+            ConversationRow::Gap(_) => Line::default(),
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .style(Theme::main_bg())
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// "▾ Sender  date", with the start of the message when it is collapsed
+fn header_line(message: &ConversationMessage, selected: bool, width: usize) -> Line<'static> {
+    let email = &message.email;
+    let marker = if message.expanded { "▾ " } else { "▸ " };
+    let from_style = if email.is_seen() {
+        Theme::text()
+    } else {
+        Theme::text().add_modifier(Modifier::BOLD)
+    };
+    let from = sanitize_text(email.display_from());
+    let date = format_date(email.date);
+    let mut spans = vec![
+        Span::styled(marker, Theme::text_accent()),
+        Span::styled(from.clone(), from_style),
+        Span::styled(format!("  {}", date), Theme::text_muted()),
+    ];
+    if !message.expanded {
+        // Kept to one line so collapsed messages stay one row each
+        let used = 2 + from.chars().count() + 2 + date.chars().count() + 2;
+        let preview: String = email
+            .preview
+            .as_deref()
+            .map(sanitize_text)
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(width.saturating_sub(used))
+            .collect();
+        spans.push(Span::styled(
+            format!("  {}", preview),
+            Theme::text_secondary(),
+        ));
+    }
+    let line = Line::from(spans);
+    if selected {
+        line.style(Theme::selected())
+    } else {
+        line
+    }
+}
+
+fn indent(mut line: Line<'_>) -> Line<'_> {
+    line.spans.insert(0, Span::raw("  "));
+    line
+}
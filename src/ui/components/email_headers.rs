@@ -1,12 +1,48 @@
 use ratatui::Frame;
 use ratatui::layout::Rect;
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 
-use crate::mail::types::EmailHeader;
+use crate::app::state::ReaderState;
+use crate::mail::auth::{self, AuthSummary, Verdict};
+use crate::mail::types::{EmailHeader, SecurityInfo, SignatureState, Trust};
 use crate::ui::theme::Theme;
 use crate::ui::widgets::format_date;
 
+/// Verification results shown with the headers
+pub struct HeaderStatus<'a> {
+    /// OpenPGP/S/MIME status of the loaded body (adds a "Security:" line)
+    pub security: Option<&'a SecurityInfo>,
+    /// Sender authentication; its warnings are always shown as a banner
+    pub auth: AuthSummary,
+    /// Add the DKIM/SPF/DMARC line (expanded headers with a loaded body)
+    pub show_auth: bool,
+}
+
+impl<'a> HeaderStatus<'a> {
+    pub fn new(reader: &'a ReaderState, email: &EmailHeader) -> Self {
+        let body = reader.body.as_ref();
+        Self {
+            security: body.and_then(|b| b.security.as_ref()),
+            auth: auth::evaluate(
+                body.map_or(&[], |b| b.auth_results.as_slice()),
+                &reader.auth_policy,
+                &email.from_addr,
+                email.from_name.as_deref(),
+            ),
+            show_auth: reader.headers_expanded && body.is_some(),
+        }
+    }
+
+    /// Lines this adds to the header block
+    pub fn height(&self) -> u16 {
+        self.auth.warnings.len() as u16
+            + u16::from(self.security.is_some())
+            + u16::from(self.show_auth)
+    }
+}
+
 /// Renders email headers (From, To, CC, Date, Subject) in a bordered area.
 ///
 /// # Arguments
@@ -15,12 +51,14 @@ use crate::ui::widgets::format_date;
 /// * `email` - The email header data
 /// * `show_attachments` - Whether to show the attachment indicator
 /// * `wrap` - Whether to wrap long lines (used in expanded preview)
+/// * `status` - Signature, encryption and sender authentication results
 pub fn render_email_headers(
     frame: &mut Frame,
     area: Rect,
     email: &EmailHeader,
     show_attachments: bool,
     wrap: bool,
+    status: &HeaderStatus,
 ) {
     let block = Block::default()
         .borders(Borders::BOTTOM)
@@ -41,6 +79,14 @@ pub fn render_email_headers(
 
     let mut lines: Vec<Line> = Vec::new();
 
+    // Spoofing warnings go first so they can't be missed
+    for warning in &status.auth.warnings {
+        lines.push(Line::from(Span::styled(
+            format!("\u{26A0} {}", warning),
+            Theme::text_danger(),
+        )));
+    }
+
     // From
     lines.push(Line::from(vec![
         Span::styled("From:    ", label_style),
@@ -83,6 +129,22 @@ pub fn render_email_headers(
         ]));
     }
 
+    // Signature / encryption status
+    if let Some(security) = status.security {
+        let (text, style) = security_status(security);
+        lines.push(Line::from(vec![
+            Span::styled("Security:", label_style),
+            Span::styled(text, style),
+        ]));
+    }
+
+    // DKIM / SPF / DMARC badges
+    if status.show_auth {
+        let mut spans = vec![Span::styled("Auth:    ", label_style)];
+        spans.extend(auth_badges(&status.auth));
+        lines.push(Line::from(spans));
+    }
+
     let paragraph = if wrap {
         Paragraph::new(lines)
             .style(Theme::main_bg())
@@ -93,3 +155,114 @@ pub fn render_email_headers(
 
     frame.render_widget(paragraph, inner);
 }
+
+/// "DKIM pass  SPF fail  DMARC pass", coloured by verdict
+fn auth_badges(auth: &AuthSummary) -> Vec<Span<'static>> {
+    if !auth.trusted {
+        return vec![Span::styled(
+            "no results from a trusted server",
+            Theme::text_muted(),
+        )];
+    }
+    let results = [
+        ("DKIM", auth.dkim),
+        ("SPF", auth.spf),
+        ("DMARC", auth.dmarc),
+    ];
+    let mut spans = Vec::new();
+    for (method, verdict) in results {
+        let Some(verdict) = verdict else {
+            continue;
+        };
+        let style = match verdict {
+            Verdict::Pass => Theme::text_success(),
+            Verdict::Fail | Verdict::PermError => Theme::text_danger(),
+            Verdict::SoftFail | Verdict::Neutral | Verdict::None | Verdict::TempError => {
+                Theme::text_warning()
+            }
+        };
+        if !spans.is_empty() {
+            spans.push(Span::raw("  "));
+        }
+        spans.push(Span::styled(
+            format!("{} {}", method, verdict.name()),
+            style,
+        ));
+    }
+    if spans.is_empty() {
+        spans.push(Span::styled(
+            "no DKIM, SPF or DMARC results",
+            Theme::text_muted(),
+        ));
+    }
+    spans
+}
+
+/// Describe a message's OpenPGP status, styled by how much it can be trusted
+fn security_status(security: &SecurityInfo) -> (String, Style) {
+    let mut parts = Vec::new();
+    let mut style = Theme::text_success();
+
+    if security.encrypted {
+        parts.push(
+            if security.error.is_some() && security.signature.is_none() {
+                "\u{1F512} Encrypted, not decrypted".to_string()
+            } else {
+                "\u{1F512} Encrypted".to_string()
+            },
+        );
+    }
+
+    if let Some(ref signature) = security.signature {
+        let signer = signature.signer.as_deref().unwrap_or("unknown signer");
+        let (text, sig_style) = match signature.state {
+            SignatureState::Good => match signature.trust {
+                Trust::Full | Trust::Ultimate => (
+                    format!("Good signature from {}", signer),
+                    Theme::text_success(),
+                ),
+                Trust::Marginal => (
+                    format!("Good signature from {} (marginal trust)", signer),
+                    Theme::text_warning(),
+                ),
+                Trust::Never => (
+                    format!("Good signature from {} (untrusted key)", signer),
+                    Theme::text_danger(),
+                ),
+                Trust::Unknown => (
+                    format!("Good signature from {} (unverified key)", signer),
+                    Theme::text_warning(),
+                ),
+            },
+            SignatureState::Bad => (
+                format!("BAD signature from {}", signer),
+                Theme::text_danger(),
+            ),
+            SignatureState::UnknownKey => (
+                format!("Signed with unknown {}", signer),
+                Theme::text_warning(),
+            ),
+            SignatureState::ExpiredKey => (
+                format!("Signed by {} (key expired)", signer),
+                Theme::text_warning(),
+            ),
+            SignatureState::RevokedKey => (
+                format!("Signed by {} (key revoked)", signer),
+                Theme::text_danger(),
+            ),
+            SignatureState::Error => ("Signature not checked".to_string(), Theme::text_warning()),
+        };
+        parts.push(text);
+        style = sig_style;
+    }
+
+    if let Some(ref error) = security.error {
+        parts.push(error.clone());
+        style = Theme::text_danger();
+    }
+
+    (
+        format!(" {}: {}", security.scheme.name(), parts.join(", ")),
+        style,
+    )
+}
@@ -0,0 +1,163 @@
+use chrono::{Duration, Local};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::mail::calendar::{Event, Invite, Method, PartStat, When};
+use crate::ui::theme::Theme;
+
+/// Attendees listed by name before the rest are summarized as "+N more"
+const MAX_ATTENDEES: usize = 6;
+/// Conflicting events listed individually
+const MAX_CONFLICTS: usize = 3;
+
+/// Lines of the invitation card; `conflicts` is None while still being looked up
+pub fn invite_lines(invite: &Invite, conflicts: Option<&[Event]>) -> Vec<Line<'static>> {
+    let event = &invite.event;
+    let label_style = Theme::label();
+    let value_style = Theme::text();
+
+    let (heading, heading_style) = match invite.method {
+        _ if invite.is_cancelled() => ("Cancelled", Theme::text_danger()),
+        Method::Request => ("Invitation", Theme::text_accent()),
+        Method::Reply => ("Invitation reply", Theme::text_accent()),
+        _ => ("Event", Theme::text_accent()),
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("\u{1F4C5} {}: ", heading), heading_style),
+        Span::styled(event.title().to_string(), Theme::text_unread()),
+    ])];
+
+    let mut when = format_span(event);
+    if event.rrule.is_some() {
+        when.push_str(" (repeats)");
+    }
+    lines.push(Line::from(vec![
+        Span::styled("When:    ", label_style),
+        Span::styled(when, value_style),
+    ]));
+
+    if let Some(ref location) = event.location {
+        lines.push(Line::from(vec![
+            Span::styled("Where:   ", label_style),
+            Span::styled(location.clone(), value_style),
+        ]));
+    }
+
+    if let Some(ref organizer) = event.organizer {
+        let text = match organizer.name {
+            Some(ref name) => format!("{} <{}>", name, organizer.email),
+            None => organizer.email.clone(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Organizer:", label_style),
+            Span::styled(format!(" {}", text), value_style),
+        ]));
+    }
+
+    if !event.attendees.is_empty() {
+        let mut spans = vec![Span::styled("Guests:  ", label_style)];
+        for (i, attendee) in event.attendees.iter().take(MAX_ATTENDEES).enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(", ", Theme::text_muted()));
+            }
+            let (mark, style) = status_mark(attendee.status);
+            spans.push(Span::styled(
+                attendee.person.display().to_string(),
+                value_style,
+            ));
+            spans.push(Span::styled(format!(" {}", mark), style));
+            if attendee.optional {
+                spans.push(Span::styled(" (optional)", Theme::text_muted()));
+            }
+        }
+        if event.attendees.len() > MAX_ATTENDEES {
+            spans.push(Span::styled(
+                format!(", +{} more", event.attendees.len() - MAX_ATTENDEES),
+                Theme::text_muted(),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    if invite.is_request() {
+        match conflicts {
+            None => {}
+            Some([]) => lines.push(Line::from(Span::styled(
+                "No conflicts with other invitations",
+                Theme::text_success(),
+            ))),
+            Some(conflicts) => {
+                for other in conflicts.iter().take(MAX_CONFLICTS) {
+                    lines.push(Line::from(Span::styled(
+                        format!(
+                            "\u{26A0} Conflicts with {} ({})",
+                            other.title(),
+                            format_span(other)
+                        ),
+                        Theme::text_warning(),
+                    )));
+                }
+                if conflicts.len() > MAX_CONFLICTS {
+                    lines.push(Line::from(Span::styled(
+                        format!("  and {} more", conflicts.len() - MAX_CONFLICTS),
+                        Theme::text_warning(),
+                    )));
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// Render the card built by `invite_lines` above the message body
+pub fn render_invite(frame: &mut Frame, area: Rect, lines: Vec<Line<'static>>) {
+    let block = Block::default()
+        .borders(Borders::BOTTOM)
+        .border_style(Theme::border())
+        .style(Theme::main_bg());
+    let paragraph = Paragraph::new(lines).block(block).style(Theme::main_bg());
+    frame.render_widget(paragraph, area);
+}
+
+fn status_mark(status: PartStat) -> (&'static str, ratatui::style::Style) {
+    match status {
+        PartStat::Accepted => ("\u{2713}", Theme::text_success()),
+        PartStat::Tentative => ("?", Theme::text_warning()),
+        PartStat::Declined => ("\u{2717}", Theme::text_danger()),
+        PartStat::Delegated => ("\u{2192}", Theme::text_muted()),
+        PartStat::NeedsAction => ("\u{2026}", Theme::text_muted()),
+    }
+}
+
+/// "Mon Mar 04, 2024 10:00 - 11:00" in local time, or a date range for all-day events
+fn format_span(event: &Event) -> String {
+    const DAY: &str = "%a %b %d, %Y";
+    match (event.start, event.end) {
+        (When::Date(start), When::Date(end)) => {
+            // DTEND of an all-day event is exclusive
+            let last = end - Duration::days(1);
+            if last <= start {
+                format!("{} (all day)", start.format(DAY))
+            } else {
+                format!("{} - {} (all day)", start.format(DAY), last.format(DAY))
+            }
+        }
+        (start, end) => {
+            let start = start.instant().with_timezone(&Local);
+            let end = end.instant().with_timezone(&Local);
+            let end_format = if end.date_naive() == start.date_naive() {
+                "%H:%M".to_string()
+            } else {
+                format!("{} %H:%M", DAY)
+            };
+            format!(
+                "{} - {}",
+                start.format(&format!("{} %H:%M", DAY)),
+                end.format(&end_format)
+            )
+        }
+    }
+}
@@ -1,7 +1,17 @@
+mod body;
+mod conversation;
 mod email_headers;
+mod invite;
+mod pipe;
 mod popup;
 mod quotes;
+mod source;
 
-pub use email_headers::render_email_headers;
+pub use body::render_body_lines;
+pub use conversation::render_conversation;
+pub use email_headers::{HeaderStatus, render_email_headers};
+pub use invite::{invite_lines, render_invite};
+pub use pipe::{render_pipe_output, render_pipe_prompt};
 pub use popup::centered_rect_constrained;
-pub use quotes::render_quoted_text;
+pub use quotes::{folded_placeholder, render_quoted_text};
+pub use source::render_source;
@@ -0,0 +1,61 @@
+//! Pipe command prompt and output popups
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::app::state::{PipeOutputView, PipePrompt};
+use crate::config::PipeInput;
+use crate::ui::theme::{Theme, borders};
+
+use super::popup::{centered_rect_constrained, render_text_popup};
+
+pub fn render_pipe_prompt(frame: &mut Frame, area: Rect, prompt: &PipePrompt) {
+    let popup_area = centered_rect_constrained(area, 40, 70, 4, 4);
+    frame.render_widget(Clear, popup_area);
+
+    let target = match prompt.target {
+        PipeInput::Raw => "raw message",
+        PipeInput::Text => "text body",
+        PipeInput::Thread => "thread (mbox)",
+    };
+    let block = Block::default()
+        .title(format!(" Pipe {} to ", target))
+        .title_bottom(" Tab input │ Enter run │ Esc cancel ")
+        .borders(Borders::ALL)
+        .border_type(borders::popup())
+        .border_style(Theme::border_focused())
+        .style(Theme::main_bg());
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let line = Line::from(vec![
+        Span::styled("| ", Theme::text_accent()),
+        Span::styled(prompt.input.as_str(), Theme::text()),
+        Span::styled("█", Theme::text_muted()),
+    ]);
+    frame.render_widget(Paragraph::new(line).style(Theme::main_bg()), inner);
+}
+
+pub fn render_pipe_output(frame: &mut Frame, area: Rect, output: &PipeOutputView) {
+    let status_style = if output.success {
+        Theme::text_success()
+    } else {
+        Theme::text_danger()
+    };
+    let title = Line::from(vec![
+        Span::styled(format!(" {} ", output.command), Theme::text()),
+        Span::styled(format!("[{}] ", output.status), status_style),
+    ]);
+    render_text_popup(
+        frame,
+        area,
+        title,
+        " j/k scroll │ Esc close ",
+        &output.lines,
+        output.scroll,
+    );
+}
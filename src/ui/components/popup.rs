@@ -1,4 +1,12 @@
-use ratatui::layout::Rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::ui::theme::{Theme, borders};
+use crate::ui::widgets::sanitize_text;
 
 /// Creates a centered rect with min/max constraints for width and height.
 /// The actual size is clamped between min and max, then constrained to fit within area.
@@ -19,3 +27,37 @@ pub fn centered_rect_constrained(
     let y = (area.height.saturating_sub(h)) / 2;
     Rect::new(x, y, w, h)
 }
+
+/// Render a scrollable popup of plain text lines over `area`
+pub fn render_text_popup(
+    frame: &mut Frame,
+    area: Rect,
+    title: Line,
+    footer: &str,
+    lines: &[String],
+    scroll: usize,
+) {
+    let max_height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+    let popup_area = centered_rect_constrained(area, 40, 100, 6, max_height.max(6));
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(title)
+        .title_bottom(footer)
+        .borders(Borders::ALL)
+        .border_type(borders::popup())
+        .border_style(Theme::border_focused())
+        .style(Theme::main_bg());
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let text: Vec<Line> = lines
+        .iter()
+        .skip(scroll)
+        .take(inner.height as usize)
+        .map(|line| Line::from(Span::styled(sanitize_text(line), Theme::text())))
+        .collect();
+    frame.render_widget(Paragraph::new(text).style(Theme::main_bg()), inner);
+}
@@ -1,11 +1,14 @@
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 
+use crate::mail::quotes::{Block, BlockKind};
 use crate::ui::theme::Theme;
+use crate::ui::widgets::highlight;
 
 /// Renders text with visual quote bars for email quoted lines.
 /// Alternating colors for nested quotes: cyan, yellow, magenta, green.
-pub fn render_quoted_text(text: &str) -> Vec<Line<'_>> {
+/// Matches of `query` (if not empty) are highlighted.
+pub fn render_quoted_text<'a>(text: &'a str, query: &str) -> Vec<Line<'a>> {
     let quote_colors: [Style; 4] = [
         Theme::text_accent(),      // Cyan - level 1
         Theme::star_indicator(),   // Yellow - level 2
@@ -27,11 +30,34 @@ pub fn render_quoted_text(text: &str) -> Vec<Line<'_>> {
                 }
                 // Strip leading > characters and spaces
                 let content = trimmed.trim_start_matches('>').trim_start();
-                spans.push(Span::styled(content, Theme::text_muted()));
+                if query.is_empty() {
+                    spans.push(Span::styled(content, Theme::text_muted()));
+                } else {
+                    spans.extend(highlight(content, query, Theme::text_muted()));
+                }
                 Line::from(spans)
-            } else {
+            } else if query.is_empty() {
                 Line::styled(line, Theme::text())
+            } else {
+                Line::from(highlight(line, query, Theme::text()))
             }
         })
         .collect()
 }
+
+/// One-line placeholder shown in place of a folded block
+pub fn folded_placeholder(block: Block, matches_inside: bool) -> Line<'static> {
+    let what = match block.kind {
+        BlockKind::Quote => "quoted",
+        BlockKind::Signature => "signature",
+    };
+    let found = if matches_inside {
+        " · matches inside"
+    } else {
+        ""
+    };
+    Line::styled(
+        format!("[… {} {} lines{}]", block.len, what, found),
+        Theme::text_muted().add_modifier(Modifier::ITALIC),
+    )
+}
@@ -0,0 +1,93 @@
+//! Raw message source and full header table
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::state::SourceView;
+use crate::ui::theme::Theme;
+use crate::ui::widgets::{display_width, highlight, sanitize_text};
+
+/// Widest header name column before names are cut
+const MAX_NAME_WIDTH: usize = 28;
+
+pub fn render_source(frame: &mut Frame, area: Rect, source: &SourceView) {
+    let title = if source.show_headers {
+        format!(" Headers · {} fields ", source.headers.len())
+    } else {
+        format!(" Source · {} lines ", source.lines.len())
+    };
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Theme::border())
+        .title(Span::styled(title, Theme::text_secondary()))
+        .style(Theme::main_bg());
+    let mut inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if let Some(ref input) = source.find_input {
+        inner.height = inner.height.saturating_sub(1);
+        let line = Line::from(vec![
+            Span::styled("/", Theme::text_accent()),
+            Span::styled(input.as_str(), Theme::text()),
+            Span::styled("█", Theme::text_muted()),
+        ]);
+        let prompt_area = Rect::new(inner.x, inner.y + inner.height, inner.width, 1);
+        frame.render_widget(Paragraph::new(line).style(Theme::main_bg()), prompt_area);
+    }
+
+    // Only the rows from the scroll position on; each may wrap
+    let rows = inner.height as usize;
+    let lines: Vec<Line> = if source.show_headers {
+        let name_width = source
+            .headers
+            .iter()
+            .map(|(name, _)| display_width(name))
+            .max()
+            .unwrap_or(0)
+            .min(MAX_NAME_WIDTH);
+        source
+            .headers
+            .iter()
+            .skip(source.scroll)
+            .take(rows)
+            .map(|(name, value)| {
+                let name = format!("{:<width$}  ", name, width = name_width);
+                let mut spans = highlight(
+                    &name,
+                    &source.query,
+                    Theme::text_secondary().add_modifier(Modifier::BOLD),
+                );
+                spans.extend(highlight(
+                    &sanitize_text(value),
+                    &source.query,
+                    Theme::text(),
+                ));
+                Line::from(spans)
+            })
+            .collect()
+    } else {
+        source
+            .lines
+            .iter()
+            .skip(source.scroll)
+            .take(rows)
+            .map(|line| {
+                Line::from(highlight(
+                    &sanitize_text(line),
+                    &source.query,
+                    Theme::text(),
+                ))
+            })
+            .collect()
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .style(Theme::main_bg())
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, inner);
+}
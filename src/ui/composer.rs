@@ -5,12 +5,14 @@ use ratatui::{
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
 };
 
-use super::theme::{Theme, borders};
+use super::theme::{Theme, borders, with_selection_bg};
 use super::widgets::{error_bar, help_bar, status_bar};
-use crate::app::state::{AppState, ComposerField, PolishPreview};
+use crate::app::schedule::SEND_TIME_HINT;
+use crate::app::state::{AppState, ComposerField, FromPicker, PolishPreview, TemplatePicker};
 use crate::mail::types::ComposeEmail;
+use crate::mail::{address, markdown};
 
-/// Composer layout areas computed based on account count
+/// Composer layout areas computed based on account and identity count
 struct ComposerLayout {
     status_area: Rect,
     from_area: Option<Rect>,
@@ -21,8 +23,8 @@ struct ComposerLayout {
     help_area: Rect,
 }
 
-fn compute_layout(area: Rect, has_multiple_accounts: bool) -> ComposerLayout {
-    if has_multiple_accounts {
+fn compute_layout(area: Rect, show_from: bool) -> ComposerLayout {
+    if show_from {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -77,7 +79,8 @@ pub fn render_composer(
     field: ComposerField,
 ) {
     let has_multiple_accounts = state.connection.account_names.len() > 1;
-    let layout = compute_layout(frame.area(), has_multiple_accounts);
+    let show_from = has_multiple_accounts || state.composer.from_selectable;
+    let layout = compute_layout(frame.area(), show_from);
 
     // Status bar
     let status = if email.in_reply_to.is_some() {
@@ -87,9 +90,15 @@ pub fn render_composer(
     } else {
         "New Email"
     };
-    status_bar(frame, layout.status_area, status, "");
+    let mode = match (email.markdown, email.protection.label()) {
+        (true, Some(pgp)) => format!("Markdown, {}", pgp),
+        (true, None) => "Markdown".to_string(),
+        (false, Some(pgp)) => pgp.to_string(),
+        (false, None) => String::new(),
+    };
+    status_bar(frame, layout.status_area, status, &mode);
 
-    // From field (multi-account only)
+    // From field (multiple accounts or identities only)
     if let Some(from_area) = layout.from_area {
         let from_account_index = email
             .from_account_index
@@ -99,26 +108,33 @@ pub fn render_composer(
             .account_names
             .get(from_account_index)
             .map(|s| s.as_str())
-            .unwrap_or("Unknown");
-        render_from_field(frame, from_area, from_account_name);
+            .filter(|_| has_multiple_accounts);
+        render_from_field(
+            frame,
+            from_area,
+            from_account_name,
+            &state.composer.from_label,
+        );
     }
 
     // To field
-    render_field(
+    render_address_field(
         frame,
         layout.to_area,
         "To",
         &email.to,
         field == ComposerField::To,
+        &state.composer.internal_domains,
     );
 
     // Cc field
-    render_field(
+    render_address_field(
         frame,
         layout.cc_area,
         "Cc",
         &email.cc,
         field == ComposerField::Cc,
+        &state.composer.internal_domains,
     );
 
     // Subject field
@@ -134,44 +150,38 @@ pub fn render_composer(
     render_body_field(
         frame,
         layout.body_area,
-        &email.body,
+        email,
         field == ComposerField::Body,
+        state.composer.preview,
     );
 
     // Help bar or error
     if let Some(ref error) = state.status.error {
         error_bar(frame, layout.help_area, error);
     } else {
-        let hints: &[(&str, &str)] = if state.autocomplete.visible {
-            &[("Tab", "select"), ("↑/↓", "nav"), ("Esc", "close")]
-        } else if has_multiple_accounts {
+        let hints: Vec<(&str, &str)> = if state.autocomplete.visible {
+            vec![("Tab", "select"), ("↑/↓", "nav"), ("Esc", "close")]
+        } else {
+            let mut hints = vec![("Tab", "next")];
+            if has_multiple_accounts {
+                hints.push(("Ctrl+A", "account"));
+            }
+            if state.composer.from_selectable {
+                hints.push(("Ctrl+L", "from"));
+            }
             if state.polish.enabled {
-                &[
-                    ("Tab", "next"),
-                    ("Ctrl+A", "account"),
-                    ("Ctrl+P", "polish"),
-                    ("Ctrl+S", "send"),
-                    ("Esc", "cancel"),
-                ]
-            } else {
-                &[
-                    ("Tab", "next"),
-                    ("Ctrl+A", "account"),
-                    ("Ctrl+S", "send"),
-                    ("Esc", "cancel"),
-                ]
+                hints.push(("Ctrl+P", "polish"));
             }
-        } else if state.polish.enabled {
-            &[
-                ("Tab", "next"),
-                ("Ctrl+P", "polish"),
-                ("Ctrl+S", "send"),
-                ("Esc", "cancel"),
-            ]
-        } else {
-            &[("Tab", "next"), ("Ctrl+S", "send"), ("Esc", "cancel")]
+            hints.push(("Ctrl+G", "template"));
+            hints.push(("Ctrl+E", "markdown"));
+            hints.push(("Ctrl+K", "sign"));
+            if email.markdown {
+                hints.push(("Ctrl+O", "preview"));
+            }
+            hints.extend([("Ctrl+S", "send"), ("Ctrl+W", "later"), ("Esc", "cancel")]);
+            hints
         };
-        help_bar(frame, layout.help_area, hints);
+        help_bar(frame, layout.help_area, &hints);
     }
 
     // Autocomplete dropdown (rendered last, on top)
@@ -184,6 +194,21 @@ pub fn render_composer(
         render_autocomplete_dropdown(frame, dropdown_area, state);
     }
 
+    // From selector popup
+    if let (Some(picker), Some(from_area)) = (&state.composer.from_picker, layout.from_area) {
+        render_from_picker(frame, from_area, picker);
+    }
+
+    // Template picker
+    if let Some(ref picker) = state.composer.template_picker {
+        render_template_picker(frame, layout.help_area, picker);
+    }
+
+    // Send-later prompt
+    if let Some(ref input) = state.composer.schedule_input {
+        render_schedule_prompt(frame, layout.help_area, input);
+    }
+
     // Polish preview modal (rendered on top of everything)
     if let Some(ref preview) = state.polish.preview {
         render_polish_preview(frame, preview);
@@ -216,11 +241,14 @@ fn render_autocomplete_dropdown(frame: &mut Frame, field_area: Rect, state: &App
                 Theme::text()
             };
 
-            let text = if let Some(ref name) = contact.name {
+            let mut text = if let Some(ref name) = contact.name {
                 format!("{} <{}>", name, contact.email)
             } else {
                 contact.email.clone()
             };
+            if let Some(ref organization) = contact.organization {
+                text.push_str(&format!(" \u{00B7} {}", organization));
+            }
 
             ListItem::new(text).style(style)
         })
@@ -237,7 +265,143 @@ fn render_autocomplete_dropdown(frame: &mut Frame, field_area: Rect, state: &App
     frame.render_widget(list, dropdown_area);
 }
 
-fn render_from_field(frame: &mut Frame, area: Rect, account_name: &str) {
+fn render_from_picker(frame: &mut Frame, field_area: Rect, picker: &FromPicker) {
+    let visible = 8.min(picker.choices.len());
+    let dropdown_area = Rect {
+        x: field_area.x,
+        y: field_area.y + field_area.height,
+        width: field_area.width,
+        height: (visible as u16) + 2, // +2 for borders
+    }
+    .intersection(frame.area());
+
+    frame.render_widget(Clear, dropdown_area);
+
+    // Keep the selection in view when there are more choices than rows
+    let offset = picker.selected.saturating_sub(visible.saturating_sub(1));
+    let items: Vec<ListItem> = picker
+        .choices
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(idx, choice)| {
+            let style = if idx == picker.selected {
+                Theme::selected()
+            } else {
+                Theme::text()
+            };
+            ListItem::new(choice.label.as_str()).style(style)
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(borders::popup())
+            .border_style(Theme::border_focused())
+            .style(Theme::main_bg())
+            .title(" Send from "),
+    );
+
+    frame.render_widget(list, dropdown_area);
+}
+
+fn render_template_picker(frame: &mut Frame, help_area: Rect, picker: &TemplatePicker) {
+    // Sits just above the help bar, like the send-later prompt
+    let visible = 8.min(picker.templates.len());
+    let height = (visible as u16) + 2; // +2 for borders
+    let area = Rect {
+        x: help_area.x,
+        y: help_area.y.saturating_sub(height),
+        width: help_area.width,
+        height,
+    }
+    .intersection(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    // Keep the selection in view when there are more templates than rows
+    let offset = picker.selected.saturating_sub(visible.saturating_sub(1));
+    let items: Vec<ListItem> = picker
+        .templates
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(idx, template)| {
+            let selected = idx == picker.selected;
+            let mut spans = vec![Span::styled(
+                template.name.as_str(),
+                if selected {
+                    Theme::selected()
+                } else {
+                    Theme::text()
+                },
+            )];
+            if let Some(ref subject) = template.subject {
+                spans.push(Span::styled(
+                    format!("  {}", subject),
+                    with_selection_bg(Theme::text_muted(), selected),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(borders::popup())
+            .border_style(Theme::border_focused())
+            .style(Theme::main_bg())
+            .title(" Templates ")
+            .title_bottom(" Enter insert · Esc cancel "),
+    );
+    frame.render_widget(list, area);
+}
+
+fn render_schedule_prompt(frame: &mut Frame, help_area: Rect, input: &str) {
+    // Sits just above the help bar so the body stays visible
+    let height = 4;
+    let area = Rect {
+        x: help_area.x,
+        y: help_area.y.saturating_sub(height),
+        width: help_area.width,
+        height,
+    }
+    .intersection(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("When: ", Theme::label()),
+            Span::styled(format!("{}│", input), Theme::text()),
+        ]),
+        Line::from(Span::styled(
+            format!("e.g. {}", SEND_TIME_HINT),
+            Theme::text_muted(),
+        )),
+    ];
+    let prompt = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(borders::popup())
+            .border_style(Theme::border_focused())
+            .style(Theme::main_bg())
+            .title(" Send later ")
+            .title_bottom(" Enter schedule · Esc cancel "),
+    );
+    frame.render_widget(prompt, area);
+}
+
+fn render_from_field(
+    frame: &mut Frame,
+    area: Rect,
+    account_name: Option<&str>,
+    identity_label: &str,
+) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Theme::border())
@@ -247,10 +411,14 @@ fn render_from_field(frame: &mut Frame, area: Rect, account_name: &str) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let line = Line::from(vec![
-        Span::styled(account_name, Theme::text_accent()),
-        Span::styled("  (Ctrl+A to change)", Theme::text_muted()),
-    ]);
+    let mut spans = Vec::new();
+    if let Some(account_name) = account_name {
+        spans.push(Span::styled(account_name, Theme::text_accent()));
+        spans.push(Span::styled(" · ", Theme::text_muted()));
+    }
+    spans.push(Span::styled(identity_label, Theme::text()));
+    spans.push(Span::styled("  (Ctrl+L to change)", Theme::text_muted()));
+    let line = Line::from(spans);
 
     let paragraph = Paragraph::new(line).style(Theme::main_bg());
     frame.render_widget(paragraph, inner);
@@ -295,7 +463,109 @@ fn render_field(frame: &mut Frame, area: Rect, label: &str, value: &str, focused
     frame.render_widget(paragraph, inner);
 }
 
-fn render_body_field(frame: &mut Frame, area: Rect, body: &str, focused: bool) {
+/// To/Cc field: recipients as chips, or the raw text with invalid
+/// addresses highlighted while editing
+fn render_address_field(
+    frame: &mut Frame,
+    area: Rect,
+    label: &str,
+    value: &str,
+    focused: bool,
+    internal_domains: &[String],
+) {
+    let entries = address::parse_entries(value);
+    let is_external = |entry: &address::Entry| matches!(entry.parsed, Ok(ref a) if !internal_domains.is_empty() && !a.is_in_domains(internal_domains));
+    // Don't flag the address still being typed at the end of the field
+    let typing_end = focused && !value.trim_end().ends_with([',', ';']);
+    let is_invalid = |entry: &address::Entry| {
+        entry.parsed.is_err() && !(typing_end && entry.range.end == value.trim_end().len())
+    };
+
+    let mut title = vec![Span::raw(format!(" {} ", label))];
+    let invalid = entries.iter().filter(|e| is_invalid(e)).count();
+    if invalid > 0 {
+        title.push(Span::styled(
+            format!("{} invalid ", invalid),
+            Theme::chip_invalid(),
+        ));
+    }
+    let external = entries.iter().filter(|e| is_external(e)).count();
+    if external > 0 {
+        title.push(Span::styled(
+            format!("{} external ", external),
+            Theme::chip_external(),
+        ));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(if focused {
+            borders::input_focused()
+        } else {
+            borders::panel()
+        })
+        .border_style(if focused {
+            Theme::border_focused()
+        } else {
+            Theme::border()
+        })
+        .style(Theme::main_bg())
+        .title(Line::from(title));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut spans = Vec::new();
+    if focused {
+        // Raw text, with invalid addresses underlined in red
+        let mut pos = 0;
+        for entry in &entries {
+            spans.push(Span::styled(&value[pos..entry.range.start], Theme::text()));
+            let style = if is_invalid(entry) {
+                Theme::chip_invalid()
+            } else {
+                Theme::text()
+            };
+            spans.push(Span::styled(&value[entry.range.clone()], style));
+            pos = entry.range.end;
+        }
+        spans.push(Span::styled(&value[pos..], Theme::text()));
+        spans.push(Span::styled("│", Theme::text()));
+    } else {
+        for entry in &entries {
+            let (text, style) = match entry.parsed {
+                Ok(ref a) => (
+                    a.name.clone().unwrap_or_else(|| a.email.clone()),
+                    if is_external(entry) {
+                        Theme::chip_external()
+                    } else {
+                        Theme::chip()
+                    },
+                ),
+                Err(_) => (
+                    value[entry.range.clone()].to_string(),
+                    Theme::chip_invalid(),
+                ),
+            };
+            spans.push(Span::styled(format!(" {} ", text), style));
+            spans.push(Span::raw(" "));
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(Line::from(spans)).style(Theme::main_bg()),
+        inner,
+    );
+}
+
+fn render_body_field(
+    frame: &mut Frame,
+    area: Rect,
+    email: &ComposeEmail,
+    focused: bool,
+    preview: bool,
+) {
+    let body = email.body.as_str();
     let border_style = if focused {
         Theme::border_focused()
     } else {
@@ -309,15 +579,32 @@ fn render_body_field(frame: &mut Frame, area: Rect, body: &str, focused: bool) {
     };
 
     let char_count = body.chars().count();
-    let title = format!(" Body ({} chars) ", char_count);
+    let title = if preview {
+        format!(" Body ({} chars, Markdown preview) ", char_count)
+    } else if email.markdown {
+        format!(" Body ({} chars, Markdown) ", char_count)
+    } else {
+        format!(" Body ({} chars) ", char_count)
+    };
 
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_type(border_type)
         .border_style(border_style)
         .style(Theme::main_bg())
         .title(title);
 
+    // List attachments along the bottom border
+    if !email.attachments.is_empty() {
+        let names = email
+            .attachments
+            .iter()
+            .map(|a| format!("📎 {}", a.filename))
+            .collect::<Vec<_>>()
+            .join("  ");
+        block = block.title_bottom(Line::styled(format!(" {} ", names), Theme::text_accent()));
+    }
+
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
@@ -327,7 +614,12 @@ fn render_body_field(frame: &mut Frame, area: Rect, body: &str, focused: bool) {
         Theme::text_secondary()
     };
 
-    let text = if focused {
+    let text = if preview && email.markdown {
+        // Show roughly what the HTML part will look like to the recipient
+        let html = markdown::render_html(body);
+        html2text::from_read(html.as_bytes(), inner.width.max(1) as usize)
+            .unwrap_or_else(|_| body.to_string())
+    } else if focused {
         format!("{}│", body)
     } else {
         body.to_string()
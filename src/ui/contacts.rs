@@ -11,7 +11,7 @@ use ratatui::{
 use super::components::centered_rect_constrained;
 use super::theme::{Theme, borders, with_selection_bg};
 use super::widgets::{error_bar, help_bar, truncate_string};
-use crate::app::state::AppState;
+use crate::app::state::{AppState, ContactField};
 
 pub fn render_contacts(frame: &mut Frame, state: &AppState) {
     let chunks = Layout::default()
@@ -34,12 +34,14 @@ pub fn render_contacts(frame: &mut Frame, state: &AppState) {
         error_bar(frame, chunks[2], error);
     } else {
         let hints = if state.contacts.editing.is_some() {
-            &[("Enter", "save"), ("Esc", "cancel")][..]
+            &[("Tab", "field"), ("Enter", "save"), ("Esc", "cancel")][..]
         } else {
             &[
                 ("j/k", "nav"),
                 ("e", "edit"),
                 ("d", "delete"),
+                ("Space", "mark"),
+                ("x", "export"),
                 ("Enter", "compose"),
                 ("Esc", "back"),
             ][..]
@@ -55,7 +57,10 @@ pub fn render_contacts(frame: &mut Frame, state: &AppState) {
 
 fn render_contacts_status_bar(frame: &mut Frame, area: Rect, state: &AppState) {
     let count = state.contacts.list.len();
-    let text = format!(" Contacts ({}) ", count);
+    let text = match state.contacts.marked.len() {
+        0 => format!(" Contacts ({}) ", count),
+        marked => format!(" Contacts ({}, {} marked) ", count, marked),
+    };
 
     let paragraph = Paragraph::new(text).style(Theme::status_bar());
     frame.render_widget(paragraph, area);
@@ -72,9 +77,10 @@ fn render_contacts_list(frame: &mut Frame, area: Rect, state: &AppState) {
     frame.render_widget(block, area);
 
     if state.contacts.list.is_empty() {
-        let msg =
-            Paragraph::new("No contacts yet. Contacts are added when you send or receive emails.")
-                .style(Theme::text_muted())
+        let msg = Paragraph::new(
+            "No contacts yet. Contacts are added when you send or receive emails, or with :import-contacts.",
+        )
+        .style(Theme::text_muted())
                 .alignment(ratatui::layout::Alignment::Center);
         frame.render_widget(msg, inner);
         return;
@@ -89,7 +95,8 @@ fn render_contacts_list(frame: &mut Frame, area: Rect, state: &AppState) {
         if is_selected {
             selected_index = Some(items.len());
         }
-        let contact_items = render_contact_item(contact, is_selected, inner.width);
+        let is_marked = state.contacts.marked.contains(&contact.id);
+        let contact_items = render_contact_item(contact, is_selected, is_marked, inner.width);
         items.extend(contact_items);
     }
 
@@ -120,18 +127,20 @@ fn render_contacts_list(frame: &mut Frame, area: Rect, state: &AppState) {
 fn render_contact_item(
     contact: &crate::contacts::Contact,
     selected: bool,
+    marked: bool,
     width: u16,
 ) -> Vec<ListItem<'static>> {
     let width = width as usize;
 
-    // Line 1: Name (or email if no name)
+    // Line 1: Mark, name (or email if no name)
     let display_name = contact.name.as_deref().unwrap_or(&contact.email);
     let has_name = contact.name.is_some();
+    let mark = if marked { "\u{2713} " } else { "  " };
 
     // Show contact count on right side
     let count_str = format!("({} interactions)", contact.contact_count);
     let count_width = count_str.len();
-    let name_width = width.saturating_sub(count_width + 2);
+    let name_width = width.saturating_sub(count_width + 4);
 
     let name_display = truncate_string(display_name, name_width);
     let padding = name_width.saturating_sub(name_display.len());
@@ -157,23 +166,34 @@ fn render_contact_item(
     let count_style = with_selection_bg(Theme::text_muted(), selected);
 
     let line1 = Line::from(vec![
+        Span::styled(mark, with_selection_bg(Theme::text_accent(), selected)),
         Span::styled(name_display, name_style),
         Span::styled(" ".repeat(padding), base_style),
         Span::styled(" ", base_style),
         Span::styled(count_str, count_style),
     ]);
 
-    // Line 2: Email (if name exists) or empty
-    let line2 = if has_name {
-        let email_display = truncate_string(&contact.email, width);
-        let email_padding = width.saturating_sub(email_display.len());
+    // Line 2: Email (if name exists), extra addresses, organization and phone
+    let mut details = Vec::new();
+    if has_name {
+        details.push(contact.email.clone());
+    }
+    if !contact.other_emails.is_empty() {
+        details.push(format!("+{} more", contact.other_emails.len()));
+    }
+    details.extend(contact.organization.clone());
+    details.extend(contact.phone.clone());
+
+    let line2 = if !details.is_empty() {
+        let details_display = truncate_string(&details.join(" \u{00B7} "), width);
+        let details_padding = width.saturating_sub(details_display.chars().count());
 
-        let email_style = with_selection_bg(Theme::text_muted(), selected);
+        let details_style = with_selection_bg(Theme::text_muted(), selected);
 
         Line::from(vec![
             Span::styled("  ", base_style), // indent
-            Span::styled(email_display, email_style),
-            Span::styled(" ".repeat(email_padding.saturating_sub(2)), base_style),
+            Span::styled(details_display, details_style),
+            Span::styled(" ".repeat(details_padding.saturating_sub(2)), base_style),
         ])
     } else {
         // Just show padding for alignment
@@ -190,14 +210,14 @@ fn render_contact_edit_popup(frame: &mut Frame, area: Rect, state: &AppState) {
     };
 
     // Calculate popup size and position
-    let popup_area = centered_rect_constrained(area, 30, 50, 7, 7);
+    let popup_area = centered_rect_constrained(area, 40, 60, 10, 10);
 
     // Clear the area behind the popup
     frame.render_widget(Clear, popup_area);
 
     // Create the popup block
     let block = Block::default()
-        .title(" Edit Contact Name ")
+        .title(" Edit Contact ")
         .borders(Borders::ALL)
         .border_type(borders::popup())
         .border_style(Theme::border_focused())
@@ -215,31 +235,52 @@ fn render_contact_edit_popup(frame: &mut Frame, area: Rect, state: &AppState) {
 
     let email = contact.map(|c| c.email.as_str()).unwrap_or("Unknown");
 
-    // Layout: email label, name input
-    let sections = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1), // Email (read-only)
-            Constraint::Length(1), // Spacer
-            Constraint::Length(1), // Name label
-            Constraint::Length(1), // Name input
-        ])
-        .split(inner);
-
-    // Email (read-only)
-    let email_line = Line::from(vec![
-        Span::styled("Email: ", Theme::label()),
-        Span::styled(email, Theme::text()),
-    ]);
-    frame.render_widget(Paragraph::new(email_line), sections[0]);
-
-    // Name label
-    let name_label = Paragraph::new("Name:").style(Theme::label());
-    frame.render_widget(name_label, sections[2]);
+    // Email (read-only), spacer, then one line per editable field
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Email:        ", Theme::label()),
+            Span::styled(email, Theme::text()),
+        ]),
+        Line::default(),
+    ];
+
+    let fields = [
+        (ContactField::Name, "Name:         ", &edit_state.name),
+        (
+            ContactField::OtherEmails,
+            "Also:         ",
+            &edit_state.other_emails,
+        ),
+        (ContactField::Phone, "Phone:        ", &edit_state.phone),
+        (
+            ContactField::Organization,
+            "Organization: ",
+            &edit_state.organization,
+        ),
+    ];
+    for (field, label, value) in fields {
+        // Active field gets the cursor
+        let line = if field == edit_state.field {
+            Line::from(vec![
+                Span::styled(label, Theme::label()),
+                Span::styled(
+                    format!("{}\u{2502}", value),
+                    Theme::text().add_modifier(Modifier::UNDERLINED),
+                ),
+            ])
+        } else {
+            Line::from(vec![
+                Span::styled(label, Theme::text_muted()),
+                Span::styled(value.as_str(), Theme::text()),
+            ])
+        };
+        lines.push(line);
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        "Separate extra addresses with commas",
+        Theme::text_muted(),
+    )));
 
-    // Name input with cursor
-    let input_text = format!("{}│", edit_state.name);
-    let input_style = Theme::text().add_modifier(Modifier::UNDERLINED);
-    let input = Paragraph::new(input_text).style(input_style);
-    frame.render_widget(input, sections[3]);
+    frame.render_widget(Paragraph::new(lines), inner);
 }
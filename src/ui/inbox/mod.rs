@@ -24,12 +24,15 @@ use crate::constants::{
     SPLIT_RATIO_MAX, SPLIT_RATIO_MIN, STATUS_BAR_HEIGHT_MODERN,
 };
 
-use super::components::{render_email_headers, render_quoted_text};
+use super::components::{
+    HeaderStatus, render_body_lines, render_email_headers, render_quoted_text,
+};
 use super::status_bar::spinner_char;
 use super::theme::{self, Theme};
 use super::widgets::{StatusInfo, enhanced_status_bar, error_bar, help_bar, sanitize_text};
 
-use popups::{render_command_bar, render_confirm_modal, render_unified_help_popup};
+pub(super) use popups::render_confirm_modal;
+use popups::{render_command_bar, render_unified_help_popup};
 use thread::render_thread_list;
 
 pub fn render_inbox(frame: &mut Frame, state: &AppState) {
@@ -242,6 +245,8 @@ fn render_preview(frame: &mut Frame, area: Rect, state: &AppState) {
             email.from_addr.clone()
         };
 
+        let status = HeaderStatus::new(&state.reader, email);
+        let status_lines = status.height();
         let header_lines = if expanded {
             // Calculate actual lines needed for each field when wrapped
             let from_lines = lines_for_field(&from_display);
@@ -257,9 +262,12 @@ fn render_preview(frame: &mut Frame, area: Rect, state: &AppState) {
 
             (from_lines + to_lines + cc_lines + date_lines + subject_lines + attach_lines)
                 .min(inner.height.saturating_sub(5))
+                + status_lines
         } else {
             // Collapsed: 1 line per field (From, To, Date, Subject + optional CC + optional Attach)
-            4 + if has_cc { 1 } else { 0 } + if email.has_attachments { 1 } else { 0 }
+            4 + if has_cc { 1 } else { 0 }
+                + if email.has_attachments { 1 } else { 0 }
+                + status_lines
         };
 
         let sections = Layout::default()
@@ -271,7 +279,7 @@ fn render_preview(frame: &mut Frame, area: Rect, state: &AppState) {
             .split(inner);
 
         // Render headers
-        render_email_headers(frame, sections[0], email, true, expanded);
+        render_email_headers(frame, sections[0], email, true, expanded, &status);
 
         // Render body
         render_email_body(frame, sections[1], state);
@@ -314,40 +322,40 @@ fn render_email_body(frame: &mut Frame, area: Rect, state: &AppState) {
     let current_uid = state.current_email_from_thread().map(|e| e.uid);
 
     // Get sanitized body text - handles AI summary, body, loading, and preview states
+    // (None = show the body itself, laid out for the pane width)
     let sanitized = if state.reader.summary_loading {
         // AI summary is being generated
-        format!("{} Generating AI summary...", spinner_char())
+        Some(format!("{} Generating AI summary...", spinner_char()))
     } else if state.reader.show_summary {
         // Show AI summary if available for current email
-        if let Some((cached_uid, ref summary)) = state.reader.cached_summary {
-            if Some(cached_uid) == current_uid {
+        Some(match state.reader.cached_summary {
+            Some((cached_uid, ref summary)) if Some(cached_uid) == current_uid => {
                 format!("[AI Summary]\n\n{}", summary)
-            } else {
-                get_summary_or_prompt(state)
             }
-        } else {
-            get_summary_or_prompt(state)
-        }
+            _ => get_summary_or_prompt(state),
+        })
     } else if state.reader.body.is_some() {
-        // Use cached sanitized body (computed once per body change)
-        state.reader.sanitized_body(sanitize_text)
+        None
     } else if state.status.loading {
-        format!("{} Loading...", spinner_char())
+        Some(format!("{} Loading...", spinner_char()))
     } else {
         // Show preview if body not loaded
-        if let Some(email) = state.current_email_from_thread() {
-            if let Some(ref preview) = email.preview {
-                sanitize_text(preview)
-            } else {
-                "[Press Enter to load full content]".to_string()
-            }
-        } else {
-            String::new()
-        }
+        Some(match state.current_email_from_thread() {
+            Some(email) => match email.preview {
+                Some(ref preview) => sanitize_text(preview),
+                None => "[Press Enter to load full content]".to_string(),
+            },
+            None => String::new(),
+        })
     };
+    // Cached per body change and pane width
+    let rendered = state.reader.rendered_body(area.width, sanitize_text);
 
     // Build styled text with visual quote bars for quoted lines
-    let lines = render_quoted_text(&sanitized);
+    let lines = match sanitized {
+        Some(ref sanitized) => render_quoted_text(sanitized, ""),
+        None => render_body_lines(&rendered, ""),
+    };
     let text = Text::from(lines);
 
     let scroll = state.reader.scroll.min(u16::MAX as usize) as u16;
@@ -56,9 +56,23 @@ pub fn render_confirm_modal(frame: &mut Frame, area: Rect, pending: &PendingComm
     let (title, message, warning) = match pending {
         PendingCommand::Clear => (
             " Confirm Action ",
-            "Clear all cached emails?",
-            "This will remove locally cached emails. They will be re-synced from the server.",
+            "Clear all cached emails?".to_string(),
+            "This will remove locally cached emails. They will be re-synced from the server."
+                .to_string(),
         ),
+        PendingCommand::Unsubscribe { list, uri } => {
+            let host = uri
+                .split_once("://")
+                .map_or(uri.as_str(), |(_, rest)| rest)
+                .split(['/', '?'])
+                .next()
+                .unwrap_or_default();
+            (
+                " Unsubscribe ",
+                format!("Unsubscribe from {}?", list),
+                format!("Sends a one-click unsubscribe request to {}.", host),
+            )
+        }
     };
 
     // Calculate popup size and position
@@ -17,6 +17,13 @@ use super::super::theme::{self, Theme, colors, symbols, with_selection_bg};
 use super::super::widgets::{display_width, format_relative_date, truncate_string};
 use super::format::highlight_matches;
 
+/// Longest mailing-list name shown in an inbox row badge
+const LIST_BADGE_MAX_WIDTH: usize = 20;
+
+/// Ancestor levels drawn before an email in an expanded thread (deeper
+/// replies are indented no further)
+const MAX_TREE_INDENT: usize = 5;
+
 /// Options for rendering a thread header
 pub struct ThreadHeaderOptions<'a> {
     pub selected: bool,
@@ -52,6 +59,8 @@ pub fn render_thread_list(frame: &mut Frame, area: Rect, state: &AppState, show_
             "No matching emails. Press Esc to clear search."
         } else if state.is_starred_view() {
             "No starred emails. Press s on any email to star it."
+        } else if state.is_lists_view() {
+            "No mailing-list emails. Press M to show all emails."
         } else {
             "No emails. Press c to compose."
         };
@@ -177,8 +186,7 @@ pub fn render_thread_list(frame: &mut Frame, area: Rect, state: &AppState, show_
             }
             current_line += content_lines;
 
-            // Render each email in thread
-            let email_count = thread.email_indices.len();
+            // Render each email in thread, as a reply tree
             for (email_idx, email) in thread.emails(&state.emails).enumerate() {
                 if current_line >= scroll_end {
                     break;
@@ -190,14 +198,13 @@ pub fn render_thread_list(frame: &mut Frame, area: Rect, state: &AppState, show_
                     let is_email_selected =
                         is_current_thread && state.thread.selected_in_thread == email_idx + 1;
                     let email_match_type = state.get_match_type(email.uid);
-                    let is_last = email_idx == email_count - 1;
                     let email_items = render_thread_email(
                         email,
                         is_email_selected,
                         inner.width,
                         &state.search.query,
                         email_match_type,
-                        is_last,
+                        &thread.branches(email_idx),
                         use_modern,
                     );
                     items.extend(email_items);
@@ -274,7 +281,16 @@ pub fn render_thread_header(
 
     let date_width = date.len().max(10);
     let indicator_width = 2; // "▶ " or "▼ " or "  "
-    let from_width = width.saturating_sub(indicator_width + date_width + 1);
+
+    // Mailing-list badge before the date: [Example News]
+    let list_badge = thread
+        .emails(emails)
+        .find_map(|e| e.list.as_ref().and_then(|l| l.label()))
+        .map(|label| format!("[{}] ", truncate_string(label, LIST_BADGE_MAX_WIDTH)))
+        .unwrap_or_default();
+    let list_badge_width = display_width(&list_badge);
+
+    let from_width = width.saturating_sub(indicator_width + list_badge_width + date_width + 1);
 
     let from_display = truncate_string(&from, from_width);
     let padding = from_width.saturating_sub(display_width(&from_display));
@@ -327,6 +343,10 @@ pub fn render_thread_header(
     let mut line1_spans = vec![Span::styled(expand_indicator, indicator_style)];
     line1_spans.extend(from_spans);
     line1_spans.push(Span::styled(" ".repeat(padding), base_style));
+    if !list_badge.is_empty() {
+        let list_style = with_selection_bg(Theme::text_accent(), selected);
+        line1_spans.push(Span::styled(list_badge, list_style));
+    }
     line1_spans.push(Span::styled(
         format!("{:>width$}", date, width = date_width),
         date_style,
@@ -466,14 +486,16 @@ pub fn render_thread_header(
     vec![ListItem::new(line1), ListItem::new(line2)]
 }
 
-/// Render an individual email within an expanded thread (2 lines, indented)
+/// Render an individual email within an expanded thread (2 lines, indented
+/// by its reply depth). `branches` says, for each level from the top down to
+/// the email's own, whether the tree continues below it.
 pub fn render_thread_email(
     email: &EmailHeader,
     selected: bool,
     width: u16,
     search_query: &str,
     match_type: MatchType,
-    is_last: bool,
+    branches: &[bool],
     use_modern: bool,
 ) -> Vec<ListItem<'static>> {
     let width = width as usize;
@@ -489,7 +511,14 @@ pub fn render_thread_email(
     };
     let date = format_relative_date(email.date);
 
-    let indent_width = 4; // extra indent for thread children
+    // Tree guides: "│" for ancestors with later replies, then ├─ or └─.
+    // Deep levels are left out so long chains keep room for the text.
+    let (own, ancestors) = branches.split_last().unwrap_or((&false, &[]));
+    let ancestors = &ancestors[ancestors.len().saturating_sub(MAX_TREE_INDENT)..];
+    let guide = |open: bool| if open { symbols::THREAD_CHILD } else { "    " };
+    let ancestor_guides: String = ancestors.iter().map(|&open| guide(open)).collect();
+
+    let indent_width = 4 * (ancestors.len() + 1); // extra indent for thread children
     let date_width = date.len().max(10);
     let from_width = width.saturating_sub(indent_width + date_width + 1);
 
@@ -531,13 +560,13 @@ pub fn render_thread_email(
     );
 
     // Use tree connector: ├─ for middle items, └─ for last item
-    let tree_connector = if is_last {
-        symbols::THREAD_CHILD_LAST
-    } else {
+    let tree_connector = if *own {
         symbols::THREAD_CHILD_MID
+    } else {
+        symbols::THREAD_CHILD_LAST
     };
     let mut line1_spans = vec![Span::styled(
-        tree_connector,
+        format!("{}{}", ancestor_guides, tree_connector),
         with_selection_bg(Theme::border(), selected),
     )];
     line1_spans.extend(from_spans);
@@ -661,11 +690,7 @@ pub fn render_thread_email(
     );
 
     // For line 2: use continuation │ for non-last, spaces for last
-    let line2_prefix = if is_last {
-        "    "
-    } else {
-        symbols::THREAD_CHILD
-    };
+    let line2_prefix = format!("{}{}", ancestor_guides, guide(*own));
     let mut line2_spans = vec![
         Span::styled(line2_prefix, with_selection_bg(Theme::border(), selected)),
         Span::styled(unread_indicator, unread_style),
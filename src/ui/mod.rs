@@ -5,8 +5,10 @@ mod composer;
 mod contacts;
 mod inbox;
 mod reader;
+mod scheduled;
 mod status_bar;
 pub mod theme;
 mod widgets;
 
 pub use app::render;
+pub use reader::{body_layout, pane_text, reader_layout};
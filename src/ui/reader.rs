@@ -1,18 +1,28 @@
+use std::sync::Arc;
+
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
-    style::Modifier,
-    text::Text,
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
 };
 
-use super::components::{render_email_headers, render_quoted_text};
+use super::components::{
+    HeaderStatus, folded_placeholder, invite_lines, render_body_lines, render_conversation,
+    render_email_headers, render_invite, render_quoted_text, render_source,
+};
+use super::inbox::render_confirm_modal;
 use super::theme::{self, Theme};
 use super::widgets::{
-    StatusInfo, enhanced_status_bar, error_bar, help_bar, sanitize_text, spinner_char,
+    StatusInfo, enhanced_status_bar, error_bar, help_bar, sanitize_text, spinner_char, wrap_line,
+};
+use crate::app::state::{
+    AppState, BodyFind, BodyLayout, ReaderState, RenderedBody, View, matching_rows,
 };
-use crate::app::state::AppState;
 use crate::constants::{HELP_BAR_HEIGHT_MODERN, STATUS_BAR_HEIGHT_MODERN};
+use crate::graphics::{self, Placement, Protocol};
+use crate::mail::quotes::Row;
 use crate::mail::types::Attachment;
 
 pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
@@ -22,7 +32,8 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
     let has_cc = email
         .and_then(|e| e.cc_addr.as_ref())
         .is_some_and(|cc| !cc.trim().is_empty());
-    let header_lines = if has_cc { 6 } else { 5 };
+    let status = email.map(|e| HeaderStatus::new(&state.reader, e));
+    let header_lines = if has_cc { 6 } else { 5 } + status.as_ref().map_or(0, HeaderStatus::height);
 
     // Determine attachment panel height
     let show_attachments = state.reader.show_attachments && !state.reader.attachments.is_empty();
@@ -36,6 +47,14 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
         0
     };
 
+    // Calendar invitation card (plus its bottom border)
+    let invite = state
+        .reader
+        .invite
+        .as_ref()
+        .map(|i| invite_lines(i, state.reader.invite_conflicts.as_deref()));
+    let invite_height = invite.as_ref().map_or(0, |lines| lines.len() as u16 + 1);
+
     // Use theme-aware bar heights
     let status_height = if theme::use_modern_spacing() {
         STATUS_BAR_HEIGHT_MODERN
@@ -54,6 +73,7 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
             Constraint::Length(status_height),     // Status bar
             Constraint::Length(header_lines),      // Headers
             Constraint::Length(attachment_height), // Attachments (if any)
+            Constraint::Length(invite_height),     // Invitation (if any)
             Constraint::Min(0),                    // Body
             Constraint::Length(help_height),       // Help bar
         ])
@@ -63,9 +83,9 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
     let status_info = StatusInfo::from_state(state, None);
     enhanced_status_bar(frame, chunks[0], &status_info);
 
-    if let Some(email) = email {
+    if let (Some(email), Some(status)) = (email, &status) {
         // Headers
-        render_email_headers(frame, chunks[1], email, false, false);
+        render_email_headers(frame, chunks[1], email, false, false, status);
 
         // Attachments
         if show_attachments {
@@ -80,8 +100,29 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
             render_attachment_indicator(frame, chunks[2], state.reader.attachments.len());
         }
 
-        // Body
-        render_body(frame, chunks[3], state, uid);
+        // Invitation
+        if let Some(lines) = invite {
+            render_invite(frame, chunks[3], lines);
+        }
+
+        // Body, or the source or images in its place
+        if let Some(ref source) = state.reader.source {
+            render_source(frame, chunks[4], source);
+        } else if state.reader.show_images {
+            let modal_open = state.modal.pending_confirmation().is_some()
+                || state.pipe.prompt.is_some()
+                || state.pipe.output.is_some();
+            render_images(frame, chunks[4], &state.reader, modal_open);
+        } else if let Some(conversation) = state
+            .reader
+            .conversation
+            .as_ref()
+            .filter(|_| state.reader.conversation_shown())
+        {
+            render_conversation(frame, chunks[4], conversation, state.reader.show_quotes);
+        } else {
+            render_body(frame, chunks[4], state, uid);
+        }
     } else {
         let paragraph = Paragraph::new("Email not found").style(Theme::error_bar());
         frame.render_widget(paragraph, chunks[1]);
@@ -89,15 +130,59 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
 
     // Help bar or error
     if let Some(ref error) = state.status.error {
-        error_bar(frame, chunks[4], error);
+        error_bar(frame, chunks[5], error);
     } else {
         // Dynamic hints based on context
-        let hints: &[(&str, &str)] = if state.reader.show_attachments {
+        let hints: &[(&str, &str)] = if let Some(ref hint) = state.reader.link_hint {
+            if hint.copy {
+                &[("0-9", "link number"), ("Enter", "copy"), ("Esc", "cancel")]
+            } else {
+                &[("0-9", "link number"), ("Enter", "open"), ("Esc", "cancel")]
+            }
+        } else if state.reader.source.is_some() {
+            &[
+                ("Tab", "headers"),
+                ("/", "search"),
+                ("n/N", "match"),
+                ("w", "save .eml"),
+                ("|", "pipe"),
+                ("Esc", "back"),
+            ]
+        } else if state.reader.find.input.is_some() {
+            &[("Enter", "search"), ("Esc", "cancel")]
+        } else if state.reader.find.is_active() {
+            &[
+                ("n/N", "match"),
+                ("/", "search"),
+                ("j/k", "scroll"),
+                ("Esc", "clear"),
+            ]
+        } else if state.reader.show_images {
+            &[("j/k", "image"), ("Enter", "open"), ("Esc", "back")]
+        } else if state.reader.show_attachments {
             // Attachment mode hints
             &[
                 ("j/k", "select"),
                 ("Enter", "open"),
                 ("s", "save"),
+                ("i", "import .vcf"),
+                ("Esc", "back"),
+            ]
+        } else if state.reader.invite.as_ref().is_some_and(|i| i.is_request()) {
+            &[
+                ("Y", "accept"),
+                ("E", "tentative"),
+                ("N", "decline"),
+                ("j/k", "scroll"),
+                ("Esc", "back"),
+            ]
+        } else if state.reader.conversation_shown() {
+            &[
+                ("Tab", "next msg"),
+                ("Enter", "expand"),
+                ("j/k", "scroll"),
+                ("r", "reply"),
+                ("t", "single"),
                 ("Esc", "back"),
             ]
         } else if state.reader.show_summary {
@@ -125,7 +210,12 @@ pub fn render_reader(frame: &mut Frame, state: &AppState, uid: u32) {
                 ("Esc", "back"),
             ]
         };
-        help_bar(frame, chunks[4], hints);
+        help_bar(frame, chunks[5], hints);
+    }
+
+    // Confirmation modal (e.g. one-click unsubscribe)
+    if let Some(pending) = state.modal.pending_confirmation() {
+        render_confirm_modal(frame, frame.area(), pending);
     }
 }
 
@@ -184,65 +274,313 @@ fn render_body(frame: &mut Frame, area: ratatui::layout::Rect, state: &AppState,
     let block = Block::default()
         .borders(Borders::NONE)
         .style(Theme::main_bg());
-    let inner = block.inner(area);
+    let mut inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let body_text: String = if state.reader.summary_loading {
-        format!("{} Generating AI summary...", spinner_char())
+    let text = pane_text(state, uid);
+    // The message itself, laid out for this pane's width (cached)
+    let rendered = state.reader.rendered_body(inner.width, sanitize_text);
+    let (lines, layout) = pane_lines(&state.reader, &rendered, text.as_deref(), inner.width);
+
+    let find = &state.reader.find;
+    if text.is_none() && find.is_active() && inner.height > 1 {
+        let (texts, rows) = state.reader.body_rows();
+        let matches = matching_rows(&texts, &rows, &find.query);
+        inner.height -= 1;
+        let bar = Rect::new(inner.x, inner.y + inner.height, inner.width, 1);
+        let top = layout.line_at(state.reader.scroll);
+        render_find_bar(frame, bar, top, &matches, find);
+    }
+
+    // Lines are already wrapped, so the scroll offset counts wrapped rows
+    let scroll = state.reader.scroll.min(ReaderState::max_scroll(&layout));
+    let paragraph = Paragraph::new(Text::from(lines))
+        .style(Theme::main_bg())
+        .scroll((scroll.min(u16::MAX as usize) as u16, 0));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Wrapped layout of the reader's body pane, for scrolling and search
+pub fn body_layout(state: &AppState) -> BodyLayout {
+    let View::Reader { uid } = state.view else {
+        return BodyLayout::default();
+    };
+    let text = pane_text(state, uid);
+    reader_layout(&state.reader, text.as_deref())
+}
+
+/// Wrapped layout of the body pane showing `text` in place of the body
+pub fn reader_layout(reader: &ReaderState, text: Option<&str>) -> BodyLayout {
+    let rendered = reader.rendered_body(reader.width, sanitize_text);
+    pane_lines(reader, &rendered, text, reader.width).1
+}
+
+/// Text shown in place of the body (summary, loading notice), sanitized
+pub fn pane_text(state: &AppState, uid: u32) -> Option<String> {
+    let text = if state.reader.summary_loading {
+        Some(format!("{} Generating AI summary...", spinner_char()))
     } else if state.reader.show_summary {
-        // Show AI summary if available
-        if let Some((cached_uid, ref summary)) = state.reader.cached_summary {
-            if cached_uid == uid {
-                format!("[AI Summary]\n\n{}", summary)
-            } else {
-                // Show thread summary if available
-                if let Some((ref thread_id, ref summary)) = state.reader.cached_thread_summary {
-                    if state
-                        .current_thread()
-                        .map(|t| &t.id == thread_id)
-                        .unwrap_or(false)
-                    {
-                        format!("[AI Thread Summary]\n\n{}", summary)
-                    } else {
-                        "[Press T to generate summary]".to_string()
-                    }
+        Some(summary_text(state, uid))
+    } else if state.reader.body.is_some() {
+        None
+    } else if state.status.loading {
+        Some(format!("{} Loading...", spinner_char()))
+    } else {
+        Some("[No content]".to_string())
+    };
+    // Remove ANSI sequences and control characters
+    text.as_deref().map(sanitize_text)
+}
+
+/// Lines of the body pane wrapped to `width`, and where each unwrapped line
+/// (body row or link footnote) starts
+fn pane_lines<'a>(
+    reader: &ReaderState,
+    rendered: &'a RenderedBody,
+    text: Option<&'a str>,
+    width: u16,
+) -> (Vec<Line<'a>>, BodyLayout) {
+    // Styled text with visual quote bars for quoted lines
+    let lines = match text {
+        Some(text) => render_quoted_text(text, ""),
+        None => {
+            let (texts, rows) = reader.body_rows();
+            let matches = matching_rows(&texts, &rows, &reader.find.query);
+            let mut lines = fold_lines(
+                render_body_lines(rendered, &reader.find.query),
+                &rows,
+                &matches,
+            );
+            lines.extend(link_footnotes(reader));
+            lines
+        }
+    };
+    let mut wrapped = Vec::with_capacity(lines.len());
+    let mut starts = Vec::with_capacity(lines.len());
+    for line in lines {
+        starts.push(wrapped.len());
+        wrapped.extend(wrap_line(line, width));
+    }
+    let rows = wrapped.len();
+    (wrapped, BodyLayout { starts, rows })
+}
+
+/// Body lines with each folded block replaced by a one-line placeholder
+fn fold_lines<'a>(lines: Vec<Line<'a>>, rows: &[Row], matches: &[usize]) -> Vec<Line<'a>> {
+    let mut lines = lines.into_iter();
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| match *row {
+            Row::Line(_) => lines.next().unwrap_or_default(),
+            Row::Folded(block) => {
+                for _ in 0..block.len {
+                    lines.next();
+                }
+                folded_placeholder(block, matches.binary_search(&i).is_ok())
+            }
+        })
+        .collect()
+}
+
+/// Search term being typed, or the one highlighted with a match counter
+fn render_find_bar(frame: &mut Frame, area: Rect, top: usize, matches: &[usize], find: &BodyFind) {
+    let line = if let Some(ref input) = find.input {
+        Line::from(vec![
+            Span::styled("/", Theme::text_accent()),
+            Span::styled(input.as_str(), Theme::text()),
+            Span::styled("█", Theme::text_muted()),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled("/", Theme::text_accent()),
+            Span::styled(find.query.as_str(), Theme::text()),
+            Span::styled(
+                format!("  {}", match_counter(top, matches)),
+                Theme::text_muted(),
+            ),
+        ])
+    };
+    frame.render_widget(Paragraph::new(line).style(Theme::main_bg()), area);
+}
+
+/// Which match is on the `top` row ("2 of 5"), else how many there are
+fn match_counter(top: usize, matches: &[usize]) -> String {
+    match matches.iter().position(|&row| row == top) {
+        Some(i) => format!("{} of {}", i + 1, matches.len()),
+        None if matches.is_empty() => "no matches".to_string(),
+        None if matches.len() == 1 => "1 match".to_string(),
+        None => format!("{} matches", matches.len()),
+    }
+}
+
+/// The selected image: drawn as half blocks here, or placed for the render
+/// thread to draw with the terminal's graphics protocol after the frame
+fn render_images(
+    frame: &mut Frame,
+    area: ratatui::layout::Rect,
+    reader: &ReaderState,
+    modal_open: bool,
+) {
+    let Some(image) = reader.images.get(reader.image_selected) else {
+        return;
+    };
+    let size = image
+        .pixels
+        .as_ref()
+        .map(|p| format!(" ({}×{})", p.width, p.height))
+        .unwrap_or_default();
+    let title = format!(
+        " Image {}/{} · {}{} ",
+        reader.image_selected + 1,
+        reader.images.len(),
+        sanitize_text(&image.name),
+        size
+    );
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Theme::border())
+        .title(Span::styled(title, Theme::text_secondary()))
+        .style(Theme::main_bg());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(protocol) = graphics::protocol().filter(|p| image.drawable(*p)) else {
+        let note = Paragraph::new(format!(
+            "This {} image can't be shown in this terminal.\nPress Enter to open it.",
+            image.format.label()
+        ))
+        .style(Theme::text_muted());
+        frame.render_widget(note, inner);
+        return;
+    };
+
+    if let (Protocol::HalfBlocks, Some(pixels)) = (protocol, image.pixels.as_ref()) {
+        let color = |c: Option<[u8; 3]>| {
+            c.map_or(theme::colors::bg_main(), |[r, g, b]| Color::Rgb(r, g, b))
+        };
+        let lines: Vec<Line> = graphics::half_blocks(pixels, inner.width, inner.height)
+            .into_iter()
+            .map(|row| {
+                Line::from(
+                    row.into_iter()
+                        .map(|(top, bottom)| {
+                            Span::styled("▀", Style::default().fg(color(top)).bg(color(bottom)))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines).style(Theme::main_bg()), inner);
+    } else if !modal_open {
+        *reader.image_placement.borrow_mut() = Some(Placement {
+            image: Arc::clone(image),
+            x: inner.x,
+            y: inner.y,
+            cols: inner.width,
+            rows: inner.height,
+        });
+    }
+}
+
+/// AI summary of the message or its thread, or a prompt to generate one
+fn summary_text(state: &AppState, uid: u32) -> String {
+    // Show AI summary if available
+    if let Some((cached_uid, ref summary)) = state.reader.cached_summary {
+        if cached_uid == uid {
+            format!("[AI Summary]\n\n{}", summary)
+        } else {
+            // Show thread summary if available
+            if let Some((ref thread_id, ref summary)) = state.reader.cached_thread_summary {
+                if state
+                    .current_thread()
+                    .map(|t| &t.id == thread_id)
+                    .unwrap_or(false)
+                {
+                    format!("[AI Thread Summary]\n\n{}", summary)
                 } else {
                     "[Press T to generate summary]".to_string()
                 }
-            }
-        } else if let Some((ref thread_id, ref summary)) = state.reader.cached_thread_summary {
-            if state
-                .current_thread()
-                .map(|t| &t.id == thread_id)
-                .unwrap_or(false)
-            {
-                format!("[AI Thread Summary]\n\n{}", summary)
             } else {
                 "[Press T to generate summary]".to_string()
             }
+        }
+    } else if let Some((ref thread_id, ref summary)) = state.reader.cached_thread_summary {
+        if state
+            .current_thread()
+            .map(|t| &t.id == thread_id)
+            .unwrap_or(false)
+        {
+            format!("[AI Thread Summary]\n\n{}", summary)
         } else {
             "[Press T to generate summary]".to_string()
         }
-    } else if let Some(ref body) = state.reader.body {
-        body.display_text()
-    } else if state.status.loading {
-        format!("{} Loading...", spinner_char())
     } else {
-        "[No content]".to_string()
-    };
-
-    // Sanitize: remove ANSI sequences and control characters
-    let sanitized = sanitize_text(&body_text);
+        "[Press T to generate summary]".to_string()
+    }
+}
 
-    // Build styled text with visual quote bars for quoted lines
-    let lines = render_quoted_text(&sanitized);
-    let text = Text::from(lines);
+/// Numbered link list shown under the body, with the host each link really opens
+fn link_footnotes(reader: &ReaderState) -> Vec<Line<'static>> {
+    if reader.links.is_empty() {
+        return Vec::new();
+    }
+    let typed = reader.link_hint.as_ref().map(|h| h.label.as_str());
+    let mut lines = vec![
+        Line::default(),
+        Line::styled(format!("Links ({})", reader.links.len()), Theme::label()),
+    ];
+    for (i, link) in reader.links.iter().enumerate() {
+        let label = (i + 1).to_string();
+        // While choosing, dim the links the typed label has ruled out
+        let ruled_out = typed.is_some_and(|t| !label.starts_with(t));
+        let label_style = if ruled_out {
+            Theme::text_muted()
+        } else if typed.is_some() {
+            Theme::input_highlight()
+        } else {
+            Theme::text_accent()
+        };
+        let domain = link.domain().unwrap_or_else(|| "?".to_string());
+        let mut spans = vec![Span::styled(format!("[{}]", label), label_style)];
+        if let Some(ref text) = link.text {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(sanitize_text(text), Theme::text()));
+        }
+        if link.is_deceptive() {
+            spans.push(Span::styled(
+                format!(" — ⚠ opens {}", sanitize_text(&domain)),
+                Theme::text_danger(),
+            ));
+        } else {
+            spans.push(Span::styled(
+                format!(" — {}", sanitize_text(&domain)),
+                Theme::text_secondary(),
+            ));
+        }
+        lines.push(Line::from(spans));
+        lines.push(Line::styled(
+            format!("    {}", sanitize_text(&link.url)),
+            if ruled_out {
+                Theme::text_muted()
+            } else {
+                Theme::text_link()
+            },
+        ));
+    }
+    lines
+}
 
-    let scroll = state.reader.scroll.min(u16::MAX as usize) as u16;
-    let paragraph = Paragraph::new(text)
-        .style(Theme::main_bg())
-        .wrap(Wrap { trim: false })
-        .scroll((scroll, 0));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    frame.render_widget(paragraph, inner);
+    #[test]
+    fn test_match_counter() {
+        assert_eq!(match_counter(4, &[1, 4, 9]), "2 of 3");
+        assert_eq!(match_counter(0, &[1, 4, 9]), "3 matches");
+        assert_eq!(match_counter(0, &[4]), "1 match");
+        assert_eq!(match_counter(4, &[4]), "1 of 1");
+        assert_eq!(match_counter(0, &[]), "no matches");
+    }
 }
@@ -0,0 +1,140 @@
+//! Send-later queue view rendering
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+
+use super::theme::{Theme, with_selection_bg};
+use super::widgets::{error_bar, help_bar, truncate_string};
+use crate::app::schedule::format_send_time;
+use crate::app::state::AppState;
+use crate::mail::types::ScheduledMessage;
+
+pub fn render_scheduled(frame: &mut Frame, state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Status bar
+            Constraint::Min(0),    // Queue
+            Constraint::Length(1), // Help bar
+        ])
+        .split(frame.area());
+
+    let text = format!(" Scheduled ({}) ", state.scheduled.messages.len());
+    frame.render_widget(Paragraph::new(text).style(Theme::status_bar()), chunks[0]);
+
+    render_scheduled_list(frame, chunks[1], state);
+
+    if let Some(ref error) = state.status.error {
+        error_bar(frame, chunks[2], error);
+    } else {
+        help_bar(
+            frame,
+            chunks[2],
+            &[
+                ("j/k", "nav"),
+                ("Enter", "edit"),
+                ("d", "cancel send"),
+                ("Esc", "back"),
+            ],
+        );
+    }
+}
+
+fn render_scheduled_list(frame: &mut Frame, area: Rect, state: &AppState) {
+    let block = Block::default()
+        .title(" Send later ")
+        .borders(Borders::ALL)
+        .border_style(Theme::border())
+        .style(Theme::main_bg());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if state.scheduled.messages.is_empty() {
+        let msg = Paragraph::new("Nothing scheduled. Press Ctrl+W in the composer to send later.")
+            .style(Theme::text_muted())
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(msg, inner);
+        return;
+    }
+
+    // 2 lines per message; keep the selection in view
+    let visible = (inner.height as usize / 2).max(1);
+    let offset = state
+        .scheduled
+        .selected
+        .saturating_sub(visible.saturating_sub(1));
+
+    let items: Vec<ListItem> = state
+        .scheduled
+        .messages
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .flat_map(|(idx, message)| {
+            render_scheduled_item(message, idx == state.scheduled.selected, inner.width)
+        })
+        .collect();
+
+    frame.render_widget(List::new(items), inner);
+}
+
+fn render_scheduled_item(
+    message: &ScheduledMessage,
+    selected: bool,
+    width: u16,
+) -> Vec<ListItem<'static>> {
+    let width = width as usize;
+    let base_style = if selected {
+        Theme::selected()
+    } else {
+        ratatui::style::Style::default()
+    };
+
+    // Line 1: send time and subject
+    let when = format!("{}  ", format_send_time(message.send_at));
+    let subject = if message.email.subject.is_empty() {
+        "(no subject)"
+    } else {
+        message.email.subject.as_str()
+    };
+    let subject = truncate_string(subject, width.saturating_sub(when.len()));
+    let padding = width.saturating_sub(when.len() + subject.len());
+    let line1 = Line::from(vec![
+        Span::styled(when, with_selection_bg(Theme::text_accent(), selected)),
+        Span::styled(
+            subject,
+            if selected {
+                Theme::selected_bold()
+            } else {
+                Theme::text_unread()
+            },
+        ),
+        Span::styled(" ".repeat(padding), base_style),
+    ]);
+
+    // Line 2: recipients, or the error from the last attempt
+    let (detail, style) = match message.last_error {
+        Some(ref error) => (
+            format!("  Failed: {}", error),
+            with_selection_bg(Theme::status_disconnected(), selected),
+        ),
+        None => (
+            format!("  To: {}", message.email.to),
+            with_selection_bg(Theme::text_muted(), selected),
+        ),
+    };
+    let detail = truncate_string(&detail, width);
+    let padding = width.saturating_sub(detail.len());
+    let line2 = Line::from(vec![
+        Span::styled(detail, style),
+        Span::styled(" ".repeat(padding), base_style),
+    ]);
+
+    vec![ListItem::new(line1), ListItem::new(line2)]
+}
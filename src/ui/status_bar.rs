@@ -9,8 +9,9 @@ use ratatui::{
 };
 
 use super::theme::{self, Theme, symbols};
-use crate::app::state::{AppState, OtherAccountInfo};
+use crate::app::state::{AppState, OtherAccountInfo, ViewMode};
 use crate::constants::{CONTENT_PADDING_H, SPINNER_FRAME_MS};
+use crate::mail::types::SortOrder;
 
 /// Get current process memory usage (RSS) in bytes
 /// Returns None if unable to read memory info
@@ -117,10 +118,12 @@ pub struct StatusInfo<'a> {
     pub status_message: Option<&'a str>,
     /// Other accounts for status bar indicators (empty if single account)
     pub other_accounts: &'a [OtherAccountInfo],
-    /// Whether currently showing starred emails view
-    pub starred_view: bool,
+    /// Current view filter (all, starred, mailing lists)
+    pub view_mode: ViewMode,
     /// Whether conversation mode is enabled (show sent in threads)
     pub conversation_mode: bool,
+    /// Sort order of the list
+    pub sort_order: SortOrder,
     /// Whether there's an unacknowledged error (show indicator)
     pub has_error: bool,
 }
@@ -151,8 +154,9 @@ impl<'a> StatusInfo<'a> {
                 Some(&state.status.message)
             },
             other_accounts: &state.connection.other_accounts,
-            starred_view: state.is_starred_view(),
+            view_mode: state.view_mode,
             conversation_mode: state.conversation_mode,
+            sort_order: state.pagination.order,
             has_error: state.has_unacknowledged_error(),
         }
     }
@@ -226,43 +230,21 @@ pub fn enhanced_status_bar(frame: &mut Frame, area: Rect, info: &StatusInfo) {
 
     // Build folder info as spans (unread count is bold)
     let unread_style = style.add_modifier(ratatui::style::Modifier::BOLD);
-    let folder_info_spans: Vec<(String, Style)> = if let Some(query) = info.search_query {
-        if !query.is_empty() {
-            vec![(
-                format!("\"{}\" ({} results)", query, info.search_results),
-                style,
-            )]
-        } else if info.starred_view {
-            vec![
-                (
-                    format!("{} {} [Starred] ", symbols::STARRED, info.folder),
-                    style,
-                ),
-                (info.unread.to_string(), unread_style),
-                (format!(" / {}", info.total), style),
-            ]
-        } else {
-            vec![
-                (format!("{} ", info.folder), style),
-                (info.unread.to_string(), unread_style),
-                (format!(" / {}", info.total), style),
-            ]
-        }
-    } else if info.starred_view {
-        vec![
-            (
-                format!("{} {} [Starred] ", symbols::STARRED, info.folder),
-                style,
-            ),
-            (info.unread.to_string(), unread_style),
-            (format!(" / {}", info.total), style),
-        ]
-    } else {
-        vec![
-            (format!("{} ", info.folder), style),
+    let folder_label = match info.view_mode {
+        ViewMode::All => format!("{} ", info.folder),
+        ViewMode::Starred => format!("{} {} [Starred] ", symbols::STARRED, info.folder),
+        ViewMode::Lists => format!("{} [Lists] ", info.folder),
+    };
+    let folder_info_spans: Vec<(String, Style)> = match info.search_query {
+        Some(query) if !query.is_empty() => vec![(
+            format!("\"{}\" ({} results)", query, info.search_results),
+            style,
+        )],
+        _ => vec![
+            (folder_label, style),
             (info.unread.to_string(), unread_style),
             (format!(" / {}", info.total), style),
-        ]
+        ],
     };
 
     // Add conversation mode indicator if enabled (only show in INBOX)
@@ -270,6 +252,10 @@ pub fn enhanced_status_bar(frame: &mut Frame, area: Rect, info: &StatusInfo) {
     if info.conversation_mode && info.folder == "INBOX" {
         folder_info_spans.push((" [Conv]".to_string(), style));
     }
+    // Only orders other than the default are worth pointing out
+    if info.sort_order != SortOrder::default() {
+        folder_info_spans.push((format!(" [{}]", info.sort_order.label()), style));
+    }
 
     let folder_info_width: usize = folder_info_spans
         .iter()
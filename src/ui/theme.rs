@@ -781,6 +781,28 @@ impl Theme {
             .add_modifier(Modifier::BOLD)
     }
 
+    /// Recipient chip in the composer's To/Cc fields
+    pub fn chip() -> Style {
+        Style::default()
+            .fg(colors::fg_primary())
+            .bg(colors::bg_selection())
+    }
+
+    /// Recipient outside the sending account's internal domains (yellow)
+    pub fn chip_external() -> Style {
+        Style::default()
+            .fg(colors::fg_warning())
+            .bg(colors::bg_selection())
+    }
+
+    /// Recipient that isn't a valid address (red, underlined)
+    pub fn chip_invalid() -> Style {
+        Style::default()
+            .fg(colors::status_disconnected())
+            .bg(colors::bg_main())
+            .add_modifier(Modifier::UNDERLINED)
+    }
+
     /// Success/confirmation text (green)
     pub fn text_success() -> Style {
         Style::default()
@@ -788,6 +810,21 @@ impl Theme {
             .bg(colors::bg_main())
     }
 
+    /// Caution text, e.g. an unverified signature (yellow)
+    pub fn text_warning() -> Style {
+        Style::default()
+            .fg(colors::fg_warning())
+            .bg(colors::bg_main())
+    }
+
+    /// Danger text, e.g. a bad signature (red, bold)
+    pub fn text_danger() -> Style {
+        Style::default()
+            .fg(colors::status_disconnected())
+            .bg(colors::bg_main())
+            .add_modifier(Modifier::BOLD)
+    }
+
     /// Link/URL text (cyan, underlined)
     pub fn text_link() -> Style {
         Style::default()
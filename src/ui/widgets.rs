@@ -3,6 +3,7 @@
 use ratatui::{
     Frame,
     layout::Rect,
+    style::Style,
     text::{Line, Span},
     widgets::Paragraph,
 };
@@ -214,3 +215,85 @@ pub fn sanitize_text(text: &str) -> String {
 
     result
 }
+
+/// Split `text` into spans, marking case-insensitive matches of `query`
+pub fn highlight<'a>(text: &str, query: &str, style: Style) -> Vec<Span<'a>> {
+    let lower = text.to_lowercase();
+    let needle = query.to_lowercase();
+    // Lowercasing can change byte lengths; only highlight when it didn't
+    if needle.is_empty() || lower.len() != text.len() {
+        return vec![Span::styled(text.to_string(), style)];
+    }
+
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (start, _) in lower.match_indices(&needle) {
+        let end = start + needle.len();
+        if start < last || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+            continue;
+        }
+        if start > last {
+            spans.push(Span::styled(text[last..start].to_string(), style));
+        }
+        spans.push(Span::styled(
+            text[start..end].to_string(),
+            Theme::selected_bold(),
+        ));
+        last = end;
+    }
+    if last < text.len() {
+        spans.push(Span::styled(text[last..].to_string(), style));
+    }
+    spans
+}
+
+/// Split `line` into rows at most `width` columns wide, breaking after the
+/// last space that fits (or mid-word when none does), keeping span styles.
+/// A width of 0 leaves the line whole.
+pub fn wrap_line(line: Line<'_>, width: u16) -> Vec<Line<'_>> {
+    use unicode_width::UnicodeWidthChar;
+
+    let width = usize::from(width);
+    if width == 0 || line.width() <= width {
+        return vec![line];
+    }
+    let chars: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start;
+        let mut cols = 0;
+        let mut space = None;
+        while let Some(&(c, _)) = chars.get(end) {
+            let w = c.width().unwrap_or(0);
+            if cols + w > width && end > start {
+                break;
+            }
+            cols += w;
+            end += 1;
+            if c.is_whitespace() {
+                space = Some(end);
+            }
+        }
+        if end < chars.len()
+            && let Some(space) = space
+        {
+            end = space;
+        }
+        // Regroup the row's characters into spans of one style
+        let mut spans: Vec<Span> = Vec::new();
+        for &(c, style) in &chars[start..end] {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.content.to_mut().push(c),
+                _ => spans.push(Span::styled(c.to_string(), style)),
+            }
+        }
+        rows.push(Line::from(spans).style(line.style));
+        start = end;
+    }
+    rows
+}